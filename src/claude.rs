@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
@@ -102,3 +102,130 @@ pub fn prune_stale_entries() -> Result<usize> {
 
     Ok(removed_count)
 }
+
+/// Copy the per-project block in ~/.claude.json for `from` (e.g. the main
+/// worktree) to `to`, so a new worktree starts with the same MCP servers,
+/// allowed tools, and history. An existing block for `to` is replaced.
+pub fn import_project_settings(from: &Path, to: &Path) -> Result<()> {
+    let config_path =
+        get_config_path().ok_or_else(|| anyhow!("Could not determine home directory"))?;
+    import_project_settings_in(&config_path, from, to)
+}
+
+fn import_project_settings_in(config_path: &Path, from: &Path, to: &Path) -> Result<()> {
+    if !config_path.exists() {
+        return Err(anyhow!(
+            "No Claude configuration found at {}",
+            config_path.display()
+        ));
+    }
+
+    let contents = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read Claude config: {:?}", config_path))?;
+
+    // Parse loosely so fields workmux doesn't know about are written back untouched
+    let mut config_value: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse Claude config: {:?}", config_path))?;
+
+    let from_key = from.to_string_lossy();
+    let to_key = to.to_string_lossy().into_owned();
+    let projects = config_value
+        .as_object_mut()
+        .and_then(|root| root.get_mut("projects"))
+        .and_then(|projects| projects.as_object_mut())
+        .ok_or_else(|| anyhow!("No projects section found in {}", config_path.display()))?;
+    let settings = projects.get(from_key.as_ref()).cloned().ok_or_else(|| {
+        anyhow!(
+            "No Claude project settings found for '{}' in {}",
+            from_key,
+            config_path.display()
+        )
+    })?;
+    let replaced = projects.insert(to_key.clone(), settings).is_some();
+
+    // Create a backup
+    let backup_path = config_path.with_extension("json.bak");
+    fs::copy(config_path, &backup_path).with_context(|| {
+        format!(
+            "Failed to create backup of Claude config at {:?}",
+            backup_path
+        )
+    })?;
+    println!("✓ Created backup at {}", backup_path.display());
+
+    let new_contents = serde_json::to_string_pretty(&config_value)?;
+    fs::write(config_path, new_contents)
+        .with_context(|| format!("Failed to write updated Claude config to {:?}", config_path))?;
+
+    println!(
+        "✓ {} Claude settings for {} with those of {}",
+        if replaced { "Replaced" } else { "Seeded" },
+        to_key,
+        from_key
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import_project_settings_in;
+    use serde_json::json;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn import_copies_project_block_and_preserves_other_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".claude.json");
+        let original = json!({
+            "numStartups": 42,
+            "projects": {
+                "/repo": {
+                    "mcpServers": { "db": { "command": "db-mcp" } },
+                    "allowedTools": ["Bash"],
+                    "someFutureField": { "nested": true }
+                },
+                "/other": { "allowedTools": [] }
+            }
+        });
+        fs::write(&config_path, original.to_string()).unwrap();
+
+        import_project_settings_in(
+            &config_path,
+            Path::new("/repo"),
+            Path::new("/repo__worktrees/feature"),
+        )
+        .unwrap();
+
+        let updated: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(updated["numStartups"], 42);
+        assert_eq!(
+            updated["projects"]["/repo__worktrees/feature"],
+            original["projects"]["/repo"]
+        );
+        assert_eq!(updated["projects"]["/repo"], original["projects"]["/repo"]);
+        assert_eq!(
+            updated["projects"]["/other"],
+            original["projects"]["/other"]
+        );
+
+        let backup = fs::read_to_string(config_path.with_extension("json.bak")).unwrap();
+        assert_eq!(backup, original.to_string());
+    }
+
+    #[test]
+    fn import_fails_without_source_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".claude.json");
+        fs::write(&config_path, json!({ "projects": {} }).to_string()).unwrap();
+
+        let err = import_project_settings_in(&config_path, Path::new("/repo"), Path::new("/wt"))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("No Claude project settings found for '/repo'")
+        );
+        assert!(!config_path.with_extension("json.bak").exists());
+    }
+}
@@ -1,9 +1,13 @@
-use crate::command::args::{MultiArgs, PromptArgs, RescueArgs, SetupFlags};
+use crate::command::args::{BranchArgs, MultiArgs, PromptArgs, RescueArgs, SetupFlags};
+use crate::config::CliOverrides;
+use crate::workflow::MergeOptions;
 use crate::{claude, command, git};
 use anyhow::{Context, Result};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
 use clap::{CommandFactory, Parser, Subcommand};
-use clap_complete::{Shell, generate};
-use std::io;
+use clap_complete::Shell;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 struct WorktreeBranchParser;
@@ -54,31 +58,114 @@ impl clap::builder::TypedValueParser for WorktreeBranchParser {
     fn possible_values(
         &self,
     ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
-        let branches = self.get_branches();
-        // Note: Box::leak is used here because clap's PossibleValue::new requires 'static str.
-        // This is unavoidable with the current clap API for dynamic completions.
-        // The memory leak is small (proportional to number of branches) and only occurs
-        // during shell completion queries, which are infrequent.
-        let branches_static: Vec<&'static str> = branches
+        branch_possible_values(self.get_branches())
+    }
+}
+
+/// Completes every local and remote-tracking branch, whether or not it has a worktree
+#[derive(Clone, Debug)]
+struct AllBranchParser;
+
+impl AllBranchParser {
+    fn new() -> Self {
+        Self
+    }
+
+    fn get_branches(&self) -> Vec<String> {
+        // Don't attempt completions if not in a git repo.
+        if !git::is_git_repo().unwrap_or(false) {
+            return Vec::new();
+        }
+
+        let branches = match git::list_all_branches() {
+            Ok(branches) => branches,
+            // Fail silently on completion; don't disrupt the user's shell.
+            Err(_) => return Vec::new(),
+        };
+
+        let main_branch = git::get_default_branch().ok();
+
+        branches
             .into_iter()
-            .map(|s| Box::leak(s.into_boxed_str()) as &'static str)
-            .collect();
-
-        Some(Box::new(
-            branches_static
-                .into_iter()
-                .map(clap::builder::PossibleValue::new),
-        ))
+            .filter(|branch| main_branch.as_deref() != Some(branch.as_str()))
+            .filter(|branch| branch != "(detached)")
+            .collect()
+    }
+}
+
+impl clap::builder::TypedValueParser for AllBranchParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        _arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, None, value)
     }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        branch_possible_values(self.get_branches())
+    }
+}
+
+/// Turn branch names into completion candidates
+fn branch_possible_values(
+    branches: Vec<String>,
+) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue>>> {
+    // Note: Box::leak is used here because clap's PossibleValue::new requires 'static str.
+    // This is unavoidable with the current clap API for dynamic completions.
+    // The memory leak is small (proportional to number of branches) and only occurs
+    // during shell completion queries, which are infrequent.
+    let branches_static: Vec<&'static str> = branches
+        .into_iter()
+        .map(|s| Box::leak(s.into_boxed_str()) as &'static str)
+        .collect();
+
+    Some(Box::new(
+        branches_static
+            .into_iter()
+            .map(clap::builder::PossibleValue::new),
+    ))
 }
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 #[command(name = "workmux")]
 #[command(about = "An opinionated workflow tool that orchestrates git worktrees and tmux")]
-struct Cli {
+pub struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Use the branch name verbatim as the window/tab name, ignoring window_prefix
+    #[arg(long, global = true)]
+    no_prefix: bool,
+
+    /// Open and manage tabs in this zellij session instead of the current one
+    #[arg(long, global = true, value_name = "SESSION")]
+    zellij_session: Option<String>,
+
+    /// Log more detail to ~/.workmux/workmux.log (-v for debug, -vv for trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only log errors to ~/.workmux/workmux.log and hide progress messages
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Answer yes to confirmation prompts
+    #[arg(short, long, global = true)]
+    yes: bool,
+}
+
+impl Cli {
+    /// Log level override from `--verbose`/`--quiet`, if any
+    pub fn log_level(&self) -> Option<tracing_subscriber::filter::LevelFilter> {
+        crate::logger::level_from_flags(self.verbose, self.quiet)
+    }
 }
 
 #[derive(Subcommand)]
@@ -87,16 +174,15 @@ enum Commands {
     Add {
         /// Name of the branch (creates if it doesn't exist) or remote ref (e.g., origin/feature).
         /// When used with --pr, this becomes the custom local branch name.
-        #[arg(required_unless_present = "pr")]
+        #[arg(required_unless_present_any = ["pr", "prompt_from_issue"])]
         branch_name: Option<String>,
 
         /// Pull request number to checkout
         #[arg(long, conflicts_with = "base")]
         pr: Option<u32>,
 
-        /// Base branch/commit/tag to branch from (defaults to current branch)
-        #[arg(long)]
-        base: Option<String>,
+        #[command(flatten)]
+        branch: BranchArgs,
 
         #[command(flatten)]
         prompt: PromptArgs,
@@ -114,8 +200,16 @@ enum Commands {
     /// Open a tmux window for an existing worktree
     Open {
         /// Name of the branch with an existing worktree
-        #[arg(value_parser = WorktreeBranchParser::new())]
-        branch_name: String,
+        #[arg(value_parser = WorktreeBranchParser::new(), required_unless_present = "all")]
+        branch_name: Option<String>,
+
+        /// Open a window for every worktree that doesn't have one
+        #[arg(long, conflicts_with = "branch_name")]
+        all: bool,
+
+        /// With --all, run file operations and hooks for up to N worktrees at once
+        #[arg(short, long, value_name = "N", default_value = "1", requires = "all")]
+        jobs: NonZeroUsize,
 
         /// Re-run post-create hooks (e.g., pnpm install)
         #[arg(long)]
@@ -124,6 +218,11 @@ enum Commands {
         /// Re-apply file operations (copy/symlink)
         #[arg(long)]
         force_files: bool,
+
+        /// Focus the pane at this position in the `panes` list (0-based)
+        /// instead of the one configured with `focus`
+        #[arg(long, value_name = "INDEX")]
+        select_pane: Option<usize>,
     },
 
     /// Merge a branch, then clean up the worktree and tmux window
@@ -132,14 +231,35 @@ enum Commands {
         #[arg(value_parser = WorktreeBranchParser::new())]
         branch_name: Option<String>,
 
+        /// Branch to merge into (defaults to the main branch)
+        #[arg(long, value_parser = AllBranchParser::new())]
+        target: Option<String>,
+
+        /// Create the --target branch from the main worktree's branch if it doesn't exist
+        #[arg(long, requires = "target")]
+        create_target: bool,
+
+        /// Merge into the branch currently checked out in the worktree at this path
+        #[arg(long, value_name = "DIR", conflicts_with = "target")]
+        target_path: Option<PathBuf>,
+
         /// Ignore uncommitted and staged changes
         #[arg(long)]
         ignore_uncommitted: bool,
 
+        /// Stash unstaged changes in the worktree before merging and restore them
+        /// afterwards (the stash is kept if the worktree is removed or the merge fails)
+        #[arg(long, conflicts_with = "ignore_uncommitted")]
+        stash: bool,
+
         /// Also delete the remote branch
         #[arg(short = 'r', long)]
         delete_remote: bool,
 
+        /// Delete the remote branch without asking for confirmation
+        #[arg(short, long, requires = "delete_remote")]
+        force: bool,
+
         /// Rebase the branch onto the main branch before merging (fast-forward)
         #[arg(long, group = "merge_strategy")]
         rebase: bool,
@@ -151,16 +271,33 @@ enum Commands {
         /// Keep the worktree, window, and branch after merging (skip cleanup)
         #[arg(short = 'k', long, conflicts_with = "delete_remote")]
         keep: bool,
+
+        /// With --keep, switch to the target branch's window after merging
+        #[arg(long, requires = "keep")]
+        open: bool,
+
+        /// Show the commits that will be rebased and ask before continuing
+        #[arg(long, requires = "rebase")]
+        preview: bool,
+
+        /// Merge even if the target worktree has uncommitted changes (git still
+        /// refuses if the merge would overwrite them)
+        #[arg(long)]
+        force_dirty_target: bool,
+
+        /// Print the result as JSON instead of progress messages
+        #[arg(long)]
+        json: bool,
     },
 
     /// Remove a worktree, tmux window, and branch without merging
     #[command(visible_alias = "rm")]
     Remove {
-        /// Name of the branch to remove (defaults to current branch)
+        /// Names of the branches to remove (defaults to current branch)
         #[arg(value_parser = WorktreeBranchParser::new())]
-        branch_name: Option<String>,
+        branch_names: Vec<String>,
 
-        /// Skip confirmation and ignore uncommitted changes
+        /// Ignore uncommitted changes and unmerged commits
         #[arg(short, long)]
         force: bool,
 
@@ -171,11 +308,91 @@ enum Commands {
         /// Keep the local branch (only remove worktree and tmux window)
         #[arg(short = 'k', long, conflicts_with = "delete_remote")]
         keep_branch: bool,
+
+        /// Print the result as JSON instead of progress messages
+        #[arg(long)]
+        json: bool,
+
+        /// For a worktree whose directory is already gone: only prune git's worktree
+        /// metadata and delete the branch, leaving files and windows alone
+        #[arg(long, conflicts_with_all = ["delete_remote", "json"])]
+        prune_metadata_only: bool,
     },
 
     /// List all worktrees
     #[command(visible_alias = "ls")]
-    List,
+    List {
+        /// Show the state of each branch's pull request (requires gh)
+        #[arg(long)]
+        pr_status: bool,
+
+        /// Only show worktrees with an open window/tab
+        #[arg(long, visible_alias = "with-tabs", conflicts_with = "no_tab")]
+        open_only: bool,
+
+        /// Only show worktrees without an open window/tab
+        #[arg(long)]
+        no_tab: bool,
+
+        /// Also show worktrees hidden by `list_exclude`
+        #[arg(short = 'a', long)]
+        all: bool,
+
+        /// Add a column with the age and subject of each worktree's last commit
+        #[arg(short = 'l', long)]
+        long: bool,
+    },
+
+    /// Fetch and fast-forward worktree branches from their remotes
+    Fetch {
+        /// Name of the branch to update (defaults to all worktrees)
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: Option<String>,
+    },
+
+    /// Show how a branch compares to its base and whether it has local changes
+    Status {
+        /// Name of the branch to inspect (defaults to current branch)
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: Option<String>,
+
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the commits a branch has made since its base
+    Log {
+        /// Name of the branch to inspect (defaults to current branch)
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: Option<String>,
+
+        /// Show at most this many commits
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Show the changes a branch has committed since its base
+    Diff {
+        /// Name of the branch to inspect (defaults to current branch)
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: Option<String>,
+
+        /// Only list the names of changed files
+        #[arg(long, conflicts_with = "stat")]
+        name_only: bool,
+
+        /// Summarize changed lines per file
+        #[arg(long)]
+        stat: bool,
+    },
+
+    /// Clean up worktrees whose directories were deleted outside of workmux
+    Prune {
+        /// Close the pruned worktrees' windows without asking and also delete their local branches
+        #[arg(short, long)]
+        force: bool,
+    },
 
     /// Generate example .workmux.yaml configuration file
     Init,
@@ -186,11 +403,46 @@ enum Commands {
         command: ClaudeCommands,
     },
 
+    /// Inspect the workmux configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Run a command in a branch's worktree without switching to it
+    Exec {
+        /// Branch whose worktree to run the command in
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: String,
+
+        /// Command and arguments to run, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Print the workmux version (with -v, also git, zellij, tmux, gh, and the backend in use)
+    Version,
+
     /// Generate shell completions
     Completions {
-        /// The shell to generate completions for
+        /// The shell to generate completions for (defaults to the one in $SHELL)
         #[arg(value_enum)]
-        shell: Shell,
+        shell: Option<Shell>,
+
+        /// Write the completions where the shell loads them from, instead of printing them
+        #[arg(long)]
+        install: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the configuration in effect: global and project files merged, with
+    /// defaults, the main branch, and the window prefix resolved
+    Show {
+        /// Print JSON instead of YAML
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -198,25 +450,47 @@ enum Commands {
 enum ClaudeCommands {
     /// Remove stale entries from ~/.claude.json for deleted worktrees
     Prune,
+
+    /// Copy the main worktree's ~/.claude.json settings to a branch's worktree
+    Import {
+        /// Branch whose worktree should receive the settings
+        #[arg(value_parser = WorktreeBranchParser::new())]
+        branch_name: String,
+    },
 }
 
 // --- Public Entry Point ---
-pub fn run() -> Result<()> {
-    let cli = Cli::parse();
+/// Parse the command line, exiting with usage help on errors
+pub fn parse() -> Cli {
+    Cli::try_parse().unwrap_or_else(|e| explain_conflict(e).exit())
+}
+
+pub fn run(cli: Cli) -> Result<()> {
+    let overrides = CliOverrides {
+        no_prefix: cli.no_prefix,
+        zellij_session: cli.zellij_session,
+        verbose: cli.verbose > 0,
+        quiet: cli.quiet,
+        yes: cli.yes,
+    };
+    if let Some(session) = &overrides.zellij_session {
+        crate::zellij::ensure_session_exists(session)?;
+    }
 
     match cli.command {
         Commands::Add {
             branch_name,
             pr,
-            base,
+            branch,
             prompt,
             setup,
             rescue,
             multi,
         } => command::add::run(
+            &overrides,
             branch_name.as_deref(),
             pr,
-            base.as_deref(),
+            branch,
             prompt,
             setup,
             rescue,
@@ -224,45 +498,212 @@ pub fn run() -> Result<()> {
         ),
         Commands::Open {
             branch_name,
+            all,
+            jobs,
+            run_hooks,
+            force_files,
+            select_pane,
+        } => command::open::run(
+            &overrides,
+            branch_name.as_deref(),
+            all,
+            jobs.get(),
             run_hooks,
             force_files,
-        } => command::open::run(&branch_name, run_hooks, force_files),
+            select_pane,
+        ),
         Commands::Merge {
             branch_name,
+            target,
+            target_path,
             ignore_uncommitted,
+            stash,
             delete_remote,
+            force,
             rebase,
             squash,
             keep,
+            open,
+            preview,
+            force_dirty_target,
+            create_target,
+            json,
         } => command::merge::run(
+            &overrides,
             branch_name.as_deref(),
-            ignore_uncommitted,
-            delete_remote,
-            rebase,
-            squash,
-            keep,
+            target.as_deref(),
+            target_path.as_deref(),
+            MergeOptions {
+                ignore_uncommitted,
+                delete_remote,
+                rebase,
+                squash,
+                keep,
+                preview,
+                stash,
+                open,
+                force_dirty_target,
+                create_target,
+            },
+            force,
+            json,
         ),
         Commands::Remove {
-            branch_name,
+            branch_names,
             force,
             delete_remote,
             keep_branch,
-        } => command::remove::run(branch_name.as_deref(), force, delete_remote, keep_branch),
-        Commands::List => command::list::run(),
+            json,
+            prune_metadata_only,
+        } => {
+            if prune_metadata_only {
+                command::remove::run_prune_metadata_only(&branch_names, force, keep_branch)
+            } else {
+                command::remove::run(
+                    &overrides,
+                    &branch_names,
+                    force,
+                    delete_remote,
+                    keep_branch,
+                    json,
+                )
+            }
+        }
+        Commands::List {
+            pr_status,
+            open_only,
+            no_tab,
+            all,
+            long,
+        } => command::list::run(
+            &overrides,
+            pr_status,
+            long,
+            command::list::ListFilter {
+                open_only,
+                no_tab,
+                all,
+            },
+        ),
+        Commands::Fetch { branch_name } => command::fetch::run(&overrides, branch_name.as_deref()),
+        Commands::Status { branch_name, json } => {
+            command::status::run(&overrides, branch_name.as_deref(), json)
+        }
+        Commands::Log { branch_name, limit } => {
+            command::log::run(&overrides, branch_name.as_deref(), limit)
+        }
+        Commands::Diff {
+            branch_name,
+            name_only,
+            stat,
+        } => {
+            let format = if name_only {
+                git::DiffFormat::NameOnly
+            } else if stat {
+                git::DiffFormat::Stat
+            } else {
+                git::DiffFormat::Patch
+            };
+            command::diff::run(&overrides, branch_name.as_deref(), format)
+        }
+        Commands::Prune { force } => command::prune::run(&overrides, force),
         Commands::Init => crate::config::Config::init(),
         Commands::Claude { command } => match command {
             ClaudeCommands::Prune => prune_claude_config(),
+            ClaudeCommands::Import { branch_name } => import_claude_config(&branch_name),
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Show { json } => command::config::show(&overrides, json),
         },
-        Commands::Completions { shell } => {
-            let mut cmd = Cli::command();
-            let name = cmd.get_name().to_string();
-            generate(shell, &mut cmd, name, &mut io::stdout());
-            Ok(())
+        Commands::Exec {
+            branch_name,
+            command: exec_args,
+        } => command::exec::run(&branch_name, &exec_args),
+        Commands::Version => command::version::run(&overrides, cli.verbose > 0),
+        Commands::Completions { shell, install } => {
+            command::completions::run(shell, install, Cli::command())
         }
     }
 }
 
+/// Add the reason to clap's conflict error for `--keep`/`--keep-branch` with
+/// `--delete-remote`, which otherwise only says the flags can't be combined
+fn explain_conflict(mut err: clap::Error) -> clap::Error {
+    if err.kind() != ErrorKind::ArgumentConflict {
+        return err;
+    }
+
+    let mut args = Vec::new();
+    for kind in [ContextKind::InvalidArg, ContextKind::PriorArg] {
+        match err.get(kind) {
+            Some(ContextValue::String(arg)) => args.push(arg.clone()),
+            Some(ContextValue::Strings(values)) => args.extend(values.iter().cloned()),
+            _ => {}
+        }
+    }
+
+    let delete_remote = args.iter().any(|arg| arg.starts_with("--delete-remote"));
+    let keep = args.iter().any(|arg| arg.starts_with("--keep"));
+    if delete_remote && keep {
+        err.insert(
+            ContextKind::Suggested,
+            ContextValue::StyledStrs(vec![
+                "a remote branch can't be deleted while the local branch is kept; \
+                 to drop only the remote, run `git push origin --delete <branch>`"
+                    .into(),
+            ]),
+        );
+    }
+    err
+}
+
 fn prune_claude_config() -> Result<()> {
     claude::prune_stale_entries().context("Failed to prune Claude configuration")?;
     Ok(())
 }
+
+fn import_claude_config(branch_name: &str) -> Result<()> {
+    let main_root =
+        git::get_main_worktree_root().context("Could not find the main git worktree")?;
+    let worktree_path = git::get_worktree_path(branch_name).with_context(|| {
+        format!(
+            "No worktree found for branch '{}'. Use 'workmux add {}' to create it.",
+            branch_name, branch_name
+        )
+    })?;
+    claude::import_project_settings(&main_root, &worktree_path)
+        .context("Failed to import Claude configuration")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict_message(args: &[&str]) -> String {
+        match Cli::try_parse_from(args) {
+            Ok(_) => panic!("expected {:?} to be rejected", args),
+            Err(err) => explain_conflict(err).to_string(),
+        }
+    }
+
+    #[test]
+    fn explains_remove_keep_branch_with_delete_remote() {
+        let message = conflict_message(&["workmux", "remove", "feat", "-k", "-r"]);
+        assert!(message.contains("cannot be used with"));
+        assert!(message.contains("can't be deleted while the local branch is kept"));
+    }
+
+    #[test]
+    fn explains_merge_keep_with_delete_remote() {
+        let message = conflict_message(&["workmux", "merge", "--delete-remote", "--keep"]);
+        assert!(message.contains("can't be deleted while the local branch is kept"));
+    }
+
+    #[test]
+    fn leaves_other_conflicts_alone() {
+        let message = conflict_message(&["workmux", "merge", "--stash", "--ignore-uncommitted"]);
+        assert!(message.contains("cannot be used with"));
+        assert!(!message.contains("tip:"));
+    }
+}
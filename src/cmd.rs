@@ -1,13 +1,54 @@
 use anyhow::{Context, Result, anyhow};
+use std::io::{self, Read};
 use std::path::Path;
-use std::process::{Command, Output};
-use tracing::{debug, trace};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, trace, warn};
+
+/// Default timeout for network-facing commands (git fetch/push, gh), in seconds
+pub const DEFAULT_NETWORK_TIMEOUT_SECS: u64 = 60;
+
+/// How long a timed-out shell command gets to exit after SIGTERM before SIGKILL
+const TERMINATE_GRACE: Duration = Duration::from_secs(5);
+
+/// Error returned when a command is killed for exceeding its timeout
+#[derive(Debug, thiserror::Error)]
+#[error("Command timed out after {}s: {command}", timeout.as_secs_f64())]
+pub struct CommandTimeout {
+    pub command: String,
+    pub timeout: Duration,
+}
+
+/// Whether a failed command's stderr points at a network blip worth retrying
+pub fn is_transient_network_error(stderr: &str) -> bool {
+    const TRANSIENT: &[&str] = &[
+        "Could not resolve host",
+        "Connection timed out",
+        "Connection refused",
+        "Connection reset by peer",
+        "Operation timed out",
+        "The remote end hung up unexpectedly",
+        "early EOF",
+    ];
+    TRANSIENT.iter().any(|needle| stderr.contains(needle))
+}
+
+/// How a failed command is retried, see [`Cmd::with_retries`]
+#[derive(Clone, Copy)]
+struct Retry {
+    retries: u32,
+    base: Duration,
+    is_retryable: fn(&str) -> bool,
+}
 
 /// A builder for executing shell commands with unified error handling
 pub struct Cmd<'a> {
     command: &'a str,
     args: Vec<&'a str>,
     workdir: Option<&'a Path>,
+    timeout: Option<Duration>,
+    retry: Option<Retry>,
 }
 
 impl<'a> Cmd<'a> {
@@ -17,6 +58,8 @@ impl<'a> Cmd<'a> {
             command,
             args: Vec::new(),
             workdir: None,
+            timeout: None,
+            retry: None,
         }
     }
 
@@ -38,25 +81,90 @@ impl<'a> Cmd<'a> {
         self
     }
 
+    /// Kill the command and return a [`CommandTimeout`] error if it runs longer than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry [`Cmd::run`] up to `retries` more times when the command fails and
+    /// `is_retryable` accepts its stderr, waiting `base`, `2 * base`, `4 * base`, ...
+    /// between attempts
+    pub fn with_retries(
+        mut self,
+        retries: u32,
+        base: Duration,
+        is_retryable: fn(&str) -> bool,
+    ) -> Self {
+        self.retry = Some(Retry {
+            retries,
+            base,
+            is_retryable,
+        });
+        self
+    }
+
     /// Execute the command and return the output
     /// Returns an error if the command fails (non-zero exit code)
     pub fn run(self) -> Result<Output> {
+        self.run_reporting(false)
+    }
+
+    /// Execute the command and return its stdout and stderr as one trimmed string.
+    /// On failure the error carries both streams, since some commands (e.g. the
+    /// `CONFLICT` lines of `git merge`) explain themselves on stdout.
+    pub fn run_and_capture_combined(self) -> Result<String> {
+        let output = self.run_reporting(true)?;
+        Ok(combined_output(&output))
+    }
+
+    /// Shared body of [`Cmd::run`]: `stdout_in_error` adds stdout to the error of
+    /// a failed command, which otherwise only carries stderr
+    fn run_reporting(self, stdout_in_error: bool) -> Result<Output> {
         let Cmd {
             command,
             args,
             workdir,
+            timeout,
+            retry,
         } = self;
         let workdir_display = workdir.map(|p| p.display().to_string());
 
         trace!(command, args = ?args, workdir = ?workdir_display, "cmd:run start");
 
-        let mut cmd = Command::new(command);
-        if let Some(dir) = workdir {
-            cmd.current_dir(dir);
-        }
-        let output = cmd.args(&args).output().with_context(|| {
-            format!("Failed to execute command: {} {}", command, args.join(" "))
-        })?;
+        let mut attempt = 0;
+        let output = loop {
+            let mut cmd = Command::new(command);
+            if let Some(dir) = workdir {
+                cmd.current_dir(dir);
+            }
+            cmd.args(&args);
+            let output = output_with_timeout(&mut cmd, timeout).with_context(|| {
+                format!("Failed to execute command: {} {}", command, args.join(" "))
+            })?;
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match retry {
+                Some(retry)
+                    if !output.status.success()
+                        && attempt < retry.retries
+                        && (retry.is_retryable)(&stderr) =>
+                {
+                    let delay = retry.base.saturating_mul(1 << attempt.min(16));
+                    attempt += 1;
+                    warn!(
+                        command,
+                        args = ?args,
+                        attempt,
+                        delay = ?delay,
+                        stderr = %stderr.trim(),
+                        "cmd:run transient failure, retrying"
+                    );
+                    thread::sleep(delay);
+                }
+                _ => break output,
+            }
+        };
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -67,17 +175,44 @@ impl<'a> Cmd<'a> {
                 stderr = %stderr.trim(),
                 "cmd:run failure"
             );
+            let details = if stdout_in_error {
+                combined_output(&output)
+            } else {
+                stderr.trim().to_string()
+            };
             return Err(anyhow!(
                 "Command failed: {} {}\n{}",
                 command,
                 args.join(" "),
-                stderr.trim()
+                details
             ));
         }
         trace!(command, "cmd:run success");
         Ok(output)
     }
 
+    /// Execute the command with inherited stdin/stdout/stderr and return its exit
+    /// status. Unlike [`Cmd::run`], a non-zero exit is not an error.
+    pub fn status(self) -> Result<ExitStatus> {
+        let Cmd {
+            command,
+            args,
+            workdir,
+            timeout: _,
+            retry: _,
+        } = self;
+
+        trace!(command, args = ?args, workdir = ?workdir.map(Path::display), "cmd:status start");
+
+        let mut cmd = Command::new(command);
+        if let Some(dir) = workdir {
+            cmd.current_dir(dir);
+        }
+        cmd.args(&args)
+            .status()
+            .with_context(|| format!("Failed to execute command: {} {}", command, args.join(" ")))
+    }
+
     /// Execute the command and return stdout as a trimmed string
     pub fn run_and_capture_stdout(self) -> Result<String> {
         let output = self.run()?;
@@ -91,6 +226,8 @@ impl<'a> Cmd<'a> {
             command,
             args,
             workdir,
+            timeout,
+            retry: _,
         } = self;
         let workdir_display = workdir.map(|p| p.display().to_string());
         trace!(command, args = ?args, workdir = ?workdir_display, "cmd:check start");
@@ -99,7 +236,8 @@ impl<'a> Cmd<'a> {
         if let Some(dir) = workdir {
             cmd.current_dir(dir);
         }
-        let output = cmd.args(&args).output().with_context(|| {
+        cmd.args(&args);
+        let output = output_with_timeout(&mut cmd, timeout).with_context(|| {
             format!("Failed to execute command: {} {}", command, args.join(" "))
         })?;
 
@@ -109,15 +247,199 @@ impl<'a> Cmd<'a> {
     }
 }
 
+/// A command's stdout followed by its stderr, trimmed, skipping empty streams
+fn combined_output(output: &Output) -> String {
+    [&output.stdout, &output.stderr]
+        .into_iter()
+        .map(|stream| String::from_utf8_lossy(stream).trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run a command to completion and collect its output, like [`Command::output`].
+///
+/// With a timeout, the child is killed once the deadline passes and an
+/// `io::ErrorKind::TimedOut` error wrapping [`CommandTimeout`] is returned.
+pub fn output_with_timeout(cmd: &mut Command, timeout: Option<Duration>) -> io::Result<Output> {
+    let Some(timeout) = timeout else {
+        return cmd.output();
+    };
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain the pipes on separate threads so a chatty child can't block on a full pipe
+    let stdout = spawn_reader(child.stdout.take());
+    let stderr = spawn_reader(child.stderr.take());
+
+    let status = match wait_until(&mut child, Instant::now() + timeout)? {
+        Some(status) => status,
+        None => {
+            let command = describe(cmd);
+            warn!(command, timeout = ?timeout, "cmd:timeout, killing process");
+            // The child may exit on its own between the last poll and the kill
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                CommandTimeout { command, timeout },
+            ));
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Poll the child until it exits or the deadline passes
+fn wait_until(
+    child: &mut Child,
+    deadline: Instant,
+) -> io::Result<Option<std::process::ExitStatus>> {
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn describe(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Helper to create a shell command that runs in a shell
 pub fn shell_command(command: &str, workdir: &Path) -> Result<()> {
+    shell_command_with_env(command, workdir, &[])
+}
+
+/// Like [`shell_command`], with extra environment variables set for the command
+pub fn shell_command_with_env(command: &str, workdir: &Path, envs: &[(&str, &str)]) -> Result<()> {
     let mut cmd = Command::new("sh");
-    cmd.arg("-c").arg(command).current_dir(workdir);
+    cmd.arg("-c")
+        .arg(command)
+        .current_dir(workdir)
+        .envs(envs.iter().copied());
 
     let status = cmd
         .status()
         .with_context(|| format!("Failed to execute shell command: {}", command))?;
 
+    check_shell_status(status, command)
+}
+
+/// Like [`shell_command`], but appends the command's stdout and stderr
+/// (interleaved) to `output` instead of writing them to the terminal
+pub fn shell_command_captured(command: &str, workdir: &Path, output: &mut String) -> Result<()> {
+    let result = Command::new("sh")
+        .arg("-c")
+        .arg(format!("exec 2>&1\n{}", command))
+        .current_dir(workdir)
+        .output()
+        .with_context(|| format!("Failed to execute shell command: {}", command))?;
+    output.push_str(&String::from_utf8_lossy(&result.stdout));
+
+    check_shell_status(result.status, command)
+}
+
+/// Like [`shell_command`], or [`shell_command_captured`] when given `output`,
+/// but a command still running after `timeout` is stopped and fails with a
+/// [`CommandTimeout`] error. Without a timeout this is the plain variant.
+pub fn shell_command_with_timeout(
+    command: &str,
+    workdir: &Path,
+    output: Option<&mut String>,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let Some(timeout) = timeout else {
+        return match output {
+            Some(output) => shell_command_captured(command, workdir, output),
+            None => shell_command(command, workdir),
+        };
+    };
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").current_dir(workdir);
+    if output.is_some() {
+        cmd.arg(format!("exec 2>&1\n{}", command))
+            .stdout(Stdio::piped());
+    } else {
+        cmd.arg(command);
+    }
+    // A process group of its own, so whatever the shell started (e.g. the
+    // node processes of `pnpm install`) is stopped along with it
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to execute shell command: {}", command))?;
+    let stdout = spawn_reader(child.stdout.take());
+
+    let status = wait_until(&mut child, Instant::now() + timeout)?;
+    if status.is_none() {
+        warn!(command, timeout = ?timeout, "cmd:timeout, terminating process group");
+        terminate_group(&mut child, TERMINATE_GRACE);
+    }
+    // What a hung command printed before it was stopped helps tell why
+    if let Some(output) = output {
+        output.push_str(&String::from_utf8_lossy(&stdout.join().unwrap_or_default()));
+    }
+
+    match status {
+        Some(status) => check_shell_status(status, command),
+        None => Err(CommandTimeout {
+            command: command.to_string(),
+            timeout,
+        }
+        .into()),
+    }
+}
+
+/// Send SIGTERM to the process group led by `child`, then SIGKILL whatever is
+/// left once `child` has exited or `grace` has passed
+fn terminate_group(child: &mut Child, grace: Duration) {
+    let group = format!("-{}", child.id());
+    let signal = |name: &str| {
+        let _ = Command::new("kill")
+            .args([name, "--", &group])
+            .stderr(Stdio::null())
+            .status();
+    };
+
+    signal("-TERM");
+    let _ = wait_until(child, Instant::now() + grace);
+    signal("-KILL");
+    let _ = child.wait();
+}
+
+fn check_shell_status(status: ExitStatus, command: &str) -> Result<()> {
     if !status.success() {
         return Err(anyhow!(
             "Shell command failed with exit code {}: {}",
@@ -127,3 +449,202 @@ pub fn shell_command(command: &str, workdir: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_command_with_timeout_stops_hung_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut output = String::new();
+        let start = Instant::now();
+        let err = shell_command_with_timeout(
+            "echo started; sleep 5; touch finished",
+            dir.path(),
+            Some(&mut output),
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap_err();
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        let timeout = err.downcast_ref::<CommandTimeout>().expect("timeout error");
+        assert_eq!(timeout.timeout, Duration::from_millis(200));
+        assert!(err.to_string().contains("sleep 5"));
+        assert_eq!(output, "started\n");
+        assert!(!dir.path().join("finished").exists());
+    }
+
+    #[test]
+    fn test_shell_command_with_timeout_runs_fast_commands_normally() {
+        let dir = tempfile::tempdir().unwrap();
+        let timeout = Some(Duration::from_secs(5));
+        shell_command_with_timeout("touch done", dir.path(), None, timeout).unwrap();
+        assert!(dir.path().join("done").exists());
+
+        let err = shell_command_with_timeout("exit 3", dir.path(), None, timeout).unwrap_err();
+        assert!(err.to_string().contains("exit code 3"));
+
+        let mut output = String::new();
+        shell_command_with_timeout("echo err >&2", dir.path(), Some(&mut output), None).unwrap();
+        assert_eq!(output, "err\n");
+    }
+
+    #[test]
+    fn test_shell_command_captured_collects_stdout_and_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut output = String::new();
+        shell_command_captured("echo out; echo err >&2", dir.path(), &mut output).unwrap();
+        assert_eq!(output, "out\nerr\n");
+
+        let err =
+            shell_command_captured("echo failing; exit 2", dir.path(), &mut output).unwrap_err();
+        assert!(err.to_string().contains("exit code 2"));
+        assert!(output.ends_with("failing\n"));
+    }
+
+    #[test]
+    fn test_shell_command_with_env_exports_variables() {
+        let dir = std::env::temp_dir();
+        let check = r#"test "$WORKMUX_BRANCH" = "feature x" && test -n "$PATH""#;
+        shell_command_with_env(check, &dir, &[("WORKMUX_BRANCH", "feature x")]).unwrap();
+        assert!(shell_command(check, &dir).is_err());
+    }
+
+    /// Fails with `stderr` until its third run, counting runs in `counter`
+    fn flaky_script(counter: &Path, stderr: &str) -> String {
+        format!(
+            "n=$(($(cat '{0}' 2>/dev/null || echo 0) + 1)); echo $n > '{0}'; \
+             [ $n -ge 3 ] || {{ echo '{1}' >&2; exit 1; }}; echo done",
+            counter.display(),
+            stderr
+        )
+    }
+
+    #[test]
+    fn test_retries_transient_failures_until_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let counter = dir.path().join("runs");
+        let script = flaky_script(&counter, "fatal: Could not resolve host: example.com");
+
+        let stdout = Cmd::new("sh")
+            .args(&["-c", &script])
+            .with_retries(3, Duration::from_millis(1), is_transient_network_error)
+            .run_and_capture_stdout()
+            .unwrap();
+
+        assert_eq!(stdout, "done");
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "3");
+    }
+
+    #[test]
+    fn test_retries_stop_after_limit_or_on_permanent_failure() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let counter = dir.path().join("limited");
+        let script = flaky_script(&counter, "fatal: Could not resolve host: example.com");
+        let err = Cmd::new("sh")
+            .args(&["-c", &script])
+            .with_retries(1, Duration::from_millis(1), is_transient_network_error)
+            .run()
+            .unwrap_err();
+        assert!(err.to_string().contains("Could not resolve host"));
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "2");
+
+        let counter = dir.path().join("permanent");
+        let script = flaky_script(&counter, "error: remote ref does not exist");
+        assert!(
+            Cmd::new("sh")
+                .args(&["-c", &script])
+                .with_retries(3, Duration::from_millis(1), is_transient_network_error)
+                .run()
+                .is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "1");
+
+        let counter = dir.path().join("custom");
+        let script = flaky_script(&counter, "error: remote ref does not exist");
+        Cmd::new("sh")
+            .args(&["-c", &script])
+            .with_retries(3, Duration::from_millis(1), |stderr| {
+                stderr.contains("remote ref")
+            })
+            .run()
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().trim(), "3");
+    }
+
+    #[test]
+    fn test_timeout_kills_slow_command() {
+        let started = Instant::now();
+        let err = Cmd::new("sleep")
+            .arg("5")
+            .timeout(Duration::from_millis(200))
+            .run()
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(3));
+        let timeout = err
+            .root_cause()
+            .downcast_ref::<io::Error>()
+            .and_then(|e| e.get_ref())
+            .and_then(|inner| inner.downcast_ref::<CommandTimeout>())
+            .expect("expected a CommandTimeout error");
+        assert_eq!(timeout.command, "sleep 5");
+        assert_eq!(timeout.timeout, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_timeout_captures_output_of_fast_command() {
+        let stdout = Cmd::new("echo")
+            .arg("hello")
+            .timeout(Duration::from_secs(5))
+            .run_and_capture_stdout()
+            .unwrap();
+        assert_eq!(stdout, "hello");
+    }
+
+    #[test]
+    fn test_run_and_capture_combined_reports_both_streams() {
+        let output = Cmd::new("sh")
+            .args(&["-c", "echo out; echo err >&2"])
+            .run_and_capture_combined()
+            .unwrap();
+        assert_eq!(output, "out\nerr");
+
+        let err = Cmd::new("sh")
+            .args(&[
+                "-c",
+                "echo 'CONFLICT (content)'; echo 'fatal: no way' >&2; exit 1",
+            ])
+            .run_and_capture_combined()
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Command failed: sh -c"));
+        assert!(err.contains("CONFLICT (content)\nfatal: no way"));
+    }
+
+    #[test]
+    fn test_run_error_includes_stderr() {
+        let err = Cmd::new("sh")
+            .args(&[
+                "-c",
+                "echo noise; echo 'fatal: invalid reference: nope' >&2; exit 128",
+            ])
+            .run()
+            .unwrap_err()
+            .to_string();
+        // Only stderr follows the command line; stdout is left out
+        let (_, details) = err.split_once('\n').unwrap();
+        assert_eq!(details, "fatal: invalid reference: nope");
+    }
+
+    #[test]
+    fn test_timeout_reports_failure_status() {
+        let ok = Cmd::new("false")
+            .timeout(Duration::from_secs(5))
+            .run_as_check()
+            .unwrap();
+        assert!(!ok);
+    }
+}
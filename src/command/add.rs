@@ -1,25 +1,27 @@
+use crate::config::CliOverrides;
+use crate::github::IssueDetails;
 use crate::prompt::{
-    Prompt, PromptDocument, PromptMetadata, foreach_from_frontmatter, parse_prompt_document,
+    Prompt, PromptDocument, PromptMetadata, edit_prompt, foreach_from_frontmatter,
+    parse_prompt_document, prompt_file_from_reader, stdin_prompt_path,
 };
 use crate::template::{
-    TemplateEnv, WorktreeSpec, create_template_env, generate_worktree_specs, parse_foreach_matrix,
-    render_prompt_body,
+    TemplateEnv, WorktreeSpec, create_template_env, expand_includes, generate_worktree_specs,
+    parse_foreach_matrix, render_prompt_body,
 };
 use crate::workflow::SetupOptions;
-use crate::{config, git, workflow};
+use crate::{config, git, github, template, workflow};
 use anyhow::{Context, Result, anyhow};
-use edit::Builder;
-use git_url_parse::GitUrl;
-use git_url_parse::types::provider::GenericProvider;
 use std::collections::BTreeMap;
 
 // Re-export the arg types that are used by the CLI
-pub use super::args::{MultiArgs, PromptArgs, RescueArgs, SetupFlags};
+pub use super::args::{BranchArgs, MultiArgs, PromptArgs, RescueArgs, SetupFlags};
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
+    overrides: &CliOverrides,
     branch_name: Option<&str>,
     pr: Option<u32>,
-    base: Option<&str>,
+    branch: BranchArgs,
     prompt_args: PromptArgs,
     setup: SetupFlags,
     rescue: RescueArgs,
@@ -28,28 +30,58 @@ pub fn run(
     // Construct setup options from flags
     let mut options = SetupOptions::new(!setup.no_hooks, !setup.no_file_ops, !setup.no_pane_cmds);
     options.focus_window = !setup.background;
+    options.rewrite_agent_prompt = !prompt_args.no_prompt_rewrite;
+    options.agent_args = prompt_args.agent_arg.clone();
+    options.orphan = branch.orphan;
+    options.copy_config = setup.copy_config;
+    let base = branch.base.as_deref();
+
+    // Fetch the issue up front: it provides the prompt and, when no branch name
+    // was given, the branch name
+    let issue = match prompt_args.prompt_from_issue {
+        Some(number) => {
+            println!("Fetching issue #{}...", number);
+            let timeout = config::Config::load(overrides, None)?.network_timeout();
+            let issue = github::get_issue_details(number, timeout)
+                .with_context(|| format!("Failed to fetch details for issue #{}", number))?;
+            println!("Issue #{}: {}", issue.number, issue.title);
+            Some(issue)
+        }
+        None => None,
+    };
 
     // Handle PR checkout if --pr flag is provided
-    let (final_branch_name, remote_branch_for_pr, resolved_base_for_pr) =
-        if let Some(pr_number) = pr {
-            handle_pr_checkout(pr_number, branch_name)?
-        } else {
-            // Normal flow: use provided branch name and base
-            (
-                branch_name
-                    .expect("branch_name required when --pr not provided")
-                    .to_string(),
-                None,
-                base,
-            )
+    let (final_branch_name, pr_head, pr_checkout) = if let Some(pr_number) = pr {
+        let config = config::Config::load(overrides, None)?;
+        let (name, head, checkout) = handle_pr_checkout(pr_number, branch_name, &config)?;
+        (name, Some(head), Some(checkout))
+    } else {
+        // Normal flow: use provided branch name (or one named after the issue) and base
+        let name = match (branch_name, issue.as_ref()) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(issue)) => {
+                let config = config::Config::load(overrides, None)?;
+                issue_branch_name(config.issue_branch_template(), issue.number, &issue.title)
+            }
+            (None, None) => {
+                unreachable!("clap requires branch_name without --pr or --prompt-from-issue")
+            }
         };
+        (name, None, None)
+    };
 
-    // Use the determined branch name and override base/remote_branch if from PR
+    // Use the determined branch name; a PR branch is created from its remote branch,
+    // or already holds the PR head when it was fetched from a fork
     let branch_name = &final_branch_name;
-    let base = if remote_branch_for_pr.is_some() {
-        resolved_base_for_pr
-    } else {
-        base
+    let remote_branch_for_pr = match &pr_head {
+        Some(PrHead::Remote(remote_branch)) => Some(remote_branch.clone()),
+        _ => None,
+    };
+    let base = match &pr_head {
+        Some(PrHead::Remote(_)) => None,
+        // Extra worktrees (--count, --agent) branch off the fetched PR head
+        Some(PrHead::Local) => Some(branch_name.as_str()),
+        None => base,
     };
 
     // Validate --with-changes compatibility
@@ -61,7 +93,8 @@ pub fn run(
 
     // Handle rescue flow early if requested
     if rescue.with_changes {
-        let rescue_config = config::Config::load(multi.agent.first().map(|s| s.as_str()))?;
+        let rescue_config =
+            config::Config::load(overrides, multi.agent.first().map(|s| s.as_str()))?;
         let rescue_context = workflow::WorkflowContext::new(rescue_config)?;
         if handle_rescue_flow(branch_name, &rescue, &rescue_context, options.clone())? {
             return Ok(());
@@ -69,10 +102,10 @@ pub fn run(
     }
 
     // Load prompt from arguments
-    let prompt_template = load_prompt(&prompt_args)?;
+    let prompt_template = load_prompt(&prompt_args, issue.as_ref(), pr_checkout.as_ref())?;
 
     // Parse prompt document to extract frontmatter (if applicable)
-    let prompt_doc = if let Some(ref prompt_src) = prompt_template {
+    let mut prompt_doc = if let Some(ref prompt_src) = prompt_template {
         // Parse frontmatter from file or editor content, but skip for inline prompts
         // that didn't come from the editor (those are pure strings from -p flag)
         let should_parse_frontmatter =
@@ -94,6 +127,28 @@ pub fn run(
         None
     };
 
+    // A prompt read from stdin was staged in a temporary file only so it could
+    // be read like a prompt file; its text now lives in `prompt_doc`.
+    if prompt_args
+        .prompt_file
+        .as_deref()
+        .is_some_and(|path| path.as_os_str() == "-")
+    {
+        let _ = std::fs::remove_file(stdin_prompt_path(&std::env::temp_dir()));
+    }
+
+    // Inline `{{include "path"}}` snippets, relative to the prompt file. Issue
+    // bodies come from GitHub and must not be able to pull in local files.
+    if let Some(doc) = prompt_doc.as_mut()
+        && issue.is_none()
+    {
+        let template_path = prompt_args
+            .prompt_file
+            .as_deref()
+            .filter(|path| path.as_os_str() != "-");
+        doc.body = expand_includes(&doc.body, template_path)?;
+    }
+
     // Validate multi-worktree arguments
     if multi.count.is_some() && multi.agent.len() > 1 {
         return Err(anyhow!(
@@ -122,7 +177,7 @@ pub fn run(
     let (remote_branch, template_base_name) = if let Some(ref pr_remote) = remote_branch_for_pr {
         (Some(pr_remote.clone()), branch_name.to_string())
     } else {
-        detect_remote_branch(branch_name, base)?
+        detect_remote_branch(branch_name, base, options.orphan)?
     };
     let resolved_base = if remote_branch.is_some() { None } else { base };
 
@@ -145,12 +200,14 @@ pub fn run(
 
     // Create worktrees from specs
     create_worktrees_from_specs(
+        overrides,
         &specs,
         resolved_base,
         remote_branch.as_deref(),
         prompt_doc.as_ref(),
         options,
         &env,
+        pr_checkout.as_ref(),
     )
 }
 
@@ -184,42 +241,116 @@ fn handle_rescue_flow(
     Ok(true)
 }
 
-/// Load prompt from the provided arguments (editor, inline, or file).
-fn load_prompt(prompt_args: &PromptArgs) -> Result<Option<Prompt>> {
-    if prompt_args.prompt_editor {
-        let mut builder = Builder::new();
-        builder.suffix(".md");
-        let editor_content = edit::edit_with_builder("", &builder)
-            .context("Failed to open editor or read content")?;
-        let trimmed = editor_content.trim();
-        if trimmed.is_empty() {
-            return Err(anyhow!("Aborting: prompt is empty"));
+/// Join repeated `--prompt` values into one prompt, one value per line
+fn inline_prompt(lines: &[String]) -> Option<String> {
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Load prompt from the provided arguments (inline, file, or issue). With
+/// `--prompt-editor`, that prompt (or the PR's title) pre-fills the editor.
+fn load_prompt(
+    prompt_args: &PromptArgs,
+    issue: Option<&IssueDetails>,
+    pr: Option<&PrCheckout>,
+) -> Result<Option<Prompt>> {
+    let prompt = if let Some(issue) = issue {
+        Some(Prompt::Inline(issue_prompt(issue)))
+    } else {
+        match (
+            inline_prompt(&prompt_args.prompt),
+            prompt_args.prompt_file.as_ref(),
+        ) {
+            (Some(inline), None) => Some(Prompt::Inline(inline)),
+            (None, Some(path)) if path.as_os_str() == "-" => {
+                prompt_file_from_reader(std::io::stdin().lock(), &std::env::temp_dir())?
+                    .map(Prompt::FromFile)
+            }
+            (None, Some(path)) => Some(Prompt::FromFile(path.clone())),
+            (None, None) => None,
+            _ => None, // clap enforces exclusivity; this is unreachable
         }
-        Ok(Some(Prompt::Inline(trimmed.to_string())))
+    };
+
+    if !prompt_args.prompt_editor {
+        return Ok(prompt);
+    }
+
+    let initial = match (&prompt, pr) {
+        (Some(prompt), _) => prompt.read_text()?,
+        (None, Some(pr)) => format!("# {} (#{})\n", pr.title, pr.number),
+        (None, None) => String::new(),
+    };
+    edit_prompt(&initial).map(Some)
+}
+
+/// Build the prompt text for an issue: its title as a heading, then its body
+fn issue_prompt(issue: &IssueDetails) -> String {
+    let body = issue.body.trim();
+    if body.is_empty() {
+        format!("# {} (#{})", issue.title, issue.number)
     } else {
-        Ok(
-            match (
-                prompt_args.prompt.as_ref(),
-                prompt_args.prompt_file.as_ref(),
-            ) {
-                (Some(inline), None) => Some(Prompt::Inline(inline.clone())),
-                (None, Some(path)) => Some(Prompt::FromFile(path.clone())),
-                (None, None) => None,
-                _ => None, // clap enforces exclusivity; this is unreachable
-            },
-        )
+        format!("# {} (#{})\n\n{}", issue.title, issue.number, body)
+    }
+}
+
+/// Longest slug taken from an issue title, so branch names stay manageable
+const ISSUE_SLUG_MAX_LEN: usize = 40;
+
+/// Slugify an issue title for use in a branch name, cutting long titles at a
+/// word boundary
+fn issue_slug(title: &str) -> String {
+    let slug = template::slugify(title);
+    if slug.len() <= ISSUE_SLUG_MAX_LEN {
+        return slug;
+    }
+
+    // The slug is ASCII, so byte indices are char boundaries
+    let truncated = &slug[..ISSUE_SLUG_MAX_LEN];
+    match truncated.rfind('-') {
+        Some(idx) if idx > 0 => truncated[..idx].to_string(),
+        _ => truncated.to_string(),
     }
 }
 
-/// Detect if branch_name is a remote ref and extract the base name.
+/// Render `issue_branch_template`, substituting `{number}` and `{slug}`
+fn issue_branch_name(template: &str, number: u32, title: &str) -> String {
+    let name = template
+        .replace("{number}", &number.to_string())
+        .replace("{slug}", &issue_slug(title));
+    // An empty slug (e.g. a title of only punctuation) would leave a dangling separator
+    name.trim_matches(|c| c == '-' || c == '/').to_string()
+}
+
+/// Render `pr_branch_template`, substituting `{number}`, `{head}`, and `{author}`,
+/// and make sure the result can be used as a branch name
+fn pr_branch_name(template: &str, number: u32, head: &str, author: &str) -> Result<String> {
+    let name = template
+        .replace("{number}", &number.to_string())
+        .replace("{head}", head)
+        .replace("{author}", author);
+    if !git::is_valid_branch_name(&name) {
+        return Err(anyhow!(
+            "pr_branch_template '{}' produced '{}', which is not a valid branch name",
+            template,
+            name
+        ));
+    }
+    Ok(name)
+}
+
+/// Detect if branch_name is a remote ref and extract the base name. A plain name
+/// that only exists as a remote-tracking branch (`feature` with just
+/// `origin/feature`) is checked out from the remote too, unless `--base` or
+/// `--orphan` asked for a new branch.
 /// Returns (remote_branch, template_base_name).
-fn detect_remote_branch(branch_name: &str, base: Option<&str>) -> Result<(Option<String>, String)> {
+fn detect_remote_branch(
+    branch_name: &str,
+    base: Option<&str>,
+    orphan: bool,
+) -> Result<(Option<String>, String)> {
     let remotes = git::list_remotes().context("Failed to list git remotes")?;
-    let detected_remote = remotes
-        .iter()
-        .find(|r| branch_name.starts_with(&format!("{}/", r)));
 
-    if let Some(remote_name) = detected_remote {
+    if let Some(remote_name) = remote_prefix(branch_name, &remotes) {
         if base.is_some() {
             return Err(anyhow!(
                 "Cannot use --base with a remote branch reference. \
@@ -231,16 +362,64 @@ fn detect_remote_branch(branch_name: &str, base: Option<&str>) -> Result<(Option
         let spec = git::parse_remote_branch_spec(branch_name)
             .context("Invalid remote branch format. Use <remote>/<branch>")?;
 
-        if spec.remote != *remote_name {
+        if spec.remote != remote_name {
             return Err(anyhow!("Mismatched remote detection"));
         }
 
         Ok((Some(branch_name.to_string()), spec.branch))
+    } else if base.is_none() && !orphan && !git::branch_exists(branch_name)? {
+        let mut remotes_with_branch = Vec::new();
+        for remote in &remotes {
+            if git::remote_branch_exists(&format!("{}/{}", remote, branch_name))? {
+                remotes_with_branch.push(remote.as_str());
+            }
+        }
+        let remote_branch = remote_only_source(branch_name, &remotes_with_branch)?;
+        if let Some(remote_branch) = &remote_branch {
+            println!(
+                "Branch '{}' only exists as '{}'; creating a local branch that tracks it",
+                branch_name, remote_branch
+            );
+        }
+        Ok((remote_branch, branch_name.to_string()))
     } else {
         Ok((None, branch_name.to_string()))
     }
 }
 
+/// The remote-tracking branch to create a branch that doesn't exist locally
+/// from, given the remotes that have it: origin's copy when it has one, else
+/// the only remote that does. `None` means the branch is new.
+fn remote_only_source(branch_name: &str, remotes_with_branch: &[&str]) -> Result<Option<String>> {
+    let remote = match remotes_with_branch {
+        [] => return Ok(None),
+        _ if remotes_with_branch.contains(&"origin") => "origin",
+        [only] => only,
+        several => {
+            return Err(anyhow!(
+                "Branch '{}' exists on several remotes ({}). Use '<remote>/{}' to pick one.",
+                branch_name,
+                several.join(", "),
+                branch_name
+            ));
+        }
+    };
+    Ok(Some(format!("{}/{}", remote, branch_name)))
+}
+
+/// The remote `branch_name` starts with (`origin` for `origin/feature`), if any
+fn remote_prefix<'a>(branch_name: &str, remotes: &'a [String]) -> Option<&'a str> {
+    remotes
+        .iter()
+        .find(|remote| {
+            branch_name
+                .strip_prefix(remote.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|branch| !branch.is_empty())
+        })
+        .map(String::as_str)
+}
+
 /// Determine the effective foreach matrix from CLI or frontmatter.
 fn determine_foreach_matrix(
     multi: &MultiArgs,
@@ -260,15 +439,32 @@ fn determine_foreach_matrix(
     }
 }
 
+/// The pull request a worktree is being checked out for
+struct PrCheckout {
+    number: u32,
+    title: String,
+    /// Branch the PR merges into, used as the default `merge` target
+    base_ref: String,
+}
+
 /// Create worktrees from the provided specs.
+#[allow(clippy::too_many_arguments)]
 fn create_worktrees_from_specs(
+    overrides: &CliOverrides,
     specs: &[WorktreeSpec],
     resolved_base: Option<&str>,
     remote_branch: Option<&str>,
     prompt_doc: Option<&PromptDocument>,
     options: SetupOptions,
     env: &TemplateEnv,
+    pr: Option<&PrCheckout>,
 ) -> Result<()> {
+    // Catch branches that are already checked out before creating anything, rather
+    // than surfacing git's error partway through
+    for spec in specs {
+        workflow::ensure_no_worktree(&spec.branch_name)?;
+    }
+
     if specs.len() > 1 {
         println!("Preparing to create {} worktrees...", specs.len());
     }
@@ -284,7 +480,7 @@ fn create_worktrees_from_specs(
         }
 
         // Load config for this specific agent to ensure correct agent resolution
-        let config = config::Config::load(spec.agent.as_deref())?;
+        let config = config::Config::load(overrides, spec.agent.as_deref())?;
 
         let prompt_for_spec = if let Some(doc) = prompt_doc {
             Some(Prompt::Inline(
@@ -317,6 +513,14 @@ fn create_worktrees_from_specs(
             )
         })?;
 
+        // Remember the PR so `list --pr-status` can find it even if the local
+        // branch name differs from the PR's head branch, and so `merge` targets
+        // the PR's base branch
+        if let Some(pr) = pr {
+            git::set_branch_pr(&spec.branch_name, pr.number)?;
+            git::set_branch_merge_target(&spec.branch_name, &pr.base_ref)?;
+        }
+
         if result.post_create_hooks_run > 0 {
             println!("✓ Setup complete");
         }
@@ -329,22 +533,36 @@ fn create_worktrees_from_specs(
             println!("  Base: {}", base);
         }
         println!("  Worktree: {}", result.worktree_path.display());
+        if !options.rewrite_agent_prompt
+            && let Some(ref prompt_path) = result.prompt_file_path
+        {
+            println!("  Prompt: {}", prompt_path.display());
+        }
     }
 
     Ok(())
 }
 
+/// Where the head of a PR checked out with `--pr` comes from
+enum PrHead {
+    /// A branch on origin, e.g. `origin/feature`, to create the local branch from
+    Remote(String),
+    /// Already fetched into the local branch (PRs from forks)
+    Local,
+}
+
 /// Handle PR checkout: fetch PR details, setup remote, and return branch info
-/// Returns (local_branch_name, remote_branch, base)
+/// Returns (local_branch_name, pr_head, pr_checkout)
 fn handle_pr_checkout(
     pr_number: u32,
     custom_branch_name: Option<&str>,
-) -> Result<(String, Option<String>, Option<&'static str>)> {
+    config: &config::Config,
+) -> Result<(String, PrHead, PrCheckout)> {
     use crate::github;
 
     // Fetch PR details
     println!("Fetching PR #{}...", pr_number);
-    let pr_details = github::get_pr_details(pr_number)
+    let pr_details = github::get_pr_details(pr_number, config.network_timeout())
         .with_context(|| format!("Failed to fetch details for PR #{}", pr_number))?;
 
     // Display PR information
@@ -363,79 +581,218 @@ fn handle_pr_checkout(
         eprintln!("\n⚠️  Warning: PR #{} is a DRAFT.", pr_number);
     }
 
-    // Determine local branch name
-    // Match gh pr checkout behavior: default to the PR's actual branch name
+    // Determine if this is a fork PR
+    let current_repo_owner =
+        git::get_repo_owner().context("Failed to determine repository owner from origin remote")?;
+    let is_fork = pr_details.is_fork(&current_repo_owner);
+
+    // Determine local branch name. The default template for same-repo PRs matches
+    // gh pr checkout behavior: the PR's actual branch name
     let local_branch_name = if let Some(custom) = custom_branch_name {
         custom.to_string()
     } else {
-        pr_details.head_ref_name.clone()
+        pr_branch_name(
+            config.pr_branch_template(is_fork),
+            pr_number,
+            &pr_details.head_ref_name,
+            &pr_details.author.login,
+        )?
     };
 
-    // Determine if this is a fork PR
-    let current_repo_owner =
-        git::get_repo_owner().context("Failed to determine repository owner from origin remote")?;
+    let fork_remote = github::fork_remote_name(&pr_details);
+    let checkout = PrCheckout {
+        number: pr_number,
+        title: pr_details.title,
+        base_ref: pr_details.base_ref_name,
+    };
 
-    let remote_name = if pr_details.is_fork(&current_repo_owner) {
-        // Fork PR: need to add or update remote for the fork
-        let fork_owner = &pr_details.head_repository_owner.login;
-        let remote_name = format!("fork-{}", fork_owner);
+    if is_fork {
+        // Fork PR: GitHub mirrors the head on origin as pull/<n>/head, so fetch it
+        // straight into the local branch without fetching the whole fork
+        let owner = &pr_details.head_repository_owner.login;
+        println!(
+            "Fetching PR #{} from '{}' into '{}'...",
+            pr_number, owner, local_branch_name
+        );
+        git::fetch_pr_head(pr_number, &local_branch_name, config.network_timeout())?;
+
+        // Register the fork as a remote so the branch pushes back to the PR.
+        // The checkout is usable without it, so a conflict only warns
+        let tracked = git::get_fork_url(owner)
+            .and_then(|url| git::ensure_remote(&fork_remote, &url))
+            .and_then(|()| {
+                git::set_branch_remote(&local_branch_name, &fork_remote, &pr_details.head_ref_name)
+            });
+        match tracked {
+            Ok(()) => println!(
+                "Branch '{}' tracks '{}/{}'",
+                local_branch_name, fork_remote, pr_details.head_ref_name
+            ),
+            Err(e) => eprintln!(
+                "\n⚠️  Warning: could not set up remote '{}' for the fork: {:#}",
+                fork_remote, e
+            ),
+        }
+        return Ok((local_branch_name, PrHead::Local, checkout));
+    }
 
-        // Construct fork URL based on origin URL format, preserving host and protocol
-        let origin_url = git::get_remote_url("origin")?;
-        let parsed_url = GitUrl::parse(&origin_url).with_context(|| {
-            format!(
-                "Failed to parse origin URL for fork remote construction: {}",
-                origin_url
-            )
-        })?;
+    // Same-repo PR: fetch the PR branch from origin
+    println!(
+        "Fetching branch '{}' from 'origin'...",
+        pr_details.head_ref_name
+    );
+    git::fetch_remote("origin", config.network_timeout())
+        .context("Failed to fetch from remote 'origin'")?;
 
-        let host = parsed_url.host().unwrap_or("github.com");
-        let scheme = parsed_url.scheme().unwrap_or("ssh");
+    let remote_branch = format!("origin/{}", pr_details.head_ref_name);
+    Ok((local_branch_name, PrHead::Remote(remote_branch), checkout))
+}
 
-        let provider: GenericProvider = parsed_url
-            .provider_info()
-            .with_context(|| "Failed to extract provider info from origin URL")?;
-        let repo_name = provider.repo();
+#[cfg(test)]
+mod tests {
+    use super::{
+        inline_prompt, issue_branch_name, issue_slug, pr_branch_name, remote_only_source,
+        remote_prefix,
+    };
+    use crate::config::Config;
+
+    #[test]
+    fn remote_prefix_detects_remote_branch_names() {
+        let remotes = vec!["origin".to_string(), "upstream".to_string()];
+        assert_eq!(remote_prefix("origin/feature", &remotes), Some("origin"));
+        assert_eq!(
+            remote_prefix("upstream/fix/login", &remotes),
+            Some("upstream")
+        );
+        assert_eq!(remote_prefix("feature/origin", &remotes), None);
+        assert_eq!(remote_prefix("originals/feature", &remotes), None);
+        assert_eq!(remote_prefix("origin/", &remotes), None);
+        assert_eq!(remote_prefix("origin/feature", &[]), None);
+    }
 
-        let fork_url = match scheme {
-            "https" => format!("https://{}/{}/{}.git", host, fork_owner, repo_name),
-            "http" => format!("http://{}/{}/{}.git", host, fork_owner, repo_name),
-            _ => {
-                // SSH or other schemes
-                format!("git@{}:{}/{}.git", host, fork_owner, repo_name)
-            }
-        };
+    #[test]
+    fn remote_only_source_prefers_origin_and_rejects_ambiguity() {
+        assert_eq!(remote_only_source("feature", &[]).unwrap(), None);
+        assert_eq!(
+            remote_only_source("feature", &["upstream"])
+                .unwrap()
+                .as_deref(),
+            Some("upstream/feature")
+        );
+        assert_eq!(
+            remote_only_source("feature", &["fork", "origin"])
+                .unwrap()
+                .as_deref(),
+            Some("origin/feature")
+        );
 
-        // Check if remote exists and update URL if needed
-        if git::remote_exists(&remote_name)? {
-            let current_url = git::get_remote_url(&remote_name)?;
-            if current_url != fork_url {
-                println!("Updating remote '{}' URL...", remote_name);
-                git::set_remote_url(&remote_name, &fork_url).with_context(|| {
-                    format!("Failed to update remote for fork '{}'", fork_owner)
-                })?;
-            }
-        } else {
-            println!("Adding remote '{}' for fork...", remote_name);
-            git::add_remote(&remote_name, &fork_url)
-                .with_context(|| format!("Failed to add remote for fork '{}'", fork_owner))?;
-        }
+        let err = remote_only_source("feature", &["fork", "upstream"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("several remotes (fork, upstream)"));
+        assert!(err.contains("'<remote>/feature'"));
+    }
 
-        remote_name
-    } else {
-        // Same-repo PR: use origin
-        "origin".to_string()
-    };
+    #[test]
+    fn inline_prompt_joins_repeated_flags_with_newlines() {
+        assert_eq!(inline_prompt(&[]), None);
+        assert_eq!(
+            inline_prompt(&["Fix the bug".to_string()]).as_deref(),
+            Some("Fix the bug")
+        );
+        assert_eq!(
+            inline_prompt(&["Step one".to_string(), "Step two\nand more".to_string()]).as_deref(),
+            Some("Step one\nStep two\nand more")
+        );
+    }
 
-    // Fetch the PR branch
-    println!(
-        "Fetching branch '{}' from '{}'...",
-        pr_details.head_ref_name, remote_name
-    );
-    git::fetch_remote(&remote_name)
-        .with_context(|| format!("Failed to fetch from remote '{}'", remote_name))?;
+    #[test]
+    fn issue_slug_strips_punctuation() {
+        assert_eq!(
+            issue_slug("Fix: login fails w/ SSO (again!)"),
+            "fix-login-fails-w-sso-again"
+        );
+        assert_eq!(
+            issue_slug("  Don't crash on `null` IDs... "),
+            "don-t-crash-on-null-ids"
+        );
+    }
+
+    #[test]
+    fn issue_slug_truncates_long_titles_at_word_boundary() {
+        let slug = issue_slug("Refactor the configuration loader to support layered overrides");
+        assert_eq!(slug, "refactor-the-configuration-loader-to");
+        assert!(slug.len() <= 40);
+    }
+
+    #[test]
+    fn issue_branch_name_renders_template() {
+        assert_eq!(
+            issue_branch_name("issue-{number}-{slug}", 123, "Fix login!"),
+            "issue-123-fix-login"
+        );
+        assert_eq!(
+            issue_branch_name("fix/{slug}-{number}", 7, "Crash on start"),
+            "fix/crash-on-start-7"
+        );
+    }
+
+    #[test]
+    fn issue_branch_name_handles_empty_slug() {
+        assert_eq!(
+            issue_branch_name("issue-{number}-{slug}", 9, "???"),
+            "issue-9"
+        );
+    }
+
+    #[test]
+    fn pr_branch_name_renders_template() {
+        assert_eq!(
+            pr_branch_name("{head}", 42, "fix-login", "octocat").unwrap(),
+            "fix-login"
+        );
+        assert_eq!(
+            pr_branch_name("pr-{number}", 42, "fix-login", "octocat").unwrap(),
+            "pr-42"
+        );
+        assert_eq!(
+            pr_branch_name("review/{author}/{head}", 42, "fix-login", "octocat").unwrap(),
+            "review/octocat/fix-login"
+        );
+    }
+
+    #[test]
+    fn pr_branch_name_defaults_to_pr_number_for_forks() {
+        let config = Config::default();
+        assert_eq!(
+            pr_branch_name(config.pr_branch_template(false), 42, "fix-login", "octocat").unwrap(),
+            "fix-login"
+        );
+        // A fork's head is often `main`, which must not clash with ours
+        assert_eq!(
+            pr_branch_name(config.pr_branch_template(true), 42, "main", "forker").unwrap(),
+            "pr-42"
+        );
+
+        let config = Config {
+            pr_branch_template: Some("review/{author}/{head}".to_string()),
+            ..Config::default()
+        };
+        for is_fork in [false, true] {
+            assert_eq!(
+                pr_branch_name(config.pr_branch_template(is_fork), 42, "main", "forker").unwrap(),
+                "review/forker/main"
+            );
+        }
+    }
 
-    // Return the branch info
-    let remote_branch = format!("{}/{}", remote_name, pr_details.head_ref_name);
-    Ok((local_branch_name, Some(remote_branch), None))
+    #[test]
+    fn pr_branch_name_rejects_illegal_refs() {
+        let err = pr_branch_name("pr {number}", 42, "fix-login", "octocat").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'pr 42', which is not a valid branch name")
+        );
+        assert!(pr_branch_name("{head}..{number}", 42, "x", "octocat").is_err());
+    }
 }
@@ -2,17 +2,50 @@ use std::path::PathBuf;
 
 #[derive(clap::Args, Debug)]
 pub struct PromptArgs {
-    /// Inline prompt text to store in the new worktree
-    #[arg(short = 'p', long, conflicts_with_all = ["prompt_file", "prompt_editor"])]
-    pub prompt: Option<String>,
+    /// Inline prompt text to store in the new worktree. Repeat the flag to add
+    /// more lines, e.g. `-p "Fix the login bug" -p "Add a regression test"`.
+    #[arg(short = 'p', long, conflicts_with_all = ["prompt_file", "prompt_from_issue"])]
+    pub prompt: Vec<String>,
 
-    /// Path to a file whose contents should be used as the prompt
-    #[arg(short = 'P', long = "prompt-file", conflicts_with_all = ["prompt", "prompt_editor"])]
+    /// Path to a file whose contents should be used as the prompt, or `-` to
+    /// read the prompt from stdin
+    #[arg(short = 'P', long = "prompt-file", conflicts_with_all = ["prompt", "prompt_from_issue"])]
     pub prompt_file: Option<PathBuf>,
 
-    /// Open $EDITOR to write the prompt
-    #[arg(short = 'e', long = "prompt-editor", conflicts_with_all = ["prompt", "prompt_file"])]
+    /// Open $EDITOR to write the prompt, pre-filled from --prompt, --prompt-file,
+    /// --prompt-from-issue, or the --pr title when given
+    #[arg(short = 'e', long = "prompt-editor")]
     pub prompt_editor: bool,
+
+    /// Use a GitHub issue's title and body as the prompt. Without a branch name,
+    /// the branch is named from the issue (see `issue_branch_template`).
+    #[arg(
+        long = "prompt-from-issue",
+        value_name = "NUMBER",
+        conflicts_with = "pr"
+    )]
+    pub prompt_from_issue: Option<u32>,
+
+    /// Run the agent command verbatim instead of appending the prompt to it.
+    /// The prompt file is still written.
+    #[arg(long)]
+    pub no_prompt_rewrite: bool,
+
+    /// Append an argument to the agent command for this run, e.g.
+    /// `--agent-arg --model --agent-arg opus`. Repeat once per argument.
+    #[arg(long = "agent-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub agent_arg: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BranchArgs {
+    /// Base branch/commit/tag to branch from (defaults to current branch)
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Create the branch with no history, in an empty worktree (e.g. for gh-pages)
+    #[arg(long, conflicts_with_all = ["base", "pr", "with_changes"])]
+    pub orphan: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -25,12 +58,17 @@ pub struct SetupFlags {
     #[arg(short = 'F', long)]
     pub no_file_ops: bool,
 
+    /// Write the effective config to .workmux.local.yaml in the new worktree,
+    /// to customize settings for that worktree only
+    #[arg(long)]
+    pub copy_config: bool,
+
     /// Skip executing pane commands (panes open with plain shells)
     #[arg(short = 'C', long)]
     pub no_pane_cmds: bool,
 
     /// Create tmux window in the background (do not switch to it)
-    #[arg(short = 'b', long = "background")]
+    #[arg(short = 'b', long = "background", visible_alias = "detach")]
     pub background: bool,
 }
 
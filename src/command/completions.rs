@@ -0,0 +1,128 @@
+use anyhow::{Context, Result, anyhow};
+use clap_complete::{Shell, generate};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Print the completion script for `shell`, or with `install` write it where
+/// the shell loads completions from. Without a shell, `$SHELL` decides.
+pub fn run(shell: Option<Shell>, install: bool, mut cmd: clap::Command) -> Result<()> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => Shell::from_env().ok_or_else(|| {
+            anyhow!(
+                "Could not detect the shell from $SHELL; pass it, e.g. `workmux completions zsh`"
+            )
+        })?,
+    };
+    let name = cmd.get_name().to_string();
+
+    if !install {
+        generate(shell, &mut cmd, name, &mut io::stdout());
+        return Ok(());
+    }
+
+    let home = home::home_dir().ok_or_else(|| anyhow!("Could not determine the home directory"))?;
+    let path = install_path(
+        shell,
+        &home,
+        xdg_dir("XDG_DATA_HOME").as_deref(),
+        xdg_dir("XDG_CONFIG_HOME").as_deref(),
+    )?;
+
+    let mut script = Vec::new();
+    generate(shell, &mut cmd, name, &mut script);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create '{}'", parent.display()))?;
+    }
+    std::fs::write(&path, script)
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+
+    println!("✓ Installed {} completions to {}", shell, path.display());
+    println!("{}", setup_hint(shell));
+    Ok(())
+}
+
+/// An XDG base directory from the environment; relative or empty values are
+/// invalid per the spec and ignored
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// Where `shell` picks up completion scripts for the current user
+fn install_path(
+    shell: Shell,
+    home: &Path,
+    xdg_data_home: Option<&Path>,
+    xdg_config_home: Option<&Path>,
+) -> Result<PathBuf> {
+    let data_home = xdg_data_home.map_or_else(|| home.join(".local/share"), Path::to_path_buf);
+    let config_home = xdg_config_home.map_or_else(|| home.join(".config"), Path::to_path_buf);
+    match shell {
+        // Loaded on demand by bash-completion
+        Shell::Bash => Ok(data_home.join("bash-completion/completions/workmux")),
+        // Not a standard location; `setup_hint` says how to add it to fpath
+        Shell::Zsh => Ok(home.join(".zsh/completions/_workmux")),
+        Shell::Fish => Ok(config_home.join("fish/completions/workmux.fish")),
+        other => Err(anyhow!(
+            "Installing completions for {} isn't supported; add the output of `workmux completions {}` to your shell configuration instead",
+            other,
+            other
+        )),
+    }
+}
+
+/// What the user still has to do for the installed script to be picked up
+fn setup_hint(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => {
+            "Completions load automatically in new shells if bash-completion is installed."
+        }
+        Shell::Zsh => {
+            "Add this to your ~/.zshrc before `compinit` runs, then start a new shell:\n  fpath=(~/.zsh/completions $fpath)\n  autoload -Uz compinit && compinit"
+        }
+        _ => "Completions load automatically in new shells.",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_path_per_shell() {
+        let home = Path::new("/home/me");
+
+        assert_eq!(
+            install_path(Shell::Bash, home, None, None).unwrap(),
+            PathBuf::from("/home/me/.local/share/bash-completion/completions/workmux")
+        );
+        assert_eq!(
+            install_path(Shell::Zsh, home, None, None).unwrap(),
+            PathBuf::from("/home/me/.zsh/completions/_workmux")
+        );
+        assert_eq!(
+            install_path(Shell::Fish, home, None, None).unwrap(),
+            PathBuf::from("/home/me/.config/fish/completions/workmux.fish")
+        );
+        assert!(install_path(Shell::PowerShell, home, None, None).is_err());
+
+        // XDG directories take precedence where the shell honors them
+        let data = Path::new("/xdg/data");
+        let config = Path::new("/xdg/config");
+        assert_eq!(
+            install_path(Shell::Bash, home, Some(data), Some(config)).unwrap(),
+            PathBuf::from("/xdg/data/bash-completion/completions/workmux")
+        );
+        assert_eq!(
+            install_path(Shell::Fish, home, Some(data), Some(config)).unwrap(),
+            PathBuf::from("/xdg/config/fish/completions/workmux.fish")
+        );
+        assert_eq!(
+            install_path(Shell::Zsh, home, Some(data), Some(config)).unwrap(),
+            PathBuf::from("/home/me/.zsh/completions/_workmux")
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use crate::config::{CliOverrides, Config};
+use crate::{git, multiplexer, template};
+use anyhow::Result;
+
+/// Print the effective configuration: global and project files merged, with
+/// defaults and values derived from the repository filled in
+pub fn show(overrides: &CliOverrides, json: bool) -> Result<()> {
+    let mut config = Config::load(overrides, None)?.with_defaults();
+
+    if config.main_branch.is_none() {
+        config.main_branch = git::get_default_branch().ok();
+    }
+    if let Ok(main_root) = git::get_main_worktree_root() {
+        config.window_prefix = Some(template::render_window_prefix(
+            config.window_prefix(),
+            &main_root,
+        ));
+    }
+    config.multiplexer = Some(multiplexer::resolve_kind(config.multiplexer()));
+
+    let output = if json {
+        serde_json::to_string_pretty(&config)?
+    } else {
+        serde_yaml::to_string(&config)?
+    };
+    println!("{}", output.trim_end());
+    Ok(())
+}
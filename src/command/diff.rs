@@ -0,0 +1,22 @@
+use crate::config::CliOverrides;
+use crate::git::DiffFormat;
+use crate::workflow::WorkflowContext;
+use crate::{config, git};
+use anyhow::{Context, Result};
+
+pub fn run(overrides: &CliOverrides, branch_name: Option<&str>, format: DiffFormat) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let branch = super::resolve_branch(branch_name, "diff")?;
+    let context = WorkflowContext::new(config)?;
+
+    let worktree_path = git::get_worktree_path(&branch)
+        .with_context(|| format!("No worktree found for branch '{}'", branch))?;
+    let base = context.branch_base(&branch);
+    let diff = git::diff(&worktree_path, &base, &branch, format)?;
+
+    if !diff.is_empty() {
+        println!("{}", diff);
+    }
+
+    Ok(())
+}
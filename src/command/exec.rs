@@ -0,0 +1,72 @@
+use crate::cmd::Cmd;
+use crate::git;
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+
+/// Run `command` in the worktree for `branch_name`, streaming its output and
+/// exiting with its exit code. No window or tab is touched.
+pub fn run(branch_name: &str, command: &[String]) -> Result<()> {
+    let worktree_path = git::get_worktree_path(branch_name).with_context(|| {
+        format!(
+            "No worktree found for branch '{}'. Use 'workmux add {}' to create it.",
+            branch_name, branch_name
+        )
+    })?;
+
+    let status = exec_command(command, &worktree_path)?.status()?;
+    if !status.success() {
+        // Killed by a signal has no code; report it as a generic failure
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Build the command to run: the first word is the program, the rest its
+/// arguments, run from `worktree_path`
+fn exec_command<'a>(command: &'a [String], worktree_path: &'a Path) -> Result<Cmd<'a>> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("No command given to run"))?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    Ok(Cmd::new(program).args(&args).workdir(worktree_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::exec_command;
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn exec_command_runs_in_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let command = words(&["pwd"]);
+        let output = exec_command(&command, dir.path())
+            .unwrap()
+            .run_and_capture_stdout()
+            .unwrap();
+        assert_eq!(
+            std::path::Path::new(&output).canonicalize().unwrap(),
+            dir.path().canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn exec_command_passes_arguments_verbatim() {
+        let dir = tempfile::tempdir().unwrap();
+        let command = words(&["printf", "%s|", "two words", "--flag"]);
+        let output = exec_command(&command, dir.path())
+            .unwrap()
+            .run_and_capture_stdout()
+            .unwrap();
+        assert_eq!(output, "two words|--flag|");
+    }
+
+    #[test]
+    fn exec_command_requires_a_program() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(exec_command(&[], dir.path()).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::config::{self, CliOverrides};
+use crate::git::FastForwardOutcome;
+use crate::workflow;
+use anyhow::{Result, anyhow};
+use pathdiff::diff_paths;
+use tabled::{
+    Table, Tabled,
+    settings::{Padding, Style, object::Columns},
+};
+
+#[derive(Tabled)]
+struct FetchRow {
+    #[tabled(rename = "BRANCH")]
+    branch: String,
+    #[tabled(rename = "STATUS")]
+    status: String,
+    #[tabled(rename = "PATH")]
+    path_str: String,
+}
+
+pub fn run(overrides: &CliOverrides, branch_name: Option<&str>) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let results = workflow::fetch(branch_name, config.network_timeout())?;
+
+    if results.is_empty() {
+        println!("No worktrees found");
+        return Ok(());
+    }
+
+    let current_dir = std::env::current_dir()?;
+    let mut failures = 0;
+
+    let display_data: Vec<FetchRow> = results
+        .into_iter()
+        .map(|result| {
+            let status = match &result.outcome {
+                Ok(outcome) => format_outcome(outcome),
+                Err(e) => {
+                    failures += 1;
+                    // Only the top-level context; the full chain goes to the log.
+                    format!("failed: {}", e)
+                }
+            };
+            let path_str = diff_paths(&result.path, &current_dir)
+                .map(|p| {
+                    let s = p.display().to_string();
+                    if s.is_empty() || s == "." {
+                        "(here)".to_string()
+                    } else {
+                        s
+                    }
+                })
+                .unwrap_or_else(|| result.path.display().to_string());
+
+            FetchRow {
+                branch: result.branch,
+                status,
+                path_str,
+            }
+        })
+        .collect();
+
+    let mut table = Table::new(display_data);
+    table
+        .with(Style::blank())
+        .modify(Columns::new(0..2), Padding::new(0, 1, 0, 0));
+
+    println!("{table}");
+
+    if failures > 0 {
+        return Err(anyhow!("Failed to update {} worktree(s)", failures));
+    }
+
+    Ok(())
+}
+
+fn format_outcome(outcome: &FastForwardOutcome) -> String {
+    match outcome {
+        FastForwardOutcome::Advanced { from, to } => {
+            format!("advanced {}..{}", short_sha(from), short_sha(to))
+        }
+        FastForwardOutcome::UpToDate => "up to date".to_string(),
+        FastForwardOutcome::Diverged => "diverged".to_string(),
+        FastForwardOutcome::NoUpstream => "no upstream".to_string(),
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FastForwardOutcome, format_outcome};
+
+    #[test]
+    fn format_outcome_advanced_uses_short_shas() {
+        let outcome = FastForwardOutcome::Advanced {
+            from: "1234567890abcdef".to_string(),
+            to: "fedcba0987654321".to_string(),
+        };
+        assert_eq!(format_outcome(&outcome), "advanced 1234567..fedcba0");
+    }
+
+    #[test]
+    fn format_outcome_simple_states() {
+        assert_eq!(format_outcome(&FastForwardOutcome::UpToDate), "up to date");
+        assert_eq!(format_outcome(&FastForwardOutcome::Diverged), "diverged");
+        assert_eq!(
+            format_outcome(&FastForwardOutcome::NoUpstream),
+            "no upstream"
+        );
+    }
+}
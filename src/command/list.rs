@@ -1,9 +1,13 @@
-use crate::{config, workflow};
+use crate::config::CliOverrides;
+use crate::workflow::types::WorktreeInfo;
+use crate::{config, github::PrSummary, workflow};
 use anyhow::Result;
 use pathdiff::diff_paths;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tabled::{
     Table, Tabled,
-    settings::{Padding, Style, object::Columns},
+    settings::{Padding, Remove, Style, location::ByColumnName, object::Columns},
 };
 
 #[derive(Tabled)]
@@ -14,13 +18,53 @@ struct WorktreeRow {
     zellij_status: String,
     #[tabled(rename = "UNMERGED")]
     unmerged_status: String,
+    #[tabled(rename = "CURRENT")]
+    current_status: String,
+    #[tabled(rename = "PR")]
+    pr_status: String,
+    #[tabled(rename = "LAST COMMIT")]
+    last_commit: String,
     #[tabled(rename = "PATH")]
     path_str: String,
 }
 
-pub fn run() -> Result<()> {
-    let config = config::Config::load(None)?;
-    let worktrees = workflow::list(&config)?;
+/// Which worktrees `list` shows. Each set flag narrows the listing further.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListFilter {
+    /// Only worktrees with an open window/tab
+    pub open_only: bool,
+    /// Only worktrees without an open window/tab
+    pub no_tab: bool,
+    /// Include worktrees hidden by `list_exclude`
+    pub all: bool,
+}
+
+impl ListFilter {
+    fn matches(&self, wt: &WorktreeInfo) -> bool {
+        if self.open_only && !wt.has_tmux {
+            return false;
+        }
+        if self.no_tab && wt.has_tmux {
+            return false;
+        }
+        true
+    }
+}
+
+/// Longest commit subject shown in the long listing before it is truncated
+const MAX_SUBJECT_CHARS: usize = 50;
+
+pub fn run(
+    overrides: &CliOverrides,
+    pr_status: bool,
+    long: bool,
+    filter: ListFilter,
+) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let worktrees: Vec<WorktreeInfo> = workflow::list(&config, pr_status, filter.all, long)?
+        .into_iter()
+        .filter(|wt| filter.matches(wt))
+        .collect();
 
     if worktrees.is_empty() {
         println!("No worktrees found");
@@ -28,10 +72,28 @@ pub fn run() -> Result<()> {
     }
 
     let current_dir = std::env::current_dir()?;
+    let now = SystemTime::now();
+
+    // Resolve symlinks on both sides so that e.g. /tmp vs /private/tmp doesn't
+    // prevent matching the worktree we're standing in.
+    let canonical_paths: Vec<PathBuf> = worktrees
+        .iter()
+        .map(|wt| wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone()))
+        .collect();
+    let canonical_cwd = current_dir
+        .canonicalize()
+        .unwrap_or_else(|_| current_dir.clone());
+    let current_index = find_current_worktree(&canonical_paths, &canonical_cwd);
+
+    let misplaced: Vec<(PathBuf, PathBuf)> = worktrees
+        .iter()
+        .filter_map(|wt| Some((wt.path.clone(), wt.outside_worktree_dir.clone()?)))
+        .collect();
 
     let display_data: Vec<WorktreeRow> = worktrees
         .into_iter()
-        .map(|wt| {
+        .enumerate()
+        .map(|(idx, wt)| {
             let path_str = diff_paths(&wt.path, &current_dir)
                 .map(|p| {
                     let s = p.display().to_string();
@@ -42,6 +104,11 @@ pub fn run() -> Result<()> {
                     }
                 })
                 .unwrap_or_else(|| wt.path.display().to_string());
+            let path_str = if wt.outside_worktree_dir.is_some() {
+                format!("{} ⚠", path_str)
+            } else {
+                path_str
+            };
 
             WorktreeRow {
                 branch: wt.branch,
@@ -56,16 +123,244 @@ pub fn run() -> Result<()> {
                 } else {
                     "-".to_string()
                 },
+                current_status: if current_index == Some(idx) {
+                    "*".to_string()
+                } else {
+                    "-".to_string()
+                },
+                pr_status: wt.pr.as_ref().map_or_else(|| "-".to_string(), format_pr),
+                last_commit: wt.last_commit.as_ref().map_or_else(
+                    || "-".to_string(),
+                    |(time, subject)| format_last_commit(now, *time, subject),
+                ),
             }
         })
         .collect();
 
     let mut table = Table::new(display_data);
+    let last_column = table.count_columns() - 1;
     table
         .with(Style::blank())
-        .modify(Columns::new(0..3), Padding::new(0, 1, 0, 0));
+        .modify(Columns::new(0..last_column), Padding::new(0, 1, 0, 0));
+    if !pr_status {
+        table.with(Remove::column(ByColumnName::new("PR")));
+    }
+    if !long {
+        table.with(Remove::column(ByColumnName::new("LAST COMMIT")));
+    }
 
     println!("{table}");
 
+    if !misplaced.is_empty() {
+        println!(
+            "\n⚠ {} outside the configured worktree_dir. To relocate:",
+            if misplaced.len() == 1 {
+                "1 worktree is".to_string()
+            } else {
+                format!("{} worktrees are", misplaced.len())
+            }
+        );
+        for (from, to) in &misplaced {
+            println!("  git worktree move {} {}", from.display(), to.display());
+        }
+    }
+
     Ok(())
 }
+
+fn format_pr(pr: &PrSummary) -> String {
+    format!("#{} {}", pr.number, pr.status())
+}
+
+/// Relative age of a commit followed by its (truncated) subject
+fn format_last_commit(now: SystemTime, time: SystemTime, subject: &str) -> String {
+    // Commits dated in the future (clock skew) count as brand new
+    let age = now.duration_since(time).unwrap_or_default();
+    format!(
+        "{} {}",
+        format_age(age),
+        truncate_subject(subject, MAX_SUBJECT_CHARS)
+    )
+}
+
+/// Compact relative age such as `5m ago` or `3w ago`
+fn format_age(age: Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    let secs = age.as_secs();
+    let (count, unit) = match secs {
+        s if s < MINUTE => return "just now".to_string(),
+        s if s < HOUR => (s / MINUTE, "m"),
+        s if s < DAY => (s / HOUR, "h"),
+        s if s < WEEK => (s / DAY, "d"),
+        s if s < MONTH => (s / WEEK, "w"),
+        s if s < YEAR => (s / MONTH, "mo"),
+        s => (s / YEAR, "y"),
+    };
+    format!("{}{} ago", count, unit)
+}
+
+/// Cut `subject` to at most `max_chars` characters, marking the cut with `…`
+fn truncate_subject(subject: &str, max_chars: usize) -> String {
+    if subject.chars().count() <= max_chars {
+        return subject.to_string();
+    }
+    let kept: String = subject.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Find the index of the worktree containing `cwd`.
+///
+/// When worktrees are nested (e.g. `worktree_dir: .worktrees` inside the main
+/// worktree), the deepest matching worktree wins.
+fn find_current_worktree(worktree_paths: &[PathBuf], cwd: &Path) -> Option<usize> {
+    worktree_paths
+        .iter()
+        .enumerate()
+        .filter(|(_, path)| cwd.starts_with(path))
+        .max_by_key(|(_, path)| path.components().count())
+        .map(|(idx, _)| idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ListFilter, WorktreeInfo, find_current_worktree, format_age, format_last_commit,
+        truncate_subject,
+    };
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn worktree(branch: &str, has_tmux: bool) -> WorktreeInfo {
+        WorktreeInfo {
+            branch: branch.to_string(),
+            path: PathBuf::from(format!("/repo__worktrees/{}", branch)),
+            has_tmux,
+            has_unmerged: false,
+            pr: None,
+            outside_worktree_dir: None,
+            last_commit: None,
+        }
+    }
+
+    #[test]
+    fn list_filter_default_matches_everything() {
+        let filter = ListFilter::default();
+        assert!(filter.matches(&worktree("open", true)));
+        assert!(filter.matches(&worktree("closed", false)));
+    }
+
+    #[test]
+    fn list_filter_open_only_keeps_worktrees_with_a_tab() {
+        let filter = ListFilter {
+            open_only: true,
+            ..Default::default()
+        };
+        assert!(filter.matches(&worktree("open", true)));
+        assert!(!filter.matches(&worktree("closed", false)));
+    }
+
+    #[test]
+    fn list_filter_no_tab_keeps_worktrees_without_a_tab() {
+        let filter = ListFilter {
+            no_tab: true,
+            ..Default::default()
+        };
+        assert!(!filter.matches(&worktree("open", true)));
+        assert!(filter.matches(&worktree("closed", false)));
+    }
+
+    fn paths() -> Vec<PathBuf> {
+        vec![
+            PathBuf::from("/repo"),
+            PathBuf::from("/repo__worktrees/feature"),
+            PathBuf::from("/repo/.worktrees/nested"),
+        ]
+    }
+
+    #[test]
+    fn find_current_worktree_exact_match() {
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/repo__worktrees/feature")),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn find_current_worktree_from_subdirectory() {
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/repo__worktrees/feature/src/bin")),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn find_current_worktree_prefers_nested_worktree() {
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/repo/.worktrees/nested/src")),
+            Some(2)
+        );
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/repo/src")),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn find_current_worktree_ignores_sibling_with_common_prefix() {
+        // "/repo-other" starts with the string "/repo" but is not inside it
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/repo-other")),
+            None
+        );
+    }
+
+    #[test]
+    fn find_current_worktree_outside_all() {
+        assert_eq!(
+            find_current_worktree(&paths(), Path::new("/elsewhere")),
+            None
+        );
+    }
+
+    #[test]
+    fn format_age_picks_the_largest_whole_unit() {
+        let secs = Duration::from_secs;
+        assert_eq!(format_age(secs(0)), "just now");
+        assert_eq!(format_age(secs(59)), "just now");
+        assert_eq!(format_age(secs(5 * 60)), "5m ago");
+        assert_eq!(format_age(secs(3 * 3600 + 59 * 60)), "3h ago");
+        assert_eq!(format_age(secs(2 * 86400)), "2d ago");
+        assert_eq!(format_age(secs(15 * 86400)), "2w ago");
+        assert_eq!(format_age(secs(95 * 86400)), "3mo ago");
+        assert_eq!(format_age(secs(800 * 86400)), "2y ago");
+    }
+
+    #[test]
+    fn truncate_subject_respects_char_boundaries() {
+        assert_eq!(truncate_subject("Short subject", 50), "Short subject");
+        assert_eq!(truncate_subject("abcdef", 6), "abcdef");
+        assert_eq!(truncate_subject("abc defg", 5), "abc…");
+        assert_eq!(truncate_subject("ééééé", 3), "éé…");
+    }
+
+    #[test]
+    fn format_last_commit_treats_future_commits_as_new() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let earlier = UNIX_EPOCH + Duration::from_secs(999_000);
+        assert_eq!(
+            format_last_commit(earlier, time, "Add login"),
+            "just now Add login"
+        );
+        let later = time + Duration::from_secs(2 * 86400);
+        assert_eq!(
+            format_last_commit(later, time, "Add login"),
+            "2d ago Add login"
+        );
+    }
+}
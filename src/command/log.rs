@@ -0,0 +1,26 @@
+use crate::config::CliOverrides;
+use crate::workflow::WorkflowContext;
+use crate::{config, git};
+use anyhow::{Context, Result};
+
+pub fn run(overrides: &CliOverrides, branch_name: Option<&str>, limit: usize) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let branch = super::resolve_branch(branch_name, "log")?;
+    let context = WorkflowContext::new(config)?;
+
+    let worktree_path = git::get_worktree_path(&branch)
+        .with_context(|| format!("No worktree found for branch '{}'", branch))?;
+    let base = context.branch_base(&branch);
+    let commits = git::log_range(&worktree_path, &base, &branch, Some(limit))?;
+
+    if commits.is_empty() {
+        println!("No commits on '{}' since '{}'", branch, base);
+        return Ok(());
+    }
+
+    for commit in &commits {
+        println!("{}", commit);
+    }
+
+    Ok(())
+}
@@ -1,57 +1,166 @@
-use crate::workflow::WorkflowContext;
-use crate::{config, workflow};
-use anyhow::{Context, Result};
+use crate::config::CliOverrides;
+use crate::workflow::{MergeOptions, WorkflowContext};
+use crate::{config, git, workflow};
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
 
 pub fn run(
+    overrides: &CliOverrides,
     branch_name: Option<&str>,
-    ignore_uncommitted: bool,
-    delete_remote: bool,
-    rebase: bool,
-    squash: bool,
-    keep: bool,
+    target: Option<&str>,
+    target_path: Option<&Path>,
+    options: MergeOptions,
+    force: bool,
+    json: bool,
 ) -> Result<()> {
-    let config = config::Config::load(None)?;
+    let config = config::Config::load(overrides, None)?;
 
-    // Resolve branch name from argument or current branch
+    // Resolve branch name from argument or current branch, and the target branch
+    // from --target, the worktree it's checked out in, or the base of the PR the
+    // branch was checked out from
     // Note: Must be done BEFORE creating WorkflowContext (which may change CWD)
     let branch_to_merge = super::resolve_branch(branch_name, "merge")?;
+    let explicit_target = match (target, target_path) {
+        (Some(target), _) => Some(target.to_string()),
+        (None, Some(path)) => Some(resolve_branch_from_worktree_path(path)?),
+        (None, None) => None,
+    };
+    let target_branch = pick_merge_target(
+        explicit_target,
+        git::get_branch_merge_target(&branch_to_merge),
+        |branch| git::branch_exists(branch).unwrap_or(false),
+    );
+
+    if options.delete_remote
+        && !super::confirm_remote_deletion(&branch_to_merge, force || !config.confirm())?
+    {
+        return Ok(());
+    }
 
     let context = WorkflowContext::new(config)?;
 
     // Only announce pre-delete hooks if we're actually going to run cleanup
-    if !keep {
+    if !options.keep && !json {
         super::announce_hooks(&context.config, None, super::HookPhase::PreDelete);
     }
 
     let result = workflow::merge(
         &branch_to_merge,
-        ignore_uncommitted,
-        delete_remote,
-        rebase,
-        squash,
-        keep,
+        target_branch.as_deref(),
+        &options,
         &context,
     )
     .context("Failed to merge worktree")?;
 
+    if json {
+        let output =
+            serde_json::to_string_pretty(&result).context("Failed to serialize merge result")?;
+        println!("{output}");
+        return Ok(());
+    }
+
     if result.had_staged_changes {
         println!("✓ Committed staged changes");
     }
 
-    println!(
-        "Merging '{}' into '{}'...",
-        result.branch_merged, result.main_branch
-    );
-    println!("✓ Merged '{}'", result.branch_merged);
-
-    if keep {
-        println!("Worktree, window, and branch kept");
+    if result.already_merged {
+        println!(
+            "'{}' is already merged into '{}'; nothing to do",
+            result.branch_merged, result.target_branch
+        );
     } else {
         println!(
-            "✓ Successfully merged and cleaned up '{}'",
-            result.branch_merged
+            "Merging '{}' into '{}'...",
+            result.branch_merged, result.target_branch
+        );
+        println!("✓ Merged '{}'", result.branch_merged);
+    }
+
+    if let Some(stash) = &result.stash {
+        println!(
+            "Unstaged changes were left in stash {}. Apply them with 'git stash apply {}'",
+            stash, stash
         );
     }
 
+    if options.keep {
+        println!("Worktree, window, and branch kept");
+    } else {
+        let done = if result.already_merged {
+            "Cleaned up"
+        } else {
+            "Successfully merged and cleaned up"
+        };
+        println!("✓ {} '{}'", done, result.branch_merged);
+        if let Some(cleanup) = &result.cleanup {
+            println!("  {}", cleanup.summary(context.mux.window_term()));
+        }
+    }
+
     Ok(())
 }
+
+/// The branch to merge into: an explicit target wins, then the recorded default
+/// (a PR's base branch) if it exists locally. None means the main branch.
+fn pick_merge_target(
+    explicit: Option<String>,
+    recorded: Option<String>,
+    branch_exists: impl Fn(&str) -> bool,
+) -> Option<String> {
+    explicit.or_else(|| recorded.filter(|branch| branch_exists(branch)))
+}
+
+/// Resolve the branch checked out in the worktree at `path`.
+///
+/// This asks the worktree itself rather than trusting the directory name, so it
+/// stays correct when a different branch was switched to inside the worktree.
+fn resolve_branch_from_worktree_path(path: &Path) -> Result<String> {
+    if !path.is_dir() {
+        return Err(anyhow!(
+            "Target path '{}' is not a directory",
+            path.display()
+        ));
+    }
+
+    let branch = git::get_current_branch_in_worktree(path).with_context(|| {
+        format!(
+            "Failed to get current branch of worktree at '{}'",
+            path.display()
+        )
+    })?;
+
+    if branch.is_empty() {
+        return Err(anyhow!(
+            "Worktree at '{}' is not on a branch (detached HEAD)",
+            path.display()
+        ));
+    }
+
+    Ok(branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pick_merge_target;
+
+    #[test]
+    fn pick_merge_target_prefers_explicit_target() {
+        let target = pick_merge_target(Some("release".into()), Some("develop".into()), |_| true);
+        assert_eq!(target.as_deref(), Some("release"));
+    }
+
+    #[test]
+    fn pick_merge_target_defaults_to_pr_base() {
+        let target = pick_merge_target(None, Some("develop".into()), |_| true);
+        assert_eq!(target.as_deref(), Some("develop"));
+    }
+
+    #[test]
+    fn pick_merge_target_falls_back_to_main_without_local_pr_base() {
+        assert_eq!(
+            pick_merge_target(None, Some("develop".into()), |_| false),
+            None
+        );
+        assert_eq!(pick_merge_target(None, None, |_| true), None);
+    }
+}
@@ -1,12 +1,22 @@
 pub mod add;
 pub mod args;
+pub mod completions;
+pub mod config;
+pub mod diff;
+pub mod exec;
+pub mod fetch;
 pub mod list;
+pub mod log;
 pub mod merge;
 pub mod open;
+pub mod prune;
 pub mod remove;
+pub mod status;
+pub mod version;
 
-use crate::{config::Config, git, workflow::SetupOptions};
-use anyhow::{Context, Result};
+use crate::{config::Config, git, prompt, workflow::SetupOptions};
+use anyhow::{Context, Result, anyhow};
+use std::io::{self, IsTerminal};
 
 /// Represents the different phases where hooks can be executed
 pub enum HookPhase {
@@ -23,7 +33,11 @@ pub fn announce_hooks(config: &Config, options: Option<&SetupOptions>, phase: Ho
                 && config.post_create.as_ref().is_some_and(|v| !v.is_empty());
 
             if should_run {
-                println!("Running setup commands...");
+                if config.hooks_in_pane() {
+                    println!("Running setup commands in a separate pane...");
+                } else {
+                    println!("Running setup commands...");
+                }
             }
             should_run
         }
@@ -47,3 +61,59 @@ pub fn resolve_branch(arg: Option<&str>, operation: &str) -> Result<String> {
             .with_context(|| format!("Failed to get current branch for {} operation", operation)),
     }
 }
+
+/// What to do before deleting a remote branch
+#[derive(Debug, PartialEq)]
+enum RemoteDeletion {
+    Proceed,
+    Ask,
+    Refuse,
+}
+
+/// `--force` or `--yes` deletes without asking; otherwise ask on a terminal and
+/// refuse when nobody is there to answer
+fn remote_deletion_gate(force: bool, interactive: bool) -> RemoteDeletion {
+    match (force, interactive) {
+        (true, _) => RemoteDeletion::Proceed,
+        (false, true) => RemoteDeletion::Ask,
+        (false, false) => RemoteDeletion::Refuse,
+    }
+}
+
+/// Show the remote branch `--delete-remote` will delete and get confirmation,
+/// unless `force` (`--force`, `--yes` or `confirm: false`) says not to ask. The
+/// notice goes to stderr so that `--json` output stays parseable.
+/// Returns false if the user declined; errors when confirmation is needed but
+/// stdin is not a terminal.
+pub fn confirm_remote_deletion(branch_name: &str, force: bool) -> Result<bool> {
+    let remote_ref = format!("origin/{}", branch_name);
+    eprintln!("The remote branch '{}' will be deleted.", remote_ref);
+
+    match remote_deletion_gate(force, io::stdin().is_terminal()) {
+        RemoteDeletion::Proceed => Ok(true),
+        RemoteDeletion::Refuse => Err(anyhow!(
+            "Refusing to delete '{}' without confirmation. Use --force or --yes to delete it non-interactively.",
+            remote_ref
+        )),
+        RemoteDeletion::Ask => {
+            let confirmed = prompt::confirm(&format!("Delete '{}'?", remote_ref), false);
+            if !confirmed {
+                eprintln!("Aborted.");
+            }
+            Ok(confirmed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RemoteDeletion, remote_deletion_gate};
+
+    #[test]
+    fn remote_deletion_asks_only_on_a_terminal_without_force() {
+        assert_eq!(remote_deletion_gate(true, true), RemoteDeletion::Proceed);
+        assert_eq!(remote_deletion_gate(true, false), RemoteDeletion::Proceed);
+        assert_eq!(remote_deletion_gate(false, true), RemoteDeletion::Ask);
+        assert_eq!(remote_deletion_gate(false, false), RemoteDeletion::Refuse);
+    }
+}
@@ -1,13 +1,26 @@
+use crate::config::CliOverrides;
 use crate::workflow::{SetupOptions, WorkflowContext};
 use crate::{config, workflow};
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 
-pub fn run(branch_name: &str, run_hooks: bool, force_files: bool) -> Result<()> {
-    let config = config::Config::load(None)?;
+pub fn run(
+    overrides: &CliOverrides,
+    branch_name: Option<&str>,
+    all: bool,
+    jobs: usize,
+    run_hooks: bool,
+    force_files: bool,
+    select_pane: Option<usize>,
+) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    if let Some(index) = select_pane {
+        check_select_pane(index, config.panes.as_deref().unwrap_or(&[]).len())?;
+    }
     let context = WorkflowContext::new(config)?;
 
     // Construct setup options (pane commands always run on open)
-    let options = SetupOptions::new(run_hooks, force_files, true);
+    let mut options = SetupOptions::new(run_hooks, force_files, true);
+    options.select_pane = select_pane;
 
     super::announce_hooks(
         &context.config,
@@ -15,6 +28,13 @@ pub fn run(branch_name: &str, run_hooks: bool, force_files: bool) -> Result<()>
         super::HookPhase::PostCreate,
     );
 
+    if all {
+        return run_all(&context, options, jobs);
+    }
+    let Some(branch_name) = branch_name else {
+        unreachable!("clap requires branch_name without --all")
+    };
+
     let result = workflow::open(branch_name, &context, options)
         .context("Failed to open worktree environment")?;
 
@@ -30,3 +50,70 @@ pub fn run(branch_name: &str, run_hooks: bool, force_files: bool) -> Result<()>
 
     Ok(())
 }
+
+/// `--select-pane` must name one of the configured panes
+fn check_select_pane(index: usize, pane_count: usize) -> Result<()> {
+    match pane_count {
+        0 => Err(anyhow!(
+            "--select-pane {} needs panes, but none are configured",
+            index
+        )),
+        n if index >= n => Err(anyhow!(
+            "--select-pane {} is out of range: {} pane(s) are configured (0-{})",
+            index,
+            n,
+            n - 1
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Open a window for every worktree missing one and summarize what happened
+fn run_all(context: &WorkflowContext, options: SetupOptions, jobs: usize) -> Result<()> {
+    let result = workflow::open_all(context, options, jobs)?;
+    let term = context.mux.window_term();
+
+    if result.opened.is_empty() && result.failed.is_empty() {
+        println!("All worktrees already have a {}", term);
+    }
+    for branch in &result.opened {
+        println!("✓ Opened {} for '{}'", term, branch);
+    }
+    for (branch, e) in &result.failed {
+        eprintln!("✗ Failed to open {} for '{}': {}", term, branch, e);
+    }
+    println!(
+        "Opened {}, skipped {} (already open), failed {}",
+        result.opened.len(),
+        result.skipped.len(),
+        result.failed.len()
+    );
+
+    if !result.failed.is_empty() {
+        return Err(anyhow!(
+            "Failed to open {} {}(s)",
+            result.failed.len(),
+            term
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_select_pane;
+
+    #[test]
+    fn check_select_pane_requires_index_within_configured_panes() {
+        assert!(check_select_pane(0, 1).is_ok());
+        assert!(check_select_pane(2, 3).is_ok());
+
+        let err = check_select_pane(3, 3).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "--select-pane 3 is out of range: 3 pane(s) are configured (0-2)"
+        );
+        assert!(check_select_pane(0, 0).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::CliOverrides;
+use crate::workflow::WorkflowContext;
+use crate::{config, workflow};
+use anyhow::{Context, Result};
+
+pub fn run(overrides: &CliOverrides, force: bool) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let context = WorkflowContext::new(config)?;
+
+    let result = workflow::prune(force, &context).context("Failed to prune worktrees")?;
+
+    if result.pruned_worktrees.is_empty()
+        && result.closed_windows.is_empty()
+        && result.orphaned_branches.is_empty()
+    {
+        println!("Nothing to prune");
+        return Ok(());
+    }
+
+    if !result.pruned_worktrees.is_empty() {
+        println!("Pruned stale worktrees:");
+        for (path, branch) in &result.pruned_worktrees {
+            println!("  {} ({})", branch, path.display());
+        }
+    }
+
+    if !result.closed_windows.is_empty() {
+        println!(
+            "Closed orphaned {} {}s:",
+            context.mux.name(),
+            context.mux.window_term()
+        );
+        for name in &result.closed_windows {
+            println!("  {}{}", context.prefix, name);
+        }
+    }
+
+    if !result.orphaned_branches.is_empty() {
+        if result.branches_deleted {
+            println!("Deleted orphaned branches:");
+        } else {
+            println!("Orphaned branches (kept):");
+        }
+        for branch in &result.orphaned_branches {
+            println!("  {}", branch);
+        }
+        if !result.branches_deleted {
+            println!("Run `workmux prune --force` to delete them.");
+        }
+    }
+
+    Ok(())
+}
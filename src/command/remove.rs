@@ -1,7 +1,8 @@
+use crate::config::CliOverrides;
 use crate::workflow::WorkflowContext;
-use crate::{config, git, workflow};
+use crate::workflow::types::RemoveResult;
+use crate::{config, git, prompt, workflow};
 use anyhow::{Context, Result, anyhow};
-use std::io::{self, Write};
 
 /// User's choice when prompted about unmerged commits.
 enum UserChoice {
@@ -10,26 +11,146 @@ enum UserChoice {
     NotNeeded, // No prompt needed (no unmerged commits)
 }
 
+/// What happened to one branch passed to `remove`
+enum Outcome {
+    Removed(RemoveResult),
+    /// The user declined a confirmation prompt
+    Aborted,
+}
+
 pub fn run(
-    branch_name: Option<&str>,
+    overrides: &CliOverrides,
+    branch_names: &[String],
     force: bool,
     delete_remote: bool,
     keep_branch: bool,
+    json: bool,
 ) -> Result<()> {
+    // A single branch (or the current one) keeps the plain output and error
+    if branch_names.len() <= 1 {
+        let branch_name = branch_names.first().map(String::as_str);
+        if let Outcome::Removed(result) = remove_one(
+            overrides,
+            branch_name,
+            force,
+            delete_remote,
+            keep_branch,
+            json,
+        )? && json
+        {
+            let output = serde_json::to_string_pretty(&result)
+                .context("Failed to serialize remove result")?;
+            println!("{output}");
+        }
+        return Ok(());
+    }
+
+    let outcomes = for_each_branch(branch_names, |branch| {
+        remove_one(
+            overrides,
+            Some(branch),
+            force,
+            delete_remote,
+            keep_branch,
+            json,
+        )
+    });
+
+    if json {
+        let removed: Vec<&RemoveResult> = outcomes
+            .iter()
+            .filter_map(|(_, outcome)| match outcome {
+                Ok(Outcome::Removed(result)) => Some(result),
+                _ => None,
+            })
+            .collect();
+        let output =
+            serde_json::to_string_pretty(&removed).context("Failed to serialize remove result")?;
+        println!("{output}");
+    } else {
+        println!();
+        for (branch, outcome) in &outcomes {
+            println!("{}", summary_line(branch, outcome));
+        }
+    }
+
+    let failures = outcomes.iter().filter(|(_, o)| o.is_err()).count();
+    if failures > 0 {
+        return Err(anyhow!(
+            "Failed to remove {} of {} branches",
+            failures,
+            outcomes.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Run `remove` for every branch, carrying on past failures so one bad branch
+/// doesn't strand the rest. Errors are reported as they happen.
+fn for_each_branch<T>(
+    branch_names: &[String],
+    mut remove: impl FnMut(&str) -> Result<T>,
+) -> Vec<(String, Result<T>)> {
+    branch_names
+        .iter()
+        .map(|branch| {
+            let outcome = remove(branch);
+            if let Err(e) = &outcome {
+                eprintln!("✗ Failed to remove '{}': {:#}", branch, e);
+            }
+            (branch.clone(), outcome)
+        })
+        .collect()
+}
+
+fn summary_line(branch: &str, outcome: &Result<Outcome>) -> String {
+    match outcome {
+        Ok(Outcome::Removed(_)) => format!("  ✓ {}: removed", branch),
+        Ok(Outcome::Aborted) => format!("  - {}: skipped", branch),
+        // Only the top-level context; the full chain was printed above
+        Err(e) => format!("  ✗ {}: failed: {}", branch, e),
+    }
+}
+
+fn remove_one(
+    overrides: &CliOverrides,
+    branch_name: Option<&str>,
+    force: bool,
+    delete_remote: bool,
+    keep_branch: bool,
+    json: bool,
+) -> Result<Outcome> {
     // Resolve branch name from argument or current branch
     let branch_to_remove = super::resolve_branch(branch_name, "remove")?;
 
+    let config = config::Config::load(overrides, None)?;
+
     // Validate removal safety and get effective force flag
-    let effective_force =
-        match validate_removal_safety(&branch_to_remove, force, delete_remote, keep_branch)? {
-            Some(force_flag) => force_flag,
-            None => return Ok(()), // User aborted
-        };
+    let effective_force = match validate_removal_safety(
+        &branch_to_remove,
+        force,
+        delete_remote,
+        keep_branch,
+        config.ignore_dirty_paths(),
+        !config.confirm(),
+    )? {
+        Some(force_flag) => force_flag,
+        None => return Ok(Outcome::Aborted), // User aborted
+    };
+
+    // A confirmed unmerged-commits prompt already covered the remote branch
+    if delete_remote
+        && !super::confirm_remote_deletion(&branch_to_remove, effective_force || !config.confirm())?
+    {
+        return Ok(Outcome::Aborted);
+    }
 
-    let config = config::Config::load(None)?;
     let context = WorkflowContext::new(config)?;
 
-    super::announce_hooks(&context.config, None, super::HookPhase::PreDelete);
+    if !json {
+        super::announce_hooks(&context.config, None, super::HookPhase::PreDelete);
+    }
 
     let result = workflow::remove(
         &branch_to_remove,
@@ -40,6 +161,10 @@ pub fn run(
     )
     .context("Failed to remove worktree")?;
 
+    if json {
+        return Ok(Outcome::Removed(result));
+    }
+
     if keep_branch {
         println!(
             "✓ Successfully removed worktree for branch '{}'. The local branch was kept.",
@@ -51,6 +176,64 @@ pub fn run(
             result.branch_removed
         );
     }
+    println!("  {}", result.cleanup.summary(context.mux.window_term()));
+
+    Ok(Outcome::Removed(result))
+}
+
+/// Clean up after a worktree whose directory was deleted by hand: prune git's
+/// worktree metadata and delete the branch (unless kept). Files, windows, and
+/// hooks are not touched.
+pub fn run_prune_metadata_only(
+    branch_names: &[String],
+    force: bool,
+    keep_branch: bool,
+) -> Result<()> {
+    if branch_names.len() <= 1 {
+        let branch_name = branch_names.first().map(String::as_str);
+        return prune_metadata_only(branch_name, force, keep_branch);
+    }
+
+    let outcomes = for_each_branch(branch_names, |branch| {
+        prune_metadata_only(Some(branch), force, keep_branch)
+    });
+    let failures = outcomes.iter().filter(|(_, o)| o.is_err()).count();
+    if failures > 0 {
+        return Err(anyhow!(
+            "Failed to prune {} of {} branches",
+            failures,
+            outcomes.len()
+        ));
+    }
+
+    Ok(())
+}
+
+fn prune_metadata_only(branch_name: Option<&str>, force: bool, keep_branch: bool) -> Result<()> {
+    let branch = super::resolve_branch(branch_name, "remove")?;
+
+    if let Ok(path) = git::get_worktree_path(&branch)
+        && path.exists()
+    {
+        return Err(anyhow!(
+            "Worktree for '{}' still exists at '{}'. Use 'workmux remove {}' instead.",
+            branch,
+            path.display(),
+            branch
+        ));
+    }
+
+    git::prune_worktrees()?;
+    println!("✓ Pruned worktree metadata");
+
+    if keep_branch {
+        println!("Branch '{}' kept", branch);
+        return Ok(());
+    }
+
+    git::delete_branch(&branch, force)
+        .with_context(|| format!("Failed to delete branch '{}'", branch))?;
+    println!("✓ Deleted branch '{}'", branch);
 
     Ok(())
 }
@@ -62,6 +245,8 @@ fn validate_removal_safety(
     force: bool,
     delete_remote: bool,
     keep_branch: bool,
+    ignore_dirty_paths: &[String],
+    assume_yes: bool,
 ) -> Result<Option<bool>> {
     if force {
         return Ok(Some(true));
@@ -69,11 +254,11 @@ fn validate_removal_safety(
 
     // First check for uncommitted changes (must be checked before unmerged prompt)
     // to avoid prompting user about unmerged commits only to error on uncommitted changes
-    check_uncommitted_changes(branch_name)?;
+    check_uncommitted_changes(branch_name, ignore_dirty_paths)?;
 
     // Check if we need to prompt for unmerged commits (only relevant when deleting the branch)
     if !keep_branch {
-        match check_unmerged_commits(branch_name, delete_remote)? {
+        match check_unmerged_commits(branch_name, delete_remote, assume_yes)? {
             UserChoice::Confirmed => return Ok(Some(true)), // User confirmed - use force
             UserChoice::Aborted => return Ok(None),         // User aborted
             UserChoice::NotNeeded => {}                     // No unmerged commits
@@ -84,17 +269,18 @@ fn validate_removal_safety(
 }
 
 /// Check for uncommitted changes in the worktree.
-fn check_uncommitted_changes(branch_name: &str) -> Result<()> {
+fn check_uncommitted_changes(branch_name: &str, ignore_dirty_paths: &[String]) -> Result<()> {
     let worktree_path = git::get_worktree_path(branch_name)
         .with_context(|| format!("Failed to get worktree path for branch '{}'", branch_name))?;
 
     if worktree_path.exists() {
-        let has_changes = git::has_uncommitted_changes(&worktree_path).with_context(|| {
-            format!(
-                "Failed to check for uncommitted changes in worktree at '{}'",
-                worktree_path.display()
-            )
-        })?;
+        let has_changes = git::has_uncommitted_changes(&worktree_path, ignore_dirty_paths)
+            .with_context(|| {
+                format!(
+                    "Failed to check for uncommitted changes in worktree at '{}'",
+                    worktree_path.display()
+                )
+            })?;
 
         if has_changes {
             return Err(anyhow!(
@@ -107,7 +293,11 @@ fn check_uncommitted_changes(branch_name: &str) -> Result<()> {
 }
 
 /// Check for unmerged commits and prompt user for confirmation.
-fn check_unmerged_commits(branch_name: &str, delete_remote: bool) -> Result<UserChoice> {
+fn check_unmerged_commits(
+    branch_name: &str,
+    delete_remote: bool,
+    assume_yes: bool,
+) -> Result<UserChoice> {
     // Try to get the stored base branch, fall back to default branch
     let base = git::get_branch_base(branch_name)
         .ok()
@@ -133,7 +323,13 @@ fn check_unmerged_commits(branch_name: &str, delete_remote: bool) -> Result<User
     let has_unmerged = unmerged_branches.contains(branch_name);
 
     if has_unmerged {
-        prompt_unmerged_confirmation(branch_name, &base_branch, &base, delete_remote)
+        Ok(prompt_unmerged_confirmation(
+            branch_name,
+            &base_branch,
+            &base,
+            delete_remote,
+            assume_yes,
+        ))
     } else {
         Ok(UserChoice::NotNeeded)
     }
@@ -145,33 +341,56 @@ fn prompt_unmerged_confirmation(
     base_branch: &str,
     base: &str,
     delete_remote: bool,
-) -> Result<UserChoice> {
-    println!(
+    assume_yes: bool,
+) -> UserChoice {
+    eprintln!(
         "This will delete the worktree, tmux window, and local branch for '{}'.",
         branch_name
     );
     if delete_remote {
-        println!("The remote branch will also be deleted.");
+        eprintln!(
+            "The remote branch 'origin/{}' will also be deleted.",
+            branch_name
+        );
     }
-    println!(
+    eprintln!(
         "Warning: Branch '{}' has commits that are not merged into '{}' (base: '{}').",
         branch_name, base_branch, base
     );
-    println!("This action cannot be undone.");
-    print!("Are you sure you want to continue? [y/N] ");
+    eprintln!("This action cannot be undone.");
+
+    if prompt::confirm("Are you sure you want to continue?", assume_yes) {
+        UserChoice::Confirmed
+    } else {
+        eprintln!("Aborted.");
+        UserChoice::Aborted
+    }
+}
 
-    // Flush stdout to ensure the prompt is displayed before reading input
-    io::stdout().flush().context("Failed to flush stdout")?;
+#[cfg(test)]
+mod tests {
+    use super::for_each_branch;
+    use anyhow::anyhow;
 
-    let mut confirmation = String::new();
-    io::stdin()
-        .read_line(&mut confirmation)
-        .context("Failed to read user confirmation")?;
+    #[test]
+    fn for_each_branch_continues_past_failures() {
+        let branches = vec!["a".to_string(), "bad".to_string(), "c".to_string()];
+        let mut attempted = Vec::new();
 
-    if confirmation.trim().to_lowercase() == "y" {
-        Ok(UserChoice::Confirmed)
-    } else {
-        println!("Aborted.");
-        Ok(UserChoice::Aborted)
+        let outcomes = for_each_branch(&branches, |branch| {
+            attempted.push(branch.to_string());
+            if branch == "bad" {
+                Err(anyhow!("uncommitted changes"))
+            } else {
+                Ok(branch.len())
+            }
+        });
+
+        assert_eq!(attempted, ["a", "bad", "c"]);
+        let results: Vec<(&str, bool)> = outcomes
+            .iter()
+            .map(|(branch, outcome)| (branch.as_str(), outcome.is_ok()))
+            .collect();
+        assert_eq!(results, [("a", true), ("bad", false), ("c", true)]);
     }
 }
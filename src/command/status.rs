@@ -0,0 +1,59 @@
+use crate::config::{self, CliOverrides};
+use crate::workflow::{self, WorkflowContext, types::BranchStatus};
+use anyhow::{Context, Result};
+use tabled::{
+    Table, Tabled,
+    settings::{Padding, Style, object::Columns},
+};
+
+#[derive(Tabled)]
+struct StatusRow {
+    #[tabled(rename = "BRANCH")]
+    branch: String,
+    #[tabled(rename = "BASE")]
+    base: String,
+    #[tabled(rename = "AHEAD")]
+    ahead: usize,
+    #[tabled(rename = "BEHIND")]
+    behind: usize,
+    #[tabled(rename = "DIRTY")]
+    dirty: String,
+    #[tabled(rename = "STAGED")]
+    staged: String,
+}
+
+pub fn run(overrides: &CliOverrides, branch_name: Option<&str>, json: bool) -> Result<()> {
+    let config = config::Config::load(overrides, None)?;
+    let branch = super::resolve_branch(branch_name, "status")?;
+    let context = WorkflowContext::new(config)?;
+
+    let status = workflow::status(&branch, &context)?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&status).context("Failed to serialize status")?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    let mut table = Table::new([status_row(&status)]);
+    let last_column = table.count_columns() - 1;
+    table
+        .with(Style::blank())
+        .modify(Columns::new(0..last_column), Padding::new(0, 1, 0, 0));
+
+    println!("{table}");
+
+    Ok(())
+}
+
+fn status_row(status: &BranchStatus) -> StatusRow {
+    let flag = |set: bool| if set { "●" } else { "-" }.to_string();
+    StatusRow {
+        branch: status.branch.clone(),
+        base: status.base.clone(),
+        ahead: status.ahead,
+        behind: status.behind,
+        dirty: flag(status.dirty),
+        staged: flag(status.staged),
+    }
+}
@@ -0,0 +1,99 @@
+use crate::cmd::Cmd;
+use crate::config::CliOverrides;
+use crate::{config, multiplexer};
+use anyhow::Result;
+
+/// External tools reported by `version --verbose`, with the flag that prints their version
+const TOOLS: &[(&str, &str)] = &[
+    ("git", "--version"),
+    ("zellij", "--version"),
+    ("tmux", "-V"),
+    ("gh", "--version"),
+];
+
+pub fn run(overrides: &CliOverrides, verbose: bool) -> Result<()> {
+    println!("workmux {}", env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return Ok(());
+    }
+
+    for (tool, flag) in TOOLS {
+        let version = tool_version(tool, flag).unwrap_or_else(|| "not found".to_string());
+        println!("{:<8}{}", tool, version);
+    }
+
+    // Outside a repo (or with a broken config) fall back to the default backend choice
+    let kind = config::Config::load(overrides, None)
+        .map(|config| config.multiplexer())
+        .unwrap_or_default();
+    println!(
+        "{:<8}{}",
+        "backend",
+        multiplexer::resolve(kind, None).name()
+    );
+
+    Ok(())
+}
+
+/// Run `tool flag` and extract its version, or None if the tool is unavailable
+fn tool_version(tool: &str, flag: &str) -> Option<String> {
+    let output = Cmd::new(tool).args(&[flag]).run_and_capture_stdout().ok()?;
+    parse_tool_version(&output)
+}
+
+/// Pull the version number out of a tool's version banner, e.g. "2.43.0" from
+/// "git version 2.43.0" or "3.3a" from "tmux 3.3a"
+fn parse_tool_version(output: &str) -> Option<String> {
+    output
+        .lines()
+        .next()?
+        .split_whitespace()
+        .map(|word| word.rsplit('-').next().unwrap_or(word))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_tool_version;
+
+    #[test]
+    fn parses_git_version() {
+        assert_eq!(
+            parse_tool_version("git version 2.43.0").as_deref(),
+            Some("2.43.0")
+        );
+        assert_eq!(
+            parse_tool_version("git version 2.39.3 (Apple Git-145)").as_deref(),
+            Some("2.39.3")
+        );
+    }
+
+    #[test]
+    fn parses_zellij_version() {
+        assert_eq!(
+            parse_tool_version("zellij 0.40.1").as_deref(),
+            Some("0.40.1")
+        );
+    }
+
+    #[test]
+    fn parses_tmux_version() {
+        assert_eq!(parse_tool_version("tmux 3.4").as_deref(), Some("3.4"));
+        assert_eq!(parse_tool_version("tmux 3.3a").as_deref(), Some("3.3a"));
+        assert_eq!(parse_tool_version("tmux next-3.5").as_deref(), Some("3.5"));
+    }
+
+    #[test]
+    fn parses_gh_version() {
+        let output = "gh version 2.40.1 (2023-12-13)\n\
+                      https://github.com/cli/cli/releases/tag/v2.40.1";
+        assert_eq!(parse_tool_version(output).as_deref(), Some("2.40.1"));
+    }
+
+    #[test]
+    fn rejects_output_without_version() {
+        assert_eq!(parse_tool_version(""), None);
+        assert_eq!(parse_tool_version("command not found"), None);
+    }
+}
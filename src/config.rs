@@ -1,9 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 
-use crate::git;
+use crate::{cmd, git, toml};
 use which::which;
 
 /// Default script for cleaning up node_modules directories before worktree deletion.
@@ -47,7 +50,103 @@ pub struct FileConfig {
     pub symlink: Option<Vec<String>>,
 }
 
-/// Configuration for the workmux tool, read from .workmux.yaml
+/// A `post_create`/`pre_delete` hook: a plain command, or a command that only
+/// runs when its `onlyif` condition holds
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HookCommand {
+    Command(String),
+    Conditional {
+        run: String,
+        #[serde(default)]
+        onlyif: Option<HookCondition>,
+    },
+}
+
+impl HookCommand {
+    /// The shell command the hook runs
+    pub fn command(&self) -> &str {
+        match self {
+            HookCommand::Command(command) | HookCommand::Conditional { run: command, .. } => {
+                command
+            }
+        }
+    }
+
+    /// Condition that must hold for the hook to run, if any
+    pub fn condition(&self) -> Option<&HookCondition> {
+        match self {
+            HookCommand::Command(_) => None,
+            HookCommand::Conditional { onlyif, .. } => onlyif.as_ref(),
+        }
+    }
+}
+
+impl From<&str> for HookCommand {
+    fn from(command: &str) -> Self {
+        HookCommand::Command(command.to_string())
+    }
+}
+
+/// When a conditional hook runs: `onlyif: <shell predicate>` runs it if the
+/// predicate exits 0, `onlyif: { exists: <path> }` if the path exists in the worktree
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HookCondition {
+    Shell(String),
+    Exists { exists: String },
+}
+
+/// List items that can be the `"<global>"` placeholder in a project config
+trait GlobalPlaceholder {
+    fn is_global_placeholder(&self) -> bool;
+}
+
+impl GlobalPlaceholder for String {
+    fn is_global_placeholder(&self) -> bool {
+        self == "<global>"
+    }
+}
+
+impl GlobalPlaceholder for HookCommand {
+    fn is_global_placeholder(&self) -> bool {
+        matches!(self, HookCommand::Command(command) if command.is_global_placeholder())
+    }
+}
+
+/// Global command-line flags that take precedence over every config file
+#[derive(Debug, Default, Clone)]
+pub struct CliOverrides {
+    /// `--no-prefix`: use branch names verbatim as window/tab names
+    pub no_prefix: bool,
+    /// `--zellij-session`: open and manage tabs in this zellij session
+    pub zellij_session: Option<String>,
+    /// `--verbose`: also print hook timing reports
+    pub verbose: bool,
+    /// `--quiet`: leave out progress messages
+    pub quiet: bool,
+    /// `--yes`: answer yes to confirmation prompts, like `confirm: false`
+    pub yes: bool,
+}
+
+impl CliOverrides {
+    /// Write the flags over the merged configuration
+    fn apply(&self, config: &mut Config) {
+        if self.no_prefix {
+            config.window_prefix = Some(String::new());
+        }
+        if let Some(session) = &self.zellij_session {
+            config.zellij_session = Some(session.clone());
+        }
+        config.verbose = self.verbose;
+        config.quiet = self.quiet;
+        if self.yes {
+            config.confirm = Some(false);
+        }
+    }
+}
+
+/// Configuration for the workmux tool, read from .workmux.yaml (or .yml/.json/.toml)
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct Config {
     /// The primary branch to merge into (optional, auto-detected if not set)
@@ -59,37 +158,143 @@ pub struct Config {
     #[serde(default)]
     pub worktree_dir: Option<String>,
 
+    /// Template for each worktree's path, overriding `worktree_dir` (optional)
+    /// Variables: {{ branch }}, {{ repo }}, {{ main_root }}
+    #[serde(default)]
+    pub worktree_path: Option<String>,
+
     /// Prefix for zellij tab names (optional, defaults to "wm-")
     #[serde(default)]
     pub window_prefix: Option<String>,
 
+    /// Branch name template for `add --prompt-from-issue` without a branch name
+    /// (optional, defaults to "issue-{number}-{slug}")
+    #[serde(default)]
+    pub issue_branch_template: Option<String>,
+
+    /// Branch name template for `add --pr` without a branch name
+    /// (optional, defaults to "{head}", the PR's head branch)
+    #[serde(default)]
+    pub pr_branch_template: Option<String>,
+
+    /// Terminal multiplexer to drive (optional, defaults to auto-detection)
+    #[serde(default)]
+    pub multiplexer: Option<Multiplexer>,
+
+    /// Named zellij session to open tabs in, instead of the session workmux runs in (optional)
+    #[serde(default)]
+    pub zellij_session: Option<String>,
+
+    /// Shell predicate polled after launching panes until it succeeds (optional)
+    #[serde(default)]
+    pub agent_ready_check: Option<String>,
+
+    /// Seconds to keep polling `agent_ready_check` before giving up (optional)
+    #[serde(default)]
+    pub agent_ready_timeout: Option<u64>,
+
+    /// Timeout in seconds for network operations like `git fetch` and `gh` (optional)
+    #[serde(default)]
+    pub network_timeout: Option<u64>,
+
     /// Pane configuration (note: zellij only supports single-pane mode, first pane is used)
     #[serde(default)]
     pub panes: Option<Vec<PaneConfig>>,
 
+    /// Predefined pane arrangement used instead of `panes` (optional)
+    #[serde(default)]
+    pub layout: Option<LayoutPreset>,
+
     /// Commands to run after creating the worktree
     #[serde(default)]
-    pub post_create: Option<Vec<String>>,
+    pub post_create: Option<Vec<HookCommand>>,
+
+    /// Shell for pane startup commands, overriding `$SHELL`
+    #[serde(default)]
+    pub startup_shell: Option<String>,
+
+    /// Run `post_create` hooks in a dedicated pane instead of blocking before the tab opens
+    #[serde(default)]
+    pub hooks_in_pane: Option<bool>,
+
+    /// Open a window for the main worktree if it is missing when merge/remove
+    /// navigates back to it
+    #[serde(default)]
+    pub create_main_window: Option<bool>,
+
+    /// Commit files created by file operations and `post_create` hooks on the new branch
+    #[serde(default)]
+    pub commit_generated_files: Option<bool>,
 
     /// Commands to run before deleting the worktree (e.g., for fast cleanup)
     #[serde(default)]
-    pub pre_delete: Option<Vec<String>>,
+    pub pre_delete: Option<Vec<HookCommand>>,
+
+    /// Seconds a `post_create` or `pre_delete` command may run before it is stopped
+    #[serde(default)]
+    pub hook_timeout_secs: Option<u64>,
+
+    /// Commands to run in the target worktree after a successful merge
+    #[serde(default)]
+    pub post_merge: Option<Vec<String>>,
+
+    /// Branch to leave checked out in the merged-into worktree once a merge is done
+    #[serde(default)]
+    pub post_merge_switch_to: Option<String>,
+
+    /// Commands to run in a new worktree once `add` has finished setting it up
+    #[serde(default)]
+    pub after_add: Option<Vec<String>>,
+
+    /// Paths whose changes don't count as uncommitted work in merge/remove safety checks
+    #[serde(default)]
+    pub ignore_dirty_paths: Option<Vec<String>>,
+
+    /// Whether destructive commands ask before going ahead (defaults to true)
+    #[serde(default)]
+    pub confirm: Option<bool>,
+
+    /// Branch-name globs of worktrees `list` leaves out unless given `--all`
+    #[serde(default)]
+    pub list_exclude: Option<Vec<String>>,
 
     /// The agent command to use (e.g., "claude", "gemini")
     #[serde(default)]
     pub agent: Option<String>,
 
+    /// Pane command that stands for the agent (optional, defaults to "<agent>")
+    #[serde(default)]
+    pub agent_placeholder: Option<String>,
+
+    /// How to pass the prompt to each agent, keyed by executable name. `{cmd}` is
+    /// the pane's command and `{path}` the prompt file.
+    #[serde(default)]
+    pub agent_prompt_format: Option<HashMap<String, String>>,
+
+    /// Command that records the agent's terminal session, with `{cmd}` standing
+    /// for the agent command and `{out}` for the recording file
+    #[serde(default)]
+    pub agent_record: Option<String>,
+
     /// File operations to perform after creating the worktree
     #[serde(default)]
     pub files: FileConfig,
+
+    /// Print hook timing reports to the terminal; set by `--verbose`, never read from a file
+    #[serde(skip)]
+    pub verbose: bool,
+
+    /// Leave out progress messages; set by `--quiet`, never read from a file
+    #[serde(skip)]
+    pub quiet: bool,
 }
 
 /// Configuration for a single pane (note: zellij only uses the first pane)
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PaneConfig {
     /// A command to run when the pane is created. The pane will remain open
-    /// with an interactive shell after the command completes. If not provided,
-    /// the pane will start with the default shell.
+    /// with an interactive shell after the command completes, unless `keep_open`
+    /// is false. If not provided, the pane will start with the default shell.
     #[serde(default)]
     pub command: Option<String>,
 
@@ -97,17 +302,19 @@ pub struct PaneConfig {
     #[serde(default)]
     pub focus: bool,
 
-    /// Split direction from the previous pane (horizontal or vertical)
+    /// Split direction from the previous pane: horizontal or vertical, or one of
+    /// right, left, down, up to also pick the side the new pane goes on
     #[serde(default)]
     pub split: Option<SplitDirection>,
 
-    /// The size of the new pane in lines (for vertical splits) or cells (for horizontal splits).
+    /// The size of the new pane: lines (for vertical splits) or cells (for horizontal
+    /// splits) as a number like `80`, or a percentage like `"30%"`.
     /// Mutually exclusive with `percentage`.
     #[serde(default)]
-    pub size: Option<u16>,
+    pub size: Option<PaneSize>,
 
     /// The size of the new pane as a percentage of the available space.
-    /// Mutually exclusive with `size`.
+    /// Kept for older configs; prefer `size: "30%"`. Mutually exclusive with `size`.
     #[serde(default)]
     pub percentage: Option<u8>,
 
@@ -116,6 +323,157 @@ pub struct PaneConfig {
     /// Only used when `split` is specified.
     #[serde(default)]
     pub target: Option<usize>,
+
+    /// Whether to drop into a login shell after `command` finishes. Set to false
+    /// for one-shot commands so the pane closes when they exit.
+    #[serde(default = "default_keep_open")]
+    pub keep_open: bool,
+
+    /// Environment variables exported in this pane before its command runs
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+
+    /// Shell to start this pane with, overriding `startup_shell` and `$SHELL`
+    #[serde(default)]
+    pub shell: Option<String>,
+
+    /// Close the whole window/tab, not just this pane, once `command` exits
+    #[serde(default)]
+    pub close_tab_on_exit: bool,
+}
+
+fn default_keep_open() -> bool {
+    true
+}
+
+impl PaneConfig {
+    /// The pane's size, from `size` or the older `percentage` field
+    pub fn effective_size(&self) -> Option<PaneSize> {
+        self.size.or(self.percentage.map(PaneSize::Percent))
+    }
+}
+
+/// Size of a new pane, written as a number of lines/cells (`80`) or a
+/// percentage of the available space (`"30%"`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneSize {
+    Cells(u16),
+    Percent(u8),
+}
+
+impl FromStr for PaneSize {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let parsed = match s.strip_suffix('%') {
+            Some(percent) => percent.trim().parse().ok().map(PaneSize::Percent),
+            None => s.parse().ok().map(PaneSize::Cells),
+        };
+        parsed.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid pane size '{}'. Use a number of cells like \"80\" or a percentage like \"30%\".",
+                s
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for PaneSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaneSize::Cells(cells) => write!(f, "{}", cells),
+            PaneSize::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaneSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawSize {
+            Number(u16),
+            Text(String),
+        }
+
+        match RawSize::deserialize(deserializer)? {
+            RawSize::Number(cells) => Ok(PaneSize::Cells(cells)),
+            RawSize::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl Serialize for PaneSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            PaneSize::Cells(cells) => serializer.serialize_u16(*cells),
+            PaneSize::Percent(_) => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+/// A predefined pane arrangement for the `layout` option
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutPreset {
+    /// The agent on the left, a shell on the right taking 30%
+    AgentShell,
+    /// Like `agent-shell`, with the right side split into a shell and a
+    /// second one for logs below it
+    AgentShellLogs,
+    /// The agent and a shell side by side, half each
+    Even,
+}
+
+impl LayoutPreset {
+    /// The panes this preset stands for, with `agent_placeholder` as the agent's command
+    pub fn panes(self, agent_placeholder: &str) -> Vec<PaneConfig> {
+        let agent = PaneConfig {
+            command: Some(agent_placeholder.to_string()),
+            focus: true,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let shell = |split, percent, target| PaneConfig {
+            command: None,
+            focus: false,
+            split: Some(split),
+            size: Some(PaneSize::Percent(percent)),
+            percentage: None,
+            target,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        match self {
+            LayoutPreset::AgentShell => vec![agent, shell(SplitDirection::Right, 30, None)],
+            LayoutPreset::AgentShellLogs => vec![
+                agent,
+                shell(SplitDirection::Right, 30, None),
+                shell(SplitDirection::Down, 50, Some(1)),
+            ],
+            LayoutPreset::Even => vec![agent, shell(SplitDirection::Right, 50, None)],
+        }
+    }
+}
+
+/// Terminal multiplexer backend selection
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Multiplexer {
+    Tmux,
+    Zellij,
+    /// Detect from the `ZELLIJ`/`TMUX` environment variables, preferring zellij
+    #[default]
+    Auto,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -123,6 +481,26 @@ pub struct PaneConfig {
 pub enum SplitDirection {
     Horizontal,
     Vertical,
+    /// Horizontal split with the new pane on the right
+    Right,
+    /// Horizontal split with the new pane on the left
+    Left,
+    /// Vertical split with the new pane below
+    Down,
+    /// Vertical split with the new pane above
+    Up,
+}
+
+impl SplitDirection {
+    /// Whether the new pane is stacked above or below the target pane
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Self::Vertical | Self::Down | Self::Up)
+    }
+
+    /// Whether the new pane is placed before (left of or above) the target pane
+    pub fn is_before(&self) -> bool {
+        matches!(self, Self::Left | Self::Up)
+    }
 }
 
 /// Validate pane configuration
@@ -152,7 +530,7 @@ pub fn validate_panes_config(panes: &[PaneConfig]) -> anyhow::Result<()> {
         }
 
         // Validate percentage range
-        if let Some(p) = pane.percentage
+        if let Some(PaneSize::Percent(p)) = pane.effective_size()
             && !(1..=100).contains(&p)
         {
             anyhow::bail!(
@@ -178,19 +556,25 @@ pub fn validate_panes_config(panes: &[PaneConfig]) -> anyhow::Result<()> {
 }
 
 impl Config {
-    /// Load and merge global and project configurations.
-    pub fn load(cli_agent: Option<&str>) -> anyhow::Result<Self> {
+    /// Load and merge global and project configurations, then apply `overrides`.
+    pub fn load(overrides: &CliOverrides, cli_agent: Option<&str>) -> anyhow::Result<Self> {
         let global_config = Self::load_global()?.unwrap_or_default();
         let project_config = Self::load_project()?.unwrap_or_default();
+        let local_config = Self::load_local()?.unwrap_or_default();
 
         let final_agent = cli_agent
             .map(|s| s.to_string())
+            .or_else(|| local_config.agent.clone())
             .or_else(|| project_config.agent.clone())
             .or_else(|| global_config.agent.clone())
             .unwrap_or_else(|| "claude".to_string());
 
-        let mut config = global_config.merge(project_config);
+        let mut config = global_config.merge(project_config).merge(local_config);
         config.agent = Some(final_agent);
+        overrides.apply(&mut config);
+
+        config.check_network_timeout()?;
+        config.apply_layout()?;
 
         // After merging, apply sensible defaults for any values that are not configured.
         let needs_defaults = config.panes.is_none() || config.pre_delete.is_none();
@@ -202,7 +586,7 @@ impl Config {
                 // Default panes based on project type
                 if config.panes.is_none() {
                     if repo_root.join("CLAUDE.md").exists() {
-                        config.panes = Some(Self::claude_default_panes());
+                        config.panes = Some(Self::claude_default_panes(config.agent_placeholder()));
                     } else {
                         config.panes = Some(Self::default_panes());
                     }
@@ -215,7 +599,7 @@ impl Config {
                         || repo_root.join("yarn.lock").exists();
 
                     if has_node_modules {
-                        config.pre_delete = Some(vec![NODE_MODULES_CLEANUP_SCRIPT.to_string()]);
+                        config.pre_delete = Some(vec![NODE_MODULES_CLEANUP_SCRIPT.into()]);
                     }
                 }
             } else {
@@ -229,13 +613,15 @@ impl Config {
         Ok(config)
     }
 
-    /// Load configuration from a specific path.
+    /// Load configuration from a specific path, parsed according to its extension.
     fn load_from_path(path: &Path) -> anyhow::Result<Option<Self>> {
         if !path.exists() {
             return Ok(None);
         }
+        let format = ConfigFormat::from_path(path)?;
         let contents = fs::read_to_string(path)?;
-        let config: Config = serde_yaml::from_str(&contents)
+        let config = format
+            .parse(&contents)
             .map_err(|e| anyhow::anyhow!("Failed to parse config at {}: {}", path.display(), e))?;
         Ok(Some(config))
     }
@@ -243,49 +629,56 @@ impl Config {
     /// Load the global configuration file from the XDG config directory.
     fn load_global() -> anyhow::Result<Option<Self>> {
         // Check ~/.config/workmux (XDG convention, works cross-platform)
-        if let Some(home_dir) = home::home_dir() {
-            let xdg_config_path = home_dir.join(".config/workmux/config.yaml");
-            if xdg_config_path.exists() {
-                return Self::load_from_path(&xdg_config_path);
-            }
-            let xdg_config_path_yml = home_dir.join(".config/workmux/config.yml");
-            if xdg_config_path_yml.exists() {
-                return Self::load_from_path(&xdg_config_path_yml);
-            }
+        let Some(home_dir) = home::home_dir() else {
+            return Ok(None);
+        };
+        match find_config_file(&home_dir.join(".config/workmux"), "config")? {
+            Some(path) => Self::load_from_path(&path),
+            None => Ok(None),
         }
-        Ok(None)
     }
 
     /// Load the project-specific configuration file from the current directory.
     fn load_project() -> anyhow::Result<Option<Self>> {
-        let config_path_yaml = Path::new(".workmux.yaml");
-        if config_path_yaml.exists() {
-            return Self::load_from_path(config_path_yaml);
+        match find_config_file(Path::new("."), ".workmux")? {
+            Some(path) => Self::load_from_path(&path),
+            None => Ok(None),
+        }
+    }
+
+    /// Load machine-specific overrides from `.workmux.local.yaml`. The file is
+    /// meant to be gitignored, so it isn't checked out into other worktrees; when
+    /// it isn't next to the project config, the main worktree's copy is used.
+    fn load_local() -> anyhow::Result<Option<Self>> {
+        let mut path = find_config_file(Path::new("."), LOCAL_CONFIG_STEM)?;
+        if path.is_none()
+            && let Ok(main_root) = git::get_main_worktree_root()
+        {
+            path = find_config_file(&main_root, LOCAL_CONFIG_STEM)?;
         }
-        let config_path_yml = Path::new(".workmux.yml");
-        if config_path_yml.exists() {
-            return Self::load_from_path(config_path_yml);
+        match path {
+            Some(path) => Self::load_from_path(&path),
+            None => Ok(None),
         }
-        Ok(None)
     }
 
     /// Merge a project config into a global config.
     /// Project config takes precedence. For lists, "<global>" placeholder expands to global items.
     fn merge(self, project: Self) -> Self {
         // Helper to merge vectors with "<global>" placeholder expansion
-        fn merge_vec_with_placeholder(
-            global: Option<Vec<String>>,
-            project: Option<Vec<String>>,
-        ) -> Option<Vec<String>> {
+        fn merge_vec_with_placeholder<T: Clone + GlobalPlaceholder>(
+            global: Option<Vec<T>>,
+            project: Option<Vec<T>>,
+        ) -> Option<Vec<T>> {
             match (global, project) {
                 (Some(global_items), Some(project_items)) => {
                     // Check if project items contain the "<global>" placeholder
-                    let has_placeholder = project_items.iter().any(|s| s == "<global>");
+                    let has_placeholder = project_items.iter().any(|s| s.is_global_placeholder());
                     if has_placeholder {
                         // Replace "<global>" with global items
                         let mut result = Vec::new();
                         for item in project_items {
-                            if item == "<global>" {
+                            if item.is_global_placeholder() {
                                 result.extend(global_items.clone());
                             } else {
                                 result.push(item);
@@ -297,32 +690,91 @@ impl Config {
                         Some(project_items)
                     }
                 }
-                (global, project) => project.or(global),
+                // Nothing to inherit, so the placeholder expands to nothing
+                (None, Some(project_items)) => Some(
+                    project_items
+                        .into_iter()
+                        .filter(|item| !item.is_global_placeholder())
+                        .collect(),
+                ),
+                (global, None) => global,
             }
         }
 
+        let (panes, layout) = if project.panes.is_some() || project.layout.is_some() {
+            (project.panes, project.layout)
+        } else {
+            (self.panes, self.layout)
+        };
+
         Self {
             // Scalar values: project wins
             main_branch: project.main_branch.or(self.main_branch),
             worktree_dir: project.worktree_dir.or(self.worktree_dir),
+            worktree_path: project.worktree_path.or(self.worktree_path),
+            issue_branch_template: project.issue_branch_template.or(self.issue_branch_template),
+            pr_branch_template: project.pr_branch_template.or(self.pr_branch_template),
             window_prefix: project.window_prefix.or(self.window_prefix),
+            multiplexer: project.multiplexer.or(self.multiplexer),
+            zellij_session: project.zellij_session.or(self.zellij_session),
             agent: project.agent.or(self.agent),
-
-            // Panes: project replaces global (no placeholder support)
-            panes: project.panes.or(self.panes),
+            agent_placeholder: project.agent_placeholder.or(self.agent_placeholder),
+            agent_prompt_format: project.agent_prompt_format.or(self.agent_prompt_format),
+            agent_record: project.agent_record.or(self.agent_record),
+            startup_shell: project.startup_shell.or(self.startup_shell),
+            hooks_in_pane: project.hooks_in_pane.or(self.hooks_in_pane),
+            create_main_window: project.create_main_window.or(self.create_main_window),
+            commit_generated_files: project
+                .commit_generated_files
+                .or(self.commit_generated_files),
+            network_timeout: project.network_timeout.or(self.network_timeout),
+            agent_ready_check: project.agent_ready_check.or(self.agent_ready_check),
+            agent_ready_timeout: project.agent_ready_timeout.or(self.agent_ready_timeout),
+            post_merge_switch_to: project.post_merge_switch_to.or(self.post_merge_switch_to),
+            hook_timeout_secs: project.hook_timeout_secs.or(self.hook_timeout_secs),
+            confirm: project.confirm.or(self.confirm),
+
+            // Panes and layout describe the same thing, so a layer setting
+            // either one replaces both from the layers below
+            panes,
+            layout,
 
             // List values with placeholder support
             post_create: merge_vec_with_placeholder(self.post_create, project.post_create),
             pre_delete: merge_vec_with_placeholder(self.pre_delete, project.pre_delete),
+            post_merge: merge_vec_with_placeholder(self.post_merge, project.post_merge),
+            after_add: merge_vec_with_placeholder(self.after_add, project.after_add),
+            ignore_dirty_paths: merge_vec_with_placeholder(
+                self.ignore_dirty_paths,
+                project.ignore_dirty_paths,
+            ),
+            list_exclude: merge_vec_with_placeholder(self.list_exclude, project.list_exclude),
 
             // File config with placeholder support
             files: FileConfig {
                 copy: merge_vec_with_placeholder(self.files.copy, project.files.copy),
                 symlink: merge_vec_with_placeholder(self.files.symlink, project.files.symlink),
             },
+
+            // Command-line only, applied after merging
+            verbose: false,
+            quiet: false,
         }
     }
 
+    /// Expand `layout` into `panes`, leaving only `panes` set. Setting both is
+    /// an error, since it isn't clear which one should win.
+    fn apply_layout(&mut self) -> anyhow::Result<()> {
+        let Some(layout) = self.layout.take() else {
+            return Ok(());
+        };
+        if self.panes.as_ref().is_some_and(|panes| !panes.is_empty()) {
+            anyhow::bail!("Both 'layout' and 'panes' are configured; remove one of them.");
+        }
+        self.panes = Some(layout.panes(self.agent_placeholder()));
+        Ok(())
+    }
+
     /// Get default panes.
     fn default_panes() -> Vec<PaneConfig> {
         vec![
@@ -333,6 +785,10 @@ impl Config {
                 size: None,
                 percentage: None,
                 target: None,
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
             PaneConfig {
                 command: Some("clear".to_string()),
@@ -341,20 +797,28 @@ impl Config {
                 size: None,
                 percentage: None,
                 target: None, // Splits most recent (pane 0)
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
         ]
     }
 
-    /// Get default panes for a Claude project.
-    fn claude_default_panes() -> Vec<PaneConfig> {
+    /// Get default panes for a Claude project, running the agent through `placeholder`.
+    fn claude_default_panes(placeholder: &str) -> Vec<PaneConfig> {
         vec![
             PaneConfig {
-                command: Some("<agent>".to_string()),
+                command: Some(placeholder.to_string()),
                 focus: true,
                 split: None,
                 size: None,
                 percentage: None,
                 target: None,
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
             PaneConfig {
                 command: Some("clear".to_string()),
@@ -363,6 +827,10 @@ impl Config {
                 size: None,
                 percentage: None,
                 target: None, // Splits most recent (pane 0)
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
         ]
     }
@@ -372,6 +840,154 @@ impl Config {
         self.window_prefix.as_deref().unwrap_or("wm-")
     }
 
+    /// Get the configured multiplexer backend, defaulting to auto-detection
+    pub fn multiplexer(&self) -> Multiplexer {
+        self.multiplexer.unwrap_or_default()
+    }
+
+    /// Zellij session to target: `--zellij-session`, then `zellij_session`
+    pub fn zellij_session(&self) -> Option<&str> {
+        self.zellij_session.as_deref()
+    }
+
+    /// Agent to substitute for `<agent>`: a per-task override (e.g. `add --agent`)
+    /// takes precedence over the configured agent
+    pub fn effective_agent<'a>(&'a self, task_agent: Option<&'a str>) -> Option<&'a str> {
+        task_agent.or(self.agent.as_deref())
+    }
+
+    /// Pane command replaced by the agent, defaulting to "<agent>"
+    pub fn agent_placeholder(&self) -> &str {
+        self.agent_placeholder.as_deref().unwrap_or("<agent>")
+    }
+
+    /// How long to wait for `agent_ready_check` to pass, defaulting to 60 seconds
+    pub fn agent_ready_timeout(&self) -> Duration {
+        Duration::from_secs(self.agent_ready_timeout.unwrap_or(60))
+    }
+
+    /// Get the paths ignored by the uncommitted-changes checks, defaulting to none
+    pub fn ignore_dirty_paths(&self) -> &[String] {
+        self.ignore_dirty_paths.as_deref().unwrap_or(&[])
+    }
+
+    /// Whether destructive commands should ask first, defaulting to true
+    pub fn confirm(&self) -> bool {
+        self.confirm.unwrap_or(true)
+    }
+
+    /// How long a hook may run before it is stopped, unlimited by default
+    pub fn hook_timeout(&self) -> Option<Duration> {
+        self.hook_timeout_secs.map(Duration::from_secs)
+    }
+
+    /// Get the branch globs hidden from `list`, defaulting to none
+    pub fn list_exclude(&self) -> &[String] {
+        self.list_exclude.as_deref().unwrap_or(&[])
+    }
+
+    /// Get the branch name template for issues, defaulting to "issue-{number}-{slug}"
+    pub fn issue_branch_template(&self) -> &str {
+        self.issue_branch_template
+            .as_deref()
+            .unwrap_or("issue-{number}-{slug}")
+    }
+
+    /// Get the branch name template for `add --pr`, defaulting to the PR's head
+    /// branch, or to `pr-{number}` for PRs from forks (whose head is often `main`)
+    pub fn pr_branch_template(&self, is_fork: bool) -> &str {
+        self.pr_branch_template
+            .as_deref()
+            .unwrap_or(if is_fork { "pr-{number}" } else { "{head}" })
+    }
+
+    /// Reject a `network_timeout` of 0, which would kill every fetch and push
+    /// as soon as it starts
+    fn check_network_timeout(&self) -> anyhow::Result<()> {
+        if self.network_timeout == Some(0) {
+            anyhow::bail!("'network_timeout' must be at least 1 second.");
+        }
+        Ok(())
+    }
+
+    /// Get the network operation timeout, defaulting to 60 seconds
+    pub fn network_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.network_timeout
+                .unwrap_or(cmd::DEFAULT_NETWORK_TIMEOUT_SECS),
+        )
+    }
+
+    /// Whether post-create hooks should run in a dedicated pane, defaulting to false
+    pub fn hooks_in_pane(&self) -> bool {
+        self.hooks_in_pane.unwrap_or(false)
+    }
+
+    /// Whether to open a missing main window after merge/remove, defaulting to false
+    pub fn create_main_window(&self) -> bool {
+        self.create_main_window.unwrap_or(false)
+    }
+
+    /// Whether to commit files generated during setup, defaulting to false
+    pub fn commit_generated_files(&self) -> bool {
+        self.commit_generated_files.unwrap_or(false)
+    }
+
+    /// A copy with every unset option that has a fixed default filled in, as
+    /// workmux uses it at runtime. Defaults that depend on the repository or the
+    /// PR (`main_branch`, `pr_branch_template`) are left as configured.
+    pub fn with_defaults(&self) -> Self {
+        let mut config = self.clone();
+        config.window_prefix = Some(self.window_prefix().to_string());
+        config.issue_branch_template = Some(self.issue_branch_template().to_string());
+        config.multiplexer = Some(self.multiplexer());
+        config.agent_placeholder = Some(self.agent_placeholder().to_string());
+        config.agent_ready_timeout = Some(self.agent_ready_timeout().as_secs());
+        config.network_timeout = Some(self.network_timeout().as_secs());
+        config.hooks_in_pane = Some(self.hooks_in_pane());
+        config.create_main_window = Some(self.create_main_window());
+        config.commit_generated_files = Some(self.commit_generated_files());
+        config.ignore_dirty_paths = Some(self.ignore_dirty_paths().to_vec());
+        config.list_exclude = Some(self.list_exclude().to_vec());
+        config.confirm = Some(self.confirm());
+        config
+    }
+
+    /// Write the project's own configuration, `.workmux.yaml` with the local
+    /// overrides on top, to `.workmux.local.yaml` in `dir` as a starting point
+    /// for per-worktree overrides. Returns the path written.
+    pub fn write_local_copy(dir: &Path) -> anyhow::Result<std::path::PathBuf> {
+        let project = Self::load_project()?.unwrap_or_default();
+        let local = Self::load_local()?.unwrap_or_default();
+        Self::project_copy(project, local)?.write_copy(dir)
+    }
+
+    /// The project and local layers merged, with the layout expanded. The
+    /// global config, defaults and command-line flags are left out, so they
+    /// keep applying to the worktree as they do everywhere else.
+    fn project_copy(project: Self, local: Self) -> anyhow::Result<Self> {
+        let mut config = project.merge(local);
+        config.apply_layout()?;
+        Ok(config)
+    }
+
+    /// Write `self` to `.workmux.local.yaml` in `dir`, leaving out unset options
+    fn write_copy(&self, dir: &Path) -> anyhow::Result<std::path::PathBuf> {
+        let mut value = serde_yaml::to_value(self)?;
+        if let serde_yaml::Value::Mapping(options) = &mut value {
+            options.retain(|_, v| !v.is_null());
+        }
+
+        let path = dir.join(format!("{}.yaml", LOCAL_CONFIG_STEM));
+        let contents = format!(
+            "# Overrides for this worktree only, seeded by `workmux add --copy-config`\n{}",
+            serde_yaml::to_string(&value)?
+        );
+        fs::write(&path, contents)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
     /// Create an example .workmux.yaml configuration file
     pub fn init() -> anyhow::Result<()> {
         use std::path::PathBuf;
@@ -386,6 +1002,7 @@ impl Config {
 
         let example_config = r#"# workmux project configuration
 # For global settings, edit ~/.config/workmux/config.yaml
+# For machine-specific overrides, use a gitignored .workmux.local.yaml
 
 # The primary branch to merge into.
 # Default: Auto-detected from remote's HEAD, or falls back to main or master.
@@ -396,12 +1013,63 @@ impl Config {
 # Default: A sibling directory named '<project_name>__worktrees'.
 # worktree_dir: .worktrees
 
-# Custom prefix for zellij tab names.
+# Template for the full path of each worktree; takes precedence over worktree_dir.
+# Variables: {{ branch }} (slashes replaced with '-'), {{ repo }}, {{ main_root }}.
+# Relative paths are resolved from the main worktree.
+# worktree_path: "../worktrees/{{ repo }}/{{ branch }}"
+
+# Custom prefix for zellij tab names. {repo} expands to the main worktree
+# directory name, e.g. "{repo}-".
 # window_prefix: wm-
 
+# Branch name used by `add --prompt-from-issue <N>` when no branch is given.
+# {number} is the issue number and {slug} the slugified issue title.
+# issue_branch_template: "issue-{number}-{slug}"
+
+# Branch name used by `add --pr <N>` when no branch is given. {number} is the
+# PR number, {head} its head branch, and {author} the PR author's login.
+# Default: "{head}", or "pr-{number}" for PRs from forks
+# pr_branch_template: "pr-{number}"
+
+# Terminal multiplexer to use: tmux, zellij, or auto.
+# Default: auto (detected from the ZELLIJ/TMUX environment variables; zellij
+# when ambiguous).
+# multiplexer: tmux
+
+# Named zellij session to open worktree tabs in, e.g. one session per project.
+# Can also be given per command with --zellij-session. The session must exist.
+# Default: the session workmux is run from.
+# zellij_session: my-project
+
 # The agent command to use when <agent> is specified in pane commands.
 # agent: claude
 
+# The pane command that is replaced by the agent. Change it if a program is
+# literally called <agent> or you prefer another marker. Default: <agent>
+# agent_placeholder: "@agent"
+
+# How to hand the prompt to an agent, keyed by its executable name. `{cmd}` is
+# the pane command and `{path}` the prompt file.
+# Default: `{cmd} -i "$(cat {path})"` for gemini, `{cmd} -- "$(cat {path})"` otherwise
+# agent_prompt_format:
+#   myagent: "{cmd} --prompt-file {path}"
+
+# Run the agent under a recorder to keep a full log of its terminal session.
+# `{cmd}` is the agent command and `{out}` a new file under
+# ~/.workmux/recordings named after the branch.
+# agent_record: asciinema rec --quiet {out} -c {cmd}
+# agent_record: script -q -f {out} -c {cmd}
+
+# Shell command polled after the panes are launched; `workmux add` waits until
+# it exits successfully, so scripts know the agent has started.
+# agent_ready_check: test -f .agent-ready
+# Seconds to keep polling before failing. Default: 60
+# agent_ready_timeout: 120
+
+# Seconds to wait for network operations (git fetch/push, gh) before giving up.
+# Default: 60
+# network_timeout: 120
+
 # Commands to run in the new worktree before the zellij tab is opened.
 # These hooks block tab creation, so reserve them for short tasks.
 # For long-running setup (e.g., pnpm install), prefer pane `command`s instead.
@@ -410,6 +1078,31 @@ impl Config {
   # Use "<global>" to inherit hooks from your global config.
   # - "<global>"
   # - mise use
+  # Run a hook only when a file exists or a shell predicate succeeds:
+  # - run: pnpm install
+  #   onlyif:
+  #     exists: package.json
+  # - run: uv sync
+  #   onlyif: command -v uv
+
+# Run post_create hooks in a dedicated pane of the new tab instead of blocking
+# `workmux add` until they finish. Useful for long installs you want to watch.
+# Default: false
+# hooks_in_pane: true
+
+# Shell used to run pane commands and left open afterward, instead of $SHELL.
+# A pane can override it with its own `shell`.
+# startup_shell: /bin/zsh
+
+# Open a tab for the main worktree when `merge` or `remove` closes the current
+# tab and the main branch has none, so you always land somewhere.
+# Default: false
+# create_main_window: true
+
+# Commit files that `files` operations and post_create hooks create in a new
+# worktree (e.g. a generated PROMPT.md), so they don't block `merge` later.
+# Hooks run with hooks_in_pane are not covered. Default: false
+# commit_generated_files: true
 
 # Cleanup commands run before worktree deletion
 # Default: Auto-detects Node.js projects and fast-deletes node_modules in background
@@ -419,12 +1112,72 @@ impl Config {
 # Or disable:
 # pre_delete: []
 
+# Stop a post_create or pre_delete command that runs longer than this many
+# seconds (SIGTERM, then SIGKILL) and treat it as failed. Default: no limit.
+# hook_timeout_secs: 600
+
+# Commands run in the merged-into worktree after a successful merge, before cleanup.
+# A failing command is reported but does not undo the merge.
+# post_merge:
+#   - cargo build
+
+# Branch to check out in the merged-into worktree once a merge and its cleanup
+# are done, e.g. to get the main worktree back to main after `merge --target`.
+# post_merge_switch_to: main
+
+# Commands run in the new worktree after `add` has set it up, with
+# WORKMUX_BRANCH, WORKMUX_WORKTREE and WORKMUX_PROMPT_FILE set. A failing
+# command is reported but does not undo the add.
+# after_add:
+#   - curl -s -X POST -d "started $WORKMUX_BRANCH" https://example.com/hook
+
+# Paths that don't count as uncommitted changes when merging or removing,
+# e.g. notes or prompts you keep untracked in each worktree.
+# ignore_dirty_paths:
+#   - NOTES.md
+
+# Set to false to answer yes to every confirmation prompt, like passing --yes.
+# confirm: false
+
+# Branch-name globs of worktrees `workmux list` hides unless run with --all,
+# e.g. long-lived worktrees. The main branch is always shown.
+# list_exclude:
+#   - docs
+#   - scratch/*
+
+# A predefined pane arrangement, instead of writing out `panes`:
+#   agent-shell       agent on the left, shell on the right (30%)
+#   agent-shell-logs  like agent-shell, plus a second shell below the first
+#   even              agent and shell side by side, 50/50
+# Cannot be combined with `panes`.
+# layout: agent-shell
+
 # Pane configuration for this project.
 # Note: zellij integration only supports single-pane mode; the first pane is used.
 # panes:
 #   # Run a command in the pane; a shell remains afterward
 #   - command: pnpm install
 #     focus: true
+#   # Close the pane once the command exits instead of leaving a shell
+#   - command: pnpm build
+#     split: horizontal
+#     keep_open: false
+#   # right/left/down/up also pick the side the new pane opens on
+#   # env sets variables for this pane only
+#   # size is lines/cells (80) or a percentage ("30%") of the split space
+#   - command: pnpm dev
+#     split: left
+#     size: "30%"
+#     env:
+#       PORT: "3000"
+#   # close_tab_on_exit closes the whole tab when this pane's command exits
+#   - command: <agent>
+#     split: horizontal
+#     close_tab_on_exit: true
+#   # shell overrides startup_shell for this pane
+#   - command: fish_config
+#     split: vertical
+#     shell: fish
 
 # File operations to perform when creating a worktree.
 files:
@@ -478,6 +1231,65 @@ pub fn resolve_executable_path(executable: &str) -> Option<String> {
     None
 }
 
+/// File formats a config can be written in, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+/// Extensions recognized for config files, in the order they are listed in errors
+const CONFIG_EXTENSIONS: [&str; 4] = ["yaml", "yml", "json", "toml"];
+
+/// Name of the uncommitted per-machine overrides file, without its extension
+const LOCAL_CONFIG_STEM: &str = ".workmux.local";
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(anyhow::anyhow!(
+                "Unrecognized config file extension: {}",
+                path.display()
+            )),
+        }
+    }
+
+    fn parse(self, contents: &str) -> anyhow::Result<Config> {
+        match self {
+            Self::Yaml => Ok(serde_yaml::from_str(contents)?),
+            Self::Json => Ok(serde_json::from_str(contents)?),
+            Self::Toml => Ok(serde_json::from_value(toml::parse(contents)?)?),
+        }
+    }
+}
+
+/// Find the config file named `<stem>.<ext>` in `dir` for any supported extension.
+/// Having more than one is an error, since it's unclear which should win.
+fn find_config_file(dir: &Path, stem: &str) -> anyhow::Result<Option<std::path::PathBuf>> {
+    let found: Vec<_> = CONFIG_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .filter(|path| path.exists())
+        .collect();
+
+    match found.as_slice() {
+        [] => Ok(None),
+        [path] => Ok(Some(path.clone())),
+        _ => Err(anyhow::anyhow!(
+            "Found multiple config files: {}. Keep only one.",
+            found
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
 pub fn split_first_token(command: &str) -> Option<(&str, &str)> {
     let trimmed = command.trim_start();
     if trimmed.is_empty() {
@@ -492,7 +1304,508 @@ pub fn split_first_token(command: &str) -> Option<(&str, &str)> {
 
 #[cfg(test)]
 mod tests {
-    use super::split_first_token;
+    use super::{
+        CliOverrides, Config, ConfigFormat, HookCommand, HookCondition, LayoutPreset, PaneSize,
+        SplitDirection, find_config_file, split_first_token, validate_panes_config,
+    };
+
+    #[test]
+    fn yaml_and_json_configs_load_identically() {
+        let yaml = r#"
+main_branch: trunk
+window_prefix: "dev-"
+post_create:
+  - pnpm install
+panes:
+  - command: "<agent>"
+    focus: true
+  - command: pnpm dev
+    split: horizontal
+    keep_open: false
+files:
+  copy:
+    - .env
+agent_prompt_format:
+  myagent: "{cmd} --prompt-file {path}"
+"#;
+        let json = r#"{
+  "main_branch": "trunk",
+  "window_prefix": "dev-",
+  "post_create": ["pnpm install"],
+  "panes": [
+    { "command": "<agent>", "focus": true },
+    { "command": "pnpm dev", "split": "horizontal", "keep_open": false }
+  ],
+  "files": { "copy": [".env"] },
+  "agent_prompt_format": { "myagent": "{cmd} --prompt-file {path}" }
+}"#;
+
+        let from_yaml = ConfigFormat::Yaml.parse(yaml).unwrap();
+        let from_json = ConfigFormat::Json.parse(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&from_yaml).unwrap(),
+            serde_json::to_value(&from_json).unwrap()
+        );
+        assert_eq!(from_json.window_prefix(), "dev-");
+    }
+
+    #[test]
+    fn toml_and_json_configs_load_identically() {
+        let toml = r#"
+main_branch = "trunk"
+window_prefix = "dev-"
+network_timeout = 30
+post_create = [
+  "pnpm install",
+  { run = "make", onlyif = { exists = "Makefile" } },
+]
+
+[files]
+copy = [".env"]
+
+[agent_prompt_format]
+myagent = "{cmd} --prompt-file {path}"
+
+[[panes]]
+command = "<agent>"
+focus = true
+
+[[panes]]
+command = "pnpm dev"
+split = "horizontal"
+keep_open = false
+"#;
+        let json = r#"{
+  "main_branch": "trunk",
+  "window_prefix": "dev-",
+  "network_timeout": 30,
+  "post_create": ["pnpm install", { "run": "make", "onlyif": { "exists": "Makefile" } }],
+  "panes": [
+    { "command": "<agent>", "focus": true },
+    { "command": "pnpm dev", "split": "horizontal", "keep_open": false }
+  ],
+  "files": { "copy": [".env"] },
+  "agent_prompt_format": { "myagent": "{cmd} --prompt-file {path}" }
+}"#;
+
+        let from_toml = ConfigFormat::Toml.parse(toml).unwrap();
+        let from_json = ConfigFormat::Json.parse(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&from_toml).unwrap(),
+            serde_json::to_value(&from_json).unwrap()
+        );
+        assert_eq!(from_toml.network_timeout, Some(30));
+        assert_eq!(from_toml.panes.as_ref().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn split_direction_accepts_side_aliases() {
+        let config = ConfigFormat::Yaml
+            .parse(
+                "panes:\n  - command: a\n  - split: right\n  - split: left\n  - split: down\n  - split: up\n",
+            )
+            .unwrap();
+        let splits: Vec<_> = config
+            .panes
+            .unwrap()
+            .into_iter()
+            .skip(1)
+            .map(|pane| pane.split.unwrap())
+            .collect();
+        assert_eq!(
+            splits,
+            vec![
+                SplitDirection::Right,
+                SplitDirection::Left,
+                SplitDirection::Down,
+                SplitDirection::Up
+            ]
+        );
+
+        assert!(!SplitDirection::Right.is_vertical() && !SplitDirection::Right.is_before());
+        assert!(!SplitDirection::Left.is_vertical() && SplitDirection::Left.is_before());
+        assert!(SplitDirection::Down.is_vertical() && !SplitDirection::Down.is_before());
+        assert!(SplitDirection::Up.is_vertical() && SplitDirection::Up.is_before());
+    }
+
+    #[test]
+    fn pane_size_accepts_cells_and_percentages() {
+        let config = ConfigFormat::Yaml
+            .parse(
+                "panes:\n  - command: a\n  - split: right\n    size: \"30%\"\n  \
+                 - split: down\n    size: \"80\"\n  - split: down\n    size: 12\n  \
+                 - split: up\n    percentage: 40\n",
+            )
+            .unwrap();
+        let sizes: Vec<_> = config
+            .panes
+            .unwrap()
+            .iter()
+            .map(|pane| pane.effective_size())
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                None,
+                Some(PaneSize::Percent(30)),
+                Some(PaneSize::Cells(80)),
+                Some(PaneSize::Cells(12)),
+                Some(PaneSize::Percent(40)),
+            ]
+        );
+
+        assert_eq!(" 25 % ".parse::<PaneSize>().unwrap(), PaneSize::Percent(25));
+        for invalid in ["", "%", "abc", "-5", "30px", "300%"] {
+            assert!(invalid.parse::<PaneSize>().is_err(), "{:?}", invalid);
+        }
+        let err = ConfigFormat::Yaml
+            .parse("panes:\n  - command: a\n  - split: right\n    size: wide\n")
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid pane size 'wide'"));
+    }
+
+    #[test]
+    fn pane_size_conflicts_with_percentage() {
+        let config = ConfigFormat::Yaml
+            .parse(
+                "panes:\n  - command: a\n  - split: right\n    size: \"30%\"\n    percentage: 30\n",
+            )
+            .unwrap();
+        let err = validate_panes_config(&config.panes.unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Pane 1 cannot have both 'size' and 'percentage' specified."
+        );
+
+        let config = ConfigFormat::Yaml
+            .parse("panes:\n  - command: a\n  - split: right\n    size: \"0%\"\n")
+            .unwrap();
+        assert!(validate_panes_config(&config.panes.unwrap()).is_err());
+    }
+
+    /// (command, focus, split, size, target) of each pane
+    type PaneSummary = (
+        Option<String>,
+        bool,
+        Option<SplitDirection>,
+        Option<PaneSize>,
+        Option<usize>,
+    );
+
+    fn layout_panes(yaml: &str) -> Vec<PaneSummary> {
+        let mut config = ConfigFormat::Yaml.parse(yaml).unwrap();
+        config.apply_layout().unwrap();
+        let panes = config.panes.unwrap();
+        validate_panes_config(&panes).unwrap();
+        panes
+            .into_iter()
+            .map(|p| (p.command, p.focus, p.split, p.size, p.target))
+            .collect()
+    }
+
+    #[test]
+    fn layout_presets_expand_to_panes() {
+        let agent = || (Some("<agent>".to_string()), true, None, None, None);
+
+        assert_eq!(
+            layout_panes("layout: agent-shell\n"),
+            vec![
+                agent(),
+                (
+                    None,
+                    false,
+                    Some(SplitDirection::Right),
+                    Some(PaneSize::Percent(30)),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(
+            layout_panes("layout: agent-shell-logs\n"),
+            vec![
+                agent(),
+                (
+                    None,
+                    false,
+                    Some(SplitDirection::Right),
+                    Some(PaneSize::Percent(30)),
+                    None
+                ),
+                (
+                    None,
+                    false,
+                    Some(SplitDirection::Down),
+                    Some(PaneSize::Percent(50)),
+                    Some(1)
+                ),
+            ]
+        );
+        assert_eq!(
+            layout_panes("layout: even\n"),
+            vec![
+                agent(),
+                (
+                    None,
+                    false,
+                    Some(SplitDirection::Right),
+                    Some(PaneSize::Percent(50)),
+                    None
+                ),
+            ]
+        );
+
+        // The agent pane follows a custom placeholder
+        assert_eq!(
+            layout_panes("layout: even\nagent_placeholder: \"@agent\"\n")[0].0,
+            Some("@agent".to_string())
+        );
+        assert_eq!(
+            LayoutPreset::AgentShell.panes("<agent>").len(),
+            layout_panes("layout: agent-shell\npanes: []\n").len()
+        );
+    }
+
+    #[test]
+    fn layout_conflicts_with_panes() {
+        let mut config = ConfigFormat::Yaml
+            .parse("layout: agent-shell\npanes:\n  - command: vim\n")
+            .unwrap();
+        let err = config.apply_layout().unwrap_err();
+        assert!(err.to_string().contains("Both 'layout' and 'panes'"));
+    }
+
+    #[test]
+    fn layout_or_panes_in_a_higher_layer_replaces_both() {
+        let global = ConfigFormat::Yaml
+            .parse("panes:\n  - command: vim\n")
+            .unwrap();
+        let project = ConfigFormat::Yaml.parse("layout: even\n").unwrap();
+        let mut merged = global.merge(project);
+        assert!(merged.panes.is_none());
+        merged.apply_layout().unwrap();
+        assert_eq!(merged.layout, None);
+        assert_eq!(merged.panes.unwrap().len(), 2);
+
+        let global = ConfigFormat::Yaml.parse("layout: even\n").unwrap();
+        let project = ConfigFormat::Yaml
+            .parse("panes:\n  - command: vim\n")
+            .unwrap();
+        let merged = global.merge(project);
+        assert_eq!(merged.layout, None);
+        assert_eq!(merged.panes.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn config_with_defaults_round_trips_through_yaml() {
+        let config = ConfigFormat::Yaml
+            .parse(
+                "main_branch: develop\nwindow_prefix: \"{repo}-\"\npost_create:\n  - make\n\
+                 panes:\n  - command: <agent>\n  - split: right\n    size: \"30%\"\n\
+                 files:\n  copy:\n    - .env\n",
+            )
+            .unwrap()
+            .with_defaults();
+        assert_eq!(config.window_prefix.as_deref(), Some("{repo}-"));
+        assert_eq!(config.network_timeout, Some(60));
+        assert_eq!(config.hooks_in_pane, Some(false));
+        assert_eq!(config.confirm, Some(true));
+        assert_eq!(
+            config.issue_branch_template.as_deref(),
+            Some("issue-{number}-{slug}")
+        );
+
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        let reloaded = ConfigFormat::Yaml.parse(&yaml).unwrap();
+        assert_eq!(
+            serde_json::to_value(&reloaded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+        assert_eq!(
+            reloaded.panes.unwrap()[1].effective_size(),
+            Some(PaneSize::Percent(30))
+        );
+    }
+
+    #[test]
+    fn hooks_accept_strings_and_conditional_objects() {
+        let global = ConfigFormat::Yaml
+            .parse("post_create:\n  - mise install\n")
+            .unwrap();
+        let project = ConfigFormat::Yaml
+            .parse(
+                "post_create:\n  - \"<global>\"\n  - run: pnpm install\n    onlyif:\n      exists: package.json\n\
+                 \x20 - run: uv sync\n    onlyif: command -v uv\n",
+            )
+            .unwrap();
+
+        let hooks = global.merge(project).post_create.unwrap();
+        assert_eq!(
+            hooks,
+            vec![
+                HookCommand::Command("mise install".to_string()),
+                HookCommand::Conditional {
+                    run: "pnpm install".to_string(),
+                    onlyif: Some(HookCondition::Exists {
+                        exists: "package.json".to_string()
+                    }),
+                },
+                HookCommand::Conditional {
+                    run: "uv sync".to_string(),
+                    onlyif: Some(HookCondition::Shell("command -v uv".to_string())),
+                },
+            ]
+        );
+        assert_eq!(hooks[1].command(), "pnpm install");
+        assert_eq!(hooks[0].condition(), None);
+    }
+
+    #[test]
+    fn config_format_follows_extension() {
+        use std::path::Path;
+        assert_eq!(
+            ConfigFormat::from_path(Path::new(".workmux.yml")).unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.json")).unwrap(),
+            ConfigFormat::Json
+        );
+        assert!(ConfigFormat::from_path(Path::new("config.ini")).is_err());
+        assert_eq!(
+            ConfigFormat::from_path(Path::new(".workmux.toml")).unwrap(),
+            ConfigFormat::Toml
+        );
+    }
+
+    #[test]
+    fn find_config_file_errors_on_ambiguity() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(find_config_file(dir.path(), ".workmux").unwrap().is_none());
+
+        std::fs::write(dir.path().join(".workmux.json"), "{}").unwrap();
+        assert_eq!(
+            find_config_file(dir.path(), ".workmux").unwrap(),
+            Some(dir.path().join(".workmux.json"))
+        );
+
+        std::fs::write(dir.path().join(".workmux.yaml"), "").unwrap();
+        let err = find_config_file(dir.path(), ".workmux").unwrap_err();
+        assert!(err.to_string().contains("Found multiple config files"));
+    }
+
+    #[test]
+    fn local_copy_reloads_as_the_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = ConfigFormat::Yaml
+            .parse("window_prefix: proj-\nlayout: agent-shell\npost_create:\n  - make\n")
+            .unwrap();
+        let local = ConfigFormat::Yaml.parse("agent: codex\n").unwrap();
+        let config = Config::project_copy(project, local).unwrap();
+
+        let path = config.write_copy(dir.path()).unwrap();
+        assert_eq!(path, dir.path().join(".workmux.local.yaml"));
+        assert!(
+            !std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("network_timeout")
+        );
+
+        // The copy must load on its own; a layout next to its expanded panes wouldn't
+        let mut reloaded = Config::load_from_path(&path).unwrap().unwrap();
+        reloaded.apply_layout().unwrap();
+        assert_eq!(reloaded.window_prefix.as_deref(), Some("proj-"));
+        assert_eq!(reloaded.layout, None);
+        assert_eq!(
+            serde_json::to_value(&reloaded.panes).unwrap(),
+            serde_json::to_value(&config.panes).unwrap()
+        );
+        assert_eq!(reloaded.agent.as_deref(), Some("codex"));
+        // Defaults are left unset so the global config still applies
+        assert_eq!(reloaded.network_timeout, None);
+        assert_eq!(reloaded.zellij_session, None);
+    }
+
+    #[test]
+    fn local_config_overrides_project_and_global() {
+        let parse = |yaml: &str| ConfigFormat::Yaml.parse(yaml).unwrap();
+        let global = parse("agent: claude\npost_create:\n  - mise install\nnetwork_timeout: 30\n");
+        let project = parse(
+            "agent: codex\nwindow_prefix: proj-\npost_create:\n  - \"<global>\"\n  - pnpm install\n",
+        );
+        let local =
+            parse("agent: /opt/bin/codex\npost_create:\n  - \"<global>\"\n  - direnv allow\n");
+
+        let config = global.merge(project).merge(local);
+        assert_eq!(config.agent.as_deref(), Some("/opt/bin/codex"));
+        assert_eq!(config.window_prefix.as_deref(), Some("proj-"));
+        assert_eq!(config.network_timeout, Some(30));
+        // In the local file "<global>" stands for everything configured below it
+        assert_eq!(
+            config.post_create.unwrap(),
+            vec![
+                HookCommand::from("mise install"),
+                HookCommand::from("pnpm install"),
+                HookCommand::from("direnv allow"),
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_network_timeout_is_rejected() {
+        let parse = |yaml: &str| ConfigFormat::Yaml.parse(yaml).unwrap();
+        let err = parse("network_timeout: 0\n")
+            .check_network_timeout()
+            .unwrap_err();
+        assert!(err.to_string().contains("network_timeout"));
+        assert!(
+            parse("network_timeout: 1\n")
+                .check_network_timeout()
+                .is_ok()
+        );
+        assert!(parse("agent: claude\n").check_network_timeout().is_ok());
+    }
+
+    #[test]
+    fn global_placeholder_without_inherited_values_expands_to_nothing() {
+        let local = ConfigFormat::Yaml
+            .parse("post_create:\n  - \"<global>\"\n  - direnv allow\n")
+            .unwrap();
+
+        let config = Config::default().merge(local);
+        assert_eq!(
+            config.post_create.unwrap(),
+            vec![HookCommand::from("direnv allow")]
+        );
+    }
+
+    #[test]
+    fn window_prefix_defaults_and_can_be_disabled() {
+        assert_eq!(Config::default().window_prefix(), "wm-");
+
+        let mut config = Config {
+            window_prefix: Some("dev-".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.window_prefix(), "dev-");
+
+        let no_prefix = CliOverrides {
+            no_prefix: true,
+            ..Default::default()
+        };
+        no_prefix.apply(&mut config);
+        assert_eq!(config.window_prefix(), "");
+    }
+
+    #[test]
+    fn effective_agent_prefers_task_override() {
+        let config = Config {
+            agent: Some("claude".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.effective_agent(Some("codex")), Some("codex"));
+        assert_eq!(config.effective_agent(None), Some("claude"));
+        assert_eq!(Config::default().effective_agent(None), None);
+    }
 
     #[test]
     fn split_first_token_single_word() {
@@ -1,9 +1,10 @@
 use anyhow::{Context, Result, anyhow};
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::cmd::Cmd;
+use crate::cmd::{self, Cmd};
 
 #[derive(Debug, Clone)]
 pub struct RemoteBranchSpec {
@@ -11,6 +12,32 @@ pub struct RemoteBranchSpec {
     pub branch: String,
 }
 
+/// Outcome of fast-forwarding a worktree's branch to its upstream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FastForwardOutcome {
+    /// The branch was fast-forwarded to its upstream
+    Advanced { from: String, to: String },
+    /// The branch already contains everything on its upstream
+    UpToDate,
+    /// The branch and its upstream have both moved; a fast-forward is impossible
+    Diverged,
+    /// The branch has no upstream configured
+    NoUpstream,
+}
+
+/// Where the branch checked out in a new worktree comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchSource<'a> {
+    /// Check out a local branch that already exists
+    Existing,
+    /// Create the branch from a start point (branch, tag, or commit)
+    NewFrom(&'a str),
+    /// Create the branch from a remote-tracking branch (`<remote>/<branch>`) and track it
+    Remote(&'a str),
+    /// Create the branch with no history, in an empty worktree
+    Orphan,
+}
+
 /// Custom error type for worktree not found
 #[derive(Debug, thiserror::Error)]
 #[error("Worktree not found for branch: {0}")]
@@ -33,20 +60,35 @@ pub fn get_repo_root() -> Result<PathBuf> {
 
 /// Get the main worktree root directory (not a linked worktree)
 pub fn get_main_worktree_root() -> Result<PathBuf> {
-    // Get all worktrees
-    let list_str = Cmd::new("git")
-        .args(&["worktree", "list", "--porcelain"])
-        .run_and_capture_stdout()
-        .context("Failed to list worktrees while locating main worktree")?;
-
-    let worktrees = parse_worktree_list_porcelain(&list_str)?;
+    WorktreeTable::load()
+        .context("Failed to list worktrees while locating main worktree")?
+        .main_root()
+}
 
-    // The first worktree in the list is always the main worktree
-    if let Some((path, _)) = worktrees.first() {
-        Ok(path.clone())
-    } else {
-        Err(anyhow!("No main worktree found"))
+/// Pick the main worktree root from parsed `git worktree list --porcelain` entries.
+///
+/// The first entry is always the main worktree. In a bare-repo setup that entry is
+/// the bare repository itself, which has no working tree, so the directory
+/// containing it is used instead.
+fn main_worktree_root(entries: &[Worktree]) -> Result<PathBuf> {
+    let main = entries
+        .first()
+        .ok_or_else(|| anyhow!("No main worktree found"))?;
+
+    if !main.bare {
+        return Ok(main.path.clone());
     }
+
+    main.path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .ok_or_else(|| {
+            anyhow!(
+                "Repository at '{}' is bare and has no directory to use as the main worktree",
+                main.path.display()
+            )
+        })
 }
 
 /// Get the default branch (main or master)
@@ -60,13 +102,13 @@ pub fn get_default_branch() -> Result<String> {
         return Ok(branch.to_string());
     }
 
-    // Fallback: check if main or master exists locally
-    if branch_exists("main")? {
-        return Ok("main".to_string());
-    }
-
-    if branch_exists("master")? {
-        return Ok("master".to_string());
+    // Fallback: check if main, master or the configured init.defaultBranch exists locally
+    let init_default = Cmd::new("git")
+        .args(&["config", "--get", "init.defaultBranch"])
+        .run_and_capture_stdout()
+        .ok();
+    if let Some(branch) = pick_local_default_branch(init_default.as_deref(), branch_exists)? {
+        return Ok(branch);
     }
 
     // No default branch could be determined - require explicit configuration
@@ -76,10 +118,66 @@ pub fn get_default_branch() -> Result<String> {
     ))
 }
 
-/// Check if a branch exists (can be local or remote tracking branch)
+/// First existing branch among `main`, `master` and git's `init.defaultBranch`
+fn pick_local_default_branch(
+    init_default: Option<&str>,
+    exists: impl Fn(&str) -> Result<bool>,
+) -> Result<Option<String>> {
+    let candidates = ["main", "master"]
+        .into_iter()
+        .chain(init_default.map(str::trim).filter(|b| !b.is_empty()));
+    for branch in candidates {
+        if exists(branch)? {
+            return Ok(Some(branch.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Fully qualified ref of a local branch, so a name like `release/1.0` can't
+/// resolve to a tag or remote-tracking branch of the same name
+pub fn local_branch_ref(branch_name: &str) -> String {
+    format!("refs/heads/{}", branch_name)
+}
+
+/// Fully qualified ref of a remote-tracking branch given as `<remote>/<branch>`
+pub fn remote_branch_ref(remote_branch: &str) -> String {
+    format!("refs/remotes/{}", remote_branch)
+}
+
+/// Check if a local branch exists
 pub fn branch_exists(branch_name: &str) -> Result<bool> {
+    ref_exists(&local_branch_ref(branch_name))
+}
+
+/// Whether `name` is a legal branch name under git's ref naming rules
+/// (see `git check-ref-format`)
+pub fn is_valid_branch_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "@"
+        && !name.starts_with('-')
+        && !name.starts_with('/')
+        && !name.ends_with('/')
+        && !name.ends_with('.')
+        && !name.contains("..")
+        && !name.contains("//")
+        && !name.contains("@{")
+        && !name
+            .chars()
+            .any(|c| c.is_ascii_control() || " ~^:?*[\\".contains(c))
+        && name
+            .split('/')
+            .all(|part| !part.starts_with('.') && !part.ends_with(".lock"))
+}
+
+/// Check if a remote-tracking branch (`<remote>/<branch>`) exists
+pub fn remote_branch_exists(remote_branch: &str) -> Result<bool> {
+    ref_exists(&remote_branch_ref(remote_branch))
+}
+
+fn ref_exists(full_ref: &str) -> Result<bool> {
     Cmd::new("git")
-        .args(&["rev-parse", "--verify", "--quiet", branch_name])
+        .args(&["rev-parse", "--verify", "--quiet", full_ref])
         .run_as_check()
 }
 
@@ -117,35 +215,50 @@ pub fn list_remotes() -> Result<Vec<String>> {
         .collect())
 }
 
+/// Extra attempts for idempotent remote commands (fetch, remote branch delete)
+/// that fail with a transient network error
+const NETWORK_RETRIES: u32 = 2;
+const NETWORK_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 /// Check if a remote exists
 pub fn remote_exists(remote: &str) -> Result<bool> {
     Ok(list_remotes()?.into_iter().any(|name| name == remote))
 }
 
 /// Fetch updates from the given remote
-pub fn fetch_remote(remote: &str) -> Result<()> {
+pub fn fetch_remote(remote: &str, timeout: Duration) -> Result<()> {
     Cmd::new("git")
         .args(&["fetch", remote])
+        .timeout(timeout)
+        .with_retries(
+            NETWORK_RETRIES,
+            NETWORK_RETRY_DELAY,
+            cmd::is_transient_network_error,
+        )
         .run()
         .with_context(|| format!("Failed to fetch from remote '{}'", remote))?;
     Ok(())
 }
 
-/// Add a git remote if it doesn't exist
-pub fn add_remote(name: &str, url: &str) -> Result<()> {
-    Cmd::new("git")
-        .args(&["remote", "add", name, url])
-        .run()
-        .with_context(|| format!("Failed to add remote '{}' with URL '{}'", name, url))?;
-    Ok(())
-}
-
-/// Set the URL for an existing git remote
-pub fn set_remote_url(name: &str, url: &str) -> Result<()> {
+/// Fetch a pull request's head from origin (`pull/<n>/head`) into a local branch.
+/// This works for PRs from forks without adding the fork as a remote.
+pub fn fetch_pr_head(pr_number: u32, local_branch: &str, timeout: Duration) -> Result<()> {
+    let refspec = format!("pull/{}/head:refs/heads/{}", pr_number, local_branch);
     Cmd::new("git")
-        .args(&["remote", "set-url", name, url])
+        .args(&["fetch", "origin", &refspec])
+        .timeout(timeout)
+        .with_retries(
+            NETWORK_RETRIES,
+            NETWORK_RETRY_DELAY,
+            cmd::is_transient_network_error,
+        )
         .run()
-        .with_context(|| format!("Failed to set URL for remote '{}' to '{}'", name, url))?;
+        .with_context(|| {
+            format!(
+                "Failed to fetch PR #{} into branch '{}'",
+                pr_number, local_branch
+            )
+        })?;
     Ok(())
 }
 
@@ -188,57 +301,220 @@ pub fn get_repo_owner() -> Result<String> {
         .map(|s| s.to_string())
 }
 
-/// Check if a worktree already exists for a branch
-pub fn worktree_exists(branch_name: &str) -> Result<bool> {
-    match get_worktree_path(branch_name) {
-        Ok(_) => Ok(true),
-        Err(e) => {
-            // Check if this is a WorktreeNotFound error
-            if e.is::<WorktreeNotFound>() {
-                Ok(false)
-            } else {
-                Err(e)
-            }
+/// The URL of `owner`'s fork of the repository at `url`: the same host, path
+/// layout and protocol, with the owner segment replaced
+fn fork_url_from(url: &str, owner: &str) -> Option<String> {
+    let current = parse_owner_from_git_url(url)?;
+    let (prefix, rest) = if let Some(scheme_end) = url.find("://") {
+        let host_end = scheme_end + 3 + url[scheme_end + 3..].find('/')? + 1;
+        url.split_at(host_end)
+    } else {
+        url.split_at(url.find(':')? + 1)
+    };
+    let repo = rest.strip_prefix(current)?.strip_prefix('/')?;
+    Some(format!("{}{}/{}", prefix, owner, repo))
+}
+
+/// Get the URL of `owner`'s fork, derived from the origin remote URL
+pub fn get_fork_url(owner: &str) -> Result<String> {
+    let url = get_remote_url("origin")?;
+
+    fork_url_from(&url, owner)
+        .ok_or_else(|| anyhow!("Could not derive a fork URL from origin URL: {}", url))
+}
+
+/// Add a remote, or reuse it when a remote of that name already points at `url`
+pub fn ensure_remote(remote: &str, url: &str) -> Result<()> {
+    if remote_exists(remote)? {
+        let existing = get_remote_url(remote)?;
+        if existing == url {
+            return Ok(());
         }
+        return Err(anyhow!(
+            "Remote '{}' already exists with URL '{}' (expected '{}')",
+            remote,
+            existing,
+            url
+        ));
     }
+
+    Cmd::new("git")
+        .args(&["remote", "add", remote, url])
+        .run()
+        .with_context(|| format!("Failed to add remote '{}'", remote))?;
+    Ok(())
+}
+
+/// Point a local branch at `<remote>/<remote_branch>` for pull and push without
+/// requiring the remote-tracking branch to have been fetched
+pub fn set_branch_remote(branch_name: &str, remote: &str, remote_branch: &str) -> Result<()> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.remote", branch_name),
+            remote,
+        ])
+        .run()
+        .context("Failed to set branch remote")?;
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.merge", branch_name),
+            &local_branch_ref(remote_branch),
+        ])
+        .run()
+        .context("Failed to set branch merge ref")?;
+    Ok(())
+}
+
+/// Check if a worktree already exists for a branch
+pub fn worktree_exists(branch_name: &str) -> Result<bool> {
+    Ok(WorktreeTable::load()?.contains(branch_name))
 }
 
 /// Create a new git worktree
 pub fn create_worktree(
     worktree_path: &Path,
     branch_name: &str,
-    create_branch: bool,
-    base_branch: Option<&str>,
-    track_upstream: bool,
+    source: BranchSource<'_>,
 ) -> Result<()> {
     let path_str = worktree_path
         .to_str()
         .ok_or_else(|| anyhow!("Invalid worktree path"))?;
 
-    let mut cmd = Cmd::new("git").arg("worktree").arg("add");
-
-    if create_branch {
-        cmd = cmd.arg("-b").arg(branch_name).arg(path_str);
-        if let Some(base) = base_branch {
-            cmd = cmd.arg(base);
-        }
-    } else {
-        cmd = cmd.arg(path_str).arg(branch_name);
+    if source == BranchSource::Orphan && !git_version().is_ok_and(supports_worktree_orphan) {
+        return create_orphan_worktree_fallback(worktree_path, branch_name);
     }
 
-    cmd.run().context("Failed to create worktree")?;
+    let args = worktree_add_args(path_str, branch_name, source);
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    Cmd::new("git")
+        .args(&arg_refs)
+        .run_and_capture_combined()
+        .context("Failed to create worktree")?;
 
     // When creating a new branch from a remote tracking branch (e.g., origin/main),
     // git automatically sets up tracking for the new branch. This is desirable when
     // opening a remote branch locally, but we unset the upstream when the new branch
     // should be independent.
-    if create_branch && !track_upstream {
+    if let BranchSource::NewFrom(_) = source {
         unset_branch_upstream(branch_name)?;
     }
 
     Ok(())
 }
 
+/// `git worktree add --orphan` arrived in git 2.42
+fn supports_worktree_orphan(version: (u32, u32)) -> bool {
+    version >= (2, 42)
+}
+
+/// The (major, minor) version of the installed git
+fn git_version() -> Result<(u32, u32)> {
+    let output = Cmd::new("git")
+        .args(&["--version"])
+        .run_and_capture_stdout()
+        .context("Failed to get git version")?;
+    parse_git_version(&output).ok_or_else(|| anyhow!("Unrecognized git version: {}", output))
+}
+
+/// Parse (major, minor) from `git --version` output such as
+/// "git version 2.39.3 (Apple Git-146)" or "git version 2.42.0.windows.1"
+fn parse_git_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.trim().strip_prefix("git version ")?;
+    let mut parts = version.split(['.', ' ']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Emulate `git worktree add --orphan` for older git: add a detached worktree,
+/// switch it to a new orphan branch, and empty it
+fn create_orphan_worktree_fallback(worktree_path: &Path, branch_name: &str) -> Result<()> {
+    let path_str = worktree_path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid worktree path"))?;
+    Cmd::new("git")
+        .args(&["worktree", "add", "--detach", path_str])
+        .run()
+        .context("Failed to create worktree")?;
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["checkout", "--orphan", branch_name])
+        .run()
+        .with_context(|| format!("Failed to create orphan branch '{}'", branch_name))?;
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["rm", "-r", "-f", "--quiet", "--ignore-unmatch", "."])
+        .run()
+        .context("Failed to empty the orphan worktree")?;
+    Ok(())
+}
+
+/// Create a worktree on a new branch that starts at `base`, which may be a
+/// branch, tag, or (short) commit SHA. Fails before touching anything if `base`
+/// doesn't name a commit.
+pub fn create_worktree_from_base(
+    worktree_path: &Path,
+    branch_name: &str,
+    base: &str,
+) -> Result<()> {
+    resolve_commit(base)?;
+    create_worktree(worktree_path, branch_name, BranchSource::NewFrom(base))
+}
+
+/// Resolve a branch, tag, or commit SHA to the full hash of the commit it names
+pub fn resolve_commit(rev: &str) -> Result<String> {
+    let spec = format!("{}^{{commit}}", rev);
+    Cmd::new("git")
+        .args(&["rev-parse", "--verify", "--quiet", &spec])
+        .run_and_capture_stdout()
+        .map_err(|_| {
+            anyhow!(
+                "Base '{}' does not resolve to a commit. Use a branch, tag, or commit SHA.",
+                rev
+            )
+        })
+}
+
+/// Build the `git worktree add` arguments for checking out or creating `branch_name`
+fn worktree_add_args(path: &str, branch_name: &str, source: BranchSource<'_>) -> Vec<String> {
+    let mut args = vec!["worktree".to_string(), "add".to_string()];
+    match source {
+        // git treats a bare name as a branch checkout when refs/heads/<name> exists,
+        // whereas a qualified ref would give a detached HEAD
+        BranchSource::Existing => {
+            args.push(path.to_string());
+            args.push(branch_name.to_string());
+        }
+        BranchSource::NewFrom(start_point) => {
+            args.extend(["-b".to_string(), branch_name.to_string(), path.to_string()]);
+            args.push(start_point.to_string());
+        }
+        // Explicit --track, so the upstream is set even with branch.autoSetupMerge=false
+        BranchSource::Remote(remote_branch) => {
+            args.extend([
+                "--track".to_string(),
+                "-b".to_string(),
+                branch_name.to_string(),
+                path.to_string(),
+            ]);
+            args.push(remote_branch_ref(remote_branch));
+        }
+        BranchSource::Orphan => {
+            args.extend([
+                "--orphan".to_string(),
+                "-b".to_string(),
+                branch_name.to_string(),
+                path.to_string(),
+            ]);
+        }
+    }
+    args
+}
+
 /// Unset the upstream tracking for a branch
 pub fn unset_branch_upstream(branch_name: &str) -> Result<()> {
     if !branch_has_upstream(branch_name)? {
@@ -277,62 +553,201 @@ pub fn prune_worktrees() -> Result<()> {
     Ok(())
 }
 
+/// A single entry from `git worktree list --porcelain`
+#[derive(Debug, PartialEq)]
+pub struct Worktree {
+    pub path: PathBuf,
+    /// Checked-out branch; None for a detached HEAD or a bare repo
+    pub branch: Option<String>,
+    /// Commit checked out; empty for a bare repo, which has no HEAD line
+    pub head: String,
+    /// HEAD is detached rather than on a branch
+    pub detached: bool,
+    /// Locked with `git worktree lock`, so git won't prune or remove it
+    #[allow(dead_code)]
+    pub locked: bool,
+    /// The entry is a bare repository rather than a working tree
+    pub bare: bool,
+}
+
 /// Parse the output of `git worktree list --porcelain`
-fn parse_worktree_list_porcelain(output: &str) -> Result<Vec<(PathBuf, String)>> {
-    let mut worktrees = Vec::new();
+fn parse_worktree_list_porcelain(output: &str) -> Result<Vec<Worktree>> {
+    let mut entries = Vec::new();
     for block in output.trim().split("\n\n") {
         let mut path: Option<PathBuf> = None;
         let mut branch: Option<String> = None;
+        let mut head = String::new();
+        let mut detached = false;
+        let mut locked = false;
+        let mut bare = false;
 
         for line in block.lines() {
+            let line = line.trim();
             if let Some(p) = line.strip_prefix("worktree ") {
                 path = Some(PathBuf::from(p));
+            } else if let Some(sha) = line.strip_prefix("HEAD ") {
+                head = sha.to_string();
             } else if let Some(b) = line.strip_prefix("branch refs/heads/") {
                 branch = Some(b.to_string());
-            } else if line.trim() == "detached" {
-                branch = Some("(detached)".to_string());
+            } else if line == "detached" {
+                detached = true;
+            } else if line == "locked" || line.starts_with("locked ") {
+                locked = true;
+            } else if line == "bare" {
+                bare = true;
             }
         }
 
-        if let (Some(p), Some(b)) = (path, branch) {
-            worktrees.push((p, b));
+        if let Some(path) = path {
+            entries.push(Worktree {
+                path,
+                branch,
+                head,
+                detached,
+                locked,
+                bare,
+            });
         }
     }
-    Ok(worktrees)
+    Ok(entries)
+}
+
+/// The (path, branch) pairs of entries that have a working tree, with
+/// "(detached)" standing in for the branch of a detached HEAD
+fn checked_out_worktrees(entries: &[Worktree]) -> Vec<(PathBuf, String)> {
+    entries
+        .iter()
+        .filter(|entry| !entry.bare)
+        .filter_map(|entry| {
+            let branch = match &entry.branch {
+                Some(branch) => branch.clone(),
+                None if entry.detached => "(detached)".to_string(),
+                None => return None,
+            };
+            Some((entry.path.clone(), branch))
+        })
+        .collect()
+}
+
+/// The repository's worktrees as read by one `git worktree list --porcelain`,
+/// so a command needing several lookups runs git once. It is a snapshot: it
+/// doesn't see worktrees added or removed after it was loaded.
+#[derive(Debug)]
+pub struct WorktreeTable {
+    entries: Vec<Worktree>,
+}
+
+impl WorktreeTable {
+    /// List the worktrees of the repository in the current directory
+    pub fn load() -> Result<Self> {
+        let list = Cmd::new("git")
+            .args(&["worktree", "list", "--porcelain"])
+            .run_and_capture_stdout()
+            .context("Failed to list worktrees")?;
+        Self::from_porcelain(&list)
+    }
+
+    fn from_porcelain(output: &str) -> Result<Self> {
+        Ok(Self {
+            entries: parse_worktree_list_porcelain(output)?,
+        })
+    }
+
+    /// Path of the worktree that has `branch_name` checked out
+    pub fn path_for(&self, branch_name: &str) -> Result<PathBuf> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.bare)
+            .find(|entry| entry.branch.as_deref() == Some(branch_name))
+            .map(|entry| entry.path.clone())
+            .ok_or_else(|| WorktreeNotFound(branch_name.to_string()).into())
+    }
+
+    /// Whether some worktree has `branch_name` checked out
+    pub fn contains(&self, branch_name: &str) -> bool {
+        self.path_for(branch_name).is_ok()
+    }
+
+    /// Root of the main worktree (see [`get_main_worktree_root`])
+    pub fn main_root(&self) -> Result<PathBuf> {
+        main_worktree_root(&self.entries)
+    }
+
+    /// (path, branch) of every worktree with a working tree
+    pub fn all(&self) -> Vec<(PathBuf, String)> {
+        checked_out_worktrees(&self.entries)
+    }
+
+    /// The commit checked out in the worktree at `path`, if its HEAD is detached
+    pub fn detached_head(&self, path: &Path) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.detached && entry.path == path)
+            .map(|entry| entry.head.as_str())
+            .filter(|head| !head.is_empty())
+    }
 }
 
 /// Get the path to a worktree for a given branch
 pub fn get_worktree_path(branch_name: &str) -> Result<PathBuf> {
-    let list_str = Cmd::new("git")
-        .args(&["worktree", "list", "--porcelain"])
-        .run_and_capture_stdout()
-        .context("Failed to list worktrees while locating worktree path")?;
-
-    let worktrees = parse_worktree_list_porcelain(&list_str)?;
+    WorktreeTable::load()
+        .context("Failed to list worktrees while locating worktree path")?
+        .path_for(branch_name)
+}
 
-    for (path, branch) in worktrees {
-        if branch == branch_name {
-            return Ok(path);
+/// Resolve symlinks in `path` so it can be compared with the canonical paths
+/// git reports for worktrees. Works for paths that don't exist yet (e.g. a
+/// worktree about to be created) by resolving the longest existing ancestor;
+/// `.` and `..` in the remainder are folded lexically.
+pub fn resolve_symlinks(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return normalize_path(&canonical.join(rest));
         }
     }
+    normalize_path(path)
+}
 
-    Err(WorktreeNotFound(branch_name.to_string()).into())
+/// Lexically drop `.` and fold `..` components
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
 }
 
 /// List all worktrees with their branches
 pub fn list_worktrees() -> Result<Vec<(PathBuf, String)>> {
-    let list = Cmd::new("git")
-        .args(&["worktree", "list", "--porcelain"])
-        .run_and_capture_stdout()
-        .context("Failed to list worktrees")?;
-    parse_worktree_list_porcelain(&list)
+    Ok(WorktreeTable::load()?.all())
 }
 
-/// Check if the worktree has uncommitted changes
-pub fn has_uncommitted_changes(worktree_path: &Path) -> Result<bool> {
+/// Pathspec arguments that limit a command to everything except `ignored`.
+/// Empty when nothing is ignored, so the command sees the whole worktree.
+fn excluding_pathspec(ignored: &[String]) -> Vec<String> {
+    if ignored.is_empty() {
+        return Vec::new();
+    }
+    let mut args = vec!["--".to_string(), ".".to_string()];
+    args.extend(ignored.iter().map(|path| format!(":!{}", path)));
+    args
+}
+
+/// Check if the worktree has uncommitted changes, not counting changes to `ignored` paths
+pub fn has_uncommitted_changes(worktree_path: &Path, ignored: &[String]) -> Result<bool> {
+    let pathspec = excluding_pathspec(ignored);
+    let pathspec: Vec<&str> = pathspec.iter().map(String::as_str).collect();
     let output = Cmd::new("git")
         .workdir(worktree_path)
         .args(&["status", "--porcelain"])
+        .args(&pathspec)
         .run_and_capture_stdout()?;
 
     Ok(!output.is_empty())
@@ -382,17 +797,107 @@ pub fn has_staged_changes(worktree_path: &Path) -> Result<bool> {
     Ok(!no_changes)
 }
 
-/// Check if the worktree has unstaged changes
-pub fn has_unstaged_changes(worktree_path: &Path) -> Result<bool> {
+/// Check if the worktree has unstaged changes, not counting changes to `ignored` paths
+pub fn has_unstaged_changes(worktree_path: &Path, ignored: &[String]) -> Result<bool> {
+    let pathspec = excluding_pathspec(ignored);
+    let pathspec: Vec<&str> = pathspec.iter().map(String::as_str).collect();
     // Exit code 0 = no changes, 1 = has changes
     // So we invert the result of run_as_check
     let no_changes = Cmd::new("git")
         .workdir(worktree_path)
         .args(&["diff", "--quiet"])
+        .args(&pathspec)
         .run_as_check()?;
     Ok(!no_changes)
 }
 
+/// Stash the unstaged changes of a worktree, leaving staged changes in the index,
+/// and return the stash commit
+pub fn stash_push_in_worktree(worktree_path: &Path, message: &str) -> Result<String> {
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["stash", "push", "--keep-index", "-m", message])
+        .run()
+        .context("Failed to stash changes")?;
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["rev-parse", "refs/stash"])
+        .run_and_capture_stdout()
+        .context("Failed to read the stash that was just created")
+}
+
+/// Restore the stash `stash` (a commit returned by `stash_push_in_worktree`) in a
+/// worktree and drop it, even if newer stashes were pushed since
+pub fn stash_pop_in_worktree(worktree_path: &Path, stash: &str) -> Result<()> {
+    let list = Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["stash", "list", "--format=%H"])
+        .run_and_capture_stdout()
+        .context("Failed to list stashes")?;
+    let entry = stash_entry(&list, stash)
+        .ok_or_else(|| anyhow!("Stash {} is no longer in the stash list", stash))?;
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["stash", "pop", &entry])
+        .run()
+        .context("Failed to pop stash")?;
+    Ok(())
+}
+
+/// The `stash@{n}` entry for the stash commit `stash` in `git stash list --format=%H` output
+fn stash_entry(list: &str, stash: &str) -> Option<String> {
+    list.lines()
+        .position(|commit| commit.trim() == stash)
+        .map(|index| format!("stash@{{{}}}", index))
+}
+
+/// Paths with staged, unstaged, or untracked changes in a worktree
+pub fn changed_paths(worktree_path: &Path) -> Result<BTreeSet<String>> {
+    let output = Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["status", "--porcelain", "-z", "--untracked-files=all"])
+        .run()?;
+    Ok(parse_porcelain_z_paths(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse `git status --porcelain -z` into the changed paths. Renames and copies
+/// are followed by their source path, which is skipped.
+fn parse_porcelain_z_paths(output: &str) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let Some((status, path)) = entry.split_at_checked(3) else {
+            continue;
+        };
+        paths.insert(path.to_string());
+        if status.starts_with(['R', 'C']) {
+            entries.next();
+        }
+    }
+    paths
+}
+
+/// Commit only `paths` in a worktree with the given message, leaving any other
+/// changes uncommitted
+pub fn commit_with_message(worktree_path: &Path, message: &str, paths: &[String]) -> Result<()> {
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["add", "--"])
+        .args(&paths)
+        .run()
+        .context("Failed to stage files for commit")?;
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["commit", "-m", message, "--"])
+        .args(&paths)
+        .run()
+        .context("Failed to commit files")?;
+    Ok(())
+}
+
 /// Commit staged changes in a worktree using the user's editor
 pub fn commit_with_editor(worktree_path: &Path) -> Result<()> {
     let status = Command::new("git")
@@ -408,6 +913,14 @@ pub fn commit_with_editor(worktree_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Whether git ignores `path` in the given worktree
+pub fn is_ignored(worktree_path: &Path, path: &Path) -> Result<bool> {
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["check-ignore", "--quiet", &path.to_string_lossy()])
+        .run_as_check()
+}
+
 /// Get the base branch for merge checks, preferring remote tracking branch
 pub fn get_merge_base(main_branch: &str) -> Result<String> {
     // Try to get the configured upstream tracking branch
@@ -422,7 +935,7 @@ pub fn get_merge_base(main_branch: &str) -> Result<String> {
 
     // Fallback: check if origin/<main_branch> exists
     let remote_main = format!("origin/{}", main_branch);
-    if branch_exists(&remote_main)? {
+    if remote_branch_exists(&remote_main)? {
         Ok(remote_main)
     } else {
         Ok(main_branch.to_string())
@@ -464,11 +977,39 @@ pub fn merge_in_worktree(worktree_path: &Path, branch_name: &str) -> Result<()>
     Cmd::new("git")
         .workdir(worktree_path)
         .args(&["merge", branch_name])
-        .run()
+        .run_and_capture_combined()
         .context("Failed to merge")?;
     Ok(())
 }
 
+/// Whether `ancestor` is reachable from `descendant`, i.e. merging `ancestor`
+/// into `descendant` would change nothing
+pub fn is_ancestor(worktree_path: &Path, ancestor: &str, descendant: &str) -> Result<bool> {
+    let output = cmd::output_with_timeout(
+        Command::new("git").current_dir(worktree_path).args([
+            "merge-base",
+            "--is-ancestor",
+            ancestor,
+            descendant,
+        ]),
+        None,
+    )
+    .context("Failed to execute git merge-base")?;
+
+    // Exit status 1 means "not an ancestor"; anything else is a real failure
+    // such as an unknown ref
+    match output.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        _ => Err(anyhow!(
+            "Failed to check whether '{}' is merged into '{}': {}",
+            ancestor,
+            descendant,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
 /// Rebase the current branch in a worktree onto a base branch
 pub fn rebase_branch_onto_base(worktree_path: &Path, base_branch: &str) -> Result<()> {
     Cmd::new("git")
@@ -484,16 +1025,29 @@ pub fn merge_squash_in_worktree(worktree_path: &Path, branch_name: &str) -> Resu
     Cmd::new("git")
         .workdir(worktree_path)
         .args(&["merge", "--squash", branch_name])
-        .run()
+        .run_and_capture_combined()
         .context("Failed to perform squash merge")?;
     Ok(())
 }
 
-/// Switch to a different branch in a specific worktree
-pub fn switch_branch_in_worktree(worktree_path: &Path, branch_name: &str) -> Result<()> {
+/// Switch to a different branch in a specific worktree. With `create`, a branch
+/// that doesn't exist locally is created from the worktree's HEAD.
+pub fn switch_branch_in_worktree(
+    worktree_path: &Path,
+    branch_name: &str,
+    create: bool,
+) -> Result<()> {
+    let local_ref = local_branch_ref(branch_name);
+    let create = create
+        && !Cmd::new("git")
+            .workdir(worktree_path)
+            .args(&["rev-parse", "--verify", "--quiet", &local_ref])
+            .run_as_check()?;
+    let args = switch_args(branch_name, create);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
     Cmd::new("git")
         .workdir(worktree_path)
-        .args(&["switch", branch_name])
+        .args(&args)
         .run()
         .with_context(|| {
             format!(
@@ -505,54 +1059,301 @@ pub fn switch_branch_in_worktree(worktree_path: &Path, branch_name: &str) -> Res
     Ok(())
 }
 
-/// Get the current branch name
-pub fn get_current_branch() -> Result<String> {
+/// Fetch from the remote configured for the branch checked out in a worktree
+pub fn fetch_in_worktree(worktree_path: &Path, timeout: Duration) -> Result<()> {
     Cmd::new("git")
-        .args(&["branch", "--show-current"])
-        .run_and_capture_stdout()
+        .workdir(worktree_path)
+        .args(&["fetch", "--quiet"])
+        .timeout(timeout)
+        .with_retries(
+            NETWORK_RETRIES,
+            NETWORK_RETRY_DELAY,
+            cmd::is_transient_network_error,
+        )
+        .run()
+        .with_context(|| format!("Failed to fetch in worktree '{}'", worktree_path.display()))?;
+    Ok(())
 }
 
-/// Delete a local branch
-pub fn delete_branch(branch_name: &str, force: bool) -> Result<()> {
-    // Run from main worktree root to avoid issues when deleting from within a worktree
-    // or after a worktree has been removed
-    let main_worktree_root = get_main_worktree_root()?;
+/// Fast-forward the branch checked out in a worktree to its upstream.
+///
+/// Behaves like `git pull --ff-only` without fetching again, so call
+/// `fetch_in_worktree` first. Diverged branches are reported, not treated as errors.
+pub fn ff_pull_in_worktree(worktree_path: &Path) -> Result<FastForwardOutcome> {
+    let upstream = match Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["rev-parse", "--verify", "--quiet", "@{upstream}"])
+        .run_and_capture_stdout()
+    {
+        Ok(sha) if !sha.is_empty() => sha,
+        _ => return Ok(FastForwardOutcome::NoUpstream),
+    };
 
-    let mut cmd = Cmd::new("git").workdir(&main_worktree_root).arg("branch");
+    let head = Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["rev-parse", "HEAD"])
+        .run_and_capture_stdout()
+        .context("Failed to resolve HEAD")?;
 
-    if force {
-        cmd = cmd.arg("-D");
-    } else {
-        cmd = cmd.arg("-d");
+    if head == upstream {
+        return Ok(FastForwardOutcome::UpToDate);
     }
 
-    cmd.arg(branch_name)
-        .run()
-        .context("Failed to delete branch")?;
-    Ok(())
-}
+    // Local commits on top of the upstream: nothing to pull.
+    if is_ancestor(worktree_path, &upstream, &head)? {
+        return Ok(FastForwardOutcome::UpToDate);
+    }
+
+    if !is_ancestor(worktree_path, &head, &upstream)? {
+        return Ok(FastForwardOutcome::Diverged);
+    }
 
-/// Delete a remote branch
-pub fn delete_remote_branch(branch_name: &str) -> Result<()> {
     Cmd::new("git")
-        .args(&["push", "origin", "--delete", branch_name])
+        .workdir(worktree_path)
+        .args(&["merge", "--ff-only", "--quiet", &upstream])
         .run()
-        .with_context(|| format!("Failed to delete remote branch '{}'", branch_name))?;
-    Ok(())
+        .with_context(|| {
+            format!(
+                "Failed to fast-forward worktree '{}'",
+                worktree_path.display()
+            )
+        })?;
+
+    Ok(FastForwardOutcome::Advanced {
+        from: head,
+        to: upstream,
+    })
 }
 
-/// Stash uncommitted changes, optionally including untracked files or using patch mode.
-pub fn stash_push(message: &str, include_untracked: bool, patch: bool) -> Result<()> {
-    use std::process::Command;
+/// Arguments for `git switch`, adding `-c` when the branch has to be created
+fn switch_args(branch_name: &str, create: bool) -> Vec<String> {
+    let mut args = vec!["switch".to_string()];
+    if create {
+        args.push("-c".to_string());
+    }
+    args.push(branch_name.to_string());
+    args
+}
 
-    if patch {
-        // For --patch mode, we need an interactive terminal
-        let status = Command::new("git")
-            .args(["stash", "push", "-m", message, "--patch"])
-            .status()
-            .context("Failed to run interactive git stash")?;
+/// Build the `git log` arguments listing commits reachable from `to` but not from `from`,
+/// newest first and at most `limit` of them
+fn log_range_args(from: &str, to: &str, limit: Option<usize>) -> Vec<String> {
+    let mut args = vec![
+        "log".to_string(),
+        "--oneline".to_string(),
+        "--no-decorate".to_string(),
+    ];
+    if let Some(limit) = limit {
+        args.push(format!("--max-count={}", limit));
+    }
+    args.push(format!("{}..{}", from, to));
+    args
+}
 
-        if !status.success() {
+/// How `git diff` presents the changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// The full patch
+    Patch,
+    /// Only the names of changed files
+    NameOnly,
+    /// A per-file summary of changed lines
+    Stat,
+}
+
+/// Build the `git diff` arguments for the changes `branch` made since it forked from `base`
+fn diff_args(base: &str, branch: &str, format: DiffFormat) -> Vec<String> {
+    let mut args = vec!["diff".to_string()];
+    match format {
+        DiffFormat::Patch => {}
+        DiffFormat::NameOnly => args.push("--name-only".to_string()),
+        DiffFormat::Stat => args.push("--stat".to_string()),
+    }
+    args.push(format!("{}...{}", base, local_branch_ref(branch)));
+    args
+}
+
+/// Get the diff of the changes committed on `branch` since it forked from `base`
+pub fn diff(worktree_path: &Path, base: &str, branch: &str, format: DiffFormat) -> Result<String> {
+    let args = diff_args(base, branch, format);
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&arg_refs)
+        .run_and_capture_stdout()
+        .with_context(|| format!("Failed to diff '{}' against '{}'", branch, base))
+}
+
+/// Count commits `branch` has that `base` lacks (ahead) and the reverse (behind),
+/// both measured from their merge base
+pub fn ahead_behind(base: &str, branch: &str) -> Result<(usize, usize)> {
+    let range = format!("{}...{}", base, local_branch_ref(branch));
+    let output = Cmd::new("git")
+        .args(&["rev-list", "--left-right", "--count", &range])
+        .run_and_capture_stdout()
+        .with_context(|| format!("Failed to compare '{}' with '{}'", branch, base))?;
+
+    parse_ahead_behind(&output)
+}
+
+/// Parse `rev-list --left-right --count base...branch` output ("<behind>\t<ahead>")
+/// into (ahead, behind)
+fn parse_ahead_behind(output: &str) -> Result<(usize, usize)> {
+    let mut counts = output.split_whitespace().map(str::parse::<usize>);
+    match (counts.next(), counts.next(), counts.next()) {
+        (Some(Ok(behind)), Some(Ok(ahead)), None) => Ok((ahead, behind)),
+        _ => Err(anyhow!("Unexpected rev-list output: '{}'", output)),
+    }
+}
+
+/// List commits in `from..to` as `git log --oneline` lines, run inside a worktree
+pub fn log_range(
+    worktree_path: &Path,
+    from: &str,
+    to: &str,
+    limit: Option<usize>,
+) -> Result<Vec<String>> {
+    let args = log_range_args(from, to, limit);
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let output = Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&arg_refs)
+        .run_and_capture_stdout()
+        .with_context(|| format!("Failed to list commits in '{}..{}'", from, to))?;
+
+    Ok(output.lines().map(String::from).collect())
+}
+
+/// Commit time and subject of the latest commit checked out in a worktree
+pub fn last_commit_info(worktree_path: &Path) -> Result<(SystemTime, String)> {
+    let output = Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["log", "-1", "--format=%ct%x00%s"])
+        .run_and_capture_stdout()
+        .with_context(|| {
+            format!(
+                "Failed to read the last commit in '{}'",
+                worktree_path.display()
+            )
+        })?;
+
+    parse_last_commit(&output)
+}
+
+/// Parse `git log -1 --format=%ct%x00%s` output ("<unix seconds>\0<subject>")
+fn parse_last_commit(output: &str) -> Result<(SystemTime, String)> {
+    let (timestamp, subject) = output
+        .split_once('\0')
+        .ok_or_else(|| anyhow!("Unexpected git log output: '{}'", output))?;
+    let seconds: u64 = timestamp
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid commit timestamp: '{}'", timestamp))?;
+
+    Ok((
+        UNIX_EPOCH + Duration::from_secs(seconds),
+        subject.trim_end().to_string(),
+    ))
+}
+
+/// Get the current branch name
+pub fn get_current_branch() -> Result<String> {
+    Cmd::new("git")
+        .args(&["branch", "--show-current"])
+        .run_and_capture_stdout()
+}
+
+/// Get the branch currently checked out in the given worktree
+pub fn get_current_branch_in_worktree(worktree_path: &Path) -> Result<String> {
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["branch", "--show-current"])
+        .run_and_capture_stdout()
+}
+
+/// List local branches plus remote-tracking branches, the latter by their name
+/// on the remote (`origin/feature` is listed as `feature`) so `git switch`
+/// can create a local tracking branch from them
+pub fn list_all_branches() -> Result<Vec<String>> {
+    let output = Cmd::new("git")
+        .args(&[
+            "for-each-ref",
+            "--format=%(refname)",
+            "refs/heads",
+            "refs/remotes",
+        ])
+        .run_and_capture_stdout()
+        .context("Failed to list branches")?;
+
+    Ok(parse_branch_refs(&output))
+}
+
+/// Parse full ref names into sorted, de-duplicated branch names
+fn parse_branch_refs(output: &str) -> Vec<String> {
+    let mut branches: Vec<String> = output
+        .lines()
+        .filter_map(|line| {
+            if let Some(branch) = line.strip_prefix("refs/heads/") {
+                return Some(branch);
+            }
+            // refs/remotes/<remote>/<branch>; skip the symbolic <remote>/HEAD
+            let (_, branch) = line.strip_prefix("refs/remotes/")?.split_once('/')?;
+            (branch != "HEAD").then_some(branch)
+        })
+        .map(String::from)
+        .collect();
+    branches.sort();
+    branches.dedup();
+    branches
+}
+
+/// Delete a local branch
+pub fn delete_branch(branch_name: &str, force: bool) -> Result<()> {
+    // Run from main worktree root to avoid issues when deleting from within a worktree
+    // or after a worktree has been removed
+    let main_worktree_root = get_main_worktree_root()?;
+
+    let mut cmd = Cmd::new("git").workdir(&main_worktree_root).arg("branch");
+
+    if force {
+        cmd = cmd.arg("-D");
+    } else {
+        cmd = cmd.arg("-d");
+    }
+
+    cmd.arg(branch_name)
+        .run()
+        .context("Failed to delete branch")?;
+    Ok(())
+}
+
+/// Delete a remote branch
+pub fn delete_remote_branch(branch_name: &str, timeout: Duration) -> Result<()> {
+    Cmd::new("git")
+        .args(&["push", "origin", "--delete", branch_name])
+        .timeout(timeout)
+        .with_retries(
+            NETWORK_RETRIES,
+            NETWORK_RETRY_DELAY,
+            cmd::is_transient_network_error,
+        )
+        .run()
+        .with_context(|| format!("Failed to delete remote branch '{}'", branch_name))?;
+    Ok(())
+}
+
+/// Stash uncommitted changes, optionally including untracked files or using patch mode.
+pub fn stash_push(message: &str, include_untracked: bool, patch: bool) -> Result<()> {
+    use std::process::Command;
+
+    if patch {
+        // For --patch mode, we need an interactive terminal
+        let status = Command::new("git")
+            .args(["stash", "push", "-m", message, "--patch"])
+            .status()
+            .context("Failed to run interactive git stash")?;
+
+        if !status.success() {
             return Err(anyhow!(
                 "Git stash --patch failed. Make sure you select at least one hunk."
             ));
@@ -580,7 +1381,12 @@ pub fn stash_pop(worktree_path: &Path) -> Result<()> {
 }
 
 /// Reset the worktree to HEAD, discarding all local changes.
+///
+/// Does nothing when there are no tracked changes to discard.
 pub fn reset_hard(worktree_path: &Path) -> Result<()> {
+    if !has_tracked_changes(worktree_path)? {
+        return Ok(());
+    }
     Cmd::new("git")
         .workdir(worktree_path)
         .args(&["reset", "--hard", "HEAD"])
@@ -589,8 +1395,21 @@ pub fn reset_hard(worktree_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Check if a merge is in progress (MERGE_HEAD exists) in a specific worktree
+pub fn is_merge_in_progress(worktree_path: &Path) -> Result<bool> {
+    Cmd::new("git")
+        .workdir(worktree_path)
+        .args(&["rev-parse", "-q", "--verify", "MERGE_HEAD"])
+        .run_as_check()
+}
+
 /// Abort a merge in progress in a specific worktree
+///
+/// Does nothing when no merge is in progress.
 pub fn abort_merge_in_worktree(worktree_path: &Path) -> Result<()> {
+    if !is_merge_in_progress(worktree_path)? {
+        return Ok(());
+    }
     Cmd::new("git")
         .workdir(worktree_path)
         .args(&["merge", "--abort"])
@@ -631,9 +1450,733 @@ pub fn get_branch_base(branch: &str) -> Result<String> {
     Ok(output)
 }
 
+/// Remember the pull request a branch was checked out from
+pub fn set_branch_pr(branch: &str, pr_number: u32) -> Result<()> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.workmux-pr", branch),
+            &pr_number.to_string(),
+        ])
+        .run()
+        .context("Failed to set workmux-pr config")?;
+    Ok(())
+}
+
+/// Get the pull request numbers recorded for all branches, keyed by branch name
+pub fn get_branch_prs() -> Result<HashMap<String, u32>> {
+    Ok(get_branch_config_values("workmux-pr")?
+        .into_iter()
+        .filter_map(|(branch, value)| Some((branch, value.parse().ok()?)))
+        .collect())
+}
+
+/// Remember the branch that `workmux merge` should merge a branch into by default
+pub fn set_branch_merge_target(branch: &str, target: &str) -> Result<()> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.workmux-merge-target", branch),
+            target,
+        ])
+        .run()
+        .context("Failed to set workmux-merge-target config")?;
+    Ok(())
+}
+
+/// The default merge target recorded for a branch, if any
+pub fn get_branch_merge_target(branch: &str) -> Option<String> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.workmux-merge-target", branch),
+        ])
+        .run_and_capture_stdout()
+        .ok()
+        .filter(|target| !target.is_empty())
+}
+
+/// Flag a branch whose worktree disappeared, so a later `prune --force` can delete it
+pub fn set_branch_orphaned(branch: &str) -> Result<()> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            &format!("branch.{}.workmux-orphaned", branch),
+            "true",
+        ])
+        .run()
+        .context("Failed to set workmux-orphaned config")?;
+    Ok(())
+}
+
+/// Remove the orphaned flag, e.g. when the branch has a worktree again
+pub fn unset_branch_orphaned(branch: &str) -> Result<()> {
+    Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            "--unset",
+            &format!("branch.{}.workmux-orphaned", branch),
+        ])
+        .run()
+        .context("Failed to unset workmux-orphaned config")?;
+    Ok(())
+}
+
+/// Get all branches flagged as orphaned by a previous prune
+pub fn get_orphaned_branches() -> Result<HashSet<String>> {
+    Ok(get_branch_config_values("workmux-orphaned")?
+        .into_keys()
+        .collect())
+}
+
+/// Read a workmux `branch.<name>.<key>` setting for every branch that has it
+fn get_branch_config_values(key: &str) -> Result<HashMap<String, String>> {
+    // Exits non-zero when nothing matches, which just means no branch has the key
+    let output = Cmd::new("git")
+        .args(&[
+            "config",
+            "--local",
+            "--get-regexp",
+            &format!(r"^branch\..*\.{}$", key),
+        ])
+        .run_and_capture_stdout()
+        .unwrap_or_default();
+
+    Ok(parse_branch_config(&output, key))
+}
+
+/// Parse `git config --get-regexp` output of `branch.<name>.<key> <value>` lines
+fn parse_branch_config(output: &str, key: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            let branch = name
+                .strip_prefix("branch.")?
+                .strip_suffix(key)?
+                .strip_suffix('.')?;
+            Some((branch.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_owner_from_git_url;
+    use super::{
+        BranchSource, DiffFormat, Worktree, WorktreeNotFound, WorktreeTable, checked_out_worktrees,
+        diff_args, excluding_pathspec, fork_url_from, is_valid_branch_name, log_range_args,
+        main_worktree_root, parse_ahead_behind, parse_branch_config, parse_branch_refs,
+        parse_git_version, parse_last_commit, parse_owner_from_git_url, parse_porcelain_z_paths,
+        parse_worktree_list_porcelain, pick_local_default_branch, resolve_symlinks,
+        supports_worktree_orphan, switch_args, worktree_add_args,
+    };
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn default_branch_falls_back_to_init_default_branch() {
+        let only = |existing: &'static str| move |branch: &str| Ok(branch == existing);
+
+        assert_eq!(
+            pick_local_default_branch(Some("trunk\n"), only("trunk")).unwrap(),
+            Some("trunk".to_string())
+        );
+        // main and master still win when they exist
+        assert_eq!(
+            pick_local_default_branch(Some("trunk"), only("master")).unwrap(),
+            Some("master".to_string())
+        );
+        // A configured default that doesn't exist yet isn't chosen
+        assert_eq!(
+            pick_local_default_branch(Some("trunk"), only("other")).unwrap(),
+            None
+        );
+        assert_eq!(
+            pick_local_default_branch(None, only("trunk")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn resolve_symlinks_follows_links_in_missing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().canonicalize().unwrap().join("real");
+        std::fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        assert_eq!(resolve_symlinks(&link), real);
+        assert_eq!(
+            resolve_symlinks(&link.join("not-yet/./feature")),
+            real.join("not-yet/feature")
+        );
+        assert_eq!(
+            resolve_symlinks(&link.join("missing/../feature")),
+            real.join("feature")
+        );
+        assert_eq!(
+            resolve_symlinks(&PathBuf::from("/nonexistent/a/../b")),
+            PathBuf::from("/nonexistent/b")
+        );
+    }
+
+    #[test]
+    fn test_parse_porcelain_z_paths() {
+        let output = " M src/main.rs\0?? PROMPT.md\0R  new name.md\0old name.md\0A  notes/a.md\0";
+        let paths: Vec<String> = parse_porcelain_z_paths(output).into_iter().collect();
+        assert_eq!(
+            paths,
+            vec!["PROMPT.md", "new name.md", "notes/a.md", "src/main.rs"]
+        );
+        assert!(parse_porcelain_z_paths("").is_empty());
+    }
+
+    #[test]
+    fn test_stash_push_and_pop_in_worktree() {
+        use crate::cmd::Cmd;
+
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Cmd::new("git")
+                .workdir(dir.path())
+                .args(args)
+                .run_and_capture_stdout()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        std::fs::write(dir.path().join("file.txt"), "committed\n").unwrap();
+        git(&["add", "file.txt"]);
+        git(&["commit", "-q", "-m", "initial"]);
+
+        std::fs::write(dir.path().join("file.txt"), "edited\n").unwrap();
+        assert!(super::has_unstaged_changes(dir.path(), &[]).unwrap());
+
+        let stash = super::stash_push_in_worktree(dir.path(), "test stash").unwrap();
+        assert_eq!(stash, git(&["rev-parse", "refs/stash"]));
+        assert!(!super::has_unstaged_changes(dir.path(), &[]).unwrap());
+
+        // A newer stash must not be the one that gets popped
+        std::fs::write(dir.path().join("file.txt"), "unrelated\n").unwrap();
+        git(&["stash", "push", "-q", "-m", "unrelated"]);
+
+        super::stash_pop_in_worktree(dir.path(), &stash).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            "edited\n"
+        );
+        let remaining = git(&["stash", "list", "--format=%s"]);
+        assert!(remaining.ends_with("unrelated"), "{}", remaining);
+        assert_eq!(remaining.lines().count(), 1);
+    }
+
+    #[test]
+    fn stash_entry_finds_the_recorded_stash() {
+        let list = "aaa\nbbb\nccc\n";
+        assert_eq!(
+            super::stash_entry(list, "bbb"),
+            Some("stash@{1}".to_string())
+        );
+        assert_eq!(
+            super::stash_entry(list, "aaa"),
+            Some("stash@{0}".to_string())
+        );
+        assert_eq!(super::stash_entry(list, "ddd"), None);
+    }
+
+    #[test]
+    fn test_is_valid_branch_name() {
+        for name in [
+            "main",
+            "pr-42",
+            "review/octocat/fix-login",
+            "feature/a.b",
+            "v1.0",
+        ] {
+            assert!(is_valid_branch_name(name), "{} should be valid", name);
+        }
+        for name in [
+            "",
+            "@",
+            "-flag",
+            "/lead",
+            "trail/",
+            "dot.",
+            "a..b",
+            "a//b",
+            "a@{1}",
+            "with space",
+            "tilde~1",
+            "caret^",
+            "colon:x",
+            "glob*",
+            "what?",
+            "br[acket",
+            "back\\slash",
+            "feature/.hidden",
+            "topic.lock",
+            "topic.lock/x",
+        ] {
+            assert!(!is_valid_branch_name(name), "{:?} should be invalid", name);
+        }
+    }
+
+    #[test]
+    fn test_switch_args_creates_only_when_asked() {
+        assert_eq!(switch_args("release", false), vec!["switch", "release"]);
+        assert_eq!(
+            switch_args("release", true),
+            vec!["switch", "-c", "release"]
+        );
+    }
+
+    #[test]
+    fn test_switch_branch_in_worktree_creates_missing_branch() {
+        use crate::cmd::Cmd;
+
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Cmd::new("git")
+                .workdir(dir.path())
+                .args(args)
+                .run_and_capture_stdout()
+                .unwrap()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+        git(&["branch", "existing"]);
+
+        super::switch_branch_in_worktree(dir.path(), "release", true).unwrap();
+        super::switch_branch_in_worktree(dir.path(), "existing", true).unwrap();
+        assert!(super::switch_branch_in_worktree(dir.path(), "nope", false).is_err());
+        assert_eq!(git(&["branch", "--show-current"]), "existing");
+        assert_eq!(git(&["rev-parse", "release"]), git(&["rev-parse", "main"]));
+    }
+
+    #[test]
+    fn test_abort_merge_and_reset_hard_without_anything_to_undo() {
+        use crate::cmd::Cmd;
+
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Cmd::new("git")
+                .workdir(dir.path())
+                .args(args)
+                .run_and_capture_stdout()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        std::fs::write(dir.path().join("file.txt"), "committed\n").unwrap();
+        git(&["add", "file.txt"]);
+        git(&["commit", "-q", "-m", "initial"]);
+        std::fs::write(dir.path().join("untracked.txt"), "keep me\n").unwrap();
+
+        assert!(!super::is_merge_in_progress(dir.path()).unwrap());
+        super::abort_merge_in_worktree(dir.path()).unwrap();
+        super::reset_hard(dir.path()).unwrap();
+        assert!(dir.path().join("untracked.txt").exists());
+
+        std::fs::write(dir.path().join("file.txt"), "edited\n").unwrap();
+        super::reset_hard(dir.path()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            "committed\n"
+        );
+    }
+
+    #[test]
+    fn test_abort_merge_in_worktree_aborts_conflicted_merge() {
+        use crate::cmd::Cmd;
+
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Cmd::new("git")
+                .workdir(dir.path())
+                .args(args)
+                .run_and_capture_stdout()
+        };
+        let file = dir.path().join("file.txt");
+        git(&["init", "-q", "-b", "main"]).unwrap();
+        git(&["config", "user.name", "Test"]).unwrap();
+        git(&["config", "user.email", "test@example.com"]).unwrap();
+        std::fs::write(&file, "base\n").unwrap();
+        git(&["add", "file.txt"]).unwrap();
+        git(&["commit", "-q", "-m", "base"]).unwrap();
+        git(&["checkout", "-q", "-b", "other"]).unwrap();
+        std::fs::write(&file, "other\n").unwrap();
+        git(&["commit", "-q", "-am", "other"]).unwrap();
+        git(&["checkout", "-q", "main"]).unwrap();
+        std::fs::write(&file, "main\n").unwrap();
+        git(&["commit", "-q", "-am", "main"]).unwrap();
+        assert!(git(&["merge", "other"]).is_err());
+
+        assert!(super::is_merge_in_progress(dir.path()).unwrap());
+        super::abort_merge_in_worktree(dir.path()).unwrap();
+        assert!(!super::is_merge_in_progress(dir.path()).unwrap());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "main\n");
+    }
+
+    #[test]
+    fn test_is_ancestor_detects_merged_branches() {
+        use crate::cmd::Cmd;
+
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Cmd::new("git")
+                .workdir(dir.path())
+                .args(args)
+                .run_and_capture_stdout()
+                .unwrap()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+        git(&["branch", "merged"]);
+        git(&["checkout", "-q", "-b", "feature"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "feature work"]);
+        git(&["checkout", "-q", "main"]);
+
+        let is_ancestor = |a: &str, d: &str| super::is_ancestor(dir.path(), a, d);
+        assert!(is_ancestor("merged", "main").unwrap());
+        assert!(is_ancestor("main", "main").unwrap());
+        assert!(!is_ancestor("feature", "main").unwrap());
+        assert!(is_ancestor("main", "feature").unwrap());
+
+        git(&["merge", "-q", "--no-ff", "-m", "merge feature", "feature"]);
+        assert!(is_ancestor("feature", "main").unwrap());
+
+        let err = is_ancestor("missing", "main").unwrap_err().to_string();
+        assert!(err.contains("Failed to check whether 'missing' is merged into 'main'"));
+    }
+
+    #[test]
+    fn test_parse_worktree_list_porcelain_bare_repo() {
+        let output = "worktree /home/user/project/.bare\n\
+                      bare\n\
+                      \n\
+                      worktree /home/user/project/main\n\
+                      HEAD 1234567890abcdef1234567890abcdef12345678\n\
+                      branch refs/heads/main\n\
+                      \n\
+                      worktree /home/user/project/scratch\n\
+                      HEAD abcdef1234567890abcdef1234567890abcdef12\n\
+                      detached\n";
+
+        let entries = parse_worktree_list_porcelain(output).unwrap();
+        assert_eq!(
+            entries[0],
+            Worktree {
+                path: PathBuf::from("/home/user/project/.bare"),
+                branch: None,
+                head: String::new(),
+                detached: false,
+                locked: false,
+                bare: true,
+            }
+        );
+        assert!(!entries[1].bare);
+
+        assert_eq!(
+            main_worktree_root(&entries).unwrap(),
+            PathBuf::from("/home/user/project")
+        );
+        assert_eq!(
+            checked_out_worktrees(&entries),
+            vec![
+                (PathBuf::from("/home/user/project/main"), "main".to_string()),
+                (
+                    PathBuf::from("/home/user/project/scratch"),
+                    "(detached)".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_worktree_list_porcelain_head_and_lock_state() {
+        let output = "worktree /repo\n\
+                      HEAD 1111111111111111111111111111111111111111\n\
+                      branch refs/heads/main\n\
+                      \n\
+                      worktree /repo__worktrees/feature\n\
+                      HEAD 2222222222222222222222222222222222222222\n\
+                      branch refs/heads/feature\n\
+                      locked reason: on a removable drive\n\
+                      \n\
+                      worktree /repo__worktrees/scratch\n\
+                      HEAD 3333333333333333333333333333333333333333\n\
+                      detached\n\
+                      locked\n";
+
+        let entries = parse_worktree_list_porcelain(output).unwrap();
+        let heads: Vec<_> = entries.iter().map(|e| e.head.as_str()).collect();
+        assert_eq!(
+            heads,
+            vec![
+                "1111111111111111111111111111111111111111",
+                "2222222222222222222222222222222222222222",
+                "3333333333333333333333333333333333333333",
+            ]
+        );
+        assert_eq!(
+            entries.iter().map(|e| e.locked).collect::<Vec<_>>(),
+            vec![false, true, true]
+        );
+        assert_eq!(entries[1].branch.as_deref(), Some("feature"));
+        assert!(!entries[1].detached);
+        assert!(entries[2].detached);
+        assert_eq!(entries[2].branch, None);
+    }
+
+    #[test]
+    fn test_main_worktree_root_non_bare_is_first_entry() {
+        let output = "worktree /repo\nHEAD 1234\nbranch refs/heads/main\n\n\
+                      worktree /repo__worktrees/feature\nHEAD 5678\nbranch refs/heads/feature\n";
+        let entries = parse_worktree_list_porcelain(output).unwrap();
+        assert_eq!(
+            main_worktree_root(&entries).unwrap(),
+            PathBuf::from("/repo")
+        );
+    }
+
+    #[test]
+    fn test_main_worktree_root_errors_without_entries() {
+        assert!(main_worktree_root(&[]).is_err());
+    }
+
+    #[test]
+    fn worktree_table_lookups() {
+        let table = WorktreeTable::from_porcelain(
+            "worktree /repo\nHEAD 1234\nbranch refs/heads/main\n\n\
+             worktree /repo__worktrees/feature\nHEAD 5678\nbranch refs/heads/feature\n\n\
+             worktree /repo__worktrees/scratch\nHEAD 9abc\ndetached\n",
+        )
+        .unwrap();
+
+        assert_eq!(table.main_root().unwrap(), PathBuf::from("/repo"));
+        assert_eq!(
+            table.path_for("feature").unwrap(),
+            PathBuf::from("/repo__worktrees/feature")
+        );
+        assert!(table.contains("main"));
+        assert!(!table.contains("missing"));
+        assert!(
+            table
+                .path_for("missing")
+                .unwrap_err()
+                .is::<WorktreeNotFound>()
+        );
+        assert_eq!(
+            table.all(),
+            vec![
+                (PathBuf::from("/repo"), "main".to_string()),
+                (
+                    PathBuf::from("/repo__worktrees/feature"),
+                    "feature".to_string()
+                ),
+                (
+                    PathBuf::from("/repo__worktrees/scratch"),
+                    "(detached)".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            table.detached_head(Path::new("/repo__worktrees/scratch")),
+            Some("9abc")
+        );
+        assert_eq!(
+            table.detached_head(Path::new("/repo__worktrees/feature")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_excluding_pathspec() {
+        assert!(excluding_pathspec(&[]).is_empty());
+        assert_eq!(
+            excluding_pathspec(&["PROMPT.md".to_string(), "notes/".to_string()]),
+            vec!["--", ".", ":!PROMPT.md", ":!notes/"]
+        );
+    }
+
+    #[test]
+    fn test_parse_ahead_behind() {
+        assert_eq!(parse_ahead_behind("2\t5").unwrap(), (5, 2));
+        assert_eq!(parse_ahead_behind("0\t0").unwrap(), (0, 0));
+        assert!(parse_ahead_behind("").is_err());
+        assert!(parse_ahead_behind("3").is_err());
+        assert!(parse_ahead_behind("a\tb").is_err());
+    }
+
+    #[test]
+    fn test_parse_last_commit() {
+        let (time, subject) = parse_last_commit("1700000000\0Fix: handle a|b; c\n").unwrap();
+        assert_eq!(time, UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        assert_eq!(subject, "Fix: handle a|b; c");
+
+        // Empty subjects are possible with --allow-empty-message
+        let (_, subject) = parse_last_commit("1700000000\0").unwrap();
+        assert_eq!(subject, "");
+
+        assert!(parse_last_commit("").is_err());
+        assert!(parse_last_commit("1700000000 no separator").is_err());
+        assert!(parse_last_commit("soon\0subject").is_err());
+    }
+
+    #[test]
+    fn test_parse_branch_refs_merges_local_and_remote() {
+        let output = "refs/heads/main\n\
+                      refs/heads/feature/login\n\
+                      refs/remotes/origin/HEAD\n\
+                      refs/remotes/origin/main\n\
+                      refs/remotes/origin/release/1.0\n\
+                      refs/remotes/fork-alice/fix\n";
+
+        assert_eq!(
+            parse_branch_refs(output),
+            vec!["feature/login", "fix", "main", "release/1.0"]
+        );
+    }
+
+    #[test]
+    fn test_worktree_add_args_existing_branch_uses_bare_name() {
+        assert_eq!(
+            worktree_add_args("/wt/release", "release/1.0", BranchSource::Existing),
+            vec!["worktree", "add", "/wt/release", "release/1.0"]
+        );
+    }
+
+    #[test]
+    fn test_worktree_add_args_new_branch_from_start_point() {
+        assert_eq!(
+            worktree_add_args(
+                "/wt/feature",
+                "feature",
+                BranchSource::NewFrom("refs/heads/main")
+            ),
+            vec![
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                "/wt/feature",
+                "refs/heads/main"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktree_add_args_remote_branch_is_qualified() {
+        assert_eq!(
+            worktree_add_args(
+                "/wt/release",
+                "release/1.0",
+                BranchSource::Remote("origin/release/1.0")
+            ),
+            vec![
+                "worktree",
+                "add",
+                "--track",
+                "-b",
+                "release/1.0",
+                "/wt/release",
+                "refs/remotes/origin/release/1.0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktree_add_args_orphan_branch() {
+        assert_eq!(
+            worktree_add_args("/wt/gh-pages", "gh-pages", BranchSource::Orphan),
+            vec![
+                "worktree",
+                "add",
+                "--orphan",
+                "-b",
+                "gh-pages",
+                "/wt/gh-pages"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worktree_orphan_support_is_gated_on_git_version() {
+        assert_eq!(parse_git_version("git version 2.39.5\n"), Some((2, 39)));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.42.0.windows.1"),
+            Some((2, 42))
+        );
+        assert_eq!(parse_git_version("hub version 2.14.2"), None);
+
+        assert!(!supports_worktree_orphan((2, 41)));
+        assert!(supports_worktree_orphan((2, 42)));
+        assert!(supports_worktree_orphan((3, 0)));
+    }
+
+    #[test]
+    fn test_parse_branch_config() {
+        let output = "branch.feature/login.workmux-pr 12\n\
+                      branch.release.1.0.workmux-pr 7\n\
+                      branch.other.workmux-base main";
+        let prs = parse_branch_config(output, "workmux-pr");
+        assert_eq!(prs.len(), 2);
+        assert_eq!(prs.get("feature/login").map(String::as_str), Some("12"));
+        assert_eq!(prs.get("release.1.0").map(String::as_str), Some("7"));
+    }
+
+    #[test]
+    fn test_log_range_args() {
+        assert_eq!(
+            log_range_args("main", "feature/login", None),
+            vec!["log", "--oneline", "--no-decorate", "main..feature/login"]
+        );
+    }
+
+    #[test]
+    fn test_log_range_args_with_limit() {
+        assert_eq!(
+            log_range_args("main", "feature", Some(20)),
+            vec![
+                "log",
+                "--oneline",
+                "--no-decorate",
+                "--max-count=20",
+                "main..feature"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_args_map_format_to_flag() {
+        assert_eq!(
+            diff_args("main", "feature", DiffFormat::Patch),
+            vec!["diff", "main...refs/heads/feature"]
+        );
+        assert_eq!(
+            diff_args("main", "feature", DiffFormat::NameOnly),
+            vec!["diff", "--name-only", "main...refs/heads/feature"]
+        );
+        assert_eq!(
+            diff_args("v1.0", "feature", DiffFormat::Stat),
+            vec!["diff", "--stat", "v1.0...refs/heads/feature"]
+        );
+    }
 
     #[test]
     fn test_parse_repo_owner_https_github_com() {
@@ -729,4 +2272,23 @@ mod tests {
     fn test_parse_repo_owner_file_protocol() {
         assert_eq!(parse_owner_from_git_url("file:///local/path/to/repo"), None);
     }
+
+    #[test]
+    fn test_fork_url_from_replaces_owner() {
+        assert_eq!(
+            fork_url_from("https://github.com/owner/repo.git", "forker").as_deref(),
+            Some("https://github.com/forker/repo.git")
+        );
+        assert_eq!(
+            fork_url_from("git@github.enterprise.net:org/project", "forker").as_deref(),
+            Some("git@github.enterprise.net:forker/project")
+        );
+        // The owner is only replaced in the path, not wherever it appears
+        assert_eq!(
+            fork_url_from("https://owner.example.com/owner/owner.git", "forker").as_deref(),
+            Some("https://owner.example.com/forker/owner.git")
+        );
+        assert_eq!(fork_url_from("https://github.com/owner", "forker"), None);
+        assert_eq!(fork_url_from("/local/path/to/repo", "forker"), None);
+    }
 }
@@ -1,6 +1,10 @@
 use anyhow::{Context, Result, anyhow};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::process::Command;
+use std::time::Duration;
+
+use crate::cmd;
 
 #[derive(Debug, Deserialize)]
 pub struct PrDetails {
@@ -8,6 +12,9 @@ pub struct PrDetails {
     pub head_ref_name: String,
     #[serde(rename = "headRepositoryOwner")]
     pub head_repository_owner: RepositoryOwner,
+    /// The branch the PR merges into
+    #[serde(rename = "baseRefName")]
+    pub base_ref_name: String,
     pub state: String,
     #[serde(rename = "isDraft")]
     pub is_draft: bool,
@@ -25,25 +32,72 @@ pub struct Author {
     pub login: String,
 }
 
+/// The subset of pull request fields shown in `workmux list --pr-status`
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct PrSummary {
+    pub number: u32,
+    #[serde(rename = "headRefName")]
+    pub head_ref_name: String,
+    pub state: String,
+    #[serde(rename = "isDraft")]
+    pub is_draft: bool,
+}
+
+impl PrSummary {
+    /// Short lowercase status: open, draft, merged, or closed
+    pub fn status(&self) -> String {
+        if self.is_draft && self.state == "OPEN" {
+            "draft".to_string()
+        } else {
+            self.state.to_lowercase()
+        }
+    }
+}
+
 impl PrDetails {
     pub fn is_fork(&self, current_repo_owner: &str) -> bool {
         self.head_repository_owner.login != current_repo_owner
     }
 }
 
+/// Name of the git remote for the fork a PR comes from: the head repository
+/// owner's login, reduced to characters that are safe in a remote name.
+/// Every PR from the same fork maps to the same remote.
+pub fn fork_remote_name(pr: &PrDetails) -> String {
+    let name: String = pr
+        .head_repository_owner
+        .login
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() {
+        "fork".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
 /// Fetches pull request details using the GitHub CLI
-pub fn get_pr_details(pr_number: u32) -> Result<PrDetails> {
+pub fn get_pr_details(pr_number: u32, timeout: Duration) -> Result<PrDetails> {
     // Fetch PR details using gh CLI
     // Note: We don't pre-check with 'which' because it doesn't respect test PATH modifications
-    let output = Command::new("gh")
-        .args([
+    let output = cmd::output_with_timeout(
+        Command::new("gh").args([
             "pr",
             "view",
             &pr_number.to_string(),
             "--json",
-            "headRefName,headRepositoryOwner,state,isDraft,title,author",
-        ])
-        .output();
+            "headRefName,headRepositoryOwner,baseRefName,state,isDraft,title,author",
+        ]),
+        Some(timeout),
+    );
 
     let output = match output {
         Ok(out) => out,
@@ -73,3 +127,226 @@ pub fn get_pr_details(pr_number: u32) -> Result<PrDetails> {
 
     Ok(pr_details)
 }
+
+/// The fields of a GitHub issue used to seed a prompt
+#[derive(Debug, Deserialize)]
+pub struct IssueDetails {
+    pub number: u32,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+}
+
+/// Fetches issue details using the GitHub CLI
+pub fn get_issue_details(issue_number: u32, timeout: Duration) -> Result<IssueDetails> {
+    let output = cmd::output_with_timeout(
+        Command::new("gh").args([
+            "issue",
+            "view",
+            &issue_number.to_string(),
+            "--json",
+            "number,title,body",
+        ]),
+        Some(timeout),
+    );
+
+    let output = match output {
+        Ok(out) => out,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(anyhow!(
+                "GitHub CLI (gh) is required for --prompt-from-issue. Install from https://cli.github.com"
+            ));
+        }
+        Err(e) => {
+            return Err(e).context("Failed to execute gh command");
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "Failed to fetch issue #{}: {}",
+            issue_number,
+            stderr.trim()
+        ));
+    }
+
+    let json_str = String::from_utf8(output.stdout).context("gh output is not valid UTF-8")?;
+
+    serde_json::from_str(&json_str).context("Failed to parse gh JSON output")
+}
+
+/// Number of recent PRs requested by `get_prs_for_branches`
+const PR_LIST_LIMIT: &str = "200";
+
+/// Looks up pull requests for many branches with a single `gh pr list` call.
+///
+/// `pr_numbers` holds PR numbers recorded when worktrees were created with
+/// `--pr`; those take precedence over matching by head branch name, which is
+/// ambiguous for fork PRs. Branches without a matching PR are absent from the map.
+pub fn get_prs_for_branches(
+    branches: &[String],
+    pr_numbers: &HashMap<String, u32>,
+    timeout: Duration,
+) -> Result<HashMap<String, PrSummary>> {
+    let output = cmd::output_with_timeout(
+        Command::new("gh").args([
+            "pr",
+            "list",
+            "--state",
+            "all",
+            "--limit",
+            PR_LIST_LIMIT,
+            "--json",
+            "number,headRefName,state,isDraft",
+        ]),
+        Some(timeout),
+    );
+
+    let output = match output {
+        Ok(out) => out,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(anyhow!(
+                "GitHub CLI (gh) is required for --pr-status. Install from https://cli.github.com"
+            ));
+        }
+        Err(e) => {
+            return Err(e).context("Failed to execute gh command");
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Failed to list PRs: {}", stderr.trim()));
+    }
+
+    let json_str = String::from_utf8(output.stdout).context("gh output is not valid UTF-8")?;
+    let prs: Vec<PrSummary> =
+        serde_json::from_str(&json_str).context("Failed to parse gh JSON output")?;
+
+    Ok(map_prs_to_branches(prs, branches, pr_numbers))
+}
+
+/// Correlate listed PRs with branches, by recorded PR number first and head ref second.
+///
+/// `gh pr list` returns the newest PRs first, so when a branch has been used
+/// for several PRs the most recent one wins.
+fn map_prs_to_branches(
+    prs: Vec<PrSummary>,
+    branches: &[String],
+    pr_numbers: &HashMap<String, u32>,
+) -> HashMap<String, PrSummary> {
+    let mut by_number = HashMap::new();
+    let mut by_head_ref = HashMap::new();
+    for pr in prs {
+        by_head_ref
+            .entry(pr.head_ref_name.clone())
+            .or_insert_with(|| pr.clone());
+        by_number.insert(pr.number, pr);
+    }
+
+    branches
+        .iter()
+        .filter_map(|branch| {
+            let pr = match pr_numbers.get(branch) {
+                Some(number) => by_number.get(number),
+                None => by_head_ref.get(branch),
+            }?;
+            Some((branch.clone(), pr.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PR_LIST_JSON: &str = r#"[
+        {"number": 42, "headRefName": "feature/login", "state": "OPEN", "isDraft": false},
+        {"number": 41, "headRefName": "wip", "state": "OPEN", "isDraft": true},
+        {"number": 40, "headRefName": "main", "state": "MERGED", "isDraft": false},
+        {"number": 12, "headRefName": "feature/login", "state": "CLOSED", "isDraft": false}
+    ]"#;
+
+    fn branches(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pr_details_parses_base_ref_name() {
+        let json = r#"{
+            "headRefName": "feature/login",
+            "headRepositoryOwner": {"login": "octocat"},
+            "baseRefName": "develop",
+            "state": "OPEN",
+            "isDraft": false,
+            "title": "Add login",
+            "author": {"login": "octocat"}
+        }"#;
+        let pr: PrDetails = serde_json::from_str(json).unwrap();
+        assert_eq!(pr.head_ref_name, "feature/login");
+        assert_eq!(pr.base_ref_name, "develop");
+    }
+
+    fn pr_from(owner: &str) -> PrDetails {
+        PrDetails {
+            head_ref_name: "main".to_string(),
+            head_repository_owner: RepositoryOwner {
+                login: owner.to_string(),
+            },
+            base_ref_name: "main".to_string(),
+            state: "OPEN".to_string(),
+            is_draft: false,
+            title: "Fix".to_string(),
+            author: Author {
+                login: owner.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_fork_remote_name_uses_sanitized_owner_login() {
+        assert_eq!(fork_remote_name(&pr_from("forker")), "forker");
+        assert_eq!(fork_remote_name(&pr_from("Octo-Cat_2")), "octo-cat_2");
+        // Bots and odd logins must not produce refspec syntax or option-like names
+        assert_eq!(
+            fork_remote_name(&pr_from("app/dependabot")),
+            "app-dependabot"
+        );
+        assert_eq!(fork_remote_name(&pr_from("-x:y")), "x-y");
+        assert_eq!(fork_remote_name(&pr_from("..")), "fork");
+    }
+
+    #[test]
+    fn test_map_prs_to_branches_by_head_ref() {
+        let prs: Vec<PrSummary> = serde_json::from_str(PR_LIST_JSON).unwrap();
+        let map = map_prs_to_branches(
+            prs,
+            &branches(&["feature/login", "wip", "no-pr"]),
+            &HashMap::new(),
+        );
+
+        assert_eq!(map.len(), 2);
+        // Newest PR for a reused head ref wins
+        assert_eq!(map["feature/login"].number, 42);
+        assert_eq!(map["feature/login"].status(), "open");
+        assert_eq!(map["wip"].status(), "draft");
+        assert!(!map.contains_key("no-pr"));
+    }
+
+    #[test]
+    fn test_map_prs_to_branches_prefers_recorded_number() {
+        let prs: Vec<PrSummary> = serde_json::from_str(PR_LIST_JSON).unwrap();
+        // A fork PR checked out under a custom local name, and a branch whose
+        // recorded PR is older than the latest one with the same head ref
+        let pr_numbers = HashMap::from([
+            ("fork-fix".to_string(), 40),
+            ("feature/login".to_string(), 12),
+        ]);
+        let map = map_prs_to_branches(prs, &branches(&["fork-fix", "feature/login"]), &pr_numbers);
+
+        assert_eq!(map["fork-fix"].status(), "merged");
+        assert_eq!(map["feature/login"].number, 12);
+        assert_eq!(map["feature/login"].status(), "closed");
+    }
+}
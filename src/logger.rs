@@ -5,23 +5,26 @@ use std::sync::OnceLock;
 use anyhow::{Context, Result, anyhow};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_appender::rolling;
+use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{EnvFilter, fmt};
 
 static INIT: OnceLock<()> = OnceLock::new();
 static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
 
-pub fn init() -> Result<()> {
+/// Start logging to ~/.workmux/workmux.log. `level` replaces the default `info`
+/// level, but an explicitly set `RUST_LOG` still wins.
+pub fn init(level: Option<LevelFilter>) -> Result<()> {
     if INIT.get().is_some() {
         return Ok(());
     }
 
-    init_inner()?;
+    init_inner(level)?;
     let _ = INIT.set(());
     Ok(())
 }
 
-fn init_inner() -> Result<()> {
+fn init_inner(level: Option<LevelFilter>) -> Result<()> {
     let log_path = determine_log_path()?;
     if let Some(parent) = log_path.parent() {
         fs::create_dir_all(parent)
@@ -33,7 +36,9 @@ fn init_inner() -> Result<()> {
     let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
     let _ = GUARD.set(guard);
 
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::default().add_directive(level.unwrap_or(LevelFilter::INFO).into())
+    });
 
     tracing_subscriber::registry()
         .with(env_filter)
@@ -49,6 +54,17 @@ fn init_inner() -> Result<()> {
     Ok(())
 }
 
+/// Log level requested by `--verbose`/`--quiet`: `-v` for debug, `-vv` or more
+/// for trace, `--quiet` for errors only. None keeps the default.
+pub fn level_from_flags(verbose: u8, quiet: bool) -> Option<LevelFilter> {
+    match (quiet, verbose) {
+        (true, _) => Some(LevelFilter::ERROR),
+        (false, 0) => None,
+        (false, 1) => Some(LevelFilter::DEBUG),
+        (false, _) => Some(LevelFilter::TRACE),
+    }
+}
+
 fn determine_log_path() -> Result<PathBuf> {
     if let Some(home_dir) = home::home_dir() {
         return Ok(home_dir.join(".workmux").join("workmux.log"));
@@ -70,3 +86,18 @@ fn split_path(path: &Path) -> Result<(PathBuf, &str)> {
 
     Ok((dir, file_name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::level_from_flags;
+    use tracing_subscriber::filter::LevelFilter;
+
+    #[test]
+    fn level_from_flags_maps_verbosity_and_quiet() {
+        assert_eq!(level_from_flags(0, false), None);
+        assert_eq!(level_from_flags(1, false), Some(LevelFilter::DEBUG));
+        assert_eq!(level_from_flags(2, false), Some(LevelFilter::TRACE));
+        assert_eq!(level_from_flags(5, false), Some(LevelFilter::TRACE));
+        assert_eq!(level_from_flags(0, true), Some(LevelFilter::ERROR));
+    }
+}
@@ -6,8 +6,11 @@ mod config;
 mod git;
 mod github;
 mod logger;
+mod multiplexer;
 mod prompt;
 mod template;
+mod tmux;
+mod toml;
 mod zellij;
 mod workflow;
 
@@ -15,10 +18,11 @@ use anyhow::Result;
 use tracing::{error, info};
 
 fn main() -> Result<()> {
-    logger::init()?;
+    let cli = cli::parse();
+    logger::init(cli.log_level())?;
     info!(args = ?std::env::args().collect::<Vec<_>>(), "workmux start");
 
-    match cli::run() {
+    match cli::run(cli) {
         Ok(result) => {
             info!("workmux finished successfully");
             Ok(result)
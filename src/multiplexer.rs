@@ -0,0 +1,1199 @@
+use anyhow::{Result, anyhow};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::{self, PaneConfig};
+use crate::{git, tmux, zellij};
+
+/// Helper function to add prefix to a window/tab name
+pub fn prefixed(prefix: &str, name: &str) -> String {
+    format!("{}{}", prefix, name)
+}
+
+pub struct PaneSetupOptions<'a> {
+    pub run_commands: bool,
+    pub prompt_file_path: Option<&'a Path>,
+    /// Agent command substituted for the placeholder (task override or configured agent)
+    pub agent: Option<&'a str>,
+    /// Pane command that stands for the agent (`agent_placeholder`, default `<agent>`)
+    pub agent_placeholder: &'a str,
+    /// Extra arguments appended to the agent command (`--agent-arg`)
+    pub agent_args: &'a [String],
+    /// Configured prompt-injection templates, keyed by agent executable name
+    pub prompt_formats: Option<&'a HashMap<String, String>>,
+    /// The agent is already running in this worktree, so panes that would start
+    /// it are left with a plain shell instead
+    pub agent_running: bool,
+    /// Configured shell for pane startup commands, used instead of `$SHELL`
+    pub startup_shell: Option<&'a str>,
+    /// Index of the pane to focus instead of the configured `focus` pane
+    pub select_pane: Option<usize>,
+    /// `agent_record` template and the file it records to, for wrapping the
+    /// agent command
+    pub agent_record: Option<(&'a str, &'a Path)>,
+}
+
+/// Operations workflows need from a terminal multiplexer.
+///
+/// Names passed in are unprefixed branch names; implementations apply `prefix`.
+pub trait Multiplexer {
+    /// Short name used in messages (e.g. "tmux")
+    fn name(&self) -> &'static str;
+
+    /// What the multiplexer calls the unit workmux creates per worktree ("window" or "tab")
+    fn window_term(&self) -> &'static str;
+
+    /// Check if the multiplexer is available to drive
+    fn is_running(&self) -> Result<bool>;
+
+    /// Get all window/tab names in the current session
+    fn list_names(&self) -> Result<HashSet<String>>;
+
+    /// Check if a window/tab with the given name exists
+    fn exists(&self, prefix: &str, name: &str) -> Result<bool> {
+        Ok(self.list_names()?.contains(&prefixed(prefix, name)))
+    }
+
+    /// Return the name of the window/tab the command is running in, if any
+    fn current_name(&self) -> Result<Option<String>>;
+
+    /// Create a new window/tab. When `detached` is true, focus stays where it was.
+    fn create(&self, prefix: &str, name: &str, working_dir: &Path, detached: bool) -> Result<()>;
+
+    /// Switch to a window/tab
+    fn select(&self, prefix: &str, name: &str) -> Result<()>;
+
+    /// Close a window/tab
+    fn close(&self, prefix: &str, name: &str) -> Result<()>;
+
+    /// Close a window/tab after a delay. Used when the command runs inside it.
+    fn schedule_close(&self, prefix: &str, name: &str, delay: Duration) -> Result<()>;
+
+    /// After a delay, switch to `select_name` and then close `close_name`
+    fn schedule_select_and_close(
+        &self,
+        prefix: &str,
+        select_name: &str,
+        close_name: &str,
+        delay: Duration,
+    ) -> Result<()>;
+
+    /// Populate a freshly created window/tab with the configured panes
+    fn setup_panes(
+        &self,
+        prefix: &str,
+        name: &str,
+        panes: &[PaneConfig],
+        working_dir: &Path,
+        options: PaneSetupOptions<'_>,
+    ) -> Result<()>;
+
+    /// Run a command in a new pane of the given window/tab
+    fn run_in_new_pane(
+        &self,
+        prefix: &str,
+        name: &str,
+        working_dir: &Path,
+        command: &str,
+    ) -> Result<()>;
+
+    /// A shell command the user can run to switch to the window/tab themselves
+    fn select_hint(&self, prefix: &str, name: &str) -> String;
+
+    /// List the foreground command of every pane, in any window/tab, whose
+    /// working directory is inside `working_dir`. Backends that can't inspect
+    /// panes report none.
+    fn pane_commands_in(&self, _working_dir: &Path) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Resolve the configured backend, detecting it from the environment for `auto`.
+/// `zellij_session` names the session the zellij backend drives, if any.
+pub fn resolve(kind: config::Multiplexer, zellij_session: Option<&str>) -> Box<dyn Multiplexer> {
+    match resolve_kind(kind) {
+        config::Multiplexer::Tmux => Box::new(tmux::Tmux),
+        _ => Box::new(zellij::Zellij::new(zellij_session.map(str::to_string))),
+    }
+}
+
+/// The backend `kind` stands for, detecting it from the environment for `auto`
+pub fn resolve_kind(kind: config::Multiplexer) -> config::Multiplexer {
+    match kind {
+        config::Multiplexer::Auto => detect(
+            std::env::var_os("ZELLIJ").is_some(),
+            std::env::var_os("TMUX").is_some(),
+        ),
+        explicit => explicit,
+    }
+}
+
+/// Pick a backend from which session environment variables are set.
+/// Zellij wins when both or neither are present.
+fn detect(in_zellij: bool, in_tmux: bool) -> config::Multiplexer {
+    if in_tmux && !in_zellij {
+        config::Multiplexer::Tmux
+    } else {
+        config::Multiplexer::Zellij
+    }
+}
+
+/// Resolve which command a pane should run, substituting the agent placeholder and
+/// injecting the prompt file when the command invokes the agent.
+pub fn resolve_pane_command(
+    pane: &PaneConfig,
+    options: &PaneSetupOptions<'_>,
+    working_dir: &Path,
+) -> Option<String> {
+    let effective_agent = options.agent;
+    let command = if pane.command.as_deref() == Some(options.agent_placeholder) {
+        effective_agent.map(|agent_cmd| agent_cmd.to_string())
+    } else {
+        pane.command.clone()
+    }?;
+
+    if options.agent_running
+        && let Some(agent) = effective_agent
+        && is_agent_command(&command, agent)
+    {
+        return None;
+    }
+
+    // Extra args go after the agent's configured ones and before the prompt
+    let command = match effective_agent {
+        Some(agent) if is_agent_command(&command, agent) => {
+            append_args(&command, options.agent_args)
+        }
+        _ => command,
+    };
+
+    let adjusted = adjust_command(
+        &command,
+        options.prompt_file_path,
+        working_dir,
+        effective_agent,
+        options.prompt_formats,
+    );
+
+    // Recording wraps the whole agent invocation, prompt included
+    match (options.agent_record, effective_agent) {
+        (Some((template, out)), Some(agent)) if is_agent_command(&command, agent) => {
+            Some(expand_record_command(template, out, &adjusted))
+        }
+        _ => Some(adjusted.into_owned()),
+    }
+}
+
+/// Fill in an `agent_record` template: `{out}` becomes the recording file and
+/// `{cmd}` the agent command, each as a single shell word. Quotes the template
+/// already puts around a placeholder are dropped so they don't nest.
+pub fn expand_record_command(template: &str, out: &Path, command: &str) -> String {
+    let mut expanded = template.to_string();
+    for placeholder in ["{out}", "{cmd}"] {
+        for quote in ['"', '\''] {
+            expanded = expanded.replace(&format!("{quote}{placeholder}{quote}"), placeholder);
+        }
+    }
+    expanded
+        .replace("{out}", &shell_quote(&out.to_string_lossy()))
+        .replace("{cmd}", &shell_quote(command))
+}
+
+/// Wrap `value` in single quotes so the shell takes it literally
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r#"'\''"#))
+}
+
+/// Whether `command` runs the same executable as `agent`
+fn is_agent_command(command: &str, agent: &str) -> bool {
+    command_stem(command).is_some_and(|stem| command_stem(agent) == Some(stem))
+}
+
+/// Append `args` to `command`, quoting those the shell would otherwise split or expand
+fn append_args(command: &str, args: &[String]) -> String {
+    let mut command = command.to_string();
+    for arg in args {
+        command.push(' ');
+        let plain = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@+%".contains(c));
+        if plain {
+            command.push_str(arg);
+        } else {
+            command.push_str(&shell_quote(arg));
+        }
+    }
+    command
+}
+
+/// Check whether any of `pane_commands` (process names as reported by the
+/// multiplexer) is the agent, matching on the executable's file stem.
+pub fn agent_already_running(agent: &str, pane_commands: &[String]) -> bool {
+    let Some(agent_stem) = command_stem(agent) else {
+        return false;
+    };
+    pane_commands
+        .iter()
+        .any(|command| command_stem(command).as_deref() == Some(agent_stem.as_str()))
+}
+
+/// File stem of a command's executable, e.g. `claude` for `/usr/bin/claude --resume`.
+/// For a pipeline or command list this is the last command's executable.
+fn command_stem(command: &str) -> Option<String> {
+    let (token, _) = config::split_first_token(last_command(command))?;
+    Path::new(token)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
+
+/// The last command of a pipeline or command list (split on `|`, `&&`, `||`
+/// and `;` outside quotes), e.g. `claude` for `my-wrapper | claude`. A plain
+/// command is returned whole. The result is a suffix of `command`.
+fn last_command(command: &str) -> &str {
+    let bytes = command.as_bytes();
+    let mut start = 0;
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        match quote {
+            Some(q) if byte == q => quote = None,
+            Some(b'"') if byte == b'\\' => i += 1,
+            Some(_) => {}
+            None => match byte {
+                b'\'' | b'"' => quote = Some(byte),
+                b'\\' => i += 1,
+                b'|' | b';' => start = i + 1,
+                // `&&`, but not redirections like `2>&1` or `&>`
+                b'&' if i > 0
+                    && !matches!(bytes[i - 1], b'>' | b'<')
+                    && bytes.get(i + 1) != Some(&b'>') =>
+                {
+                    start = i + 1
+                }
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    command[start.min(command.len())..].trim_start()
+}
+
+/// Builds a shell command string that executes an optional user command
+/// and then, when `keep_open` is set, leaves an interactive shell open.
+///
+/// Variables in `env` are exported first. A pane with variables but no command
+/// still gets a startup command, so its shell sees them.
+///
+/// `shell` overrides `$SHELL` for both running the command and the shell left open.
+pub fn build_startup_command(
+    command: Option<&str>,
+    keep_open: bool,
+    env: Option<&HashMap<String, String>>,
+    shell: Option<&str>,
+) -> Result<Option<String>> {
+    let env_prologue = build_env_prologue(env.into_iter().flatten())?;
+    let keep_open = keep_open || command.is_none();
+    let command = match (command, env_prologue.is_empty()) {
+        (Some(c), _) => c,
+        (None, false) => "true",
+        (None, true) => return Ok(None),
+    };
+
+    let shell_path = match shell {
+        Some(shell) => resolve_shell(shell)?,
+        None => std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
+    };
+    let pre_command_hook = pre_prompt_hook(&shell_path);
+
+    let escaped_command = command.replace('\'', r#"'\''"#);
+
+    let mut inner_command = format!(
+        "{env}{pre_hook}; {user_cmd}",
+        env = env_prologue.replace('\'', r#"'\''"#),
+        pre_hook = pre_command_hook,
+        user_cmd = escaped_command,
+    );
+    if keep_open {
+        inner_command.push_str(&format!("; exec {} -l", shell_path));
+    }
+
+    let full_command = format!(
+        "{shell} -ic '{inner_command}'",
+        shell = shell_path,
+        inner_command = inner_command,
+    );
+
+    Ok(Some(full_command))
+}
+
+/// Append `close_command` to the command of a pane marked `close_tab_on_exit`,
+/// so its window/tab closes once the command exits. Other panes are unchanged.
+pub fn close_on_exit(
+    pane: &PaneConfig,
+    command: Option<String>,
+    close_command: &str,
+) -> Option<String> {
+    match command {
+        Some(command) if pane.close_tab_on_exit => Some(format!("{}; {}", command, close_command)),
+        command => command,
+    }
+}
+
+/// Resolve a configured shell name or path to an existing executable
+fn resolve_shell(shell: &str) -> Result<String> {
+    config::resolve_executable_path(shell)
+        .filter(|path| Path::new(path).is_file())
+        .ok_or_else(|| anyhow!("Shell '{}' was not found", shell))
+}
+
+/// Command that manually triggers the shell's pre-prompt hooks, so tools like
+/// direnv, nvm, and rbenv are loaded before the user command is executed
+fn pre_prompt_hook(shell_path: &str) -> &'static str {
+    let shell_name = Path::new(shell_path)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+
+    match shell_name {
+        "zsh" => {
+            "if (( ${#precmd_functions[@]} )); then for f in \"${precmd_functions[@]}\"; do \"$f\"; done; fi"
+        }
+        "bash" => "eval \"${PROMPT_COMMAND:-}\"",
+        "fish" => "functions -q fish_prompt; and emit fish_prompt",
+        _ => "true",
+    }
+}
+
+/// `export KEY='value'; ` for each variable, sorted by name, with values quoted
+/// for POSIX shells
+fn build_env_prologue<'a>(
+    env: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> Result<String> {
+    let mut vars: Vec<_> = env.into_iter().collect();
+    vars.sort();
+
+    let mut prologue = String::new();
+    for (key, value) in vars {
+        let valid_name = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(anyhow!(
+                "Invalid environment variable name '{}' in pane config",
+                key
+            ));
+        }
+        prologue.push_str(&format!("export {}={}; ", key, shell_quote(value)));
+    }
+    Ok(prologue)
+}
+
+/// Builds a shell script that runs post-create hooks one after another in a pane,
+/// printing progress, and then leaves an interactive shell open.
+///
+/// Each hook runs in its own `sh -c` so multi-line scripts behave as they do
+/// when run synchronously. Execution stops at the first failing hook.
+pub fn build_hooks_pane_command(hooks: &[config::HookCommand]) -> Option<String> {
+    if hooks.is_empty() {
+        return None;
+    }
+
+    let total = hooks.len();
+    let steps: Vec<String> = hooks
+        .iter()
+        .enumerate()
+        .map(|(idx, hook)| {
+            let command = hook.command();
+            let label = format!("==> [{}/{}] {}", idx + 1, total, command);
+            let step = format!(
+                "echo {} && sh -c {}",
+                shell_quote(&label),
+                shell_quote(command)
+            );
+            let condition = match hook.condition() {
+                None => return step,
+                Some(config::HookCondition::Exists { exists }) => {
+                    format!("[ -e {} ]", shell_quote(exists))
+                }
+                Some(config::HookCondition::Shell(predicate)) => {
+                    format!("sh -c {} >/dev/null 2>&1", shell_quote(predicate))
+                }
+            };
+            let skipped = format!("==> [{}/{}] skipped: {}", idx + 1, total, command);
+            format!(
+                "if {condition}; then {step}; else echo {skipped}; fi",
+                skipped = shell_quote(&skipped),
+            )
+        })
+        .collect();
+
+    Some(format!(
+        "{steps} && echo '✓ post-create hooks complete' || echo '✗ post-create hook failed'; exec \"${{SHELL:-sh}}\" -l",
+        steps = steps.join(" && "),
+    ))
+}
+
+fn adjust_command<'a>(
+    command: &'a str,
+    prompt_file_path: Option<&Path>,
+    working_dir: &Path,
+    effective_agent: Option<&str>,
+    prompt_formats: Option<&HashMap<String, String>>,
+) -> Cow<'a, str> {
+    if let Some(prompt_path) = prompt_file_path
+        && let Some(rewritten) = rewrite_agent_command(
+            command,
+            prompt_path,
+            working_dir,
+            effective_agent,
+            prompt_formats,
+        )
+    {
+        return Cow::Owned(rewritten);
+    }
+    Cow::Borrowed(command)
+}
+
+/// Rewrites an agent command to inject a prompt file's contents.
+///
+/// Uses the agent's entry in `prompt_formats` if there is one, otherwise the
+/// built-in format for that agent.
+fn rewrite_agent_command(
+    command: &str,
+    prompt_file: &Path,
+    working_dir: &Path,
+    effective_agent: Option<&str>,
+    prompt_formats: Option<&HashMap<String, String>>,
+) -> Option<String> {
+    let agent_command = effective_agent?;
+    let trimmed_command = command.trim();
+    if trimmed_command.is_empty() {
+        return None;
+    }
+
+    // In a pipeline or command list only the last command can be the agent,
+    // so that's where the prompt goes
+    let pane_last = last_command(trimmed_command);
+    let (pane_token, pane_rest) = config::split_first_token(pane_last)?;
+    let (config_token, _) = config::split_first_token(last_command(agent_command))?;
+
+    let resolved_pane_path =
+        config::resolve_executable_path(pane_token).unwrap_or_else(|| pane_token.to_string());
+    let resolved_config_path =
+        config::resolve_executable_path(config_token).unwrap_or_else(|| config_token.to_string());
+
+    let pane_stem = Path::new(&resolved_pane_path).file_stem();
+    let config_stem = Path::new(&resolved_config_path).file_stem();
+
+    if pane_stem != config_stem {
+        return None;
+    }
+
+    // The worktree may be reached through a symlink on one side only, e.g. a
+    // symlinked worktree_dir vs the canonical path git reports
+    let relative = prompt_file
+        .strip_prefix(working_dir)
+        .map(Path::to_path_buf)
+        .or_else(|_| {
+            git::resolve_symlinks(prompt_file)
+                .strip_prefix(git::resolve_symlinks(working_dir))
+                .map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|_| prompt_file.to_path_buf());
+    let prompt_path = relative.to_string_lossy();
+    let rest = pane_rest.trim_start();
+
+    let mut cmd = trimmed_command[..trimmed_command.len() - pane_last.len()].to_string();
+    cmd.push_str(pane_token);
+
+    if !rest.is_empty() {
+        cmd.push(' ');
+        cmd.push_str(rest);
+    }
+
+    let stem = pane_stem.and_then(|s| s.to_str()).unwrap_or_default();
+    let format = prompt_formats
+        .and_then(|formats| formats.get(stem))
+        .map(String::as_str)
+        .unwrap_or_else(|| default_prompt_format(stem));
+
+    Some(
+        format
+            .replace("{path}", &prompt_path)
+            .replace("{cmd}", &cmd),
+    )
+}
+
+/// The built-in prompt-injection template for an agent
+fn default_prompt_format(agent_stem: &str) -> &'static str {
+    if agent_stem == "gemini" {
+        "{cmd} -i \"$(cat {path})\""
+    } else {
+        "{cmd} -- \"$(cat {path})\""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Options that run pane commands with `agent` and leave everything else off
+    fn pane_options(agent: &str) -> PaneSetupOptions<'_> {
+        PaneSetupOptions {
+            run_commands: true,
+            prompt_file_path: None,
+            agent: Some(agent),
+            agent_placeholder: "<agent>",
+            agent_args: &[],
+            prompt_formats: None,
+            agent_running: false,
+            startup_shell: None,
+            select_pane: None,
+            agent_record: None,
+        }
+    }
+
+    #[test]
+    fn test_prefixed() {
+        assert_eq!(prefixed("wm-", "feature"), "wm-feature");
+        assert_eq!(prefixed("", "feature"), "feature");
+    }
+
+    #[test]
+    fn test_detect_prefers_zellij_when_ambiguous() {
+        assert_eq!(detect(true, false), config::Multiplexer::Zellij);
+        assert_eq!(detect(false, true), config::Multiplexer::Tmux);
+        assert_eq!(detect(true, true), config::Multiplexer::Zellij);
+        assert_eq!(detect(false, false), config::Multiplexer::Zellij);
+    }
+
+    #[test]
+    fn test_resolve_explicit_backend() {
+        assert_eq!(resolve(config::Multiplexer::Tmux, None).name(), "tmux");
+        assert_eq!(resolve(config::Multiplexer::Zellij, None).name(), "zellij");
+    }
+
+    #[test]
+    fn test_resolve_pane_command_uses_agent_override() {
+        let pane = PaneConfig {
+            command: Some("<agent>".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let options = pane_options("codex");
+
+        let command = resolve_pane_command(&pane, &options, Path::new("/tmp/worktree"));
+        assert_eq!(command.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn test_resolve_pane_command_expands_custom_placeholder() {
+        let mut pane = PaneConfig {
+            command: Some("@agent".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let options = PaneSetupOptions {
+            agent_placeholder: "@agent",
+            ..pane_options("codex")
+        };
+        let working_dir = Path::new("/tmp/worktree");
+
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("codex")
+        );
+
+        // The default marker is then just another command
+        pane.command = Some("<agent>".to_string());
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("<agent>")
+        );
+    }
+
+    #[test]
+    fn test_resolve_pane_command_skips_running_agent() {
+        let mut pane = PaneConfig {
+            command: Some("<agent>".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let options = PaneSetupOptions {
+            agent_running: true,
+            ..pane_options("claude --verbose")
+        };
+        let working_dir = Path::new("/tmp/worktree");
+
+        assert_eq!(resolve_pane_command(&pane, &options, working_dir), None);
+
+        pane.command = Some("/usr/local/bin/claude".to_string());
+        assert_eq!(resolve_pane_command(&pane, &options, working_dir), None);
+
+        // Other pane commands still start
+        pane.command = Some("npm run dev".to_string());
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("npm run dev")
+        );
+    }
+
+    #[test]
+    fn test_resolve_pane_command_without_prompt_runs_verbatim() {
+        let pane = PaneConfig {
+            command: Some("claude --verbose".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let working_dir = Path::new("/tmp/worktree");
+        let mut options = PaneSetupOptions {
+            prompt_file_path: Some(Path::new("/tmp/workmux-prompt-feature.md")),
+            ..pane_options("claude")
+        };
+
+        let rewritten = resolve_pane_command(&pane, &options, working_dir).unwrap();
+        assert!(rewritten.contains("workmux-prompt-feature.md"));
+
+        // What setup passes when --no-prompt-rewrite is given
+        options.prompt_file_path = None;
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("claude --verbose")
+        );
+    }
+
+    #[test]
+    fn test_resolve_pane_command_places_agent_args_before_prompt() {
+        let pane = PaneConfig {
+            command: Some("<agent>".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let agent_args = vec![
+            "--model".to_string(),
+            "opus".to_string(),
+            "--append-system-prompt".to_string(),
+            "be brief".to_string(),
+        ];
+        let mut options = PaneSetupOptions {
+            prompt_file_path: Some(Path::new("/tmp/worktree/.workmux/PROMPT.md")),
+            agent_args: &agent_args,
+            ..pane_options("claude --verbose")
+        };
+        let working_dir = Path::new("/tmp/worktree");
+
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some(
+                "claude --verbose --model opus --append-system-prompt 'be brief' -- \"$(cat .workmux/PROMPT.md)\""
+            )
+        );
+
+        options.prompt_file_path = None;
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("claude --verbose --model opus --append-system-prompt 'be brief'")
+        );
+
+        // Panes running something other than the agent are left alone
+        let pane = PaneConfig {
+            command: Some("npm run dev".to_string()),
+            ..pane
+        };
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("npm run dev")
+        );
+    }
+
+    #[test]
+    fn test_expand_record_command_quotes_cmd_and_out() {
+        let out = Path::new("/home/me/.workmux/recordings/fix-login-1700000000.cast");
+        let cmd = "claude -- \"$(cat /tmp/it's.md)\"";
+
+        assert_eq!(
+            expand_record_command("asciinema rec {out} -c {cmd}", out, cmd),
+            "asciinema rec '/home/me/.workmux/recordings/fix-login-1700000000.cast' \
+             -c 'claude -- \"$(cat /tmp/it'\\''s.md)\"'"
+        );
+        // Quoting the placeholders in the template doesn't double-quote them
+        assert_eq!(
+            expand_record_command("asciinema rec \"{out}\" -c \"{cmd}\"", out, "claude"),
+            expand_record_command("asciinema rec {out} -c {cmd}", out, "claude")
+        );
+        assert_eq!(
+            expand_record_command(
+                "script -q -f {out} -c '{cmd}'",
+                Path::new("/r.cast"),
+                "claude"
+            ),
+            "script -q -f '/r.cast' -c 'claude'"
+        );
+    }
+
+    #[test]
+    fn test_resolve_pane_command_records_only_the_agent() {
+        let pane = PaneConfig {
+            command: Some("<agent>".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let options = PaneSetupOptions {
+            agent_record: Some(("script -q -f {out} -c {cmd}", Path::new("/r/feature.cast"))),
+            ..pane_options("claude")
+        };
+        let working_dir = Path::new("/tmp/worktree");
+
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("script -q -f '/r/feature.cast' -c 'claude'")
+        );
+
+        let pane = PaneConfig {
+            command: Some("npm run dev".to_string()),
+            ..pane
+        };
+        assert_eq!(
+            resolve_pane_command(&pane, &options, working_dir).as_deref(),
+            Some("npm run dev")
+        );
+    }
+
+    #[test]
+    fn test_agent_already_running_matches_command_stem() {
+        let panes = vec!["zsh".to_string(), "claude".to_string()];
+        assert!(agent_already_running("claude", &panes));
+        assert!(agent_already_running(
+            "/opt/bin/claude --model opus",
+            &panes
+        ));
+        assert!(!agent_already_running("gemini", &panes));
+        assert!(!agent_already_running("claude", &["zsh".to_string()]));
+        assert!(!agent_already_running("", &panes));
+        assert!(!agent_already_running("claude", &[]));
+    }
+
+    #[test]
+    fn test_rewrite_claude_command() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+
+        let result =
+            rewrite_agent_command("claude", &prompt_file, &working_dir, Some("claude"), None);
+        assert_eq!(result, Some("claude -- \"$(cat PROMPT.md)\"".to_string()));
+    }
+
+    #[test]
+    fn test_rewrite_with_symlinked_worktree_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("worktrees");
+        std::fs::create_dir_all(real.join("feature")).unwrap();
+        let link = dir.path().join("linked-worktrees");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let canonical_worktree = real.join("feature").canonicalize().unwrap();
+        let linked_worktree = link.join("feature");
+        let expected = Some("claude -- \"$(cat PROMPT.md)\"".to_string());
+
+        // Config path through the symlink, canonical prompt path from git
+        let result = rewrite_agent_command(
+            "claude",
+            &canonical_worktree.join("PROMPT.md"),
+            &linked_worktree,
+            Some("claude"),
+            None,
+        );
+        assert_eq!(result, expected);
+
+        // And the other way around
+        let result = rewrite_agent_command(
+            "claude",
+            &linked_worktree.join("PROMPT.md"),
+            &canonical_worktree,
+            Some("claude"),
+            None,
+        );
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_rewrite_gemini_command() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+
+        let result =
+            rewrite_agent_command("gemini", &prompt_file, &working_dir, Some("gemini"), None);
+        assert_eq!(result, Some("gemini -i \"$(cat PROMPT.md)\"".to_string()));
+    }
+
+    #[test]
+    fn test_rewrite_uses_configured_prompt_format() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+        let formats = HashMap::from([(
+            "myagent".to_string(),
+            "{cmd} --prompt-file {path}".to_string(),
+        )]);
+
+        let result = rewrite_agent_command(
+            "/usr/local/bin/myagent --fast",
+            &prompt_file,
+            &working_dir,
+            Some("myagent"),
+            Some(&formats),
+        );
+        assert_eq!(
+            result,
+            Some("/usr/local/bin/myagent --fast --prompt-file PROMPT.md".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rewrite_falls_back_for_agents_without_a_format() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+        let formats = HashMap::from([(
+            "myagent".to_string(),
+            "{cmd} --prompt-file {path}".to_string(),
+        )]);
+
+        let result = rewrite_agent_command(
+            "gemini",
+            &prompt_file,
+            &working_dir,
+            Some("gemini"),
+            Some(&formats),
+        );
+        assert_eq!(result, Some("gemini -i \"$(cat PROMPT.md)\"".to_string()));
+
+        let result = rewrite_agent_command(
+            "claude",
+            &prompt_file,
+            &working_dir,
+            Some("claude"),
+            Some(&formats),
+        );
+        assert_eq!(result, Some("claude -- \"$(cat PROMPT.md)\"".to_string()));
+    }
+
+    #[test]
+    fn test_rewrite_mismatched_agent() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+
+        let result =
+            rewrite_agent_command("claude", &prompt_file, &working_dir, Some("gemini"), None);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_build_startup_command_keep_open_execs_login_shell() {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+
+        let command = build_startup_command(Some("npm test"), true, None, None)
+            .unwrap()
+            .unwrap();
+        assert!(command.starts_with(&format!("{} -ic '", shell)));
+        assert!(command.ends_with(&format!("; npm test; exec {} -l'", shell)));
+    }
+
+    #[test]
+    fn test_build_startup_command_without_keep_open_exits_after_command() {
+        let command = build_startup_command(Some("npm test"), false, None, None)
+            .unwrap()
+            .unwrap();
+        assert!(command.ends_with("; npm test'"));
+        assert!(!command.contains("exec"));
+    }
+
+    #[test]
+    fn test_build_startup_command_no_command() {
+        assert_eq!(
+            build_startup_command(None, false, None, None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_build_startup_command_exports_pane_env() {
+        let env = HashMap::from([
+            ("PORT".to_string(), "3000".to_string()),
+            ("API_URL".to_string(), "http://localhost".to_string()),
+        ]);
+        let command = build_startup_command(Some("npm run dev"), false, Some(&env), None)
+            .unwrap()
+            .unwrap();
+        // Each value is single-quoted, and the quotes escaped again for `-ic '...'`
+        assert!(command.contains(
+            r#"-ic 'export API_URL='\''http://localhost'\''; export PORT='\''3000'\''; "#
+        ));
+        assert!(command.ends_with("; npm run dev'"));
+    }
+
+    #[test]
+    fn test_close_on_exit_only_wraps_marked_pane_with_command() {
+        let mut pane = PaneConfig {
+            command: Some("claude".to_string()),
+            focus: false,
+            split: None,
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        };
+        let close = "tmux kill-window -t \"$TMUX_PANE\"";
+        assert_eq!(
+            close_on_exit(&pane, Some("claude".to_string()), close).as_deref(),
+            Some("claude")
+        );
+
+        pane.close_tab_on_exit = true;
+        assert_eq!(
+            close_on_exit(&pane, Some("claude".to_string()), close).as_deref(),
+            Some("claude; tmux kill-window -t \"$TMUX_PANE\"")
+        );
+        assert_eq!(close_on_exit(&pane, None, close), None);
+
+        // The close runs inside the pane's shell, after the command
+        let startup = build_startup_command(
+            close_on_exit(&pane, Some("claude".to_string()), close).as_deref(),
+            pane.keep_open,
+            None,
+            Some("/bin/sh"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            startup,
+            "/bin/sh -ic 'true; claude; tmux kill-window -t \"$TMUX_PANE\"; exec /bin/sh -l'"
+        );
+    }
+
+    #[test]
+    fn test_build_startup_command_uses_overridden_shell_and_its_hook() {
+        let command = build_startup_command(Some("npm test"), true, None, Some("/bin/bash"))
+            .unwrap()
+            .unwrap();
+        assert!(command.starts_with("/bin/bash -ic 'eval \"${PROMPT_COMMAND:-}\"; npm test"));
+        assert!(command.ends_with("; exec /bin/bash -l'"));
+    }
+
+    #[test]
+    fn test_build_startup_command_rejects_missing_shell() {
+        let err = build_startup_command(Some("npm test"), true, None, Some("/no/such/shell"))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Shell '/no/such/shell' was not found")
+        );
+    }
+
+    #[test]
+    fn test_pre_prompt_hook_follows_shell_name() {
+        assert!(pre_prompt_hook("/usr/bin/zsh").contains("precmd_functions"));
+        assert_eq!(pre_prompt_hook("/bin/bash"), "eval \"${PROMPT_COMMAND:-}\"");
+        assert!(pre_prompt_hook("/opt/homebrew/bin/fish").contains("emit fish_prompt"));
+        assert_eq!(pre_prompt_hook("/bin/dash"), "true");
+    }
+
+    #[test]
+    fn test_build_env_prologue_escapes_single_quotes() {
+        let env = HashMap::from([("GREETING".to_string(), "it's here".to_string())]);
+        assert_eq!(
+            build_env_prologue(&env).unwrap(),
+            r#"export GREETING='it'\''s here'; "#
+        );
+    }
+
+    #[test]
+    fn test_build_env_prologue_rejects_invalid_names() {
+        let env = HashMap::from([("BAD-NAME".to_string(), "x".to_string())]);
+        assert!(build_env_prologue(&env).is_err());
+    }
+
+    #[test]
+    fn test_build_startup_command_env_without_command_opens_shell() {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+        let env = HashMap::from([("PORT".to_string(), "3000".to_string())]);
+        let command = build_startup_command(None, false, Some(&env), None)
+            .unwrap()
+            .unwrap();
+        assert!(command.contains("export PORT="));
+        assert!(command.ends_with(&format!("; exec {} -l'", shell)));
+    }
+
+    #[test]
+    fn test_build_hooks_pane_command_checks_conditions() {
+        let hooks: Vec<config::HookCommand> = serde_yaml::from_str(
+            "
+- run: touch ran-exists
+  onlyif:
+    exists: marker
+- run: touch ran-missing
+  onlyif:
+    exists: missing
+- run: touch ran-predicate
+  onlyif: test -f marker
+",
+        )
+        .unwrap();
+        let command = build_hooks_pane_command(&hooks).unwrap();
+        // Drop the trailing interactive shell so the script can run in a test
+        let script = command.split("; exec").next().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("marker"), "").unwrap();
+        let output = std::process::Command::new("sh")
+            .args(["-c", script])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        assert!(dir.path().join("ran-exists").exists());
+        assert!(!dir.path().join("ran-missing").exists());
+        assert!(dir.path().join("ran-predicate").exists());
+        assert!(stdout.contains("==> [2/3] skipped: touch ran-missing"));
+        assert!(stdout.contains("✓ post-create hooks complete"));
+    }
+
+    #[test]
+    fn test_build_hooks_pane_command_empty() {
+        assert_eq!(build_hooks_pane_command(&[]), None);
+    }
+
+    #[test]
+    fn test_build_hooks_pane_command_wraps_each_hook() {
+        let hooks = vec!["pnpm install".into(), "echo 'done'".into()];
+
+        let result = build_hooks_pane_command(&hooks).unwrap();
+        assert_eq!(
+            result,
+            concat!(
+                "echo '==> [1/2] pnpm install' && sh -c 'pnpm install' && ",
+                r#"echo '==> [2/2] echo '\''done'\''' && sh -c 'echo '\''done'\''' && "#,
+                "echo '✓ post-create hooks complete' || echo '✗ post-create hook failed'; ",
+                r#"exec "${SHELL:-sh}" -l"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_last_command_of_pipelines_and_lists() {
+        assert_eq!(last_command("claude --verbose"), "claude --verbose");
+        assert_eq!(last_command("my-wrapper | claude"), "claude");
+        assert_eq!(last_command("cd app && claude -c"), "claude -c");
+        assert_eq!(last_command("setup || true; gemini"), "gemini");
+        assert_eq!(last_command("claude 2>&1"), "claude 2>&1");
+        assert_eq!(last_command("claude &> log"), "claude &> log");
+        assert_eq!(
+            last_command("claude --msg 'a | b; c'"),
+            "claude --msg 'a | b; c'"
+        );
+        assert_eq!(
+            last_command(r#"claude --msg "x \" | y""#),
+            r#"claude --msg "x \" | y""#
+        );
+        assert_eq!(last_command("claude;"), "");
+    }
+
+    #[test]
+    fn test_rewrite_pipeline_appends_prompt_to_last_command() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+        let agent = Some("my-wrapper | claude");
+
+        let result = rewrite_agent_command(
+            "my-wrapper | claude",
+            &prompt_file,
+            &working_dir,
+            agent,
+            None,
+        );
+        assert_eq!(
+            result,
+            Some("my-wrapper | claude -- \"$(cat PROMPT.md)\"".to_string())
+        );
+
+        // The format is picked by the last command, not the wrapper
+        let result = rewrite_agent_command(
+            "cd app && gemini",
+            &prompt_file,
+            &working_dir,
+            Some("gemini"),
+            None,
+        );
+        assert_eq!(
+            result,
+            Some("cd app && gemini -i \"$(cat PROMPT.md)\"".to_string())
+        );
+
+        // The agent isn't last, so appending would feed the prompt to something else
+        for command in ["claude | tee log", "claude; echo done", "claude &"] {
+            assert_eq!(
+                rewrite_agent_command(command, &prompt_file, &working_dir, Some("claude"), None),
+                None,
+                "{}",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite_empty_command() {
+        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
+        let working_dir = PathBuf::from("/tmp/worktree");
+
+        let result = rewrite_agent_command("", &prompt_file, &working_dir, Some("claude"), None);
+        assert_eq!(result, None);
+    }
+}
@@ -1,8 +1,10 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Debug, Clone)]
 pub enum Prompt {
@@ -59,20 +61,118 @@ fn split_frontmatter(content: &str) -> (Option<String>, &str) {
     }
 }
 
-/// Parse a prompt document, extracting frontmatter metadata and body.
-pub fn parse_prompt_document(prompt: &Prompt) -> Result<PromptDocument> {
-    // Store the file content to avoid dangling reference
-    let content_storage: String;
-    let content = match prompt {
-        Prompt::Inline(text) => text.as_str(),
-        Prompt::FromFile(path) => {
-            content_storage = fs::read_to_string(path)
-                .with_context(|| format!("Failed to read prompt file: {}", path.display()))?;
-            &content_storage
+impl Prompt {
+    /// The prompt's raw text, frontmatter included
+    pub fn read_text(&self) -> Result<String> {
+        match self {
+            Prompt::Inline(text) => Ok(text.clone()),
+            Prompt::FromFile(path) => {
+                let bytes = fs::read(path)
+                    .with_context(|| format!("Failed to read prompt file: {}", path.display()))?;
+                decode_prompt(bytes, &format!("Prompt file '{}'", path.display()))
+            }
+        }
+    }
+}
+
+/// Prompts are handed to agents as text, so anything that isn't UTF-8 is
+/// rejected rather than passed on with replacement characters
+fn decode_prompt(bytes: Vec<u8>, source: &str) -> Result<String> {
+    String::from_utf8(bytes).map_err(|e| {
+        anyhow!(
+            "{} is not valid UTF-8 (invalid byte at offset {}). Re-save it as UTF-8.",
+            source,
+            e.utf8_error().valid_up_to()
+        )
+    })
+}
+
+/// Read a prompt from `reader` (stdin for `--prompt-file -`) to EOF and save it
+/// as a prompt file in `dir`, so it can be used like any other `--prompt-file`.
+/// The file is at [`stdin_prompt_path`] and is the caller's to remove once read.
+/// Returns None when there was nothing but whitespace to read.
+pub fn prompt_file_from_reader(mut reader: impl Read, dir: &Path) -> Result<Option<PathBuf>> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .context("Failed to read prompt from stdin")?;
+    let content = decode_prompt(bytes, "The prompt read from stdin")?;
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let path = stdin_prompt_path(dir);
+    write_prompt_file(&path, &content)?;
+    Ok(Some(path))
+}
+
+/// Where [`prompt_file_from_reader`] saves the prompt read from stdin in `dir`
+pub fn stdin_prompt_path(dir: &Path) -> PathBuf {
+    dir.join(format!("workmux-prompt-stdin-{}.md", std::process::id()))
+}
+
+/// Write a prompt to `path`, ending it with a newline.
+///
+/// The text goes to a temporary file next to `path` that is then renamed over
+/// it, so a concurrent `add` for the same branch or a crash mid-write can never
+/// leave a partial prompt behind.
+pub fn write_prompt_file(path: &Path, content: &str) -> Result<()> {
+    let staged = stage_prompt_file(path, content)?;
+    fs::rename(&staged, path).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        anyhow!("Failed to write prompt file '{}': {}", path.display(), e)
+    })
+}
+
+/// Write the prompt to a fresh temporary file in `path`'s directory and return
+/// its path; nothing is visible at `path` until it is renamed there
+fn stage_prompt_file(path: &Path, content: &str) -> Result<PathBuf> {
+    // Unique per process and call, so racing writers never share a temp file
+    static STAGED: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid prompt file path '{}'", path.display()))?;
+    let staged = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        STAGED.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create_new(&staged)?;
+        file.write_all(content.as_bytes())?;
+        if !content.ends_with('\n') {
+            file.write_all(b"\n")?;
         }
+        file.sync_all()
     };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        anyhow!("Failed to write prompt file '{}': {}", path.display(), e)
+    })?;
+    Ok(staged)
+}
 
-    let (frontmatter_yaml, body) = split_frontmatter(content);
+/// Open $EDITOR on a markdown file pre-filled with `initial` and return the
+/// saved text as the prompt
+pub fn edit_prompt(initial: &str) -> Result<Prompt> {
+    let mut builder = edit::Builder::new();
+    builder.suffix(".md");
+    let content = edit::edit_with_builder(initial, &builder)
+        .context("Failed to open editor or read content")?;
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!("Aborting: prompt is empty"));
+    }
+    Ok(Prompt::Inline(trimmed.to_string()))
+}
+
+/// Parse a prompt document, extracting frontmatter metadata and body.
+pub fn parse_prompt_document(prompt: &Prompt) -> Result<PromptDocument> {
+    let content = prompt.read_text()?;
+    let (frontmatter_yaml, body) = split_frontmatter(&content);
 
     let meta = if let Some(ref yaml) = frontmatter_yaml {
         serde_yaml::from_str(yaml).context("Failed to parse YAML frontmatter")?
@@ -131,6 +231,32 @@ pub fn foreach_from_frontmatter(
     Ok(rows)
 }
 
+/// Ask `question` on stderr and read a y/N answer from stdin. Anything other
+/// than "y" or "yes" (including no answer at all) declines. With `assume_yes`
+/// (`--yes` or `confirm: false`) nothing is asked and the answer is yes.
+pub fn confirm(question: &str, assume_yes: bool) -> bool {
+    if assume_yes {
+        return true;
+    }
+    confirm_from(io::stdin().lock(), io::stderr(), question)
+}
+
+fn confirm_from(mut reader: impl BufRead, mut writer: impl Write, question: &str) -> bool {
+    // A prompt we can't show or an answer we can't read is a no
+    if write!(writer, "{} [y/N] ", question)
+        .and_then(|_| writer.flush())
+        .is_err()
+    {
+        return false;
+    }
+
+    let mut answer = String::new();
+    if reader.read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,6 +355,91 @@ mod tests {
         assert!(doc.meta.foreach.is_none());
     }
 
+    #[test]
+    fn prompt_file_from_reader_saves_stdin_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = "---\nforeach:\n  lang: [rust]\n---\nPort it to {{ lang }}\n";
+
+        let path = prompt_file_from_reader(input.as_bytes(), dir.path())
+            .unwrap()
+            .expect("prompt file");
+        assert_eq!(path, stdin_prompt_path(dir.path()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), input);
+
+        let doc = parse_prompt_document(&Prompt::FromFile(path)).unwrap();
+        assert_eq!(doc.body, "Port it to {{ lang }}\n");
+        assert!(doc.meta.foreach.is_some());
+    }
+
+    #[test]
+    fn prompt_file_from_reader_treats_empty_input_as_no_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        for input in ["", " \n\t\n"] {
+            assert_eq!(
+                prompt_file_from_reader(input.as_bytes(), dir.path()).unwrap(),
+                None
+            );
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn prompt_file_from_reader_rejects_non_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = prompt_file_from_reader(&b"Fix caf\xe9 menu"[..], dir.path()).unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-8"));
+        assert!(err.to_string().contains("offset 7"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn read_text_rejects_non_utf8_prompt_files() {
+        let mut temp_file = NamedTempFile::new().expect("create temp file");
+        temp_file.write_all(b"\xff\xfeprompt").unwrap();
+
+        let err = Prompt::FromFile(temp_file.path().to_path_buf())
+            .read_text()
+            .unwrap_err();
+        assert!(err.to_string().contains("is not valid UTF-8"));
+    }
+
+    #[test]
+    fn write_prompt_file_adds_trailing_newline_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workmux-prompt-feature.md");
+
+        write_prompt_file(&path, "Implement login").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Implement login\n");
+
+        // Overwriting replaces the whole file
+        write_prompt_file(&path, "Done\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Done\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn staged_prompt_is_invisible_until_renamed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("workmux-prompt-feature.md");
+        fs::write(&path, "old prompt\n").unwrap();
+
+        // A crash after staging leaves the previous prompt intact
+        let staged = stage_prompt_file(&path, "new prompt").unwrap();
+        assert_ne!(staged, path);
+        assert_eq!(staged.parent(), Some(dir.path()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old prompt\n");
+        assert_eq!(fs::read_to_string(&staged).unwrap(), "new prompt\n");
+
+        // Without a previous prompt, nothing is visible at all
+        let fresh = dir.path().join("workmux-prompt-other.md");
+        let fresh_staged = stage_prompt_file(&fresh, "other").unwrap();
+        assert!(!fresh.exists());
+        assert_ne!(fresh_staged, staged);
+
+        fs::rename(&staged, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new prompt\n");
+    }
+
     #[test]
     fn foreach_from_frontmatter_creates_rows() {
         let mut map = BTreeMap::new();
@@ -285,4 +496,22 @@ mod tests {
                 .contains("at least one value")
         );
     }
+
+    #[test]
+    fn confirm_from_reads_the_answer_from_piped_input() {
+        for (input, expected) in [
+            ("y\n", true),
+            ("Yes\n", true),
+            ("  y  \n", true),
+            ("n\n", false),
+            ("\n", false),
+            ("", false),
+            ("yep\n", false),
+        ] {
+            let mut shown = Vec::new();
+            let answer = confirm_from(input.as_bytes(), &mut shown, "Delete it?");
+            assert_eq!(answer, expected, "input {:?}", input);
+            assert_eq!(String::from_utf8(shown).unwrap(), "Delete it? [y/N] ");
+        }
+    }
 }
@@ -2,11 +2,14 @@ use anyhow::{Context, Result, anyhow};
 use minijinja::{AutoEscape, Environment};
 use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
 use std::collections::{BTreeMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Reserved template variable names that cannot be used in foreach
 const RESERVED_TEMPLATE_KEYS: &[&str] = &["base_name", "agent", "num", "foreach_vars"];
 
+/// How deeply `{{include "path"}}` directives may nest
+const MAX_INCLUDE_DEPTH: usize = 8;
+
 #[derive(Debug, Clone)]
 pub struct WorktreeSpec {
     pub branch_name: String,
@@ -31,6 +34,100 @@ pub fn render_prompt_body(body: &str, env: &TemplateEnv, context: &JsonValue) ->
         .context("Failed to render prompt template")
 }
 
+/// Inline the files referenced by `{{include "path"}}` directives in a prompt
+/// template, before it is rendered.
+///
+/// Paths are relative to the file containing the directive: `template_path` for
+/// the template itself, or the current directory for prompts that didn't come
+/// from a file. Included files may include others; a file including itself,
+/// directly or not, and nesting deeper than `MAX_INCLUDE_DEPTH` are errors.
+pub fn expand_includes(template: &str, template_path: Option<&Path>) -> Result<String> {
+    let mut stack = Vec::new();
+    let base_dir = match template_path {
+        Some(path) => {
+            let canonical = path
+                .canonicalize()
+                .with_context(|| format!("Failed to read prompt file '{}'", path.display()))?;
+            let dir = canonical.parent().map(Path::to_path_buf);
+            stack.push(canonical);
+            dir.unwrap_or_default()
+        }
+        None => PathBuf::from("."),
+    };
+    expand_includes_in(template, &base_dir, &mut stack)
+}
+
+/// Expand the includes of one file's contents; `stack` holds the files being
+/// expanded, outermost first
+fn expand_includes_in(template: &str, base_dir: &Path, stack: &mut Vec<PathBuf>) -> Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        expanded.push_str(&rest[..start]);
+        let Some((include, len)) = parse_include(&rest[start..]) else {
+            // An ordinary template expression, left for minijinja
+            expanded.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        };
+        rest = &rest[start + len..];
+
+        let path = base_dir.join(include);
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to include prompt file '{}'", path.display()))?;
+        if stack.contains(&canonical) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(anyhow!(
+                "Prompt includes form a cycle: {}",
+                chain.join(" -> ")
+            ));
+        }
+        if stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(anyhow!(
+                "Prompt includes are nested more than {} levels deep at '{}'",
+                MAX_INCLUDE_DEPTH,
+                canonical.display()
+            ));
+        }
+
+        let content = std::fs::read_to_string(&canonical)
+            .with_context(|| format!("Failed to include prompt file '{}'", canonical.display()))?;
+        let dir = canonical
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        stack.push(canonical);
+        expanded.push_str(&expand_includes_in(&content, &dir, stack)?);
+        stack.pop();
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Parse an `{{include "path"}}` directive (single quotes work too) at the start
+/// of `input`, returning the path and the directive's length
+fn parse_include(input: &str) -> Option<(&str, usize)> {
+    let inner = input.strip_prefix("{{")?;
+    let after_keyword = inner.trim_start().strip_prefix("include")?;
+    let quoted = after_keyword.trim_start();
+    if quoted.len() == after_keyword.len() {
+        // `{{ included }}` is a variable, not a directive
+        return None;
+    }
+    let quote = quoted.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let (path, after_path) = quoted[1..].split_once(quote)?;
+    let closing = after_path.trim_start().strip_prefix("}}")?;
+    if path.is_empty() {
+        return None;
+    }
+    Some((path, input.len() - closing.len()))
+}
+
 pub fn generate_worktree_specs(
     base_name: &str,
     agents: &[String],
@@ -256,7 +353,57 @@ pub fn parse_foreach_matrix(input: &str) -> Result<Vec<BTreeMap<String, String>>
     Ok(rows)
 }
 
+/// Render the `worktree_path` config template into the directory for a branch's worktree.
+///
+/// Slashes in the branch name are replaced with `-` so every worktree is a single
+/// directory. A relative result is resolved against `main_root`.
+pub fn render_worktree_path(
+    env: &TemplateEnv,
+    template: &str,
+    branch: &str,
+    main_root: &Path,
+) -> Result<PathBuf> {
+    let repo = main_root
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Could not determine project name"))?;
+
+    let context = serde_json::json!({
+        "branch": branch.replace('/', "-"),
+        "repo": repo,
+        "main_root": main_root.to_string_lossy(),
+    });
+    let rendered = env
+        .render_str(template, &context)
+        .with_context(|| format!("Failed to render worktree_path template '{}'", template))?;
+
+    let rendered = rendered.trim();
+    if rendered.is_empty() {
+        return Err(anyhow!(
+            "worktree_path template '{}' rendered an empty path",
+            template
+        ));
+    }
+
+    Ok(main_root.join(rendered))
+}
+
+/// Render the `window_prefix` config value, substituting `{repo}` with the name of
+/// the main worktree directory so tabs from different repos stay distinct.
+pub fn render_window_prefix(prefix: &str, main_root: &Path) -> String {
+    let repo = main_root
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    prefix.replace("{repo}", &repo)
+}
+
 fn slugify_filter(input: String) -> String {
+    slugify(&input)
+}
+
+/// Lowercase `input` and join its alphanumeric runs with '-'
+pub fn slugify(input: &str) -> String {
     input
         .to_lowercase()
         .chars()
@@ -597,4 +744,147 @@ mod tests {
             .context("Failed to render prompt template")?;
         Ok(Prompt::Inline(rendered))
     }
+
+    #[test]
+    fn render_worktree_path_sibling_layout() {
+        let env = create_test_env();
+        let path = render_worktree_path(
+            &env,
+            "../worktrees/{{ repo }}/{{ branch }}",
+            "feature",
+            Path::new("/code/myapp"),
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/code/myapp/../worktrees/myapp/feature")
+        );
+    }
+
+    #[test]
+    fn render_worktree_path_sanitizes_nested_branch() {
+        let env = create_test_env();
+        let path = render_worktree_path(
+            &env,
+            "{{ main_root }}/.worktrees/{{ branch }}",
+            "user/feature/login",
+            Path::new("/code/myapp"),
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/code/myapp/.worktrees/user-feature-login")
+        );
+    }
+
+    #[test]
+    fn render_worktree_path_absolute_template() {
+        let env = create_test_env();
+        let path = render_worktree_path(
+            &env,
+            "/tmp/wt/{{ repo }}-{{ branch | slugify }}",
+            "fix/Bug_42",
+            Path::new("/code/myapp"),
+        )
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/tmp/wt/myapp-fix-bug-42"));
+    }
+
+    #[test]
+    fn render_window_prefix_expands_repo() {
+        assert_eq!(
+            render_window_prefix("{repo}-", Path::new("/code/myapp")),
+            "myapp-"
+        );
+        assert_eq!(render_window_prefix("wm-", Path::new("/code/myapp")), "wm-");
+    }
+
+    #[test]
+    fn render_worktree_path_rejects_empty_result() {
+        let env = create_test_env();
+        let result = render_worktree_path(
+            &env,
+            "{% if false %}x{% endif %}",
+            "feature",
+            Path::new("/code/myapp"),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn expand_includes_inlines_files_relative_to_the_template() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("snippets")).unwrap();
+        fs::write(
+            dir.path().join("snippets/style.md"),
+            "Follow the style guide for {{ lang }}.",
+        )
+        .unwrap();
+        let template_path = dir.path().join("prompt.md");
+        let template = "Port it to {{ lang }}.\n{{include \"snippets/style.md\"}}\n{{ include 'snippets/style.md' }}";
+        fs::write(&template_path, template).unwrap();
+
+        let expanded = expand_includes(template, Some(&template_path)).unwrap();
+        assert_eq!(
+            expanded,
+            "Port it to {{ lang }}.\nFollow the style guide for {{ lang }}.\nFollow the style guide for {{ lang }}."
+        );
+
+        // Variables in included snippets are rendered with the rest
+        let rendered = render_prompt_body(
+            &expanded,
+            &create_test_env(),
+            &serde_json::json!({ "lang": "rust" }),
+        )
+        .unwrap();
+        assert!(rendered.ends_with("Follow the style guide for rust."));
+    }
+
+    #[test]
+    fn expand_includes_leaves_other_expressions_alone() {
+        let template = "{{ included }} {{ include_dir }} {{ \"include\" }} {{include \"\"}}";
+        assert_eq!(expand_includes(template, None).unwrap(), template);
+    }
+
+    #[test]
+    fn expand_includes_detects_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "A {{include \"b.md\"}}").unwrap();
+        fs::write(dir.path().join("b.md"), "B {{include \"a.md\"}}").unwrap();
+        let template_path = dir.path().join("a.md");
+
+        let err = expand_includes("A {{include \"b.md\"}}", Some(&template_path)).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("cycle"), "{}", message);
+        assert!(message.contains("a.md -> "), "{}", message);
+
+        // A file including itself is the shortest cycle
+        let self_path = dir.path().join("self.md");
+        fs::write(&self_path, "{{include \"self.md\"}}").unwrap();
+        let err = expand_includes("{{include \"self.md\"}}", Some(&self_path)).unwrap_err();
+        assert!(err.to_string().contains("cycle"), "{}", err);
+    }
+
+    #[test]
+    fn expand_includes_limits_nesting_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        for level in 0..=MAX_INCLUDE_DEPTH {
+            fs::write(
+                dir.path().join(format!("{}.md", level)),
+                format!("{{{{include \"{}.md\"}}}}", level + 1),
+            )
+            .unwrap();
+        }
+        fs::write(
+            dir.path().join(format!("{}.md", MAX_INCLUDE_DEPTH + 1)),
+            "end",
+        )
+        .unwrap();
+
+        let template_path = dir.path().join("prompt.md");
+        fs::write(&template_path, "{{include \"0.md\"}}").unwrap();
+
+        let err = expand_includes("{{include \"0.md\"}}", Some(&template_path)).unwrap_err();
+        assert!(err.to_string().contains("nested more than"), "{}", err);
+    }
 }
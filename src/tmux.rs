@@ -0,0 +1,536 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cmd::Cmd;
+use crate::config::{PaneConfig, SplitDirection};
+use crate::multiplexer::{self, Multiplexer, PaneSetupOptions, prefixed};
+
+/// Tmux backend for [`Multiplexer`]
+pub struct Tmux;
+
+/// Build an exact-match tmux target for a window in the current session
+fn window_target(prefix: &str, window_name: &str) -> String {
+    format!(":={}", prefixed(prefix, window_name))
+}
+
+/// Check if a tmux server is running
+pub fn is_running() -> Result<bool> {
+    // A missing tmux binary means there is nothing to drive either
+    Ok(Cmd::new("tmux")
+        .arg("has-session")
+        .run_as_check()
+        .unwrap_or(false))
+}
+
+/// Get all window names in the current tmux session
+pub fn get_all_window_names() -> Result<HashSet<String>> {
+    let windows = Cmd::new("tmux")
+        .args(&["list-windows", "-F", "#{window_name}"])
+        .run_and_capture_stdout()
+        .unwrap_or_default();
+
+    Ok(windows.lines().map(String::from).collect())
+}
+
+/// Check if a tmux window with the given name exists
+pub fn window_exists(prefix: &str, window_name: &str) -> Result<bool> {
+    let windows = get_all_window_names()?;
+    Ok(windows.contains(&prefixed(prefix, window_name)))
+}
+
+/// Return the name of the tmux window this process is running in, if any
+pub fn current_window_name() -> Result<Option<String>> {
+    // TMUX_PANE identifies our pane even when another client is focused
+    let Ok(pane) = std::env::var("TMUX_PANE") else {
+        return Ok(None);
+    };
+
+    let name = Cmd::new("tmux")
+        .args(&["display-message", "-p", "-t", &pane, "#{window_name}"])
+        .run_and_capture_stdout()
+        .context("Failed to query current tmux window")?;
+
+    Ok(Some(name).filter(|n| !n.is_empty()))
+}
+
+/// Create a new tmux window with the given name and working directory.
+///
+/// When `detached` is true, the window is created without switching to it.
+pub fn create_window(
+    prefix: &str,
+    window_name: &str,
+    working_dir: &Path,
+    detached: bool,
+) -> Result<()> {
+    let prefixed_name = prefixed(prefix, window_name);
+    let working_dir_str = working_dir
+        .to_str()
+        .ok_or_else(|| anyhow!("Working directory path contains non-UTF8 characters"))?;
+
+    let mut cmd = Cmd::new("tmux").arg("new-window");
+    if detached {
+        cmd = cmd.arg("-d");
+    }
+    cmd.args(&["-n", &prefixed_name, "-c", working_dir_str])
+        .run()
+        .context("Failed to create tmux window")?;
+
+    Ok(())
+}
+
+/// Select a specific window by name
+pub fn select_window(prefix: &str, window_name: &str) -> Result<()> {
+    Cmd::new("tmux")
+        .args(&["select-window", "-t", &window_target(prefix, window_name)])
+        .run()
+        .context("Failed to select window")?;
+
+    Ok(())
+}
+
+/// Kill a tmux window by name
+pub fn kill_window(prefix: &str, window_name: &str) -> Result<()> {
+    Cmd::new("tmux")
+        .args(&["kill-window", "-t", &window_target(prefix, window_name)])
+        .run()
+        .context("Failed to kill tmux window")?;
+
+    Ok(())
+}
+
+/// Schedule a tmux window to be killed after a short delay. This is useful when
+/// the current command is running inside the window that needs to close.
+pub fn schedule_window_close(prefix: &str, window_name: &str, delay: Duration) -> Result<()> {
+    Cmd::new("tmux")
+        .args(&["run-shell", "-b", &close_script(prefix, window_name, delay)])
+        .run()
+        .context("Failed to schedule window close")?;
+
+    Ok(())
+}
+
+/// Schedule selecting one window followed by killing another, after a delay
+pub fn schedule_select_and_close(
+    prefix: &str,
+    select_name: &str,
+    close_name: &str,
+    delay: Duration,
+) -> Result<()> {
+    let script = format!(
+        "sleep {delay:.3}; tmux select-window -t {main} 2>/dev/null; tmux kill-window -t {target} 2>/dev/null",
+        delay = delay.as_secs_f64(),
+        main = multiplexer::shell_quote(&window_target(prefix, select_name)),
+        target = multiplexer::shell_quote(&window_target(prefix, close_name)),
+    );
+
+    Cmd::new("tmux")
+        .args(&["run-shell", "-b", &script])
+        .run()
+        .context("Failed to schedule window navigation and close")?;
+
+    Ok(())
+}
+
+/// The `run-shell` script that kills a window after `delay`. Window names come
+/// from branch names, which may contain quotes and `$`, so the target is quoted.
+fn close_script(prefix: &str, window_name: &str, delay: Duration) -> String {
+    format!(
+        "sleep {delay:.3}; tmux kill-window -t {target} 2>/dev/null",
+        delay = delay.as_secs_f64(),
+        target = multiplexer::shell_quote(&window_target(prefix, window_name)),
+    )
+}
+
+/// Run a command in a new pane split from the given window, without focusing it
+pub fn run_command_in_window(
+    prefix: &str,
+    window_name: &str,
+    working_dir: &Path,
+    command: &str,
+) -> Result<()> {
+    let working_dir_str = working_dir
+        .to_str()
+        .ok_or_else(|| anyhow!("Working directory path contains non-UTF8 characters"))?;
+
+    Cmd::new("tmux")
+        .args(&[
+            "split-window",
+            "-d",
+            "-t",
+            &window_target(prefix, window_name),
+            "-c",
+            working_dir_str,
+            command,
+        ])
+        .run()
+        .context("Failed to run command in new pane")?;
+
+    Ok(())
+}
+
+/// List the current command of every pane in the server running inside `working_dir`
+pub fn pane_commands_in(working_dir: &Path) -> Result<Vec<String>> {
+    let output = Cmd::new("tmux")
+        .args(&[
+            "list-panes",
+            "-a",
+            "-F",
+            "#{pane_current_path}\t#{pane_current_command}",
+        ])
+        .run_and_capture_stdout()
+        .context("Failed to list tmux panes")?;
+
+    // tmux reports resolved paths, so compare against the canonical directory
+    let working_dir = working_dir
+        .canonicalize()
+        .unwrap_or_else(|_| working_dir.to_path_buf());
+    Ok(parse_pane_commands(&output, &working_dir))
+}
+
+/// Parse `list-panes` output of `path<TAB>command` lines, keeping the commands
+/// of panes located inside `working_dir`
+fn parse_pane_commands(output: &str, working_dir: &Path) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(path, _)| Path::new(path).starts_with(working_dir))
+        .map(|(_, command)| command.to_string())
+        .collect()
+}
+
+/// Build the `split-window` arguments for a pane, excluding the command to run
+fn split_window_args(pane: &PaneConfig, target_pane: &str, working_dir: &str) -> Vec<String> {
+    let split = pane.split.as_ref();
+    let direction = if split.is_some_and(SplitDirection::is_vertical) {
+        "-v"
+    } else {
+        "-h"
+    };
+
+    let mut args = vec!["split-window".to_string(), direction.to_string()];
+    if split.is_some_and(SplitDirection::is_before) {
+        args.push("-b".to_string());
+    }
+    args.extend([
+        "-t".to_string(),
+        target_pane.to_string(),
+        "-c".to_string(),
+        working_dir.to_string(),
+        "-P".to_string(),
+        "-F".to_string(),
+        "#{pane_id}".to_string(),
+    ]);
+
+    // tmux takes either form as-is: `-l 15` for cells, `-l 30%` for a percentage
+    if let Some(size) = pane.effective_size() {
+        args.push("-l".to_string());
+        args.push(size.to_string());
+    }
+
+    args
+}
+
+/// Closes the window containing the pane it runs in. `$TMUX_PANE` is expanded
+/// by the pane's shell, so the right window closes even if another is focused.
+const CLOSE_WINDOW_COMMAND: &str = "tmux kill-window -t \"$TMUX_PANE\"";
+
+/// Create the configured panes in a window and start their commands
+pub fn setup_panes(
+    prefix: &str,
+    window_name: &str,
+    panes: &[PaneConfig],
+    working_dir: &Path,
+    options: PaneSetupOptions<'_>,
+) -> Result<()> {
+    if panes.is_empty() {
+        return Ok(());
+    }
+
+    let working_dir_str = working_dir
+        .to_str()
+        .ok_or_else(|| anyhow!("Working directory path contains non-UTF8 characters"))?;
+
+    let first_pane = Cmd::new("tmux")
+        .args(&[
+            "display-message",
+            "-p",
+            "-t",
+            &window_target(prefix, window_name),
+            "#{pane_id}",
+        ])
+        .run_and_capture_stdout()
+        .context("Failed to find the initial pane of the new window")?;
+    let mut pane_ids = vec![first_pane];
+
+    for (idx, pane) in panes.iter().enumerate() {
+        let command = if options.run_commands {
+            multiplexer::resolve_pane_command(pane, &options, working_dir)
+        } else {
+            None
+        };
+        let command = multiplexer::close_on_exit(pane, command, CLOSE_WINDOW_COMMAND);
+        let startup_cmd = multiplexer::build_startup_command(
+            command.as_deref(),
+            pane.keep_open,
+            pane.env.as_ref(),
+            pane.shell.as_deref().or(options.startup_shell),
+        )?;
+
+        if idx == 0 {
+            if let Some(cmd) = startup_cmd {
+                Cmd::new("tmux")
+                    .args(&[
+                        "respawn-pane",
+                        "-k",
+                        "-t",
+                        &pane_ids[0],
+                        "-c",
+                        working_dir_str,
+                    ])
+                    .arg(&cmd)
+                    .run()
+                    .context("Failed to start command in the first pane")?;
+            }
+        } else {
+            let target = pane
+                .target
+                .and_then(|t| pane_ids.get(t))
+                .or(pane_ids.last())
+                .cloned()
+                .unwrap_or_default();
+            let mut args = split_window_args(pane, &target, working_dir_str);
+            if let Some(cmd) = startup_cmd {
+                args.push(cmd);
+            }
+            let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+            let pane_id = Cmd::new("tmux")
+                .args(&arg_refs)
+                .run_and_capture_stdout()
+                .with_context(|| format!("Failed to create pane {}", idx))?;
+            pane_ids.push(pane_id);
+        }
+    }
+
+    if let Some(pane_id) = focus_pane_id(&pane_ids, panes, options.select_pane) {
+        Cmd::new("tmux")
+            .args(&["select-pane", "-t", pane_id])
+            .run()
+            .context("Failed to focus pane")?;
+    }
+
+    Ok(())
+}
+
+/// The id of the pane to focus once all panes exist: the one at `select_pane`
+/// (`open --select-pane`) if given, else the last pane configured with `focus`.
+/// `pane_ids` holds the created panes in configuration order.
+fn focus_pane_id<'a>(
+    pane_ids: &'a [String],
+    panes: &[PaneConfig],
+    select_pane: Option<usize>,
+) -> Option<&'a str> {
+    select_pane
+        .or_else(|| panes.iter().rposition(|pane| pane.focus))
+        .and_then(|idx| pane_ids.get(idx))
+        .map(String::as_str)
+}
+
+impl Multiplexer for Tmux {
+    fn name(&self) -> &'static str {
+        "tmux"
+    }
+
+    fn window_term(&self) -> &'static str {
+        "window"
+    }
+
+    fn is_running(&self) -> Result<bool> {
+        is_running()
+    }
+
+    fn list_names(&self) -> Result<HashSet<String>> {
+        get_all_window_names()
+    }
+
+    fn exists(&self, prefix: &str, name: &str) -> Result<bool> {
+        window_exists(prefix, name)
+    }
+
+    fn current_name(&self) -> Result<Option<String>> {
+        current_window_name()
+    }
+
+    fn create(&self, prefix: &str, name: &str, working_dir: &Path, detached: bool) -> Result<()> {
+        create_window(prefix, name, working_dir, detached)
+    }
+
+    fn select(&self, prefix: &str, name: &str) -> Result<()> {
+        select_window(prefix, name)
+    }
+
+    fn close(&self, prefix: &str, name: &str) -> Result<()> {
+        kill_window(prefix, name)
+    }
+
+    fn schedule_close(&self, prefix: &str, name: &str, delay: Duration) -> Result<()> {
+        schedule_window_close(prefix, name, delay)
+    }
+
+    fn schedule_select_and_close(
+        &self,
+        prefix: &str,
+        select_name: &str,
+        close_name: &str,
+        delay: Duration,
+    ) -> Result<()> {
+        schedule_select_and_close(prefix, select_name, close_name, delay)
+    }
+
+    fn setup_panes(
+        &self,
+        prefix: &str,
+        name: &str,
+        panes: &[PaneConfig],
+        working_dir: &Path,
+        options: PaneSetupOptions<'_>,
+    ) -> Result<()> {
+        setup_panes(prefix, name, panes, working_dir, options)
+    }
+
+    fn run_in_new_pane(
+        &self,
+        prefix: &str,
+        name: &str,
+        working_dir: &Path,
+        command: &str,
+    ) -> Result<()> {
+        run_command_in_window(prefix, name, working_dir, command)
+    }
+
+    fn select_hint(&self, prefix: &str, name: &str) -> String {
+        format!("tmux select-window -t '{}'", window_target(prefix, name))
+    }
+
+    fn pane_commands_in(&self, working_dir: &Path) -> Result<Vec<String>> {
+        pane_commands_in(working_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PaneSize;
+
+    fn pane(split: SplitDirection) -> PaneConfig {
+        PaneConfig {
+            command: None,
+            focus: false,
+            split: Some(split),
+            size: None,
+            percentage: None,
+            target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
+        }
+    }
+
+    #[test]
+    fn test_window_target_is_exact_match() {
+        assert_eq!(window_target("wm-", "feature"), ":=wm-feature");
+    }
+
+    #[test]
+    fn test_parse_pane_commands_filters_by_worktree() {
+        let output = "/repo__worktrees/feature\tclaude\n\
+                      /repo__worktrees/feature/src\tzsh\n\
+                      /repo__worktrees/feature-two\tclaude\n\
+                      /repo\tnvim\n";
+
+        let commands = parse_pane_commands(output, Path::new("/repo__worktrees/feature"));
+        assert_eq!(commands, vec!["claude", "zsh"]);
+    }
+
+    #[test]
+    fn test_split_window_args_horizontal() {
+        let args = split_window_args(&pane(SplitDirection::Horizontal), "%1", "/tmp/wt");
+        assert_eq!(
+            args,
+            vec![
+                "split-window",
+                "-h",
+                "-t",
+                "%1",
+                "-c",
+                "/tmp/wt",
+                "-P",
+                "-F",
+                "#{pane_id}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_window_args_side_aliases() {
+        let args = split_window_args(&pane(SplitDirection::Left), "%1", "/tmp/wt");
+        assert_eq!(&args[..4], ["split-window", "-h", "-b", "-t"]);
+
+        let args = split_window_args(&pane(SplitDirection::Up), "%1", "/tmp/wt");
+        assert_eq!(&args[..4], ["split-window", "-v", "-b", "-t"]);
+
+        let args = split_window_args(&pane(SplitDirection::Right), "%1", "/tmp/wt");
+        assert_eq!(&args[..3], ["split-window", "-h", "-t"]);
+
+        let args = split_window_args(&pane(SplitDirection::Down), "%1", "/tmp/wt");
+        assert_eq!(&args[..3], ["split-window", "-v", "-t"]);
+    }
+
+    #[test]
+    fn test_split_window_args_with_size_and_percentage() {
+        let mut sized = pane(SplitDirection::Vertical);
+        sized.size = Some(PaneSize::Cells(15));
+        let args = split_window_args(&sized, "%2", "/tmp/wt");
+        assert_eq!(args[1], "-v");
+        assert_eq!(&args[args.len() - 2..], ["-l", "15"]);
+
+        let mut percent = pane(SplitDirection::Vertical);
+        percent.percentage = Some(30);
+        let args = split_window_args(&percent, "%2", "/tmp/wt");
+        assert_eq!(&args[args.len() - 2..], ["-l", "30%"]);
+
+        percent.percentage = None;
+        percent.size = Some(PaneSize::Percent(25));
+        let args = split_window_args(&percent, "%2", "/tmp/wt");
+        assert_eq!(&args[args.len() - 2..], ["-l", "25%"]);
+    }
+
+    #[test]
+    fn test_focus_pane_id_prefers_select_pane_over_configured_focus() {
+        let pane_ids: Vec<String> = ["%1", "%2", "%3"].map(String::from).to_vec();
+        let mut panes = vec![pane(SplitDirection::Vertical); 3];
+        panes[1].focus = true;
+
+        assert_eq!(focus_pane_id(&pane_ids, &panes, None), Some("%2"));
+        assert_eq!(focus_pane_id(&pane_ids, &panes, Some(0)), Some("%1"));
+        assert_eq!(focus_pane_id(&pane_ids, &panes, Some(2)), Some("%3"));
+        assert_eq!(focus_pane_id(&pane_ids, &panes, Some(3)), None);
+
+        // The last pane marked `focus` wins, and none means focus stays put
+        panes[2].focus = true;
+        assert_eq!(focus_pane_id(&pane_ids, &panes, None), Some("%3"));
+        let unfocused = vec![pane(SplitDirection::Vertical); 3];
+        assert_eq!(focus_pane_id(&pane_ids, &unfocused, None), None);
+    }
+
+    #[test]
+    fn close_script_quotes_the_window_target() {
+        let script = close_script("wm-", "fix-it's$(id)", Duration::ZERO);
+        assert_eq!(
+            script,
+            r#"sleep 0.000; tmux kill-window -t ':=wm-fix-it'\''s$(id)' 2>/dev/null"#
+        );
+    }
+}
@@ -0,0 +1,634 @@
+//! A small TOML reader for config files.
+//!
+//! It covers what a workmux config can contain: tables, arrays of tables,
+//! inline tables, arrays, strings (basic, literal and multi-line), integers,
+//! floats and booleans. Dates and times are rejected, since no setting takes
+//! one. The document is returned as a JSON value so that serde can deserialize
+//! it the same way as a `.json` config.
+
+use anyhow::{Result, anyhow};
+use serde_json::{Map as JsonMap, Number as JsonNumber, Value as JsonValue};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+type Table = JsonMap<String, JsonValue>;
+
+/// Parse a TOML document into a JSON object.
+pub fn parse(input: &str) -> Result<JsonValue> {
+    Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    }
+    .document()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn document(mut self) -> Result<JsonValue> {
+        let mut root = Table::new();
+        // Tables opened by a `[header]`, which may not be opened a second time
+        let mut defined: HashSet<Vec<String>> = HashSet::new();
+        let mut current: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_blank_lines();
+            if self.peek().is_none() {
+                break;
+            }
+
+            if self.starts_with("[[") {
+                self.pos += 2;
+                let path = self.key_path()?;
+                self.expect(']')?;
+                self.expect(']')?;
+                push_table(&mut root, &path).map_err(|e| self.error(e))?;
+                // Each new element starts with none of its sub-tables defined
+                defined.retain(|table| !table.starts_with(&path));
+                current = path;
+            } else if self.peek() == Some('[') {
+                self.pos += 1;
+                let path = self.key_path()?;
+                self.expect(']')?;
+                if !defined.insert(path.clone()) {
+                    return Err(self.error(format!("table [{}] is defined twice", path.join("."))));
+                }
+                table_at(&mut root, &path).map_err(|e| self.error(e))?;
+                current = path;
+            } else {
+                let (path, value) = self.key_value()?;
+                table_at(&mut root, &current)
+                    .and_then(|table| insert(table, &path, value))
+                    .map_err(|e| self.error(e))?;
+            }
+
+            self.end_of_line()?;
+        }
+
+        Ok(JsonValue::Object(root))
+    }
+
+    fn key_value(&mut self) -> Result<(Vec<String>, JsonValue)> {
+        let path = self.key_path()?;
+        self.expect('=')?;
+        self.skip_whitespace();
+        let value = self.value()?;
+        Ok((path, value))
+    }
+
+    /// A possibly dotted key such as `files.copy` or `"agent prompt".claude`
+    fn key_path(&mut self) -> Result<Vec<String>> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_whitespace();
+            path.push(self.key()?);
+            self.skip_whitespace();
+            if self.peek() != Some('.') {
+                return Ok(path);
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                self.basic_string()
+            }
+            Some('\'') => {
+                self.pos += 1;
+                self.literal_string()
+            }
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a key"));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue> {
+        if self.starts_with("\"\"\"") {
+            self.pos += 3;
+            return self.multiline_string('"').map(JsonValue::String);
+        }
+        if self.starts_with("'''") {
+            self.pos += 3;
+            return self.multiline_string('\'').map(JsonValue::String);
+        }
+
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                self.basic_string().map(JsonValue::String)
+            }
+            Some('\'') => {
+                self.pos += 1;
+                self.literal_string().map(JsonValue::String)
+            }
+            Some('[') => {
+                self.pos += 1;
+                self.array()
+            }
+            Some('{') => {
+                self.pos += 1;
+                self.inline_table()
+            }
+            _ if self.starts_with("true") => {
+                self.pos += 4;
+                Ok(JsonValue::Bool(true))
+            }
+            _ if self.starts_with("false") => {
+                self.pos += 5;
+                Ok(JsonValue::Bool(false))
+            }
+            _ => self.number(),
+        }
+    }
+
+    /// An array after its opening bracket. Values may span lines and be
+    /// followed by a trailing comma.
+    fn array(&mut self) -> Result<JsonValue> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(JsonValue::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank_lines();
+            match self.next_char() {
+                Some(',') => {}
+                Some(']') => return Ok(JsonValue::Array(items)),
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    /// An inline table after its opening brace, which must fit on one line.
+    fn inline_table(&mut self) -> Result<JsonValue> {
+        let mut table = Table::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(table));
+        }
+        loop {
+            let (path, value) = self.key_value()?;
+            insert(&mut table, &path, value).map_err(|e| self.error(e))?;
+            self.skip_whitespace();
+            match self.next_char() {
+                Some(',') => {}
+                Some('}') => return Ok(JsonValue::Object(table)),
+                _ => return Err(self.error("expected ',' or '}' in inline table")),
+            }
+        }
+    }
+
+    /// A `"basic"` string after its opening quote.
+    fn basic_string(&mut self) -> Result<String> {
+        let mut out = String::new();
+        loop {
+            match self.next_char() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => out.push(self.escape()?),
+                Some(c) => out.push(self.plain_char(c)?),
+            }
+        }
+    }
+
+    /// A `'literal'` string after its opening quote.
+    fn literal_string(&mut self) -> Result<String> {
+        let mut out = String::new();
+        loop {
+            match self.next_char() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(out),
+                Some(c) => out.push(self.plain_char(c)?),
+            }
+        }
+    }
+
+    /// A multi-line string after its opening delimiter, which is three `quote`
+    /// characters. Only `"""` strings process escapes.
+    fn multiline_string(&mut self, quote: char) -> Result<String> {
+        // A newline right after the opening delimiter is not part of the string
+        self.skip_newline();
+
+        let mut out = String::new();
+        loop {
+            let quotes = (0..5)
+                .take_while(|&i| self.peek_at(i) == Some(quote))
+                .count();
+            if quotes >= 3 {
+                // Up to two quotes right before the delimiter belong to the string
+                out.extend(std::iter::repeat_n(quote, quotes - 3));
+                self.pos += quotes;
+                return Ok(out);
+            }
+
+            match self.next_char() {
+                None => return Err(self.error("unterminated multi-line string")),
+                Some('\\') if quote == '"' => {
+                    let escape_start = self.pos;
+                    self.skip_whitespace();
+                    if self.skip_newline() {
+                        // A backslash ending a line trims the line break and any
+                        // whitespace up to the next non-blank character
+                        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+                            self.pos += 1;
+                        }
+                    } else {
+                        self.pos = escape_start;
+                        out.push(self.escape()?);
+                    }
+                }
+                Some('\n') => out.push('\n'),
+                Some('\r') if self.peek() == Some('\n') => {}
+                Some(c) => out.push(self.plain_char(c)?),
+            }
+        }
+    }
+
+    /// The character after a backslash in a basic string.
+    fn escape(&mut self) -> Result<char> {
+        match self.next_char() {
+            Some('b') => Ok('\u{8}'),
+            Some('t') => Ok('\t'),
+            Some('n') => Ok('\n'),
+            Some('f') => Ok('\u{c}'),
+            Some('r') => Ok('\r'),
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('u') => self.unicode_escape(4),
+            Some('U') => self.unicode_escape(8),
+            _ => Err(self.error("invalid escape sequence")),
+        }
+    }
+
+    fn unicode_escape(&mut self, len: usize) -> Result<char> {
+        let digits: String = (0..len).filter_map(|_| self.next_char()).collect();
+        Some(&digits)
+            .filter(|d| d.len() == len && d.chars().all(|c| c.is_ascii_hexdigit()))
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(format!("invalid unicode escape '{}'", digits)))
+    }
+
+    /// Check that a character may appear unescaped in a string
+    fn plain_char(&self, c: char) -> Result<char> {
+        if c != '\t' && c.is_ascii_control() {
+            return Err(self.error(format!("control character {:?} must be escaped", c)));
+        }
+        Ok(c)
+    }
+
+    fn number(&mut self) -> Result<JsonValue> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || "_+-.:".contains(c)) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a value"));
+        }
+        let token: String = self.chars[start..self.pos].iter().collect();
+        parse_number(&token).map_err(|e| self.error(e))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.next_char() == Some(c) {
+            Ok(())
+        } else {
+            self.pos -= 1;
+            Err(self.error(format!("expected '{}'", c)))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n' | '\r')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn skip_newline(&mut self) -> bool {
+        match (self.peek(), self.peek_at(1)) {
+            (Some('\n'), _) => self.pos += 1,
+            (Some('\r'), Some('\n')) => self.pos += 2,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Skip whitespace, comments and line breaks
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            if !self.skip_newline() {
+                return;
+            }
+        }
+    }
+
+    /// Require that nothing but a comment follows on the current line
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        self.skip_comment();
+        if self.peek().is_none() || self.skip_newline() {
+            Ok(())
+        } else {
+            Err(self.error("expected a new line"))
+        }
+    }
+
+    fn error(&self, message: impl Display) -> anyhow::Error {
+        let line = self.chars[..self.pos.min(self.chars.len())]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count()
+            + 1;
+        anyhow!("TOML line {}: {}", line, message)
+    }
+}
+
+/// The table at `path` below `table`, creating missing tables on the way.
+/// An array of tables along the path stands for its last element.
+fn table_at<'t>(mut table: &'t mut Table, path: &[String]) -> Result<&'t mut Table, String> {
+    for key in path {
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| JsonValue::Object(Table::new()));
+        table = match entry {
+            JsonValue::Object(inner) => inner,
+            JsonValue::Array(items) => match items.last_mut() {
+                Some(JsonValue::Object(inner)) => inner,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            _ => return Err(format!("'{}' is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+/// Start a new element of the array of tables at `path`
+fn push_table(root: &mut Table, path: &[String]) -> Result<(), String> {
+    let (name, parents) = path.split_last().expect("key paths are never empty");
+    match table_at(root, parents)?
+        .entry(name.clone())
+        .or_insert_with(|| JsonValue::Array(Vec::new()))
+    {
+        JsonValue::Array(items) => {
+            items.push(JsonValue::Object(Table::new()));
+            Ok(())
+        }
+        _ => Err(format!("'{}' is not an array of tables", path.join("."))),
+    }
+}
+
+fn insert(table: &mut Table, path: &[String], value: JsonValue) -> Result<(), String> {
+    let (name, parents) = path.split_last().expect("key paths are never empty");
+    let table = table_at(table, parents)?;
+    if table.contains_key(name) {
+        return Err(format!("key '{}' is defined twice", path.join(".")));
+    }
+    table.insert(name.clone(), value);
+    Ok(())
+}
+
+fn parse_number(token: &str) -> Result<JsonValue, String> {
+    let invalid = || format!("invalid value '{}'", token);
+
+    let bytes = token.as_bytes();
+    let is_date = bytes.len() > 4 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-';
+    let is_time = bytes.len() > 2 && bytes[..2].iter().all(u8::is_ascii_digit) && bytes[2] == b':';
+    if is_date || is_time {
+        return Err(format!("dates and times are not supported: '{}'", token));
+    }
+
+    let (sign, digits) = match token.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", token.strip_prefix('+').unwrap_or(token)),
+    };
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(invalid());
+    }
+    let digits = digits.replace('_', "");
+
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(rest) = digits.strip_prefix(prefix) {
+            if !sign.is_empty() || rest.is_empty() || !rest.chars().all(|c| c.is_digit(radix)) {
+                return Err(invalid());
+            }
+            return i64::from_str_radix(rest, radix)
+                .map(JsonValue::from)
+                .map_err(|_| invalid());
+        }
+    }
+
+    if matches!(digits.as_str(), "inf" | "nan") {
+        return Err(format!("'{}' cannot be used in a config", token));
+    }
+
+    let int_part = digits.split(['.', 'e', 'E']).next().unwrap_or_default();
+    if int_part.is_empty()
+        || !int_part.chars().all(|c| c.is_ascii_digit())
+        || (int_part.len() > 1 && int_part.starts_with('0'))
+    {
+        return Err(invalid());
+    }
+
+    if digits.contains(['.', 'e', 'E']) {
+        // A decimal point needs digits on both sides
+        if let Some((_, fraction)) = digits.split_once('.')
+            && !fraction.starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let value: f64 = format!("{}{}", sign, digits)
+            .parse()
+            .map_err(|_| invalid())?;
+        return JsonNumber::from_f64(value)
+            .map(JsonValue::Number)
+            .ok_or_else(invalid);
+    }
+
+    format!("{}{}", sign, digits)
+        .parse::<i64>()
+        .map(JsonValue::from)
+        .map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use serde_json::json;
+
+    #[test]
+    fn parses_scalars() {
+        let doc = parse(
+            "name = \"workmux\"\nbare-key_1 = 'C:\\path'\ncount = 1_000\nneg = -7\nhex = 0xff\n\
+             ratio = 0.5\nbig = 1e3\nyes = true\nno = false # trailing comment\n",
+        )
+        .unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "name": "workmux",
+                "bare-key_1": "C:\\path",
+                "count": 1000,
+                "neg": -7,
+                "hex": 255,
+                "ratio": 0.5,
+                "big": 1000.0,
+                "yes": true,
+                "no": false,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_string_escapes_and_multiline_strings() {
+        let doc = parse(concat!(
+            "escaped = \"tab\\tquote\\\" \\u00e9\"\n",
+            "basic = \"\"\"\nline one\nline two\\\n    continued\"\"\"\n",
+            "literal = '''\nkeep \\n as is'''\n",
+            "quoted = \"\"\"say \"hi\\\"\"\"\"\n",
+        ))
+        .unwrap();
+        assert_eq!(doc["escaped"], "tab\tquote\" é");
+        assert_eq!(doc["basic"], "line one\nline twocontinued");
+        assert_eq!(doc["literal"], "keep \\n as is");
+        assert_eq!(doc["quoted"], "say \"hi\"");
+    }
+
+    #[test]
+    fn parses_tables_and_dotted_keys() {
+        let doc = parse(
+            "top = 1\nfiles.copy = [\".env\"]\n\n[agent_prompt_format]\n\"my agent\" = \"{cmd}\"\n\
+             [a.b]\nc = 2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "top": 1,
+                "files": { "copy": [".env"] },
+                "agent_prompt_format": { "my agent": "{cmd}" },
+                "a": { "b": { "c": 2 } },
+            })
+        );
+    }
+
+    #[test]
+    fn parses_arrays_of_tables_with_sub_tables() {
+        let doc = parse(
+            "[[panes]]\ncommand = \"<agent>\"\n[panes.env]\nA = \"1\"\n\n\
+             [[panes]]\ncommand = \"htop\"\n[panes.env]\nA = \"2\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            doc,
+            json!({ "panes": [
+                { "command": "<agent>", "env": { "A": "1" } },
+                { "command": "htop", "env": { "A": "2" } },
+            ]})
+        );
+    }
+
+    #[test]
+    fn parses_multiline_arrays_and_inline_tables() {
+        let doc = parse(
+            "post_create = [\n  \"pnpm install\", # install deps\n\n  { run = \"make\", onlyif = { exists = \"Makefile\" } },\n]\nempty = {}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "post_create": [
+                    "pnpm install",
+                    { "run": "make", "onlyif": { "exists": "Makefile" } },
+                ],
+                "empty": {},
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_keys_and_tables() {
+        assert!(parse("a = 1\na = 2\n").is_err());
+        assert!(parse("[t]\nx = 1\n[t]\ny = 2\n").is_err());
+        assert!(parse("t = { x = 1, x = 2 }\n").is_err());
+        assert!(parse("t = 1\n[[t]]\n").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        for doc in [
+            "a = 01\n",
+            "a = 1__0\n",
+            "a = 1.\n",
+            "a = inf\n",
+            "a = \"open\n",
+            "a = [1 2]\n",
+            "a = 1 b = 2\n",
+            "a =\n",
+            "= 1\n",
+            "a = \"\\q\"\n",
+        ] {
+            assert!(parse(doc).is_err(), "{:?} should not parse", doc);
+        }
+    }
+
+    #[test]
+    fn dates_are_reported_as_unsupported() {
+        let err = parse("created = 1979-05-27T07:32:00Z\n").unwrap_err();
+        assert!(err.to_string().contains("dates and times"));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let err = parse("a = 1\n\n# comment\nb = nope\n").unwrap_err();
+        assert!(err.to_string().starts_with("TOML line 4:"), "{}", err);
+    }
+}
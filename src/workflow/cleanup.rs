@@ -1,16 +1,28 @@
 use anyhow::{Context, Result};
+use std::io::{self, Write};
 use std::path::Path;
-use std::{thread, time::Duration};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::{cmd, git, zellij};
+use crate::multiplexer::{Multiplexer, prefixed};
+use crate::git;
 use tracing::{debug, info, warn};
 
 use super::context::WorkflowContext;
+use super::hooks::HookOutput;
 use super::types::CleanupResult;
 
 const WINDOW_CLOSE_DELAY_MS: u64 = 300;
 
-/// Centralized function to clean up zellij and git resources
+/// How many times to check whether a closed tab is gone before giving up
+const TAB_CLOSE_MAX_RETRIES: u32 = 20;
+const TAB_CLOSE_RETRY_DELAY: Duration = Duration::from_millis(50);
+/// How long waiting for a tab to close stays silent before reporting progress.
+/// This is wall-clock time, so it counts the checks themselves, which is where a
+/// slow machine spends most of the wait.
+const TAB_CLOSE_PROGRESS_AFTER: Duration = Duration::from_secs(1);
+
+/// Centralized function to clean up multiplexer and git resources
 pub fn cleanup(
     context: &WorkflowContext,
     branch_name: &str,
@@ -32,10 +44,11 @@ pub fn cleanup(
     // is run from within the worktree being deleted.
     context.chdir_to_main_worktree()?;
 
-    let zellij_running = zellij::is_running().unwrap_or(false);
-    let running_inside_target_tab = if zellij_running {
-        match zellij::current_tab_name() {
-            Ok(Some(current_name)) => current_name == zellij::prefixed(&context.prefix, branch_name),
+    let mux = context.mux.as_ref();
+    let mux_running = mux.is_running().unwrap_or(false);
+    let running_inside_target_tab = if mux_running {
+        match mux.current_name() {
+            Ok(Some(current_name)) => current_name == prefixed(&context.prefix, branch_name),
             _ => false,
         }
     } else {
@@ -49,6 +62,10 @@ pub fn cleanup(
         remote_branch_deleted: false,
         remote_delete_error: None,
         ran_inside_target_window: running_inside_target_tab,
+        window_skip_reason: None,
+        worktree_skip_reason: None,
+        local_branch_skip_reason: None,
+        remote_branch_skip_reason: None,
     };
 
     // Helper closure to perform the actual filesystem and git cleanup.
@@ -61,12 +78,16 @@ pub fn cleanup(
                 count = pre_delete_hooks.len(),
                 "cleanup:running pre-delete hooks"
             );
-            for command in pre_delete_hooks {
-                // Run the hook with the worktree path as the working directory.
-                // This allows for relative paths like `node_modules` in the command.
-                cmd::shell_command(command, worktree_path)
-                    .with_context(|| format!("Failed to run pre-delete command: '{}'", command))?;
-            }
+            // Run the hooks with the worktree path as the working directory.
+            // This allows for relative paths like `node_modules` in the command.
+            super::hooks::run_hooks(
+                "pre-delete",
+                pre_delete_hooks,
+                worktree_path,
+                HookOutput::Inherit,
+                context.config.hook_timeout(),
+                context.config.verbose,
+            )?;
         }
 
         // 1. Forcefully remove the worktree directory from the filesystem.
@@ -80,6 +101,8 @@ pub fn cleanup(
             })?;
             result.worktree_removed = true;
             info!(branch = branch_name, path = %worktree_path.display(), "cleanup:worktree directory removed");
+        } else {
+            result.worktree_skip_reason = Some("already gone".to_string());
         }
 
         // Clean up the prompt file if it exists
@@ -102,15 +125,24 @@ pub fn cleanup(
             git::delete_branch(branch_name, force).context("Failed to delete local branch")?;
             result.local_branch_deleted = true;
             info!(branch = branch_name, "cleanup:local branch deleted");
+        } else {
+            result.local_branch_skip_reason = Some("kept".to_string());
         }
 
-        // 4. Delete the remote branch if requested (redundant check due to CLI conflict, but safe).
-        if delete_remote && !keep_branch {
-            match git::delete_remote_branch(branch_name) {
+        // 4. Delete the remote branch if requested. The CLI rejects this
+        // together with keeping the branch.
+        debug_assert!(!(delete_remote && keep_branch));
+        if delete_remote {
+            match git::delete_remote_branch(branch_name, context.config.network_timeout()) {
                 Ok(_) => {
                     result.remote_branch_deleted = true;
                     info!(branch = branch_name, "cleanup:remote branch deleted");
                 }
+                // Deleting a branch that was never pushed isn't a failure
+                Err(e) if format!("{:#}", e).contains("remote ref does not exist") => {
+                    info!(branch = branch_name, "cleanup:remote branch not pushed");
+                    result.remote_branch_skip_reason = Some("not pushed".to_string());
+                }
                 Err(e) => {
                     warn!(branch = branch_name, error = %e, "cleanup:failed to delete remote branch");
                     result.remote_delete_error = Some(e.to_string());
@@ -123,7 +155,9 @@ pub fn cleanup(
     if running_inside_target_tab {
         info!(
             branch = branch_name,
-            "cleanup:deferring zellij tab close because command is running inside the tab"
+            "cleanup:deferring {} {} close because command is running inside it",
+            mux.name(),
+            mux.window_term()
         );
         // Perform all filesystem and git cleanup *before* returning. The caller
         // will then schedule the asynchronous tab close.
@@ -131,34 +165,51 @@ pub fn cleanup(
     } else {
         // Not running inside the target tab, so we close the tab first
         // to release any shell locks on the directory.
-        if zellij_running && zellij::tab_exists(&context.prefix, branch_name).unwrap_or(false) {
-            zellij::close_tab(&context.prefix, branch_name)
-                .context("Failed to close zellij tab")?;
+        if !mux_running {
+            result.window_skip_reason = Some(format!("{} not running", mux.name()));
+        } else if !mux.exists(&context.prefix, branch_name).unwrap_or(false) {
+            result.window_skip_reason = Some("none open".to_string());
+        } else {
+            mux.close(&context.prefix, branch_name)
+                .with_context(|| format!("Failed to close {} {}", mux.name(), mux.window_term()))?;
             result.tmux_window_killed = true; // TODO: rename field
-            info!(branch = branch_name, "cleanup:zellij tab closed");
+            info!(
+                branch = branch_name,
+                "cleanup:{} {} closed",
+                mux.name(),
+                mux.window_term()
+            );
 
             // Poll to confirm the tab is gone before proceeding. This prevents a race
             // condition where we try to delete the directory before the shell inside
-            // the zellij tab has terminated.
-            const MAX_RETRIES: u32 = 20;
-            const RETRY_DELAY: Duration = Duration::from_millis(50);
-            let mut tab_is_gone = false;
-            for _ in 0..MAX_RETRIES {
-                if !zellij::tab_exists(&context.prefix, branch_name)? {
-                    tab_is_gone = true;
-                    break;
-                }
-                thread::sleep(RETRY_DELAY);
-            }
+            // the window/tab has terminated.
+            let started = Instant::now();
+            let mut stderr = io::stderr();
+            let progress: Option<&mut dyn Write> = if context.config.quiet {
+                None
+            } else {
+                Some(&mut stderr)
+            };
+            let tab_is_gone = wait_for_tab_close(
+                || mux.exists(&context.prefix, branch_name),
+                || started.elapsed(),
+                || thread::sleep(TAB_CLOSE_RETRY_DELAY),
+                progress,
+                &format!("{} {}", mux.name(), mux.window_term()),
+            )?;
 
             if !tab_is_gone {
                 warn!(
                     branch = branch_name,
-                    "cleanup:zellij tab did not close within retry budget"
+                    "cleanup:{} {} did not close within retry budget",
+                    mux.name(),
+                    mux.window_term()
                 );
                 eprintln!(
-                    "Warning: zellij tab for '{}' did not close in the allotted time. \
+                    "Warning: {} {} for '{}' did not close in the allotted time. \
                     Filesystem cleanup may fail.",
+                    mux.name(),
+                    mux.window_term(),
                     branch_name
                 );
             }
@@ -170,28 +221,111 @@ pub fn cleanup(
     Ok(result)
 }
 
+/// Poll `tab_exists` until the tab is gone, giving up after `TAB_CLOSE_MAX_RETRIES`.
+///
+/// Once `elapsed` passes `TAB_CLOSE_PROGRESS_AFTER`, a waiting message naming
+/// `what` is written to `progress` and rewritten in place on every poll, so a
+/// slow close doesn't look like a hang. Returns whether the tab went away.
+fn wait_for_tab_close(
+    mut tab_exists: impl FnMut() -> Result<bool>,
+    mut elapsed: impl FnMut() -> Duration,
+    mut sleep: impl FnMut(),
+    mut progress: Option<&mut dyn Write>,
+    what: &str,
+) -> Result<bool> {
+    let mut reported = false;
+    let mut tab_is_gone = false;
+    for _ in 0..TAB_CLOSE_MAX_RETRIES {
+        if !tab_exists()? {
+            tab_is_gone = true;
+            break;
+        }
+        let waited = elapsed();
+        if waited >= TAB_CLOSE_PROGRESS_AFTER
+            && let Some(out) = progress.as_mut()
+        {
+            // Progress is best effort; a broken stderr must not fail the cleanup
+            let _ = write!(
+                out,
+                "\rWaiting for {} to close... {:.1}s",
+                what,
+                waited.as_secs_f64()
+            );
+            let _ = out.flush();
+            reported = true;
+        }
+        sleep();
+    }
+
+    if reported && let Some(out) = progress {
+        let _ = writeln!(out);
+    }
+    Ok(tab_is_gone)
+}
+
+/// What to do about the main branch tab before closing the target tab
+#[derive(Debug, PartialEq)]
+enum MainTabPlan {
+    /// Switch to the existing main tab
+    Navigate,
+    /// Open a main tab first, then switch to it
+    CreateThenNavigate,
+    /// No main tab to go to; only close the target tab
+    CloseOnly,
+}
+
+fn plan_main_tab(main_exists: bool, create_if_missing: bool) -> MainTabPlan {
+    match (main_exists, create_if_missing) {
+        (true, _) => MainTabPlan::Navigate,
+        (false, true) => MainTabPlan::CreateThenNavigate,
+        (false, false) => MainTabPlan::CloseOnly,
+    }
+}
+
 /// Navigate to the main branch tab and close the target tab.
 /// Handles both cases: running inside the target tab (async) and outside (sync).
+///
+/// When `main_worktree` is given and the main tab is missing, a tab is opened
+/// there first so there is somewhere to land.
 pub fn navigate_to_main_and_close(
+    mux: &dyn Multiplexer,
     prefix: &str,
     main_branch: &str,
     target_branch: &str,
     cleanup_result: &CleanupResult,
+    main_worktree: Option<&Path>,
 ) -> Result<()> {
-    // Check if main branch tab exists
-    if !zellij::is_running()? || !zellij::tab_exists(prefix, main_branch)? {
+    let plan = if mux.is_running()? {
+        plan_main_tab(mux.exists(prefix, main_branch)?, main_worktree.is_some())
+    } else {
+        MainTabPlan::CloseOnly
+    };
+
+    if let (MainTabPlan::CreateThenNavigate, Some(path)) = (&plan, main_worktree) {
+        mux.create(prefix, main_branch, path, /* detached: */ true)
+            .with_context(|| {
+                format!("Failed to open {} for '{}'", mux.window_term(), main_branch)
+            })?;
+        info!(main = main_branch, path = %path.display(), "cleanup:created main branch tab");
+    }
+
+    if plan == MainTabPlan::CloseOnly {
         // If main tab doesn't exist, still need to close target tab if running inside it
         if cleanup_result.ran_inside_target_window {
             let delay = Duration::from_millis(WINDOW_CLOSE_DELAY_MS);
-            match zellij::schedule_tab_close(prefix, target_branch, delay) {
+            match mux.schedule_close(prefix, target_branch, delay) {
                 Ok(_) => info!(
                     branch = target_branch,
-                    "cleanup:zellij tab close scheduled"
+                    "cleanup:{} {} close scheduled",
+                    mux.name(),
+                    mux.window_term()
                 ),
                 Err(e) => warn!(
                     branch = target_branch,
                     error = %e,
-                    "cleanup:failed to schedule zellij tab close",
+                    "cleanup:failed to schedule {} {} close",
+                    mux.name(),
+                    mux.window_term()
                 ),
             }
         }
@@ -201,22 +335,7 @@ pub fn navigate_to_main_and_close(
     if cleanup_result.ran_inside_target_window {
         // Running inside target tab: schedule both navigation and close together
         let delay = Duration::from_millis(WINDOW_CLOSE_DELAY_MS);
-        let delay_secs = format!("{:.3}", delay.as_secs_f64());
-        let main_prefixed = zellij::prefixed(prefix, main_branch);
-        let target_prefixed = zellij::prefixed(prefix, target_branch);
-
-        // Use nohup for async execution since zellij has no run-shell equivalent
-        let script = format!(
-            r#"sleep {delay}; zellij action go-to-tab-name "{main}" 2>/dev/null; zellij action go-to-tab-name "{target}" 2>/dev/null && zellij action close-tab 2>/dev/null"#,
-            delay = delay_secs,
-            main = main_prefixed,
-            target = target_prefixed,
-        );
-
-        match std::process::Command::new("sh")
-            .args(["-c", &format!("nohup sh -c '{}' >/dev/null 2>&1 &", script)])
-            .spawn()
-        {
+        match mux.schedule_select_and_close(prefix, main_branch, target_branch, delay) {
             Ok(_) => info!(
                 branch = target_branch,
                 main = main_branch,
@@ -230,7 +349,7 @@ pub fn navigate_to_main_and_close(
         }
     } else {
         // Running outside target tab: synchronously navigate to main and close target
-        zellij::select_tab(prefix, main_branch)?;
+        mux.select(prefix, main_branch)?;
         info!(
             branch = target_branch,
             main = main_branch,
@@ -238,7 +357,7 @@ pub fn navigate_to_main_and_close(
         );
 
         // Close the target tab now that we've navigated away
-        match zellij::close_tab(prefix, target_branch) {
+        match mux.close(prefix, target_branch) {
             Ok(_) => info!(
                 branch = target_branch,
                 "cleanup:closed target branch tab"
@@ -253,3 +372,92 @@ pub fn navigate_to_main_and_close(
 
     Ok(())
 }
+
+/// Switch to the window for `name` without closing anything, e.g. after
+/// `merge --keep --open`. Returns false when there is no such window.
+pub fn select_target_tab(mux: &dyn Multiplexer, prefix: &str, name: &str) -> Result<bool> {
+    if !mux.is_running()? || !mux.exists(prefix, name)? {
+        return Ok(false);
+    }
+    mux.select(prefix, name)?;
+    info!(branch = name, "cleanup:selected target tab");
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MainTabPlan, plan_main_tab, wait_for_tab_close};
+    use std::cell::Cell;
+    use std::io::Write;
+    use std::time::Duration;
+
+    /// Poll with a fake clock that advances `step` per poll and a tab that
+    /// closes after `open_polls` checks. Returns whether it closed and what was
+    /// written as progress.
+    fn poll_fake(open_polls: u32, step: Duration, quiet: bool) -> (bool, String) {
+        let polls = Cell::new(0);
+        let now = Cell::new(Duration::ZERO);
+        let mut out = Vec::new();
+        let progress: Option<&mut dyn Write> = if quiet { None } else { Some(&mut out) };
+        let closed = wait_for_tab_close(
+            || {
+                polls.set(polls.get() + 1);
+                Ok(polls.get() <= open_polls)
+            },
+            || now.get(),
+            || now.set(now.get() + step),
+            progress,
+            "zellij tab",
+        )
+        .unwrap();
+        (closed, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn wait_for_tab_close_is_silent_before_threshold() {
+        // Closes after 0.8s of waiting
+        let (closed, output) = poll_fake(4, Duration::from_millis(200), false);
+        assert!(closed);
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn wait_for_tab_close_reports_progress_after_threshold() {
+        // Still open at 1.2s and 1.5s, gone at the next check
+        let (closed, output) = poll_fake(6, Duration::from_millis(300), false);
+        assert!(closed);
+        assert_eq!(
+            output,
+            "\rWaiting for zellij tab to close... 1.2s\rWaiting for zellij tab to close... 1.5s\n"
+        );
+    }
+
+    #[test]
+    fn wait_for_tab_close_gives_up_and_respects_quiet() {
+        let (closed, output) = poll_fake(u32::MAX, Duration::from_millis(100), true);
+        assert!(!closed);
+        assert_eq!(output, "");
+
+        // Twenty checks 0.1s apart: reporting starts at 1.0s, the last is at 1.9s
+        let (closed, output) = poll_fake(u32::MAX, Duration::from_millis(100), false);
+        assert!(!closed);
+        assert!(output.starts_with("\rWaiting for zellij tab to close... 1.0s\r"));
+        assert!(output.ends_with("1.9s\n"));
+    }
+
+    #[test]
+    fn plan_main_tab_navigates_to_existing_tab() {
+        assert_eq!(plan_main_tab(true, false), MainTabPlan::Navigate);
+        assert_eq!(plan_main_tab(true, true), MainTabPlan::Navigate);
+    }
+
+    #[test]
+    fn plan_main_tab_creates_missing_tab_when_enabled() {
+        assert_eq!(plan_main_tab(false, true), MainTabPlan::CreateThenNavigate);
+    }
+
+    #[test]
+    fn plan_main_tab_only_closes_when_missing_and_disabled() {
+        assert_eq!(plan_main_tab(false, false), MainTabPlan::CloseOnly);
+    }
+}
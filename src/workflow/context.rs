@@ -1,7 +1,8 @@
 use anyhow::{Context, Result, anyhow};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::{config, git, zellij};
+use crate::multiplexer::{self, Multiplexer};
+use crate::{config, git, template};
 use tracing::debug;
 
 /// Shared context for workflow operations
@@ -9,27 +10,30 @@ use tracing::debug;
 /// This struct centralizes pre-flight checks and holds essential data
 /// needed by workflow modules, reducing code duplication.
 pub struct WorkflowContext {
-    pub repo_root: PathBuf,
     pub main_worktree_root: PathBuf,
     pub main_branch: String,
     pub prefix: String,
     pub config: config::Config,
+    pub mux: Box<dyn Multiplexer>,
+    /// Worktrees as they were when the context was created
+    pub worktrees: git::WorktreeTable,
 }
 
 impl WorkflowContext {
     /// Create a new workflow context
     ///
     /// Performs the git repository check and gathers all commonly needed data.
-    /// Does NOT check if zellij is running or change the current directory - those
+    /// Does NOT check if the multiplexer is running or change the current directory - those
     /// are optional operations that can be performed via helper methods.
     pub fn new(config: config::Config) -> Result<Self> {
         if !git::is_git_repo()? {
             return Err(anyhow!("Not in a git repository"));
         }
 
-        let repo_root = git::get_repo_root()?;
-        let main_worktree_root =
-            git::get_main_worktree_root().context("Could not find the main git worktree")?;
+        let worktrees = git::WorktreeTable::load()?;
+        let main_worktree_root = worktrees
+            .main_root()
+            .context("Could not find the main git worktree")?;
 
         let main_branch = if let Some(ref branch) = config.main_branch {
             branch.clone()
@@ -38,37 +42,56 @@ impl WorkflowContext {
                 .context("Failed to determine the main branch. Specify it in .workmux.yaml")?
         };
 
-        let prefix = config.window_prefix().to_string();
+        let prefix = template::render_window_prefix(config.window_prefix(), &main_worktree_root);
+        let mux = multiplexer::resolve(config.multiplexer(), config.zellij_session());
 
         debug!(
-            repo_root = %repo_root.display(),
             main_worktree_root = %main_worktree_root.display(),
             main_branch = %main_branch,
             prefix = %prefix,
+            multiplexer = mux.name(),
             "workflow_context:created"
         );
 
         Ok(Self {
-            repo_root,
             main_worktree_root,
             main_branch,
             prefix,
             config,
+            mux,
+            worktrees,
         })
     }
 
-    /// Ensure zellij is running, returning an error if not
+    /// The main worktree, when a missing main window should be opened there
+    /// before leaving a window that is being closed
+    pub fn main_window_to_create(&self) -> Option<&Path> {
+        self.config
+            .create_main_window()
+            .then_some(self.main_worktree_root.as_path())
+    }
+
+    /// Ensure the multiplexer is running, returning an error if not
     ///
-    /// Call this at the start of workflows that require zellij.
-    pub fn ensure_zellij_running(&self) -> Result<()> {
-        if !zellij::is_running()? {
+    /// Call this at the start of workflows that require a multiplexer session.
+    pub fn ensure_multiplexer_running(&self) -> Result<()> {
+        if !self.mux.is_running()? {
+            let name = self.mux.name();
             return Err(anyhow!(
-                "zellij is not running. Please start a zellij session first."
+                "{} is not running. Please start a {} session first.",
+                name,
+                name
             ));
         }
         Ok(())
     }
 
+    /// The branch `branch` was created from, falling back to the main branch
+    /// when no base was recorded
+    pub fn branch_base(&self, branch: &str) -> String {
+        git::get_branch_base(branch).unwrap_or_else(|_| self.main_branch.clone())
+    }
+
     /// Change working directory to main worktree root
     ///
     /// This is necessary for destructive operations (merge, remove) to prevent
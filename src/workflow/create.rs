@@ -1,7 +1,7 @@
 use anyhow::{Context, Result, anyhow};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::{git, prompt::Prompt, zellij};
+use crate::{cmd, git, prompt::Prompt, template};
 use tracing::{debug, info, warn};
 
 use super::cleanup;
@@ -32,22 +32,18 @@ pub fn create(
     }
 
     // Pre-flight checks
-    context.ensure_zellij_running()?;
+    context.ensure_multiplexer_running()?;
 
-    if zellij::tab_exists(&context.prefix, branch_name)? {
+    if context.mux.exists(&context.prefix, branch_name)? {
         return Err(anyhow!(
-            "A zellij tab named '{}' already exists",
+            "A {} {} named '{}' already exists",
+            context.mux.name(),
+            context.mux.window_term(),
             branch_name
         ));
     }
 
-    if git::worktree_exists(branch_name)? {
-        return Err(anyhow!(
-            "A worktree for branch '{}' already exists. Use 'workmux open {}' to open it.",
-            branch_name,
-            branch_name
-        ));
-    }
+    ensure_no_worktree(branch_name)?;
 
     // Auto-detect: create branch if it doesn't exist
     let branch_exists = git::branch_exists(branch_name)?;
@@ -57,8 +53,18 @@ pub fn create(
             branch_name
         ));
     }
+    if options.orphan && remote_branch.is_some() {
+        return Err(anyhow!(
+            "--orphan creates a branch with no history, so it can't start from a remote branch."
+        ));
+    }
+    if branch_exists && options.orphan {
+        return Err(anyhow!(
+            "Branch '{}' already exists; --orphan only creates new branches.",
+            branch_name
+        ));
+    }
     let create_new = !branch_exists;
-    let mut track_upstream = false;
     debug!(
         branch = branch_name,
         branch_exists, create_new, "create:branch detection"
@@ -74,17 +80,19 @@ pub fn create(
                 git::list_remotes()?
             ));
         }
-        git::fetch_remote(&spec.remote)
+        git::fetch_remote(&spec.remote, context.config.network_timeout())
             .with_context(|| format!("Failed to fetch from remote '{}'", spec.remote))?;
         let remote_ref = format!("{}/{}", spec.remote, spec.branch);
-        if !git::branch_exists(&remote_ref)? {
+        if !git::remote_branch_exists(&remote_ref)? {
             return Err(anyhow!(
                 "Remote branch '{}' was not found. Double-check the name or fetch it manually.",
                 remote_ref
             ));
         }
-        track_upstream = true;
         Some(remote_ref)
+    } else if options.orphan {
+        // Nothing to branch from
+        None
     } else if create_new {
         if let Some(base) = base_branch {
             // Use the explicitly provided base branch/commit/tag
@@ -108,30 +116,39 @@ pub fn create(
         None
     };
 
-    // Determine worktree path: use config.worktree_dir or default to <project>__worktrees pattern
-    let base_dir = if let Some(ref worktree_dir) = context.config.worktree_dir {
-        let path = Path::new(worktree_dir);
-        if path.is_absolute() {
-            // Use absolute path as-is
-            path.to_path_buf()
-        } else {
-            // Relative path: resolve from repo root
-            context.repo_root.join(path)
-        }
+    // Determine worktree path: a worktree_path template wins, then config.worktree_dir,
+    // then the default <project>__worktrees pattern
+    let worktree_path = if let Some(ref path_template) = context.config.worktree_path {
+        template::render_worktree_path(
+            &template::create_template_env(),
+            path_template,
+            branch_name,
+            &context.main_worktree_root,
+        )?
     } else {
-        // Default behavior: <project_root>/../<project_name>__worktrees
-        let project_name = context
-            .repo_root
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| anyhow!("Could not determine project name"))?;
-        context
-            .repo_root
-            .parent()
-            .ok_or_else(|| anyhow!("Could not determine parent directory"))?
-            .join(format!("{}__worktrees", project_name))
+        default_worktree_path(
+            branch_name,
+            context.config.worktree_dir.as_deref(),
+            &context.main_worktree_root,
+        )?
     };
-    let worktree_path = base_dir.join(branch_name);
+
+    if let Ok(worktrees) = git::list_worktrees()
+        && let Some(enclosing) =
+            enclosing_worktree(&worktree_path, &worktrees, &context.main_worktree_root)
+    {
+        warn!(
+            branch = branch_name,
+            path = %worktree_path.display(),
+            enclosing = %enclosing.display(),
+            "create:worktree path is inside another worktree"
+        );
+        eprintln!(
+            "Warning: '{}' is inside the worktree at '{}'; the new worktree will be nested in it.",
+            worktree_path.display(),
+            enclosing.display()
+        );
+    }
 
     // Create worktree
     info!(
@@ -142,13 +159,28 @@ pub fn create(
         "create:creating worktree"
     );
 
-    git::create_worktree(
-        &worktree_path,
-        branch_name,
-        create_new,
-        base_branch_for_creation.as_deref(),
-        track_upstream,
-    )
+    match (base_branch_for_creation.as_deref(), remote_branch) {
+        (None, _) if options.orphan => {
+            git::create_worktree(&worktree_path, branch_name, git::BranchSource::Orphan)
+        }
+        (None, _) => git::create_worktree(&worktree_path, branch_name, git::BranchSource::Existing),
+        (Some(remote_ref), Some(_)) => git::create_worktree(
+            &worktree_path,
+            branch_name,
+            git::BranchSource::Remote(remote_ref),
+        ),
+        // An explicit --base may be any commit-ish (branch, tag, or SHA)
+        (Some(base), None) if base_branch.is_some() => {
+            git::create_worktree_from_base(&worktree_path, branch_name, base)
+        }
+        // The default base is always a local branch, so qualify it in case a tag
+        // shares its name
+        (Some(current_branch), None) => git::create_worktree(
+            &worktree_path,
+            branch_name,
+            git::BranchSource::NewFrom(&git::local_branch_ref(current_branch)),
+        ),
+    }
     .context("Failed to create git worktree")?;
 
     // Store the base branch in git config for future reference (used during removal checks)
@@ -182,10 +214,22 @@ pub fn create(
         branch_name,
         &worktree_path,
         &context.config,
+        context.mux.as_ref(),
+        &context.prefix,
         &options_with_prompt,
         agent,
     )?;
     result.base_branch = base_branch_for_creation.clone();
+
+    if options_with_prompt.run_hooks {
+        run_after_add_hooks(
+            context,
+            branch_name,
+            &worktree_path,
+            options_with_prompt.prompt_file_path.as_deref(),
+        );
+    }
+
     info!(
         branch = branch_name,
         path = %result.worktree_path.display(),
@@ -195,6 +239,112 @@ pub fn create(
     Ok(result)
 }
 
+/// Fail with a pointer to `workmux open` if `branch_name` already has a worktree
+pub fn ensure_no_worktree(branch_name: &str) -> Result<()> {
+    no_worktree_guard(branch_name, git::worktree_exists(branch_name)?)
+}
+
+fn no_worktree_guard(branch_name: &str, worktree_exists: bool) -> Result<()> {
+    if worktree_exists {
+        return Err(anyhow!(
+            "A worktree for branch '{}' already exists. Use 'workmux open {}' to open it.",
+            branch_name,
+            branch_name
+        ));
+    }
+    Ok(())
+}
+
+/// Run the configured `after_add` commands in the new worktree, with the branch,
+/// worktree, and prompt file exported as `WORKMUX_*` variables.
+///
+/// The worktree is already set up, so a failing command is reported and the
+/// remaining commands still run.
+fn run_after_add_hooks(
+    context: &WorkflowContext,
+    branch_name: &str,
+    worktree_path: &Path,
+    prompt_file: Option<&Path>,
+) {
+    let Some(commands) = context.config.after_add.as_deref() else {
+        return;
+    };
+
+    let worktree = worktree_path.to_string_lossy();
+    let prompt_file = prompt_file
+        .map(|path| path.to_string_lossy())
+        .unwrap_or_default();
+    let envs = [
+        ("WORKMUX_BRANCH", branch_name),
+        ("WORKMUX_WORKTREE", worktree.as_ref()),
+        ("WORKMUX_PROMPT_FILE", prompt_file.as_ref()),
+    ];
+
+    let total = commands.len();
+    for (idx, command) in commands.iter().enumerate() {
+        info!(branch = branch_name, step = idx + 1, total, command = %command, "create:after-add hook start");
+        if let Err(e) = cmd::shell_command_with_env(command, worktree_path, &envs) {
+            warn!(branch = branch_name, command = %command, error = %e, "create:after-add hook failed");
+            eprintln!("Warning: after-add command failed: {}", e);
+        }
+    }
+}
+
+/// Worktree location when no `worktree_path` template is configured:
+/// `<worktree_dir>/<branch>`, or `<project>__worktrees/<branch>` next to the repo.
+///
+/// Always anchored at the main worktree, so running `add` from inside another
+/// worktree doesn't nest the new one there.
+fn default_worktree_path(
+    branch_name: &str,
+    worktree_dir: Option<&str>,
+    main_root: &Path,
+) -> Result<PathBuf> {
+    let base_dir = if let Some(worktree_dir) = worktree_dir {
+        resolve_worktree_dir(worktree_dir, main_root)
+    } else {
+        // Default behavior: <project_root>/../<project_name>__worktrees
+        let project_name = main_root
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow!("Could not determine project name"))?;
+        main_root
+            .parent()
+            .ok_or_else(|| anyhow!("Could not determine parent directory"))?
+            .join(format!("{}__worktrees", project_name))
+    };
+    Ok(base_dir.join(branch_name))
+}
+
+/// The configured `worktree_dir`, resolved from the main worktree when relative
+pub(super) fn resolve_worktree_dir(worktree_dir: &str, main_root: &Path) -> PathBuf {
+    let path = Path::new(worktree_dir);
+    if path.is_absolute() {
+        // Use absolute path as-is
+        path.to_path_buf()
+    } else {
+        // Relative path: resolve from the main worktree
+        main_root.join(path)
+    }
+}
+
+/// The worktree other than the main one that `path` would be nested inside, if any.
+/// Symlinks are resolved on both sides, since `path` comes from config while git
+/// reports canonical worktree paths.
+fn enclosing_worktree<'a>(
+    path: &Path,
+    worktrees: &'a [(PathBuf, String)],
+    main_root: &Path,
+) -> Option<&'a Path> {
+    let path = git::resolve_symlinks(path);
+    let main_root = git::resolve_symlinks(main_root);
+    worktrees
+        .iter()
+        .map(|(worktree, _)| worktree.as_path())
+        .filter(|worktree| git::resolve_symlinks(worktree) != main_root)
+        .find(|worktree| path.starts_with(git::resolve_symlinks(worktree)))
+}
+
 /// Create a new worktree and move uncommitted changes from the current worktree into it.
 pub fn create_with_changes(
     branch_name: &str,
@@ -282,12 +432,14 @@ pub fn create_with_changes(
                 "Rollback failed: could not clean up the new worktree. Please do so manually.",
             )?;
 
-            // Handle tmux window navigation/closing based on whether we're inside the target window
+            // Handle window navigation/closing based on whether we're inside the target window
             cleanup::navigate_to_main_and_close(
+                context.mux.as_ref(),
                 &context.prefix,
                 &context.main_branch,
                 branch_name,
                 &cleanup_result,
+                context.main_window_to_create(),
             )?;
 
             Err(anyhow!(
@@ -299,3 +451,60 @@ pub fn create_with_changes(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{default_worktree_path, enclosing_worktree, no_worktree_guard};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn no_worktree_guard_suggests_open_for_existing_worktree() {
+        assert!(no_worktree_guard("feature", false).is_ok());
+
+        let err = no_worktree_guard("feature", true).unwrap_err().to_string();
+        assert!(err.contains("A worktree for branch 'feature' already exists"));
+        assert!(err.contains("workmux open feature"));
+    }
+
+    #[test]
+    fn default_worktree_path_is_sibling_of_main_worktree() {
+        let path = default_worktree_path("feature", None, Path::new("/code/myapp")).unwrap();
+        assert_eq!(path, PathBuf::from("/code/myapp__worktrees/feature"));
+    }
+
+    #[test]
+    fn default_worktree_path_resolves_relative_dir_from_main_worktree() {
+        let main_root = Path::new("/code/myapp");
+        let relative = default_worktree_path("feature", Some(".worktrees"), main_root).unwrap();
+        assert_eq!(relative, PathBuf::from("/code/myapp/.worktrees/feature"));
+
+        let absolute = default_worktree_path("feature", Some("/tmp/wt"), main_root).unwrap();
+        assert_eq!(absolute, PathBuf::from("/tmp/wt/feature"));
+    }
+
+    #[test]
+    fn enclosing_worktree_ignores_main_worktree() {
+        let main_root = Path::new("/code/myapp");
+        let worktrees = vec![
+            (PathBuf::from("/code/myapp"), "main".to_string()),
+            (
+                PathBuf::from("/code/myapp__worktrees/foo"),
+                "foo".to_string(),
+            ),
+        ];
+
+        let nested = Path::new("/code/myapp__worktrees/foo/bar");
+        assert_eq!(
+            enclosing_worktree(nested, &worktrees, main_root),
+            Some(Path::new("/code/myapp__worktrees/foo"))
+        );
+
+        let configured_inside_main = Path::new("/code/myapp/.worktrees/bar");
+        assert_eq!(
+            enclosing_worktree(configured_inside_main, &worktrees, main_root),
+            None
+        );
+        let sibling = Path::new("/code/myapp__worktrees/bar");
+        assert_eq!(enclosing_worktree(sibling, &worktrees, main_root), None);
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result, anyhow};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::git;
+
+use super::types::FetchResult;
+
+/// Fetch and fast-forward one worktree, or every worktree when no branch is given.
+///
+/// Failures in individual worktrees are recorded in the results rather than
+/// aborting the whole run. Each fetch is stopped after `timeout`.
+pub fn fetch(branch_name: Option<&str>, timeout: Duration) -> Result<Vec<FetchResult>> {
+    if !git::is_git_repo()? {
+        return Err(anyhow!("Not in a git repository"));
+    }
+
+    let targets = match branch_name {
+        Some(branch) => {
+            let path = git::get_worktree_path(branch)
+                .with_context(|| format!("No worktree found for branch '{}'", branch))?;
+            vec![(path, branch.to_string())]
+        }
+        None => git::list_worktrees()?
+            .into_iter()
+            // Detached worktrees have no branch to fast-forward.
+            .filter(|(_, branch)| branch != "(detached)")
+            .collect(),
+    };
+
+    let results = targets
+        .into_iter()
+        .map(|(path, branch)| {
+            info!(branch = %branch, path = %path.display(), "fetch:worktree");
+            let outcome = git::fetch_in_worktree(&path, timeout)
+                .and_then(|_| git::ff_pull_in_worktree(&path));
+            if let Err(e) = &outcome {
+                warn!(branch = %branch, error = %e, "fetch:worktree failed");
+            }
+            FetchResult {
+                branch,
+                path,
+                outcome,
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cmd::{self, Cmd};
+use crate::config::{HookCommand, HookCondition};
+use tracing::{debug, info};
+
+/// Where hook commands write their output
+pub enum HookOutput<'a> {
+    /// Straight to the terminal
+    Inherit,
+    /// Into a buffer, so concurrent jobs can print their output in one piece
+    Buffer(&'a mut String),
+}
+
+/// Outcome of one hook command
+#[derive(Debug)]
+pub struct HookRun {
+    pub command: String,
+    pub duration: Duration,
+    /// None when the hook was skipped because its `onlyif` condition didn't hold
+    pub result: Option<Result<()>>,
+}
+
+/// Run `commands` in order with `workdir` as the working directory, skipping
+/// those whose condition doesn't hold and stopping at the first failure. A hook
+/// running longer than `timeout` is stopped and counts as failed. A timing
+/// report is written to the debug log and, when `verbose`, printed once the
+/// hooks are done. Returns how many hooks ran.
+pub fn run_hooks(
+    kind: &str,
+    commands: &[HookCommand],
+    workdir: &Path,
+    mut output: HookOutput,
+    timeout: Option<Duration>,
+    verbose: bool,
+) -> Result<usize> {
+    let runs = run_timed(kind, commands, workdir, &mut output, timeout);
+
+    let report = format_report(&runs);
+    debug!(kind, "hooks:report\n{}", report);
+    if verbose {
+        let report = format!("{} hook timings:\n{}\n", kind, report);
+        match output {
+            HookOutput::Inherit => print!("{}", report),
+            HookOutput::Buffer(buffer) => buffer.push_str(&report),
+        }
+    }
+
+    let hooks_run = runs.iter().filter(|run| run.result.is_some()).count();
+    match runs.into_iter().find_map(|run| match run.result {
+        Some(Err(e)) => Some((run.command, e)),
+        _ => None,
+    }) {
+        Some((command, e)) if e.is::<cmd::CommandTimeout>() => Err(e).with_context(|| {
+            format!(
+                "{} command '{}' hung and was stopped (hook_timeout_secs)",
+                kind, command
+            )
+        }),
+        Some((command, e)) => {
+            Err(e).with_context(|| format!("Failed to run {} command: '{}'", kind, command))
+        }
+        None => Ok(hooks_run),
+    }
+}
+
+/// Run and time each command, stopping after the first failure
+fn run_timed(
+    kind: &str,
+    commands: &[HookCommand],
+    workdir: &Path,
+    output: &mut HookOutput,
+    timeout: Option<Duration>,
+) -> Vec<HookRun> {
+    let total = commands.len();
+    let mut runs = Vec::with_capacity(total);
+    for (idx, hook) in commands.iter().enumerate() {
+        let command = hook.command();
+        let start = Instant::now();
+        if let Some(condition) = hook.condition()
+            && !condition_holds(condition, workdir)
+        {
+            info!(command = %command, ?condition, "Skipping {} hook {}/{}: condition not met", kind, idx + 1, total);
+            runs.push(HookRun {
+                command: command.to_string(),
+                duration: start.elapsed(),
+                result: None,
+            });
+            continue;
+        }
+
+        info!(command = %command, "Running {} hook {}/{}", kind, idx + 1, total);
+        let result = match output {
+            HookOutput::Inherit => cmd::shell_command_with_timeout(command, workdir, None, timeout),
+            HookOutput::Buffer(buffer) => {
+                cmd::shell_command_with_timeout(command, workdir, Some(buffer), timeout)
+            }
+        };
+        let duration = start.elapsed();
+        info!(command = %command, ok = result.is_ok(), ?duration, "hooks:hook complete");
+
+        let failed = result.is_err();
+        runs.push(HookRun {
+            command: command.to_string(),
+            duration,
+            result: Some(result),
+        });
+        if failed {
+            break;
+        }
+    }
+    runs
+}
+
+/// Whether a hook's `onlyif` condition holds in `workdir`
+fn condition_holds(condition: &HookCondition, workdir: &Path) -> bool {
+    match condition {
+        HookCondition::Exists { exists } => workdir.join(exists).exists(),
+        HookCondition::Shell(predicate) => Cmd::new("sh")
+            .args(&["-c", predicate])
+            .workdir(workdir)
+            .run_as_check()
+            .unwrap_or(false),
+    }
+}
+
+/// Render runs as a table of command, status and duration
+fn format_report(runs: &[HookRun]) -> String {
+    let width = runs
+        .iter()
+        .map(|run| run.command.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("COMMAND".len());
+    let mut lines = vec![format!("{:<width$}  {:<7}  DURATION", "COMMAND", "STATUS")];
+    for run in runs {
+        let status = match run.result {
+            Some(Ok(())) => "ok",
+            Some(Err(_)) => "failed",
+            None => "skipped",
+        };
+        lines.push(format!(
+            "{:<width$}  {:<7}  {:.2?}",
+            run.command, status, run.duration
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks(yaml: &str) -> Vec<HookCommand> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn records_duration_of_each_hook_and_stops_at_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = hooks("[\"true\", sleep 0.05, exit 3, touch never-run]");
+
+        let mut buffer = String::new();
+        let runs = run_timed(
+            "post-create",
+            &commands,
+            dir.path(),
+            &mut HookOutput::Buffer(&mut buffer),
+            None,
+        );
+
+        assert_eq!(runs.len(), 3);
+        assert!(matches!(runs[0].result, Some(Ok(()))));
+        assert!(runs[1].duration >= Duration::from_millis(50));
+        assert!(matches!(runs[2].result, Some(Err(_))));
+        assert!(!dir.path().join("never-run").exists());
+        assert!(buffer.is_empty());
+
+        let report = format_report(&runs);
+        assert!(report.starts_with("COMMAND"));
+        assert!(report.lines().nth(2).unwrap().starts_with("sleep 0.05  ok"));
+        assert!(report.lines().nth(3).unwrap().contains("failed"));
+    }
+
+    #[test]
+    fn conditional_hooks_are_skipped_when_their_condition_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        let commands = hooks(
+            "
+- touch plain
+- run: touch has-package
+  onlyif:
+    exists: package.json
+- run: touch has-cargo
+  onlyif:
+    exists: Cargo.toml
+- run: touch predicate-true
+  onlyif: test -f plain
+- run: touch predicate-false
+  onlyif: exit 1
+",
+        );
+
+        let hooks_run = run_hooks(
+            "post-create",
+            &commands,
+            dir.path(),
+            HookOutput::Inherit,
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(hooks_run, 3);
+        for file in ["plain", "has-package", "predicate-true"] {
+            assert!(dir.path().join(file).exists(), "{} should exist", file);
+        }
+        for file in ["has-cargo", "predicate-false"] {
+            assert!(!dir.path().join(file).exists(), "{} should not exist", file);
+        }
+
+        let runs = run_timed(
+            "post-create",
+            &commands[2..3],
+            dir.path(),
+            &mut HookOutput::Inherit,
+            None,
+        );
+        assert!(runs[0].result.is_none());
+        assert!(format_report(&runs).contains("skipped"));
+    }
+
+    #[test]
+    fn hung_hook_times_out_and_stops_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = hooks("[sleep 5, touch never-run]");
+
+        let err = run_hooks(
+            "post-create",
+            &commands,
+            dir.path(),
+            HookOutput::Inherit,
+            Some(Duration::from_millis(200)),
+            false,
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("'sleep 5' hung"));
+        assert!(err.is::<cmd::CommandTimeout>());
+        assert!(!dir.path().join("never-run").exists());
+    }
+}
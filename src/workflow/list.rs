@@ -1,31 +1,54 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
+use std::path::{Path, PathBuf};
+use tracing::warn;
 
-use crate::{config, git, zellij};
+use crate::multiplexer::{self, prefixed};
+use crate::{config, git, github, template};
 
+use super::create::resolve_worktree_dir;
 use super::types::WorktreeInfo;
 
-/// List all worktrees with their status
-pub fn list(config: &config::Config) -> Result<Vec<WorktreeInfo>> {
+/// List all worktrees with their status.
+///
+/// With `include_pr_status`, PRs for all branches are fetched in one `gh` call.
+/// Worktrees matching `list_exclude` are left out unless `include_excluded` is set.
+/// With `include_last_commit`, each worktree's latest commit is read as well.
+pub fn list(
+    config: &config::Config,
+    include_pr_status: bool,
+    include_excluded: bool,
+    include_last_commit: bool,
+) -> Result<Vec<WorktreeInfo>> {
     if !git::is_git_repo()? {
         return Err(anyhow!("Not in a git repository"));
     }
 
-    let worktrees_data = git::list_worktrees()?;
+    let table = git::WorktreeTable::load()?;
+    let mut worktrees_data = table.all();
 
     if worktrees_data.is_empty() {
         return Ok(Vec::new());
     }
 
-    // Check zellij status and get all tabs once to avoid repeated process calls
-    let zellij_tabs: std::collections::HashSet<String> = if zellij::is_running().unwrap_or(false) {
-        zellij::get_all_tab_names().unwrap_or_default()
+    // Get the main branch for unmerged checks
+    let main_branch = git::get_default_branch().ok();
+
+    if !include_excluded {
+        let main_root = table.main_root().ok();
+        worktrees_data =
+            exclude_worktrees(worktrees_data, config.list_exclude(), |path, branch| {
+                main_root.as_deref() == Some(path) || main_branch.as_deref() == Some(branch)
+            })?;
+    }
+
+    // Check multiplexer status and get all windows once to avoid repeated process calls
+    let mux = multiplexer::resolve(config.multiplexer(), config.zellij_session());
+    let zellij_tabs: std::collections::HashSet<String> = if mux.is_running().unwrap_or(false) {
+        mux.list_names().unwrap_or_default()
     } else {
         std::collections::HashSet::new()
     };
 
-    // Get the main branch for unmerged checks
-    let main_branch = git::get_default_branch().ok();
-
     // Get all unmerged branches in one go for efficiency
     // Prefer checking against remote tracking branch for more accurate results
     let unmerged_branches = main_branch
@@ -34,11 +57,37 @@ pub fn list(config: &config::Config) -> Result<Vec<WorktreeInfo>> {
         .and_then(|base| git::get_unmerged_branches(&base).ok())
         .unwrap_or_default(); // Use an empty set on failure
 
-    let prefix = config.window_prefix();
+    let mut prs = if include_pr_status {
+        let branches: Vec<String> = worktrees_data.iter().map(|(_, b)| b.clone()).collect();
+        let pr_numbers = git::get_branch_prs().unwrap_or_default();
+        match github::get_prs_for_branches(&branches, &pr_numbers, config.network_timeout()) {
+            Ok(prs) => prs,
+            Err(e) => {
+                warn!(error = %e, "list:failed to fetch PR status");
+                eprintln!("Warning: could not fetch PR status: {:#}", e);
+                Default::default()
+            }
+        }
+    } else {
+        Default::default()
+    };
+
+    let main_root = table.main_root().ok();
+    let prefix = match &main_root {
+        Some(main_root) => template::render_window_prefix(config.window_prefix(), main_root),
+        None => config.window_prefix().to_string(),
+    };
+
+    // Only worktrees other than the main one are expected under worktree_dir
+    let worktree_dir = config
+        .worktree_dir
+        .as_deref()
+        .zip(main_root.as_deref())
+        .map(|(dir, root)| resolve_worktree_dir(dir, root));
     let worktrees: Vec<WorktreeInfo> = worktrees_data
         .into_iter()
         .map(|(path, branch)| {
-            let prefixed_branch_name = zellij::prefixed(prefix, &branch);
+            let prefixed_branch_name = prefixed(&prefix, &branch);
             let has_tmux = zellij_tabs.contains(&prefixed_branch_name); // TODO: rename field to has_zellij
 
             // Check for unmerged commits, but only if this isn't the main branch
@@ -52,14 +101,124 @@ pub fn list(config: &config::Config) -> Result<Vec<WorktreeInfo>> {
                 false
             };
 
+            let outside_worktree_dir = worktree_dir
+                .as_deref()
+                .filter(|_| main_root.as_deref() != Some(path.as_path()))
+                .filter(|_| branch != "(detached)")
+                .filter(|dir| !is_under_dir(&path, dir))
+                .map(|dir| dir.join(&branch));
+
+            // A branch without commits yet (e.g. from `add --orphan`) has nothing to show
+            let last_commit = if include_last_commit {
+                git::last_commit_info(&path).ok()
+            } else {
+                None
+            };
+
+            // A detached worktree is labelled with the commit it has checked out
+            let pr = prs.remove(&branch);
+            let branch = table
+                .detached_head(&path)
+                .map(|head| format!("(detached at {})", &head[..head.len().min(7)]))
+                .unwrap_or(branch);
+
             WorktreeInfo {
+                pr,
+                outside_worktree_dir,
                 branch,
                 path,
                 has_tmux,
                 has_unmerged,
+                last_commit,
             }
         })
         .collect();
 
     Ok(worktrees)
 }
+
+/// Drop worktrees whose branch matches one of the `patterns` globs, except
+/// those `is_main` says belong to the main branch, which are always listed
+fn exclude_worktrees(
+    worktrees: Vec<(PathBuf, String)>,
+    patterns: &[String],
+    is_main: impl Fn(&Path, &str) -> bool,
+) -> Result<Vec<(PathBuf, String)>> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern)
+                .with_context(|| format!("Invalid list_exclude pattern '{}'", pattern))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(worktrees
+        .into_iter()
+        .filter(|(path, branch)| {
+            is_main(path, branch) || !patterns.iter().any(|pattern| pattern.matches(branch))
+        })
+        .collect())
+}
+
+/// Whether `path` is inside `dir`, resolving symlinks on both sides
+fn is_under_dir(path: &Path, dir: &Path) -> bool {
+    git::resolve_symlinks(path).starts_with(git::resolve_symlinks(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exclude_worktrees, is_under_dir};
+    use std::path::{Path, PathBuf};
+
+    fn branches(worktrees: &[(PathBuf, String)]) -> Vec<&str> {
+        worktrees.iter().map(|(_, b)| b.as_str()).collect()
+    }
+
+    #[test]
+    fn exclude_worktrees_hides_matching_branches_but_not_main() {
+        let worktrees: Vec<(PathBuf, String)> = [
+            ("/repo", "main"),
+            ("/wt/docs", "docs"),
+            ("/wt/scratch-a", "scratch/a"),
+            ("/wt/feature", "feature"),
+            ("/wt/docs-v2", "docs-v2"),
+        ]
+        .into_iter()
+        .map(|(p, b)| (PathBuf::from(p), b.to_string()))
+        .collect();
+        let patterns = [
+            "docs".to_string(),
+            "scratch/*".to_string(),
+            "m*".to_string(),
+        ];
+        let is_main = |path: &Path, _: &str| path == Path::new("/repo");
+
+        let shown = exclude_worktrees(worktrees.clone(), &patterns, is_main).unwrap();
+        assert_eq!(branches(&shown), vec!["main", "feature", "docs-v2"]);
+
+        // Nothing configured, nothing hidden
+        let shown = exclude_worktrees(worktrees.clone(), &[], is_main).unwrap();
+        assert_eq!(shown, worktrees);
+
+        let err = exclude_worktrees(worktrees, &["[".to_string()], is_main).unwrap_err();
+        assert!(err.to_string().contains("Invalid list_exclude pattern '['"));
+    }
+
+    #[test]
+    fn is_under_dir_detects_worktrees_outside_configured_dir() {
+        let dir = Path::new("/nonexistent/repo/../worktrees");
+        let cases = [
+            ("/nonexistent/worktrees/feature", true),
+            ("/nonexistent/worktrees/nested/feature", true),
+            ("/nonexistent/worktrees/./fix", true),
+            ("/nonexistent/repo/../worktrees/fix", true),
+            ("/nonexistent/repo__worktrees/feature", false),
+            ("/nonexistent/worktrees-old/feature", false),
+            ("/nonexistent/worktrees/../elsewhere", false),
+            ("/nonexistent/repo", false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(is_under_dir(Path::new(path), dir), expected, "{}", path);
+        }
+    }
+}
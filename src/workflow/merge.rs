@@ -1,27 +1,55 @@
 use anyhow::{Context, Result, anyhow};
+use std::io::{self, IsTerminal};
+use std::path::Path;
 
-use crate::git;
-use tracing::{debug, info};
+use crate::{cmd, git, prompt};
+use tracing::{debug, info, warn};
 
 use super::cleanup;
 use super::context::WorkflowContext;
-use super::types::MergeResult;
+use super::types::{MergeOptions, MergeResult};
 
-/// Merge a branch into the main branch and clean up
+/// Merge a branch into the target branch (the main branch by default) and clean up
 pub fn merge(
     branch_name: &str,
-    ignore_uncommitted: bool,
-    delete_remote: bool,
-    rebase: bool,
-    squash: bool,
-    keep: bool,
+    target_branch: Option<&str>,
+    options: &MergeOptions,
     context: &WorkflowContext,
 ) -> Result<MergeResult> {
+    let MergeOptions {
+        ignore_uncommitted,
+        delete_remote,
+        rebase,
+        squash,
+        keep,
+        preview,
+        stash,
+        open,
+        force_dirty_target,
+        create_target,
+    } = *options;
+
     info!(
         branch = branch_name,
-        ignore_uncommitted, delete_remote, rebase, squash, keep, "merge:start"
+        ignore_uncommitted,
+        delete_remote,
+        rebase,
+        squash,
+        keep,
+        preview,
+        stash,
+        open,
+        force_dirty_target,
+        create_target,
+        "merge:start"
     );
 
+    // Reject a self-merge before anything touches the worktree (stash, commit editor)
+    let target_branch = target_branch.unwrap_or(&context.main_branch);
+    if branch_name == target_branch {
+        return Err(anyhow!("Cannot merge '{}' into itself.", branch_name));
+    }
+
     // Change CWD to main worktree to prevent errors if the command is run from within
     // the worktree that is about to be deleted.
     context.chdir_to_main_worktree()?;
@@ -29,7 +57,9 @@ pub fn merge(
     let branch_to_merge = branch_name;
 
     // Get worktree path for the branch to be merged
-    let worktree_path = git::get_worktree_path(branch_to_merge)
+    let worktree_path = context
+        .worktrees
+        .path_for(branch_to_merge)
         .with_context(|| format!("No worktree found for branch '{}'", branch_to_merge))?;
     debug!(
         branch = branch_to_merge,
@@ -37,69 +67,197 @@ pub fn merge(
         "merge:worktree resolved"
     );
 
+    let ignored = context.config.ignore_dirty_paths();
+    let stash_ref = if stash && git::has_unstaged_changes(&worktree_path, ignored)? {
+        let stash_ref = git::stash_push_in_worktree(
+            &worktree_path,
+            &format!("workmux merge {}", branch_to_merge),
+        )?;
+        info!(branch = branch_to_merge, stash = %stash_ref, "merge:stashed unstaged changes");
+        Some(stash_ref)
+    } else {
+        None
+    };
+
+    let result = merge_worktree(
+        branch_to_merge,
+        &worktree_path,
+        target_branch,
+        options,
+        context,
+    );
+    let Some(stash_ref) = stash_ref else {
+        return result;
+    };
+
+    let mut result = result.with_context(|| {
+        format!(
+            "Unstaged changes of '{}' are still stashed as {}. Restore them with 'git stash apply {}' in {}",
+            branch_to_merge,
+            stash_ref,
+            stash_ref,
+            worktree_path.display()
+        )
+    })?;
+
+    // A kept worktree gets its changes back; otherwise the worktree is gone and the
+    // stash is left for the user to apply elsewhere
+    if keep && git::stash_pop_in_worktree(&worktree_path, &stash_ref).is_ok() {
+        info!(branch = branch_to_merge, "merge:restored stashed changes");
+    } else {
+        warn!(branch = branch_to_merge, stash = %stash_ref, "merge:leaving stash in place");
+        result.stash = Some(stash_ref);
+    }
+    Ok(result)
+}
+
+/// Commit or reject the source worktree's changes, merge it, and clean up
+fn merge_worktree(
+    branch_to_merge: &str,
+    worktree_path: &Path,
+    target_branch: &str,
+    options: &MergeOptions,
+    context: &WorkflowContext,
+) -> Result<MergeResult> {
+    let MergeOptions {
+        ignore_uncommitted,
+        delete_remote,
+        rebase,
+        squash,
+        keep,
+        preview,
+        open,
+        force_dirty_target,
+        create_target,
+        ..
+    } = *options;
+
     // Handle changes in the source worktree
-    if git::has_unstaged_changes(&worktree_path)? && !ignore_uncommitted {
+    let ignored = context.config.ignore_dirty_paths();
+    if git::has_unstaged_changes(worktree_path, ignored)? && !ignore_uncommitted {
         return Err(anyhow!(
             "Worktree for '{}' has unstaged changes. Please stage or stash them, or use --ignore-uncommitted.",
             branch_to_merge
         ));
     }
 
-    let had_staged_changes = git::has_staged_changes(&worktree_path)?;
+    let had_staged_changes = git::has_staged_changes(worktree_path)?;
     if had_staged_changes && !ignore_uncommitted {
         // Commit using git's editor (respects $EDITOR or git config)
         info!(path = %worktree_path.display(), "merge:committing staged changes");
-        git::commit_with_editor(&worktree_path).context("Failed to commit staged changes")?;
+        git::commit_with_editor(worktree_path).context("Failed to commit staged changes")?;
     }
 
-    if branch_to_merge == context.main_branch {
-        return Err(anyhow!("Cannot merge the main branch into itself."));
-    }
+    // Merge where the target is already checked out; otherwise switch the main
+    // worktree to it
+    let target_worktree = context
+        .worktrees
+        .path_for(target_branch)
+        .unwrap_or_else(|_| context.main_worktree_root.clone());
     debug!(
         branch = branch_to_merge,
-        main = &context.main_branch,
-        "merge:main branch resolved"
+        target = target_branch,
+        target_worktree = %target_worktree.display(),
+        "merge:target resolved"
     );
 
-    // Safety check: Abort if the main worktree has uncommitted changes
-    if git::has_uncommitted_changes(&context.main_worktree_root)? {
+    // Safety check: Abort if the target worktree has uncommitted changes, unless
+    // forced, in which case git itself still refuses to overwrite them
+    let target_dirty = git::has_uncommitted_changes(&target_worktree, ignored)?;
+    if target_dirty {
+        let which = if target_worktree == context.main_worktree_root {
+            "Main worktree".to_string()
+        } else {
+            format!("Target worktree at '{}'", target_worktree.display())
+        };
+        if !force_dirty_target {
+            return Err(anyhow!(
+                "{} has uncommitted changes. Please commit or stash them before merging, or use --force-dirty-target.",
+                which
+            ));
+        }
+        warn!(target_worktree = %target_worktree.display(), "merge:target worktree dirty, forced");
+        eprintln!(
+            "Warning: {} has uncommitted changes; merging anyway (--force-dirty-target).",
+            which
+        );
+    }
+
+    // Explicitly switch to the target branch to ensure correct merge target.
+    // A target that doesn't exist yet, locally or on a remote git can track, is
+    // only created from the target worktree's HEAD when asked for, so a typo
+    // doesn't merge into a brand-new branch.
+    let target_missing = !git::branch_exists(target_branch)? && !on_any_remote(target_branch)?;
+    if target_missing && !create_target {
         return Err(anyhow!(
-            "Main worktree has uncommitted changes. Please commit or stash them before merging."
+            "Target branch '{}' does not exist locally or on any remote. Check the name, or pass --create-target to create it.",
+            target_branch
         ));
     }
+    if target_missing {
+        eprintln!(
+            "Target branch '{}' does not exist; creating it from '{}'.",
+            target_branch,
+            git::get_current_branch_in_worktree(&target_worktree)
+                .unwrap_or_else(|_| "HEAD".to_string())
+        );
+    }
+    git::switch_branch_in_worktree(&target_worktree, target_branch, target_missing)?;
 
-    // Explicitly switch to the main branch to ensure correct merge target
-    git::switch_branch_in_worktree(&context.main_worktree_root, &context.main_branch)?;
-
-    // Helper closure to generate the error message for merge conflicts
-    let conflict_err = |branch: &str| -> anyhow::Error {
-        anyhow!(
-            "Merge failed due to conflicts. Main worktree kept clean.\n\n\
+    // Helper closure to generate the error message for merge conflicts, keeping
+    // git's own output as the cause
+    let conflict_err = |e: anyhow::Error, branch: &str| -> anyhow::Error {
+        e.context(format!(
+            "Merge failed due to conflicts. Target worktree kept clean.\n\n\
             To resolve, update your branch in worktree at {}:\n\
               git rebase {}  (recommended)\n\
             Or:\n\
               git merge {}\n\n\
             After resolving conflicts, retry: workmux merge {}",
             worktree_path.display(),
-            &context.main_branch,
-            &context.main_branch,
+            target_branch,
+            target_branch,
             branch
-        )
+        ))
     };
 
-    if rebase {
+    // A branch the target already contains has nothing left to merge; it's only
+    // cleaned up (unless kept)
+    let already_merged = git::is_ancestor(&target_worktree, branch_to_merge, target_branch)?;
+    if already_merged {
+        info!(
+            branch = branch_to_merge,
+            target = target_branch,
+            "merge:already merged, skipping merge"
+        );
+    } else if rebase {
+        if preview
+            && !confirm_rebase_preview(
+                worktree_path,
+                branch_to_merge,
+                target_branch,
+                !context.config.confirm(),
+            )?
+        {
+            info!(
+                branch = branch_to_merge,
+                "merge:rebase declined after preview"
+            );
+            return Err(anyhow!("Rebase cancelled. Nothing was merged."));
+        }
+
         // Rebase the feature branch on top of main inside its own worktree.
         // This is where conflicts will be detected.
-        println!(
+        eprintln!(
             "Rebasing '{}' onto '{}'...",
-            &branch_to_merge, &context.main_branch
+            &branch_to_merge, target_branch
         );
         info!(
             branch = branch_to_merge,
-            base = &context.main_branch,
+            base = target_branch,
             "merge:rebase start"
         );
-        git::rebase_branch_onto_base(&worktree_path, &context.main_branch).with_context(|| {
+        git::rebase_branch_onto_base(worktree_path, target_branch).with_context(|| {
             format!(
                 "Rebase failed, likely due to conflicts.\n\n\
                 Please resolve them manually inside the worktree at '{}'.\n\
@@ -108,43 +266,76 @@ pub fn merge(
             )
         })?;
 
-        // After a successful rebase, merge into main. This will be a fast-forward.
-        git::merge_in_worktree(&context.main_worktree_root, branch_to_merge)
+        // After a successful rebase, merge into the target. This will be a fast-forward.
+        git::merge_in_worktree(&target_worktree, branch_to_merge)
             .context("Failed to merge rebased branch. This should have been a fast-forward.")?;
         info!(branch = branch_to_merge, "merge:fast-forward complete");
     } else if squash {
         // Perform the squash merge. This stages all changes from the feature branch but does not commit.
-        if let Err(e) = git::merge_squash_in_worktree(&context.main_worktree_root, branch_to_merge)
-        {
-            info!(branch = branch_to_merge, error = %e, "merge:squash merge failed, resetting main worktree");
+        if let Err(e) = git::merge_squash_in_worktree(&target_worktree, branch_to_merge) {
+            // A hard reset would also throw away the changes --force-dirty-target kept
+            if target_dirty {
+                info!(branch = branch_to_merge, error = %e, "merge:squash merge failed in dirty target worktree");
+                return Err(e.context(format!(
+                    "Squash merge of '{}' failed. The target worktree at '{}' had uncommitted changes, so it was not reset; resolve or discard the squashed changes there manually.",
+                    branch_to_merge,
+                    target_worktree.display()
+                )));
+            }
+            info!(branch = branch_to_merge, error = %e, "merge:squash merge failed, resetting target worktree");
             // Best effort to reset; ignore failure as the user message is the priority.
-            let _ = git::reset_hard(&context.main_worktree_root);
-            return Err(conflict_err(branch_to_merge));
+            let _ = git::reset_hard(&target_worktree);
+            return Err(conflict_err(e, branch_to_merge));
         }
 
         // Prompt the user to provide a commit message for the squashed changes.
-        println!("Staged squashed changes. Please provide a commit message in your editor.");
-        git::commit_with_editor(&context.main_worktree_root)
+        eprintln!("Staged squashed changes. Please provide a commit message in your editor.");
+        git::commit_with_editor(&target_worktree)
             .context("Failed to commit squashed changes. You may need to commit them manually.")?;
         info!(branch = branch_to_merge, "merge:squash merge committed");
     } else {
         // Default merge commit workflow
-        if let Err(e) = git::merge_in_worktree(&context.main_worktree_root, branch_to_merge) {
-            info!(branch = branch_to_merge, error = %e, "merge:standard merge failed, aborting merge in main worktree");
+        if let Err(e) = git::merge_in_worktree(&target_worktree, branch_to_merge) {
+            info!(branch = branch_to_merge, error = %e, "merge:standard merge failed, aborting merge in target worktree");
             // Best effort to abort; ignore failure as the user message is the priority.
-            let _ = git::abort_merge_in_worktree(&context.main_worktree_root);
-            return Err(conflict_err(branch_to_merge));
+            let _ = git::abort_merge_in_worktree(&target_worktree);
+            return Err(conflict_err(e, branch_to_merge));
         }
         info!(branch = branch_to_merge, "merge:standard merge complete");
     }
 
+    if !already_merged {
+        run_post_merge_hooks(context, &target_worktree, branch_to_merge);
+    }
+
     // Skip cleanup if --keep flag is used
-    if keep {
+    let window_plan = plan_windows(keep, open);
+    if window_plan != WindowPlan::CloseAndNavigateToMain {
         info!(branch = branch_to_merge, "merge:skipping cleanup (--keep)");
+        if window_plan == WindowPlan::SelectTarget {
+            // A target without its own worktree was merged into the main worktree
+            let target_window = if target_worktree == context.main_worktree_root {
+                context.main_branch.as_str()
+            } else {
+                target_branch
+            };
+            if !cleanup::select_target_tab(context.mux.as_ref(), &context.prefix, target_window)? {
+                eprintln!(
+                    "No {} for '{}' to switch to. Open it with: workmux open {}",
+                    context.mux.window_term(),
+                    target_window,
+                    target_window
+                );
+            }
+        }
+        switch_after_merge(context, &target_worktree);
         return Ok(MergeResult {
             branch_merged: branch_to_merge.to_string(),
-            main_branch: context.main_branch.clone(),
+            target_branch: target_branch.to_string(),
             had_staged_changes,
+            already_merged,
+            cleanup: None,
+            stash: None,
         });
     }
 
@@ -156,23 +347,167 @@ pub fn merge(
     let cleanup_result = cleanup::cleanup(
         context,
         branch_to_merge,
-        &worktree_path,
+        worktree_path,
         true,
         delete_remote,
         false, // keep_branch: always delete when merging
     )?;
+    switch_after_merge(context, &target_worktree);
 
     // Navigate to the main branch window and close the target window
     cleanup::navigate_to_main_and_close(
+        context.mux.as_ref(),
         &context.prefix,
         &context.main_branch,
         branch_to_merge,
         &cleanup_result,
+        context.main_window_to_create(),
     )?;
 
     Ok(MergeResult {
         branch_merged: branch_to_merge.to_string(),
-        main_branch: context.main_branch.clone(),
+        target_branch: target_branch.to_string(),
         had_staged_changes,
+        already_merged,
+        cleanup: Some(cleanup_result),
+        stash: None,
     })
 }
+
+/// What happens to windows once the merge has landed
+#[derive(Debug, PartialEq)]
+enum WindowPlan {
+    /// Clean up, then go to the main branch window and close the merged one
+    CloseAndNavigateToMain,
+    /// Keep everything and switch to the target branch's window
+    SelectTarget,
+    /// Keep everything and stay where we are
+    Stay,
+}
+
+fn plan_windows(keep: bool, open: bool) -> WindowPlan {
+    match (keep, open) {
+        (false, _) => WindowPlan::CloseAndNavigateToMain,
+        (true, true) => WindowPlan::SelectTarget,
+        (true, false) => WindowPlan::Stay,
+    }
+}
+
+/// Run the configured `post_merge` commands in the worktree that was merged into.
+///
+/// The merge has already landed at this point, so a failing command is reported
+/// and the remaining commands still run.
+fn run_post_merge_hooks(context: &WorkflowContext, target_worktree: &Path, branch: &str) {
+    let Some(commands) = context.config.post_merge.as_deref() else {
+        return;
+    };
+    if commands.is_empty() {
+        return;
+    }
+
+    eprintln!("Running post-merge commands...");
+    let total = commands.len();
+    for (idx, command) in commands.iter().enumerate() {
+        info!(branch, step = idx + 1, total, command = %command, "merge:post-merge hook start");
+        if let Err(e) = cmd::shell_command(command, target_worktree) {
+            warn!(branch, command = %command, error = %e, "merge:post-merge hook failed");
+            eprintln!("Warning: post-merge command failed: {}", e);
+        }
+    }
+}
+
+/// Check out `post_merge_switch_to` in the worktree that was merged into.
+///
+/// Like the post-merge hooks, this runs after the merge has landed, so a failed
+/// switch is only reported.
+fn switch_after_merge(context: &WorkflowContext, target_worktree: &Path) {
+    let checked_out = git::get_current_branch_in_worktree(target_worktree).unwrap_or_default();
+    let Some(branch) =
+        post_merge_switch(context.config.post_merge_switch_to.as_deref(), &checked_out)
+    else {
+        return;
+    };
+
+    info!(branch, worktree = %target_worktree.display(), "merge:post-merge switch");
+    if let Err(e) = git::switch_branch_in_worktree(target_worktree, branch, false) {
+        warn!(branch, error = %e, "merge:post-merge switch failed");
+        eprintln!(
+            "Warning: could not switch to '{}' after merging: {:#}",
+            branch, e
+        );
+    }
+}
+
+/// The branch to switch to after a merge, unless nothing is configured or it
+/// is already the one checked out
+fn post_merge_switch<'a>(switch_to: Option<&'a str>, checked_out: &str) -> Option<&'a str> {
+    switch_to.filter(|branch| !branch.is_empty() && *branch != checked_out)
+}
+
+/// Whether any remote has a branch named `branch` that `git switch` can track
+fn on_any_remote(branch: &str) -> Result<bool> {
+    for remote in git::list_remotes()? {
+        if git::remote_branch_exists(&format!("{}/{}", remote, branch))? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Print the commits that a rebase would replay (to stderr, keeping stdout for
+/// `--json`) and, when attached to a terminal, ask whether to continue unless
+/// `assume_yes`. Returns false if the user declined.
+fn confirm_rebase_preview(
+    worktree_path: &Path,
+    branch: &str,
+    target: &str,
+    assume_yes: bool,
+) -> Result<bool> {
+    let commits = git::log_range(worktree_path, target, branch, None)?;
+
+    if commits.is_empty() {
+        eprintln!("No commits on '{}' to rebase onto '{}'.", branch, target);
+    } else {
+        eprintln!(
+            "Commits on '{}' to rebase onto '{}' ({}):",
+            branch,
+            target,
+            commits.len()
+        );
+        for commit in &commits {
+            eprintln!("  {}", commit);
+        }
+    }
+
+    // Non-interactive runs (scripts, CI) just show the preview and proceed.
+    if !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+
+    Ok(prompt::confirm("Continue with rebase?", assume_yes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WindowPlan, plan_windows, post_merge_switch};
+
+    #[test]
+    fn keep_with_open_selects_target_without_closing() {
+        assert_eq!(plan_windows(true, true), WindowPlan::SelectTarget);
+        assert_eq!(plan_windows(true, false), WindowPlan::Stay);
+        assert_eq!(
+            plan_windows(false, false),
+            WindowPlan::CloseAndNavigateToMain
+        );
+    }
+
+    #[test]
+    fn post_merge_switch_only_when_configured_and_different() {
+        assert_eq!(post_merge_switch(None, "release"), None);
+        assert_eq!(post_merge_switch(Some(""), "release"), None);
+        assert_eq!(post_merge_switch(Some("main"), "main"), None);
+        assert_eq!(post_merge_switch(Some("main"), "release"), Some("main"));
+        // A detached HEAD reports no branch
+        assert_eq!(post_merge_switch(Some("main"), ""), Some("main"));
+    }
+}
@@ -2,20 +2,27 @@
 mod cleanup;
 mod context;
 mod create;
+mod fetch;
+mod hooks;
 mod list;
 mod merge;
 mod open;
+mod prune;
 mod remove;
 mod setup;
+mod status;
 pub mod types;
 
 // Public API re-exports
-pub use create::{create, create_with_changes};
+pub use create::{create, create_with_changes, ensure_no_worktree};
+pub use fetch::fetch;
 pub use list::list;
 pub use merge::merge;
-pub use open::open;
+pub use open::{open, open_all};
+pub use prune::prune;
 pub use remove::remove;
+pub use status::status;
 
 // Re-export commonly used types for convenience
 pub use context::WorkflowContext;
-pub use types::SetupOptions;
+pub use types::{MergeOptions, SetupOptions};
@@ -1,13 +1,19 @@
 use anyhow::{Context, Result, anyhow};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-use crate::{git, zellij};
-use tracing::info;
+use crate::multiplexer::prefixed;
+use tracing::{info, warn};
 
 use super::context::WorkflowContext;
+use super::hooks::HookOutput;
 use super::setup;
-use super::types::{CreateResult, SetupOptions};
+use super::types::{CreateResult, OpenAllResult, SetupOptions};
 
-/// Open a zellij tab for an existing worktree
+/// Open a multiplexer window for an existing worktree
 pub fn open(
     branch_name: &str,
     context: &WorkflowContext,
@@ -26,18 +32,20 @@ pub fn open(
     }
 
     // Pre-flight checks
-    context.ensure_zellij_running()?;
+    context.ensure_multiplexer_running()?;
 
-    if zellij::tab_exists(&context.prefix, branch_name)? {
+    if context.mux.exists(&context.prefix, branch_name)? {
         return Err(anyhow!(
-            "A zellij tab named '{}' already exists. To switch to it, run: zellij action go-to-tab-name '{}'",
+            "A {} {} named '{}' already exists. To switch to it, run: {}",
+            context.mux.name(),
+            context.mux.window_term(),
             branch_name,
-            zellij::prefixed(&context.prefix, branch_name)
+            context.mux.select_hint(&context.prefix, branch_name)
         ));
     }
 
     // This command requires the worktree to already exist
-    let worktree_path = git::get_worktree_path(branch_name).with_context(|| {
+    let worktree_path = context.worktrees.path_for(branch_name).with_context(|| {
         format!(
             "No worktree found for branch '{}'. Use 'workmux add {}' to create it.",
             branch_name, branch_name
@@ -45,8 +53,15 @@ pub fn open(
     })?;
 
     // Setup the environment
-    let result =
-        setup::setup_environment(branch_name, &worktree_path, &context.config, &options, None)?;
+    let result = setup::setup_environment(
+        branch_name,
+        &worktree_path,
+        &context.config,
+        context.mux.as_ref(),
+        &context.prefix,
+        &options,
+        None,
+    )?;
     info!(
         branch = branch_name,
         path = %result.worktree_path.display(),
@@ -55,3 +70,166 @@ pub fn open(
     );
     Ok(result)
 }
+
+/// Open a window for every worktree that doesn't have one, skipping the main branch.
+///
+/// Windows open in the background. File operations and post-create hooks run
+/// for up to `jobs` worktrees at once; windows are then created one at a time,
+/// since every window mutates the shared multiplexer session. Failures for
+/// individual worktrees are recorded in the result rather than aborting the
+/// whole run.
+pub fn open_all(
+    context: &WorkflowContext,
+    options: SetupOptions,
+    jobs: usize,
+) -> Result<OpenAllResult> {
+    if let Some(panes) = &context.config.panes {
+        crate::config::validate_panes_config(panes)?;
+    }
+    context.ensure_multiplexer_running()?;
+
+    let open_names = context.mux.list_names()?;
+    let (to_open, skipped) = partition_by_window(
+        context.worktrees.all(),
+        &context.main_branch,
+        &context.prefix,
+        &open_names,
+    );
+    info!(
+        to_open = to_open.len(),
+        skipped = skipped.len(),
+        jobs,
+        "open_all:start"
+    );
+
+    // Concurrent jobs buffer their hook output so it prints in one piece
+    let buffered = jobs > 1;
+    let config = &context.config;
+    let worktrees = &context.worktrees;
+    let prepared = parallel_map(&to_open, jobs, |branch| {
+        let mut output = String::new();
+        let hook_output = if buffered {
+            HookOutput::Buffer(&mut output)
+        } else {
+            HookOutput::Inherit
+        };
+        let result = worktrees
+            .path_for(branch)
+            .and_then(|path| setup::prepare_worktree(branch, &path, config, &options, hook_output));
+        (output, result)
+    });
+
+    // Inline hooks and file operations are done; hooks meant for a pane still
+    // have to be launched with the window
+    let options = SetupOptions {
+        focus_window: false,
+        run_hooks: options.run_hooks && context.config.hooks_in_pane(),
+        run_file_ops: false,
+        ..options
+    };
+
+    let mut opened = Vec::new();
+    let mut failed = Vec::new();
+    for (branch, (output, prepared)) in to_open.into_iter().zip(prepared) {
+        if !output.is_empty() {
+            println!("── {} ──\n{}", branch, output.trim_end());
+        }
+        match prepared.and_then(|_| open(&branch, context, options.clone())) {
+            Ok(_) => opened.push(branch),
+            Err(e) => {
+                warn!(branch = %branch, error = %e, "open_all:worktree failed");
+                failed.push((branch, e));
+            }
+        }
+    }
+
+    Ok(OpenAllResult {
+        opened,
+        skipped,
+        failed,
+    })
+}
+
+/// Apply `f` to every item on up to `jobs` threads, keeping results in input order
+fn parallel_map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(items.iter().map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(idx) else { break };
+                    let result = f(item);
+                    results.lock().unwrap()[idx] = Some(result);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is processed"))
+        .collect()
+}
+
+/// Split worktree branches into those without a window and those that already
+/// have one. The main branch and detached worktrees are left out of both.
+fn partition_by_window(
+    worktrees: Vec<(PathBuf, String)>,
+    main_branch: &str,
+    prefix: &str,
+    open_names: &HashSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    worktrees
+        .into_iter()
+        .map(|(_, branch)| branch)
+        .filter(|branch| branch != main_branch && branch != "(detached)")
+        .partition(|branch| !open_names.contains(&prefixed(prefix, branch)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parallel_map, partition_by_window};
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn parallel_map_keeps_order_and_bounds_concurrency() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<u64> = (0..8).collect();
+
+        let results = parallel_map(&items, 3, |&n| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            // Later items finish first, so order has to be restored
+            thread::sleep(Duration::from_millis(40 - 5 * n));
+            running.fetch_sub(1, Ordering::SeqCst);
+            n * 10
+        });
+
+        assert_eq!(results, vec![0, 10, 20, 30, 40, 50, 60, 70]);
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(peak.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn partition_by_window_skips_main_and_open_windows() {
+        let worktrees = vec![
+            (PathBuf::from("/repo"), "main".to_string()),
+            (PathBuf::from("/wt/open"), "open".to_string()),
+            (PathBuf::from("/wt/closed"), "closed".to_string()),
+            (PathBuf::from("/wt/scratch"), "(detached)".to_string()),
+        ];
+        let open_names = HashSet::from(["wm-open".to_string(), "wm-main".to_string()]);
+
+        let (to_open, skipped) = partition_by_window(worktrees, "main", "wm-", &open_names);
+        assert_eq!(to_open, vec!["closed"]);
+        assert_eq!(skipped, vec!["open"]);
+    }
+}
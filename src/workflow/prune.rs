@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::multiplexer::prefixed;
+use crate::{git, prompt};
+
+use super::context::WorkflowContext;
+use super::types::PruneResult;
+
+/// Worktrees whose directories are gone, and the windows/tabs that were opened for them
+#[derive(Debug, Default, PartialEq)]
+struct Orphans {
+    worktrees: Vec<(PathBuf, String)>,
+    windows: Vec<String>,
+}
+
+/// Clean up after worktree directories that were deleted outside of workmux.
+///
+/// Prunes git's worktree metadata and, once confirmed, closes the windows/tabs
+/// of the pruned worktrees. Branches of the pruned worktrees are only deleted
+/// when `force` is set; otherwise they are reported and flagged so a later run
+/// can still delete them. `force` also closes the windows without asking.
+pub fn prune(force: bool, context: &WorkflowContext) -> Result<PruneResult> {
+    let mux = context.mux.as_ref();
+
+    // Snapshot before pruning: afterwards git no longer knows which branches lost their worktree
+    let worktrees = context.worktrees.all();
+    let window_names = if mux.is_running().unwrap_or(false) {
+        mux.list_names().unwrap_or_default()
+    } else {
+        HashSet::new()
+    };
+
+    let orphans = find_orphans(&worktrees, |p| p.exists(), &window_names, &context.prefix);
+    info!(
+        worktrees = orphans.worktrees.len(),
+        windows = orphans.windows.len(),
+        "prune:orphans detected"
+    );
+
+    git::prune_worktrees()?;
+
+    let close_windows = !orphans.windows.is_empty()
+        && (force
+            || prompt::confirm(
+                &format!(
+                    "Close the {} {}(s) of the pruned worktrees ({})?",
+                    mux.name(),
+                    mux.window_term(),
+                    orphans.windows.join(", ")
+                ),
+                !context.config.confirm(),
+            ));
+
+    let windows_to_close: &[String] = if close_windows { &orphans.windows } else { &[] };
+
+    let current_window = mux.current_name().ok().flatten();
+    let mut closed_windows = Vec::new();
+    for name in windows_to_close {
+        // Never pull the rug out from under the shell running this command
+        if current_window.as_deref() == Some(prefixed(&context.prefix, name).as_str()) {
+            continue;
+        }
+        match mux.close(&context.prefix, name) {
+            Ok(()) => closed_windows.push(name.clone()),
+            Err(e) => warn!(window = name, error = %e, "prune:failed to close window"),
+        }
+    }
+
+    // Branches kept by an earlier prune no longer show up in `git worktree list`,
+    // so they are found through the marker left on them instead.
+    let live_branches: HashSet<&str> = worktrees
+        .iter()
+        .filter(|(path, _)| path.exists())
+        .map(|(_, branch)| branch.as_str())
+        .collect();
+    let mut orphaned_branches: Vec<String> = orphans
+        .worktrees
+        .iter()
+        .map(|(_, branch)| branch.clone())
+        .collect();
+    let mut previously_orphaned: Vec<String> = git::get_orphaned_branches()?
+        .into_iter()
+        .filter(|branch| !orphaned_branches.contains(branch))
+        .collect();
+    previously_orphaned.sort();
+    for branch in previously_orphaned {
+        if live_branches.contains(branch.as_str()) {
+            git::unset_branch_orphaned(&branch)?;
+        } else if git::branch_exists(&branch)? {
+            orphaned_branches.push(branch);
+        }
+    }
+
+    for branch in &orphaned_branches {
+        if force {
+            git::delete_branch(branch, true)
+                .with_context(|| format!("Failed to delete orphaned branch '{}'", branch))?;
+            info!(branch, "prune:branch deleted");
+        } else {
+            git::set_branch_orphaned(branch)?;
+        }
+    }
+
+    Ok(PruneResult {
+        pruned_worktrees: orphans.worktrees,
+        closed_windows,
+        orphaned_branches,
+        branches_deleted: force,
+    })
+}
+
+/// Find worktrees whose directories no longer exist, and the windows/tabs named
+/// after their branches. Windows of other repos, or any window at all when
+/// there is no prefix to tell workmux's apart, are left alone.
+fn find_orphans(
+    worktrees: &[(PathBuf, String)],
+    path_exists: impl Fn(&Path) -> bool,
+    window_names: &HashSet<String>,
+    prefix: &str,
+) -> Orphans {
+    let missing: Vec<(PathBuf, String)> = worktrees
+        .iter()
+        .filter(|(path, _)| !path_exists(path))
+        // A detached worktree has no branch to clean up
+        .filter(|(_, branch)| branch != "(detached)")
+        .cloned()
+        .collect();
+
+    let mut windows: Vec<String> = if prefix.is_empty() {
+        Vec::new()
+    } else {
+        missing
+            .iter()
+            .map(|(_, branch)| branch)
+            .filter(|branch| window_names.contains(&prefixed(prefix, branch)))
+            .cloned()
+            .collect()
+    };
+    windows.sort();
+
+    Orphans {
+        worktrees: missing,
+        windows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worktrees() -> Vec<(PathBuf, String)> {
+        vec![
+            (PathBuf::from("/repo"), "main".to_string()),
+            (PathBuf::from("/wt/alive"), "alive".to_string()),
+            (PathBuf::from("/wt/gone"), "gone".to_string()),
+            (PathBuf::from("/wt/gone-detached"), "(detached)".to_string()),
+        ]
+    }
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn find_orphans_detects_missing_directories_and_their_windows() {
+        let orphans = find_orphans(
+            &worktrees(),
+            |p| !p.starts_with("/wt/gone"),
+            &names(&[
+                "wm-main",
+                "wm-alive",
+                "wm-gone",
+                "wm-never-existed",
+                "editor",
+            ]),
+            "wm-",
+        );
+
+        assert_eq!(
+            orphans,
+            Orphans {
+                worktrees: vec![(PathBuf::from("/wt/gone"), "gone".to_string())],
+                windows: vec!["gone".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn find_orphans_leaves_windows_alone_without_a_prefix() {
+        let orphans = find_orphans(
+            &worktrees(),
+            |p| !p.starts_with("/wt/gone"),
+            &names(&["main", "alive", "gone", "editor"]),
+            "",
+        );
+        assert_eq!(orphans.windows, Vec::<String>::new());
+        assert_eq!(orphans.worktrees.len(), 1);
+    }
+
+    #[test]
+    fn find_orphans_nothing_to_do() {
+        let orphans = find_orphans(
+            &worktrees(),
+            |_| true,
+            &names(&["wm-main", "wm-alive"]),
+            "wm-",
+        );
+        assert_eq!(orphans, Orphans::default());
+    }
+}
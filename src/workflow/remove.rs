@@ -21,7 +21,9 @@ pub fn remove(
     );
 
     // Get worktree path - this also validates that the worktree exists
-    let worktree_path = git::get_worktree_path(branch_name)
+    let worktree_path = context
+        .worktrees
+        .path_for(branch_name)
         .with_context(|| format!("No worktree found for branch '{}'", branch_name))?;
     debug!(branch = branch_name, path = %worktree_path.display(), "remove:worktree resolved");
 
@@ -60,7 +62,10 @@ pub fn remove(
         ));
     }
 
-    if worktree_path.exists() && git::has_uncommitted_changes(&worktree_path)? && !force {
+    if worktree_path.exists()
+        && git::has_uncommitted_changes(&worktree_path, context.config.ignore_dirty_paths())?
+        && !force
+    {
         return Err(anyhow!(
             "Worktree has uncommitted changes. Use --force to delete anyway."
         ));
@@ -83,13 +88,16 @@ pub fn remove(
 
     // Navigate to the main branch window and close the target window
     cleanup::navigate_to_main_and_close(
+        context.mux.as_ref(),
         &context.prefix,
         &context.main_branch,
         branch_name,
         &cleanup_result,
+        context.main_window_to_create(),
     )?;
 
     Ok(RemoveResult {
         branch_removed: branch_name.to_string(),
+        cleanup: cleanup_result,
     })
 }
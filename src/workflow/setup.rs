@@ -1,33 +1,42 @@
 use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::{cmd, config, git, prompt::Prompt, zellij};
-use tracing::{debug, info, trace};
+use crate::cmd::Cmd;
+use crate::multiplexer::{self, Multiplexer, PaneSetupOptions};
+use crate::{config, git, prompt, prompt::Prompt};
+use tracing::{debug, info};
 
 use fs_extra::dir as fs_dir;
 use fs_extra::file as fs_file;
 
+use super::hooks::HookOutput;
 use super::types::CreateResult;
 
-/// Sets up the zellij tab, files, and hooks for a worktree.
-/// This is the shared logic between `create` and `open`.
-pub fn setup_environment(
+/// The filesystem half of setting up a worktree: file operations, inline
+/// post-create hooks and committing what they generated. It doesn't touch the
+/// multiplexer, so it can run for several worktrees at once. Returns the number
+/// of hooks run.
+pub fn prepare_worktree(
     branch_name: &str,
     worktree_path: &Path,
     config: &config::Config,
     options: &super::types::SetupOptions,
-    agent: Option<&str>,
-) -> Result<CreateResult> {
-    debug!(
-        branch = branch_name,
-        path = %worktree_path.display(),
-        run_hooks = options.run_hooks,
-        run_file_ops = options.run_file_ops,
-        "setup_environment:start"
-    );
-    let prefix = config.window_prefix();
-    let repo_root = git::get_repo_root()?;
+    hook_output: HookOutput,
+) -> Result<usize> {
+    // File operations always read from the main worktree, even when `add` is
+    // run from inside another worktree
+    let repo_root = git::get_main_worktree_root()?;
+
+    // Note what was already changed so only files created by setup get committed
+    let changed_before_setup = if config.commit_generated_files() {
+        Some(git::changed_paths(worktree_path)?)
+    } else {
+        None
+    };
 
     // Perform file operations (copy and symlink) if requested
     if options.run_file_ops {
@@ -35,66 +44,200 @@ pub fn setup_environment(
             .context("Failed to perform file operations")?;
         debug!(
             branch = branch_name,
-            "setup_environment:file operations applied"
+            "prepare_worktree:file operations applied"
         );
     }
 
-    // Run post-create hooks before opening zellij so the new tab appears "ready"
+    // Run post-create hooks before opening the window so it appears "ready"
     let mut hooks_run = 0;
     if options.run_hooks
+        && !config.hooks_in_pane()
         && let Some(post_create) = &config.post_create
         && !post_create.is_empty()
     {
-        hooks_run = post_create.len();
-        for (idx, command) in post_create.iter().enumerate() {
-            info!(branch = branch_name, step = idx + 1, total = hooks_run, command = %command, "setup_environment:hook start");
-            info!(command = %command, "Running post-create hook {}/{}", idx + 1, hooks_run);
-            cmd::shell_command(command, worktree_path)
-                .with_context(|| format!("Failed to run post-create command: '{}'", command))?;
-            info!(branch = branch_name, step = idx + 1, total = hooks_run, command = %command, "setup_environment:hook complete");
-        }
+        hooks_run = super::hooks::run_hooks(
+            "post-create",
+            post_create,
+            worktree_path,
+            hook_output,
+            config.hook_timeout(),
+            config.verbose,
+        )?;
         info!(
             branch = branch_name,
             total = hooks_run,
-            "setup_environment:hooks complete"
+            "prepare_worktree:hooks complete"
         );
     }
 
-    // Create zellij tab
-    zellij::create_tab(
+    if let Some(changed_before) = changed_before_setup {
+        commit_generated_files(branch_name, worktree_path, &changed_before)?;
+    }
+
+    // Written after generated files are committed; the copy is never meant to be
+    // committed itself
+    if options.copy_config {
+        let path = config::Config::write_local_copy(worktree_path)?;
+        info!(branch = branch_name, path = %path.display(), "prepare_worktree:config copied");
+        if !git::is_ignored(worktree_path, &path)? {
+            eprintln!(
+                "Warning: {} is not gitignored. Add it to .gitignore so it isn't committed or \
+                counted as an uncommitted change.",
+                path.display()
+            );
+        }
+    }
+
+    Ok(hooks_run)
+}
+
+/// Sets up the multiplexer window, files, and hooks for a worktree.
+/// This is the shared logic between `create` and `open`.
+pub fn setup_environment(
+    branch_name: &str,
+    worktree_path: &Path,
+    config: &config::Config,
+    mux: &dyn Multiplexer,
+    prefix: &str,
+    options: &super::types::SetupOptions,
+    agent: Option<&str>,
+) -> Result<CreateResult> {
+    debug!(
+        branch = branch_name,
+        path = %worktree_path.display(),
+        run_hooks = options.run_hooks,
+        run_file_ops = options.run_file_ops,
+        "setup_environment:start"
+    );
+    let hooks_run = prepare_worktree(
+        branch_name,
+        worktree_path,
+        config,
+        options,
+        HookOutput::Inherit,
+    )?;
+
+    // Hooks configured to run in their own pane are launched after the tab exists
+    let pane_hooks = if options.run_hooks && config.hooks_in_pane() {
+        config
+            .post_create
+            .as_deref()
+            .and_then(multiplexer::build_hooks_pane_command)
+    } else {
+        None
+    };
+
+    // Check for an agent still running in this worktree (e.g. its window was
+    // closed while the process survived) before our own panes exist, so
+    // reopening doesn't start a second one
+    let effective_agent = config.effective_agent(agent);
+    let agent_running = options.run_pane_commands
+        && effective_agent.is_some_and(|agent_cmd| {
+            let pane_commands = mux.pane_commands_in(worktree_path).unwrap_or_else(|e| {
+                debug!(error = %e, "setup_environment:failed to list pane commands");
+                Vec::new()
+            });
+            multiplexer::agent_already_running(agent_cmd, &pane_commands)
+        });
+    if agent_running {
+        info!(
+            branch = branch_name,
+            "setup_environment:agent already running"
+        );
+        println!("Agent is already running in this worktree; not starting another");
+    }
+
+    // Create the multiplexer window/tab
+    mux.create(
         prefix,
         branch_name,
         worktree_path,
         /* detached: */ !options.focus_window,
     )
-    .context("Failed to create zellij tab")?;
+    .with_context(|| format!("Failed to create {} {}", mux.name(), mux.window_term()))?;
     info!(
         branch = branch_name,
-        "setup_environment:zellij tab created"
+        "setup_environment:window created"
     );
 
-    // Setup tab with command (simplified single-pane model)
+    // Setup panes and their commands
     let panes = config.panes.as_deref().unwrap_or(&[]);
-    let resolved_panes = resolve_pane_configuration(panes, agent);
-    zellij::setup_tab(
+    let resolved_panes = resolve_pane_configuration(panes, agent, config.agent_placeholder());
+    let recording_path = match &config.agent_record {
+        Some(_) if options.run_pane_commands => Some(recording_path(branch_name)?),
+        _ => None,
+    };
+    mux.setup_panes(
+        prefix,
+        branch_name,
         &resolved_panes,
         worktree_path,
-        zellij::TabSetupOptions {
+        PaneSetupOptions {
             run_commands: options.run_pane_commands,
-            prompt_file_path: options.prompt_file_path.as_deref(),
+            prompt_file_path: options.agent_prompt_path(),
+            agent: effective_agent,
+            agent_args: &options.agent_args,
+            agent_placeholder: config.agent_placeholder(),
+            prompt_formats: config.agent_prompt_format.as_ref(),
+            agent_running,
+            startup_shell: config.startup_shell.as_deref(),
+            select_pane: options.select_pane,
+            agent_record: config
+                .agent_record
+                .as_deref()
+                .zip(recording_path.as_deref()),
         },
-        config,
-        agent,
     )
-    .context("Failed to setup tab")?;
+    .context("Failed to setup panes")?;
     debug!(
         branch = branch_name,
         "setup_environment:tab configured"
     );
 
+    if let Some(hooks_command) = pane_hooks {
+        mux.run_in_new_pane(prefix, branch_name, worktree_path, &hooks_command)
+            .context("Failed to start post-create hooks pane")?;
+        info!(
+            branch = branch_name,
+            "setup_environment:hooks launched in pane"
+        );
+    }
+
+    // Block until the agent reports ready, so scripts driving `add` can rely on it
+    if options.run_pane_commands
+        && let Some(check) = config.agent_ready_check.as_deref()
+    {
+        let timeout = config.agent_ready_timeout();
+        info!(
+            branch = branch_name,
+            check = %check,
+            "setup_environment:waiting for agent"
+        );
+        let ready = poll_until_ready(
+            || {
+                Cmd::new("sh")
+                    .args(&["-c", check])
+                    .workdir(worktree_path)
+                    .timeout(timeout)
+                    .run_as_check()
+                    .unwrap_or(false)
+            },
+            timeout,
+            AGENT_READY_POLL_INTERVAL,
+        );
+        if !ready {
+            return Err(anyhow!(
+                "Agent did not become ready within {}s (agent_ready_check: '{}')",
+                timeout.as_secs(),
+                check
+            ));
+        }
+        info!(branch = branch_name, "setup_environment:agent ready");
+    }
+
     // Focus the tab if requested
     if options.focus_window {
-        zellij::select_tab(prefix, branch_name)?;
+        mux.select(prefix, branch_name)?;
     }
 
     Ok(CreateResult {
@@ -102,12 +245,62 @@ pub fn setup_environment(
         branch_name: branch_name.to_string(),
         post_create_hooks_run: hooks_run,
         base_branch: None,
+        prompt_file_path: options.prompt_file_path.clone(),
     })
 }
 
+const GENERATED_FILES_COMMIT_MESSAGE: &str = "Add files generated by workmux setup";
+
+/// Commit the files that appeared in the worktree since `changed_before` was taken
+fn commit_generated_files(
+    branch_name: &str,
+    worktree_path: &Path,
+    changed_before: &BTreeSet<String>,
+) -> Result<()> {
+    let generated: Vec<String> = git::changed_paths(worktree_path)?
+        .difference(changed_before)
+        .cloned()
+        .collect();
+    if generated.is_empty() {
+        return Ok(());
+    }
+
+    git::commit_with_message(worktree_path, GENERATED_FILES_COMMIT_MESSAGE, &generated)
+        .context("Failed to commit generated files")?;
+    info!(
+        branch = branch_name,
+        count = generated.len(),
+        "setup_environment:committed generated files"
+    );
+    Ok(())
+}
+
+const AGENT_READY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Call `check` until it returns true or `timeout` elapses, sleeping `interval`
+/// between attempts. The check always runs at least once.
+fn poll_until_ready(
+    mut check: impl FnMut() -> bool,
+    timeout: Duration,
+    interval: Duration,
+) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if check() {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(interval.min(deadline - now));
+    }
+}
+
 pub fn resolve_pane_configuration(
     original_panes: &[config::PaneConfig],
     agent: Option<&str>,
+    placeholder: &str,
 ) -> Vec<config::PaneConfig> {
     let Some(agent_cmd) = agent else {
         return original_panes.to_vec();
@@ -115,7 +308,7 @@ pub fn resolve_pane_configuration(
 
     if original_panes
         .iter()
-        .any(|pane| pane.command.as_deref() == Some("<agent>"))
+        .any(|pane| pane.command.as_deref() == Some(placeholder))
     {
         return original_panes.to_vec();
     }
@@ -139,10 +332,15 @@ pub fn resolve_pane_configuration(
         size: None,
         percentage: None,
         target: None,
+        keep_open: true,
+        env: None,
+        shell: None,
+        close_tab_on_exit: false,
     }]
 }
 
-/// Performs copy and symlink operations from the repo root to the worktree
+/// Performs copy and symlink operations from the repo root to the worktree.
+/// Patterns are globs relative to `repo_root`.
 pub fn handle_file_operations(
     repo_root: &Path,
     worktree_path: &Path,
@@ -163,183 +361,281 @@ pub fn handle_file_operations(
         )
     })?;
 
+    let copy_paths = match &file_config.copy {
+        Some(patterns) => expand_file_patterns(repo_root, &canon_repo_root, patterns, "copy")?,
+        None => Vec::new(),
+    };
+    let symlink_paths = match &file_config.symlink {
+        Some(patterns) => expand_file_patterns(repo_root, &canon_repo_root, patterns, "symlink")?,
+        None => Vec::new(),
+    };
+    // A path matched by both lists is copied, so the worktree gets its own version
+    let symlink_paths = without_copied(symlink_paths, &copy_paths);
+
     // Handle copies
-    if let Some(copy_patterns) = &file_config.copy {
-        for pattern in copy_patterns {
-            let full_pattern = repo_root.join(pattern).to_string_lossy().to_string();
-            for entry in glob::glob(&full_pattern)? {
-                let source_path = entry?;
-
-                // Validate that the resolved source path stays within the repository root
-                let canon_source_path = source_path.canonicalize().with_context(|| {
-                    format!("Failed to canonicalize source path: {:?}", source_path)
-                })?;
-                if !canon_source_path.starts_with(&canon_repo_root) {
-                    return Err(anyhow!(
-                        "Path traversal detected for copy pattern '{}'. The resolved path '{}' is outside the repository root.",
-                        pattern,
-                        source_path.display()
-                    ));
-                }
-
-                let relative_path = source_path.strip_prefix(repo_root).with_context(|| {
-                    format!(
-                        "Path '{}' is outside the repository root '{}', which is not allowed.",
-                        source_path.display(),
-                        repo_root.display()
-                    )
+    for source_path in copy_paths {
+        let relative_path = source_path.strip_prefix(repo_root).with_context(|| {
+            format!(
+                "Path '{}' is outside the repository root '{}', which is not allowed.",
+                source_path.display(),
+                repo_root.display()
+            )
+        })?;
+        let dest_path = worktree_path.join(relative_path);
+
+        if source_path.is_dir() {
+            // Create destination parent directory
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            // Use fs_extra::dir::copy which handles recursion and symlinks correctly
+            let mut dir_options = fs_dir::CopyOptions::new();
+            dir_options.overwrite = true;
+            dir_options.content_only = true;
+            fs::create_dir_all(&dest_path)?; // Ensure dest exists
+            fs_dir::copy(&source_path, &dest_path, &dir_options).with_context(|| {
+                format!(
+                    "Failed to copy directory {:?} to {:?}",
+                    source_path, dest_path
+                )
+            })?;
+            debug!(
+                from = %source_path.display(),
+                to = %dest_path.display(),
+                "file_operations:copied directory"
+            );
+        } else {
+            // Copy single file
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create parent directory for {:?}", dest_path)
                 })?;
-                let dest_path = worktree_path.join(relative_path);
-
-                if source_path.is_dir() {
-                    // Create destination parent directory
-                    if let Some(parent) = dest_path.parent() {
-                        fs::create_dir_all(parent)?;
-                    }
-                    // Use fs_extra::dir::copy which handles recursion and symlinks correctly
-                    let mut dir_options = fs_dir::CopyOptions::new();
-                    dir_options.overwrite = true;
-                    dir_options.content_only = true;
-                    fs::create_dir_all(&dest_path)?; // Ensure dest exists
-                    fs_dir::copy(&source_path, &dest_path, &dir_options).with_context(|| {
-                        format!(
-                            "Failed to copy directory {:?} to {:?}",
-                            source_path, dest_path
-                        )
-                    })?;
-                    trace!(
-                        from = %source_path.display(),
-                        to = %dest_path.display(),
-                        "file_operations:copied directory"
-                    );
-                } else {
-                    // Copy single file
-                    if let Some(parent) = dest_path.parent() {
-                        fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directory for {:?}", dest_path)
-                        })?;
-                    }
-                    let mut options = fs_file::CopyOptions::new();
-                    options.overwrite = true;
-                    fs_file::copy(&source_path, &dest_path, &options).with_context(|| {
-                        format!("Failed to copy file {:?} to {:?}", source_path, dest_path)
-                    })?;
-                    trace!(
-                        from = %source_path.display(),
-                        to = %dest_path.display(),
-                        "file_operations:copied file"
-                    );
-                }
             }
+            let mut options = fs_file::CopyOptions::new();
+            options.overwrite = true;
+            fs_file::copy(&source_path, &dest_path, &options).with_context(|| {
+                format!("Failed to copy file {:?} to {:?}", source_path, dest_path)
+            })?;
+            debug!(
+                from = %source_path.display(),
+                to = %dest_path.display(),
+                "file_operations:copied file"
+            );
         }
     }
 
     // Handle symlinks
-    if let Some(symlink_patterns) = &file_config.symlink {
-        for pattern in symlink_patterns {
-            let full_pattern = repo_root.join(pattern).to_string_lossy().to_string();
-            for entry in glob::glob(&full_pattern)? {
-                let source_path = entry?;
-
-                // Validate that the resolved source path is within the repository root
-                let canon_source_path = source_path.canonicalize().with_context(|| {
-                    format!("Failed to canonicalize source path: {:?}", source_path)
+    for source_path in symlink_paths {
+        let relative_path = source_path.strip_prefix(repo_root)?;
+        let dest_path = worktree_path.join(relative_path);
+
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create parent directory for {:?}", dest_path)
+            })?;
+        }
+
+        // Critical: create a relative path for the symlink
+        let dest_parent = dest_path.parent().ok_or_else(|| {
+            anyhow!(
+                "Could not determine parent directory for destination path: {:?}",
+                dest_path
+            )
+        })?;
+
+        let relative_source = pathdiff::diff_paths(&source_path, dest_parent)
+            .ok_or_else(|| anyhow!("Could not create relative path for symlink"))?;
+
+        // Remove existing file/symlink at destination to avoid errors
+        // IMPORTANT: Use symlink_metadata to avoid following symlinks
+        if let Ok(metadata) = dest_path.symlink_metadata() {
+            if metadata.is_dir() {
+                fs::remove_dir_all(&dest_path).with_context(|| {
+                    format!("Failed to remove existing directory at {:?}", &dest_path)
                 })?;
-                if !canon_source_path.starts_with(&canon_repo_root) {
-                    return Err(anyhow!(
-                        "Path traversal detected for symlink pattern '{}'. The resolved path '{}' is outside the repository root.",
-                        pattern,
-                        source_path.display()
-                    ));
-                }
-
-                let relative_path = source_path.strip_prefix(repo_root)?;
-                let dest_path = worktree_path.join(relative_path);
-
-                if let Some(parent) = dest_path.parent() {
-                    fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directory for {:?}", dest_path)
-                    })?;
-                }
-
-                // Critical: create a relative path for the symlink
-                let dest_parent = dest_path.parent().ok_or_else(|| {
-                    anyhow!(
-                        "Could not determine parent directory for destination path: {:?}",
-                        dest_path
-                    )
+            } else {
+                // Handles both files and symlinks
+                fs::remove_file(&dest_path).with_context(|| {
+                    format!("Failed to remove existing file/symlink at {:?}", &dest_path)
                 })?;
+            }
+        }
 
-                let relative_source = pathdiff::diff_paths(&source_path, dest_parent)
-                    .ok_or_else(|| anyhow!("Could not create relative path for symlink"))?;
-
-                // Remove existing file/symlink at destination to avoid errors
-                // IMPORTANT: Use symlink_metadata to avoid following symlinks
-                if let Ok(metadata) = dest_path.symlink_metadata() {
-                    if metadata.is_dir() {
-                        fs::remove_dir_all(&dest_path).with_context(|| {
-                            format!("Failed to remove existing directory at {:?}", &dest_path)
-                        })?;
-                    } else {
-                        // Handles both files and symlinks
-                        fs::remove_file(&dest_path).with_context(|| {
-                            format!("Failed to remove existing file/symlink at {:?}", &dest_path)
-                        })?;
-                    }
-                }
-
-                #[cfg(unix)]
-                std::os::unix::fs::symlink(&relative_source, &dest_path).with_context(|| {
-                    format!(
-                        "Failed to create symlink from {:?} to {:?}",
-                        relative_source, dest_path
-                    )
-                })?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&relative_source, &dest_path).with_context(|| {
+            format!(
+                "Failed to create symlink from {:?} to {:?}",
+                relative_source, dest_path
+            )
+        })?;
+
+        #[cfg(windows)]
+        {
+            if source_path.is_dir() {
+                std::os::windows::fs::symlink_dir(&relative_source, &dest_path)
+            } else {
+                std::os::windows::fs::symlink_file(&relative_source, &dest_path)
+            }
+            .with_context(|| {
+                format!(
+                    "Failed to create symlink from {:?} to {:?}",
+                    relative_source, dest_path
+                )
+            })?;
+        }
+        debug!(
+            from = %relative_source.display(),
+            to = %dest_path.display(),
+            "file_operations:symlinked"
+        );
+    }
+
+    Ok(())
+}
 
-                #[cfg(windows)]
-                {
-                    if source_path.is_dir() {
-                        std::os::windows::fs::symlink_dir(&relative_source, &dest_path)
-                    } else {
-                        std::os::windows::fs::symlink_file(&relative_source, &dest_path)
-                    }
-                    .with_context(|| {
-                        format!(
-                            "Failed to create symlink from {:?} to {:?}",
-                            relative_source, dest_path
-                        )
-                    })?;
-                }
-                trace!(
-                    from = %relative_source.display(),
-                    to = %dest_path.display(),
-                    "file_operations:symlinked"
+/// Expand glob patterns relative to `repo_root` into the existing paths they match,
+/// in order and without duplicates. Matches that can't be resolved (e.g. broken
+/// symlinks) are skipped; matches outside the repository are an error.
+fn expand_file_patterns(
+    repo_root: &Path,
+    canon_repo_root: &Path,
+    patterns: &[String],
+    operation: &str,
+) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        let full_pattern = repo_root.join(pattern).to_string_lossy().to_string();
+        let mut matched = false;
+        for entry in glob::glob(&full_pattern)? {
+            let source_path = entry?;
+
+            // Validate that the resolved source path stays within the repository root
+            let Ok(canon_source_path) = source_path.canonicalize() else {
+                debug!(
+                    path = %source_path.display(),
+                    operation,
+                    "file_operations:skipped missing path"
                 );
+                continue;
+            };
+            if !canon_source_path.starts_with(canon_repo_root) {
+                return Err(anyhow!(
+                    "Path traversal detected for {} pattern '{}'. The resolved path '{}' is outside the repository root.",
+                    operation,
+                    pattern,
+                    source_path.display()
+                ));
+            }
+
+            matched = true;
+            if !paths.contains(&source_path) {
+                paths.push(source_path);
             }
         }
+        if !matched {
+            debug!(
+                pattern,
+                operation, "file_operations:pattern matched nothing"
+            );
+        }
     }
+    Ok(paths)
+}
 
-    Ok(())
+/// Drop symlink sources that are also copied, so copying wins for paths in both lists
+fn without_copied(symlink_paths: Vec<PathBuf>, copy_paths: &[PathBuf]) -> Vec<PathBuf> {
+    symlink_paths
+        .into_iter()
+        .filter(|path| {
+            let copied = copy_paths.contains(path);
+            if copied {
+                debug!(path = %path.display(), "file_operations:copied instead of symlinked");
+            }
+            !copied
+        })
+        .collect()
 }
 
 pub fn write_prompt_file(branch_name: &str, prompt: &Prompt) -> Result<PathBuf> {
-    let content = match prompt {
-        Prompt::Inline(text) => text.clone(),
-        Prompt::FromFile(path) => fs::read_to_string(path)
-            .with_context(|| format!("Failed to read prompt file '{}'", path.display()))?,
-    };
+    let content = prompt.read_text()?;
 
-    // Write to temp directory instead of the worktree to avoid polluting git status
-    let prompt_filename = format!("workmux-prompt-{}.md", branch_name);
+    // Write to temp directory instead of the worktree to avoid polluting git status.
+    // Slashes in branch names (e.g. `fix/login`) would otherwise point into missing directories.
+    let prompt_filename = format!("workmux-prompt-{}.md", branch_name.replace('/', "-"));
     let prompt_path = std::env::temp_dir().join(prompt_filename);
-    fs::write(&prompt_path, content)
-        .with_context(|| format!("Failed to write prompt file '{}'", prompt_path.display()))?;
+    prompt::write_prompt_file(&prompt_path, &content)?;
     Ok(prompt_path)
 }
 
+/// A new file for `agent_record` to record the agent session of `branch_name`
+/// to, under `~/.workmux/recordings`. The start time in the name keeps a
+/// reopened worktree from overwriting its earlier sessions.
+fn recording_path(branch_name: &str) -> Result<PathBuf> {
+    let dir = home::home_dir()
+        .map(|home| home.join(".workmux"))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("recordings");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create recordings directory {}", dir.display()))?;
+
+    let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Ok(dir.join(format!("{}-{}.cast", branch_name.replace('/', "-"), started)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::workflow::types::SetupOptions;
+
+    #[test]
+    fn agent_prompt_path_is_withheld_without_rewrite() {
+        let mut options = SetupOptions::with_prompt(
+            true,
+            true,
+            true,
+            Some(PathBuf::from("/tmp/workmux-prompt-feature.md")),
+        );
+        assert_eq!(
+            options.agent_prompt_path(),
+            Some(Path::new("/tmp/workmux-prompt-feature.md"))
+        );
+
+        options.rewrite_agent_prompt = false;
+        assert_eq!(options.agent_prompt_path(), None);
+    }
+
+    #[test]
+    fn poll_until_ready_succeeds_after_several_attempts() {
+        let mut attempts = 0;
+        let ready = poll_until_ready(
+            || {
+                attempts += 1;
+                attempts == 3
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(1),
+        );
+        assert!(ready);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn poll_until_ready_gives_up_after_timeout() {
+        let mut attempts = 0;
+        let ready = poll_until_ready(
+            || {
+                attempts += 1;
+                false
+            },
+            Duration::from_millis(50),
+            Duration::from_millis(10),
+        );
+        assert!(!ready);
+        assert!(attempts >= 2);
+    }
 
     #[test]
     fn resolve_pane_configuration_no_agent_returns_original() {
@@ -350,9 +646,13 @@ mod tests {
             size: None,
             percentage: None,
             target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
         }];
 
-        let result = resolve_pane_configuration(&original_panes, None);
+        let result = resolve_pane_configuration(&original_panes, None, "<agent>");
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].command, Some("vim".to_string()));
     }
@@ -366,9 +666,13 @@ mod tests {
             size: None,
             percentage: None,
             target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
         }];
 
-        let result = resolve_pane_configuration(&original_panes, Some("claude"));
+        let result = resolve_pane_configuration(&original_panes, Some("claude"), "<agent>");
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].command, Some("<agent>".to_string()));
     }
@@ -383,6 +687,10 @@ mod tests {
                 size: None,
                 percentage: None,
                 target: None,
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
             config::PaneConfig {
                 command: Some("npm run dev".to_string()),
@@ -391,10 +699,14 @@ mod tests {
                 size: None,
                 percentage: None,
                 target: None,
+                keep_open: true,
+                env: None,
+                shell: None,
+                close_tab_on_exit: false,
             },
         ];
 
-        let result = resolve_pane_configuration(&original_panes, Some("claude"));
+        let result = resolve_pane_configuration(&original_panes, Some("claude"), "<agent>");
         assert_eq!(result[0].command, Some("vim".to_string()));
         assert_eq!(result[1].command, Some("claude".to_string()));
     }
@@ -408,17 +720,101 @@ mod tests {
             size: None,
             percentage: None,
             target: None,
+            keep_open: true,
+            env: None,
+            shell: None,
+            close_tab_on_exit: false,
         }];
 
-        let result = resolve_pane_configuration(&original_panes, Some("claude"));
+        let result = resolve_pane_configuration(&original_panes, Some("claude"), "<agent>");
         assert_eq!(result[0].command, Some("claude".to_string()));
     }
 
     #[test]
     fn resolve_pane_configuration_agent_creates_new_pane_when_empty() {
-        let result = resolve_pane_configuration(&[], Some("claude"));
+        let result = resolve_pane_configuration(&[], Some("claude"), "<agent>");
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].command, Some("claude".to_string()));
         assert!(result[0].focus);
     }
+
+    /// Repo with `.env.local`, `.env.test`, `config/{a.local,b}.json`, and a broken symlink
+    fn file_ops_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".env.local"), "LOCAL=1\n").unwrap();
+        fs::write(root.join(".env.test"), "TEST=1\n").unwrap();
+        fs::create_dir(root.join("config")).unwrap();
+        fs::write(root.join("config/a.local.json"), "{}").unwrap();
+        fs::write(root.join("config/b.json"), "{}").unwrap();
+        std::os::unix::fs::symlink("missing-target", root.join(".env.broken")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn expand_file_patterns_resolves_globs_relative_to_repo_root() {
+        let repo = file_ops_fixture();
+        let root = repo.path();
+        let patterns = vec![
+            ".env.*".to_string(),
+            "config/*.local.json".to_string(),
+            ".env.local".to_string(),
+            "does-not-exist.txt".to_string(),
+        ];
+
+        let paths =
+            expand_file_patterns(root, &root.canonicalize().unwrap(), &patterns, "copy").unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                root.join(".env.local"),
+                root.join(".env.test"),
+                root.join("config/a.local.json"),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_file_patterns_rejects_paths_outside_repo() {
+        let repo = file_ops_fixture();
+        let root = repo.path().join("config");
+        let err = expand_file_patterns(
+            &root,
+            &root.canonicalize().unwrap(),
+            &["../.env.local".to_string()],
+            "symlink",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Path traversal detected for symlink pattern")
+        );
+    }
+
+    #[test]
+    fn handle_file_operations_copies_paths_matched_by_both_lists() {
+        let repo = file_ops_fixture();
+        let worktree = tempfile::tempdir().unwrap();
+        let file_config = config::FileConfig {
+            copy: Some(vec![".env.*".to_string()]),
+            symlink: Some(vec![".env.local".to_string(), "config".to_string()]),
+        };
+
+        handle_file_operations(repo.path(), worktree.path(), &file_config).unwrap();
+
+        let env_local = worktree.path().join(".env.local");
+        assert!(!env_local.symlink_metadata().unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(env_local).unwrap(), "LOCAL=1\n");
+        assert!(worktree.path().join(".env.test").is_file());
+        assert!(!worktree.path().join(".env.broken").exists());
+        assert!(
+            worktree
+                .path()
+                .join("config")
+                .symlink_metadata()
+                .unwrap()
+                .is_symlink()
+        );
+    }
 }
@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use crate::git;
+
+use super::context::WorkflowContext;
+use super::types::BranchStatus;
+
+/// Report ahead/behind counts and local changes for a branch's worktree.
+///
+/// Counts are relative to the base the branch was created from, falling back
+/// to the main branch when no base was recorded.
+pub fn status(branch_name: &str, context: &WorkflowContext) -> Result<BranchStatus> {
+    let path = context
+        .worktrees
+        .path_for(branch_name)
+        .with_context(|| format!("No worktree found for branch '{}'", branch_name))?;
+
+    let base = context.branch_base(branch_name);
+    let (ahead, behind) = git::ahead_behind(&base, branch_name)?;
+    debug!(branch = branch_name, base = %base, ahead, behind, "status:counts");
+
+    Ok(BranchStatus {
+        branch: branch_name.to_string(),
+        base,
+        ahead,
+        behind,
+        dirty: git::has_uncommitted_changes(&path, context.config.ignore_dirty_paths())?,
+        staged: git::has_staged_changes(&path)?,
+        path,
+    })
+}
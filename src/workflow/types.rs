@@ -1,4 +1,8 @@
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::github::PrSummary;
 
 /// Result of creating a worktree
 pub struct CreateResult {
@@ -6,28 +10,145 @@ pub struct CreateResult {
     pub branch_name: String,
     pub post_create_hooks_run: usize,
     pub base_branch: Option<String>,
+    /// Where the prompt was written, if one was given
+    pub prompt_file_path: Option<PathBuf>,
 }
 
 /// Result of merging a worktree
+#[derive(Debug, Serialize)]
 pub struct MergeResult {
     pub branch_merged: String,
-    pub main_branch: String,
+    pub target_branch: String,
     pub had_staged_changes: bool,
+    /// The branch was already merged into the target, so nothing was merged
+    pub already_merged: bool,
+    /// What was cleaned up afterwards; None when the worktree was kept
+    pub cleanup: Option<CleanupResult>,
+    /// Stash still holding the source worktree's unstaged changes, when `stash`
+    /// was set and they could not be restored
+    pub stash: Option<String>,
+}
+
+/// Options controlling how a branch is merged
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    pub ignore_uncommitted: bool,
+    pub delete_remote: bool,
+    pub rebase: bool,
+    pub squash: bool,
+    pub keep: bool,
+    /// Show the commits that a rebase will replay and ask before continuing
+    pub preview: bool,
+    /// Stash unstaged changes in the source worktree for the duration of the merge
+    pub stash: bool,
+    /// With `keep`, switch to the target branch's window afterwards
+    pub open: bool,
+    /// Merge even if the target worktree has uncommitted changes
+    pub force_dirty_target: bool,
+    /// Create the target branch if it doesn't exist locally or on any remote
+    pub create_target: bool,
+}
+
+/// Result of opening windows for every worktree that lacks one
+pub struct OpenAllResult {
+    /// Branches whose window was opened
+    pub opened: Vec<String>,
+    /// Branches that already had a window
+    pub skipped: Vec<String>,
+    /// Branches whose window could not be opened, with the reason
+    pub failed: Vec<(String, anyhow::Error)>,
 }
 
 /// Result of removing a worktree
+#[derive(Debug, Serialize)]
 pub struct RemoveResult {
     pub branch_removed: String,
+    pub cleanup: CleanupResult,
+}
+
+/// Result of fetching and fast-forwarding a single worktree
+pub struct FetchResult {
+    pub branch: String,
+    pub path: PathBuf,
+    pub outcome: anyhow::Result<crate::git::FastForwardOutcome>,
 }
 
 /// Result of cleanup operations
+#[derive(Debug, Serialize)]
 pub struct CleanupResult {
+    #[serde(rename = "window_closed")]
     pub tmux_window_killed: bool,
     pub worktree_removed: bool,
     pub local_branch_deleted: bool,
     pub remote_branch_deleted: bool,
     pub remote_delete_error: Option<String>,
     pub ran_inside_target_window: bool,
+    /// Why the window/tab wasn't closed, when it wasn't
+    pub window_skip_reason: Option<String>,
+    /// Why the worktree directory wasn't removed, when it wasn't
+    pub worktree_skip_reason: Option<String>,
+    /// Why the local branch wasn't deleted, when it wasn't
+    pub local_branch_skip_reason: Option<String>,
+    /// Why the remote branch wasn't deleted, when deletion was requested but skipped
+    pub remote_branch_skip_reason: Option<String>,
+}
+
+impl CleanupResult {
+    /// One line saying what cleanup did and skipped, e.g. "worktree removed,
+    /// local branch deleted, remote branch skipped (not pushed), tab closed"
+    pub fn summary(&self, window_term: &str) -> String {
+        let remote = if self.remote_delete_error.is_some() {
+            Some("remote branch deletion failed".to_string())
+        } else {
+            describe_step(
+                "remote branch",
+                self.remote_branch_deleted,
+                "deleted",
+                self.remote_branch_skip_reason.as_deref(),
+            )
+        };
+        let window = if self.ran_inside_target_window {
+            Some(format!("{} closing", window_term))
+        } else {
+            describe_step(
+                window_term,
+                self.tmux_window_killed,
+                "closed",
+                self.window_skip_reason.as_deref(),
+            )
+        };
+
+        [
+            describe_step(
+                "worktree",
+                self.worktree_removed,
+                "removed",
+                self.worktree_skip_reason.as_deref(),
+            ),
+            describe_step(
+                "local branch",
+                self.local_branch_deleted,
+                "deleted",
+                self.local_branch_skip_reason.as_deref(),
+            ),
+            remote,
+            window,
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// "<name> <verb>" for a step that ran, "<name> skipped (<reason>)" for one
+/// that was skipped, and nothing for one that wasn't attempted
+fn describe_step(name: &str, done: bool, verb: &str, skip_reason: Option<&str>) -> Option<String> {
+    if done {
+        Some(format!("{} {}", name, verb))
+    } else {
+        skip_reason.map(|reason| format!("{} skipped ({})", name, reason))
+    }
 }
 
 /// Options for setting up a worktree environment
@@ -37,8 +158,18 @@ pub struct SetupOptions {
     pub run_file_ops: bool,
     pub run_pane_commands: bool,
     pub prompt_file_path: Option<PathBuf>,
+    /// If true, append the prompt file's contents to the agent command
+    pub rewrite_agent_prompt: bool,
+    /// Extra arguments appended to the agent command, before the prompt
+    pub agent_args: Vec<String>,
+    /// Create the branch with no history, in an empty worktree
+    pub orphan: bool,
+    /// Seed the worktree's `.workmux.local.yaml` with the effective config
+    pub copy_config: bool,
     /// If true, switch to the new tmux window when done; if false, leave it in the background.
     pub focus_window: bool,
+    /// Index of the pane to focus instead of the configured `focus` pane
+    pub select_pane: Option<usize>,
 }
 
 impl SetupOptions {
@@ -50,7 +181,12 @@ impl SetupOptions {
             run_file_ops: true,
             run_pane_commands: true,
             prompt_file_path: None,
+            rewrite_agent_prompt: true,
+            agent_args: Vec::new(),
+            orphan: false,
+            copy_config: false,
             focus_window: true,
+            select_pane: None,
         }
     }
 
@@ -61,7 +197,12 @@ impl SetupOptions {
             run_file_ops,
             run_pane_commands,
             prompt_file_path: None,
+            rewrite_agent_prompt: true,
+            agent_args: Vec::new(),
+            orphan: false,
+            copy_config: false,
             focus_window: true,
+            select_pane: None,
         }
     }
 
@@ -78,9 +219,21 @@ impl SetupOptions {
             run_file_ops,
             run_pane_commands,
             prompt_file_path,
+            rewrite_agent_prompt: true,
+            agent_args: Vec::new(),
+            orphan: false,
+            copy_config: false,
             focus_window: true,
+            select_pane: None,
         }
     }
+
+    /// The prompt file to inject into the agent command, if rewriting is enabled
+    pub fn agent_prompt_path(&self) -> Option<&Path> {
+        self.prompt_file_path
+            .as_deref()
+            .filter(|_| self.rewrite_agent_prompt)
+    }
 }
 
 /// List all worktrees with their status
@@ -89,4 +242,99 @@ pub struct WorktreeInfo {
     pub path: PathBuf,
     pub has_tmux: bool,
     pub has_unmerged: bool,
+    /// Pull request for the branch, only looked up when PR status is requested
+    pub pr: Option<PrSummary>,
+    /// Where the worktree belongs under the configured `worktree_dir`, when it
+    /// currently lives outside it
+    pub outside_worktree_dir: Option<PathBuf>,
+    /// Time and subject of the checked-out commit, only looked up for the long listing
+    pub last_commit: Option<(SystemTime, String)>,
+}
+
+/// Result of pruning worktrees whose directories were deleted externally
+pub struct PruneResult {
+    /// Worktrees (path, branch) whose stale git metadata was removed
+    pub pruned_worktrees: Vec<(PathBuf, String)>,
+    /// Windows/tabs (unprefixed) that were closed because no worktree backs them
+    pub closed_windows: Vec<String>,
+    /// Branches left behind by the pruned worktrees
+    pub orphaned_branches: Vec<String>,
+    /// Whether `orphaned_branches` were deleted
+    pub branches_deleted: bool,
+}
+
+/// Where a worktree's branch stands relative to its base, and whether it has local changes
+#[derive(Debug, Serialize)]
+pub struct BranchStatus {
+    pub branch: String,
+    /// Branch the counts are relative to: the recorded base, or the main branch
+    pub base: String,
+    /// Commits on the branch that aren't on the base
+    pub ahead: usize,
+    /// Commits on the base that aren't on the branch
+    pub behind: usize,
+    /// Uncommitted changes to tracked files, or untracked files
+    pub dirty: bool,
+    /// Changes staged in the index
+    pub staged: bool,
+    pub path: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CleanupResult;
+
+    fn cleanup_result() -> CleanupResult {
+        CleanupResult {
+            tmux_window_killed: true,
+            worktree_removed: true,
+            local_branch_deleted: true,
+            remote_branch_deleted: false,
+            remote_delete_error: None,
+            ran_inside_target_window: false,
+            window_skip_reason: None,
+            worktree_skip_reason: None,
+            local_branch_skip_reason: None,
+            remote_branch_skip_reason: None,
+        }
+    }
+
+    #[test]
+    fn summary_lists_completed_steps_and_omits_unrequested_ones() {
+        assert_eq!(
+            cleanup_result().summary("tab"),
+            "worktree removed, local branch deleted, tab closed"
+        );
+    }
+
+    #[test]
+    fn summary_reports_skip_reasons() {
+        let result = CleanupResult {
+            tmux_window_killed: false,
+            local_branch_deleted: false,
+            local_branch_skip_reason: Some("kept".to_string()),
+            remote_branch_skip_reason: Some("not pushed".to_string()),
+            window_skip_reason: Some("none open".to_string()),
+            ..cleanup_result()
+        };
+        assert_eq!(
+            result.summary("window"),
+            "worktree removed, local branch skipped (kept), \
+             remote branch skipped (not pushed), window skipped (none open)"
+        );
+    }
+
+    #[test]
+    fn summary_reports_remote_failure_and_deferred_close() {
+        let result = CleanupResult {
+            tmux_window_killed: false,
+            ran_inside_target_window: true,
+            remote_delete_error: Some("Failed to delete remote branch".to_string()),
+            ..cleanup_result()
+        };
+        assert_eq!(
+            result.summary("tab"),
+            "worktree removed, local branch deleted, remote branch deletion failed, tab closing"
+        );
+    }
 }
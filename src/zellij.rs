@@ -1,5 +1,4 @@
 use anyhow::{Context, Result, anyhow};
-use std::borrow::Cow;
 use std::collections::HashSet;
 use std::path::Path;
 use std::process::Command;
@@ -7,38 +6,97 @@ use std::time::Duration;
 
 use crate::cmd::Cmd;
 use crate::config::PaneConfig;
+use crate::multiplexer::{self, Multiplexer, PaneSetupOptions, prefixed, shell_quote};
 
-/// Helper function to add prefix to tab name
-pub fn prefixed(prefix: &str, tab_name: &str) -> String {
-    format!("{}{}", prefix, tab_name)
+/// Zellij backend for [`Multiplexer`]
+pub struct Zellij {
+    /// Named session to drive instead of the one inherited via `ZELLIJ`
+    session: Option<String>,
 }
 
-/// Get all zellij tab names in the current session
-pub fn get_all_tab_names() -> Result<HashSet<String>> {
+impl Zellij {
+    pub fn new(session: Option<String>) -> Self {
+        Self { session }
+    }
+}
+
+/// Arguments for `zellij [--session <session>] action <action...>`
+fn action_args<'a>(session: Option<&'a str>, action: &[&'a str]) -> Vec<&'a str> {
+    let mut args = Vec::with_capacity(action.len() + 3);
+    if let Some(session) = session {
+        args.extend(["--session", session]);
+    }
+    args.push("action");
+    args.extend_from_slice(action);
+    args
+}
+
+/// Run `zellij action <action...>` against `session`
+fn action<'a>(session: Option<&'a str>, action: &[&'a str]) -> Cmd<'a> {
+    Cmd::new("zellij").args(&action_args(session, action))
+}
+
+/// Shell prefix for `zellij action` in generated scripts, e.g. `zellij --session 'dev' action`
+fn action_prefix(session: Option<&str>) -> String {
+    match session {
+        Some(session) => format!("zellij --session {} action", shell_quote(session)),
+        None => "zellij action".to_string(),
+    }
+}
+
+/// Get all zellij tab names in the session
+pub fn get_all_tab_names(session: Option<&str>) -> Result<HashSet<String>> {
     // zellij action query-tab-names returns tab names, one per line
-    let tabs = Cmd::new("zellij")
-        .args(&["action", "query-tab-names"])
+    let tabs = action(session, &["query-tab-names"])
         .run_and_capture_stdout()
         .unwrap_or_default();
 
     Ok(tabs.lines().map(String::from).collect())
 }
 
-/// Check if zellij is running (inside a zellij session)
-pub fn is_running() -> Result<bool> {
-    // Check if ZELLIJ environment variable is set, which indicates we're in a session
-    Ok(std::env::var("ZELLIJ").is_ok())
+/// Check if zellij is running: the named session exists, or we're inside a session
+pub fn is_running(session: Option<&str>) -> Result<bool> {
+    let Some(session) = session else {
+        // Check if ZELLIJ environment variable is set, which indicates we're in a session
+        return Ok(std::env::var("ZELLIJ").is_ok());
+    };
+
+    let sessions = Cmd::new("zellij")
+        .args(&["list-sessions", "--short", "--no-formatting"])
+        .run_and_capture_stdout()
+        .unwrap_or_default();
+    Ok(sessions.lines().any(|name| name.trim() == session))
+}
+
+/// Fail unless the named session exists, so `--zellij-session` typos are
+/// reported before any work is done
+pub fn ensure_session_exists(session: &str) -> Result<()> {
+    if is_running(Some(session))? {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "zellij session '{}' not found. Start it with `zellij attach --create {}` or check `zellij list-sessions`.",
+        session,
+        session
+    ))
 }
 
 /// Check if a zellij tab with the given name exists
-pub fn tab_exists(prefix: &str, tab_name: &str) -> Result<bool> {
+pub fn tab_exists(session: Option<&str>, prefix: &str, tab_name: &str) -> Result<bool> {
     let prefixed_name = prefixed(prefix, tab_name);
-    let tabs = get_all_tab_names()?;
+    let tabs = get_all_tab_names(session)?;
     Ok(tabs.contains(&prefixed_name))
 }
 
 /// Return the zellij tab name for the current tab, if any
-pub fn current_tab_name() -> Result<Option<String>> {
+pub fn current_tab_name(session: Option<&str>) -> Result<Option<String>> {
+    // Our own tab means nothing in a different session
+    if let Some(session) = session
+        && std::env::var("ZELLIJ_SESSION_NAME").ok().as_deref() != Some(session)
+    {
+        return Ok(None);
+    }
+
     // ZELLIJ_TAB_NAME environment variable contains the current tab name
     match std::env::var("ZELLIJ_TAB_NAME") {
         Ok(name) if !name.is_empty() => Ok(Some(name)),
@@ -50,6 +108,7 @@ pub fn current_tab_name() -> Result<Option<String>> {
 ///
 /// When `detached` is true, the tab is created but focus returns to the original tab.
 pub fn create_tab(
+    session: Option<&str>,
     prefix: &str,
     tab_name: &str,
     working_dir: &Path,
@@ -62,28 +121,28 @@ pub fn create_tab(
 
     // Remember current tab if we need to return to it
     let original_tab = if detached {
-        current_tab_name().ok().flatten()
+        current_tab_name(session).ok().flatten()
     } else {
         None
     };
 
     // Create the new tab (zellij automatically focuses it)
-    Cmd::new("zellij")
-        .args(&[
-            "action",
+    action(
+        session,
+        &[
             "new-tab",
             "--name",
             &prefixed_name,
             "--cwd",
             working_dir_str,
-        ])
-        .run()
-        .context("Failed to create zellij tab")?;
+        ],
+    )
+    .run()
+    .context("Failed to create zellij tab")?;
 
     // If detached mode, switch back to the original tab
     if let Some(orig_tab) = original_tab {
-        Cmd::new("zellij")
-            .args(&["action", "go-to-tab-name", &orig_tab])
+        action(session, &["go-to-tab-name", &orig_tab])
             .run()
             .context("Failed to return to original tab")?;
     }
@@ -92,11 +151,10 @@ pub fn create_tab(
 }
 
 /// Select a specific tab by name
-pub fn select_tab(prefix: &str, tab_name: &str) -> Result<()> {
+pub fn select_tab(session: Option<&str>, prefix: &str, tab_name: &str) -> Result<()> {
     let prefixed_name = prefixed(prefix, tab_name);
 
-    Cmd::new("zellij")
-        .args(&["action", "go-to-tab-name", &prefixed_name])
+    action(session, &["go-to-tab-name", &prefixed_name])
         .run()
         .context("Failed to select tab")?;
 
@@ -104,18 +162,16 @@ pub fn select_tab(prefix: &str, tab_name: &str) -> Result<()> {
 }
 
 /// Close a zellij tab by navigating to it and closing it
-pub fn close_tab(prefix: &str, tab_name: &str) -> Result<()> {
+pub fn close_tab(session: Option<&str>, prefix: &str, tab_name: &str) -> Result<()> {
     let prefixed_name = prefixed(prefix, tab_name);
 
     // First navigate to the tab
-    Cmd::new("zellij")
-        .args(&["action", "go-to-tab-name", &prefixed_name])
+    action(session, &["go-to-tab-name", &prefixed_name])
         .run()
         .context("Failed to navigate to tab for closing")?;
 
     // Then close the current tab
-    Cmd::new("zellij")
-        .args(&["action", "close-tab"])
+    action(session, &["close-tab"])
         .run()
         .context("Failed to close zellij tab")?;
 
@@ -124,129 +180,136 @@ pub fn close_tab(prefix: &str, tab_name: &str) -> Result<()> {
 
 /// Schedule a zellij tab to be closed after a short delay. This is useful when
 /// the current command is running inside the tab that needs to close.
-pub fn schedule_tab_close(prefix: &str, tab_name: &str, delay: Duration) -> Result<()> {
-    let prefixed_name = prefixed(prefix, tab_name);
-    let delay_secs = format!("{:.3}", delay.as_secs_f64());
+pub fn schedule_tab_close(
+    session: Option<&str>,
+    prefix: &str,
+    tab_name: &str,
+    delay: Duration,
+) -> Result<()> {
+    let script = tab_close_script(session, &prefixed(prefix, tab_name), delay);
 
     // Use nohup with shell to run asynchronously since zellij has no run-shell equivalent
-    let script = format!(
-        r#"sleep {delay}; zellij action go-to-tab-name "{tab}" 2>/dev/null && zellij action close-tab 2>/dev/null"#,
-        delay = delay_secs,
-        tab = prefixed_name
-    );
-
-    Command::new("sh")
-        .args(["-c", &format!("nohup sh -c '{}' >/dev/null 2>&1 &", script)])
-        .spawn()
-        .context("Failed to schedule tab close")?;
-
-    Ok(())
+    spawn_detached(&script).context("Failed to schedule tab close")
 }
 
-/// Builds a shell command string that executes an optional user command
-/// and then leaves an interactive shell open.
-pub fn build_startup_command(command: Option<&str>) -> Result<Option<String>> {
-    let command = match command {
-        Some(c) => c,
-        None => return Ok(None),
-    };
+/// Schedule navigation to one tab followed by closing another, after a delay
+pub fn schedule_select_and_close(
+    session: Option<&str>,
+    prefix: &str,
+    select_name: &str,
+    close_name: &str,
+    delay: Duration,
+) -> Result<()> {
+    let script = select_and_close_script(
+        session,
+        &prefixed(prefix, select_name),
+        &prefixed(prefix, close_name),
+        delay,
+    );
 
-    let shell_path = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-    let shell_name = std::path::Path::new(&shell_path)
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("");
-
-    // Manually trigger shell pre-prompt hooks to ensure tools like direnv,
-    // nvm, and rbenv are loaded before the user command is executed.
-    let pre_command_hook = match shell_name {
-        "zsh" => {
-            "if (( ${#precmd_functions[@]} )); then for f in \"${precmd_functions[@]}\"; do \"$f\"; done; fi"
-        }
-        "bash" => "eval \"${PROMPT_COMMAND:-}\"",
-        "fish" => "functions -q fish_prompt; and emit fish_prompt",
-        _ => "true",
-    };
+    spawn_detached(&script).context("Failed to schedule tab navigation and close")
+}
 
-    let escaped_command = command.replace('\'', r#"'\''"#);
+/// Script that waits `delay`, then closes the tab named `tab`
+fn tab_close_script(session: Option<&str>, tab: &str, delay: Duration) -> String {
+    format!(
+        "sleep {delay:.3}; {zellij} go-to-tab-name {tab} 2>/dev/null && {zellij} close-tab 2>/dev/null",
+        delay = delay.as_secs_f64(),
+        zellij = action_prefix(session),
+        tab = shell_quote(tab),
+    )
+}
 
-    let inner_command = format!(
-        "{pre_hook}; {user_cmd}; exec {shell} -l",
-        pre_hook = pre_command_hook,
-        user_cmd = escaped_command,
-        shell = shell_path,
-    );
+/// Script that waits `delay`, switches to `select`, then closes `close`
+fn select_and_close_script(
+    session: Option<&str>,
+    select: &str,
+    close: &str,
+    delay: Duration,
+) -> String {
+    format!(
+        "sleep {delay:.3}; {zellij} go-to-tab-name {select} 2>/dev/null; {zellij} go-to-tab-name {close} 2>/dev/null && {zellij} close-tab 2>/dev/null",
+        delay = delay.as_secs_f64(),
+        zellij = action_prefix(session),
+        select = shell_quote(select),
+        close = shell_quote(close),
+    )
+}
 
-    let full_command = format!(
-        "{shell} -ic '{inner_command}'",
-        shell = shell_path,
-        inner_command = inner_command,
-    );
+/// Closes the tab named `tab`. zellij can only close the focused tab, so this
+/// switches to it first.
+fn close_tab_command(session: Option<&str>, tab: &str) -> String {
+    format!(
+        "{zellij} go-to-tab-name {tab} && {zellij} close-tab",
+        zellij = action_prefix(session),
+        tab = shell_quote(tab),
+    )
+}
 
-    Ok(Some(full_command))
+/// Run a script in the background, detached from this process
+fn spawn_detached(script: &str) -> Result<()> {
+    Command::new("sh")
+        .args([
+            "-c",
+            &format!("nohup sh -c {} >/dev/null 2>&1 &", shell_quote(script)),
+        ])
+        .spawn()?;
+    Ok(())
 }
 
 /// Run a command in the current tab by creating a new pane and running it
-pub fn run_command_in_tab(working_dir: &Path, command: &str) -> Result<()> {
+pub fn run_command_in_tab(session: Option<&str>, working_dir: &Path, command: &str) -> Result<()> {
     let working_dir_str = working_dir
         .to_str()
         .ok_or_else(|| anyhow!("Working directory path contains non-UTF8 characters"))?;
 
     // Use zellij action new-pane with -- to run a command
     // Since we're in a single-pane model, we run in the existing pane context
-    Cmd::new("zellij")
-        .args(&["action", "new-pane", "--cwd", working_dir_str, "--", "sh", "-c", command])
-        .run()
-        .context("Failed to run command in tab")?;
+    action(
+        session,
+        &[
+            "new-pane",
+            "--cwd",
+            working_dir_str,
+            "--",
+            "sh",
+            "-c",
+            command,
+        ],
+    )
+    .run()
+    .context("Failed to run command in tab")?;
 
     Ok(())
 }
 
-/// Result of setting up the tab
-pub struct TabSetupResult {
-    // Placeholder for future use
-    _private: (),
-}
-
-pub struct TabSetupOptions<'a> {
-    pub run_commands: bool,
-    pub prompt_file_path: Option<&'a Path>,
-}
-
 /// Setup a single pane in a tab according to configuration (simplified from tmux multi-pane)
 pub fn setup_tab(
+    session: Option<&str>,
+    tab_name: &str,
     panes: &[PaneConfig],
     working_dir: &Path,
-    options: TabSetupOptions<'_>,
-    config: &crate::config::Config,
-    task_agent: Option<&str>,
-) -> Result<TabSetupResult> {
+    options: PaneSetupOptions<'_>,
+) -> Result<()> {
     if panes.is_empty() || !options.run_commands {
-        return Ok(TabSetupResult { _private: () });
+        return Ok(());
     }
 
-    let effective_agent = task_agent.or(config.agent.as_deref());
-
     // Use only the first pane configuration (simplified single-pane model)
-    if let Some(pane_config) = panes.first() {
-        let command_to_run = if pane_config.command.as_deref() == Some("<agent>") {
-            effective_agent.map(|agent_cmd| agent_cmd.to_string())
-        } else {
-            pane_config.command.clone()
-        };
-
-        if let Some(ref cmd) = command_to_run {
-            let adjusted_command = adjust_command(
-                cmd,
-                options.prompt_file_path,
-                working_dir,
-                effective_agent,
-            );
-
-            if let Some(startup_cmd) = build_startup_command(Some(&adjusted_command))? {
-                run_command_in_tab(working_dir, &startup_cmd)?;
-            }
-        }
+    if let Some(pane_config) = panes.first()
+        && let Some(command) = multiplexer::close_on_exit(
+            pane_config,
+            multiplexer::resolve_pane_command(pane_config, &options, working_dir),
+            &close_tab_command(session, tab_name),
+        )
+        && let Some(startup_cmd) = multiplexer::build_startup_command(
+            Some(&command),
+            pane_config.keep_open,
+            pane_config.env.as_ref(),
+            pane_config.shell.as_deref().or(options.startup_shell),
+        )?
+    {
+        run_command_in_tab(session, working_dir, &startup_cmd)?;
     }
 
     // Warn if multi-pane config detected
@@ -256,117 +319,230 @@ pub fn setup_tab(
         );
     }
 
-    Ok(TabSetupResult { _private: () })
+    Ok(())
 }
 
-fn adjust_command<'a>(
-    command: &'a str,
-    prompt_file_path: Option<&Path>,
-    working_dir: &Path,
-    effective_agent: Option<&str>,
-) -> Cow<'a, str> {
-    if let Some(prompt_path) = prompt_file_path
-        && let Some(rewritten) =
-            rewrite_agent_command(command, prompt_path, working_dir, effective_agent)
-    {
-        return Cow::Owned(rewritten);
+impl Multiplexer for Zellij {
+    fn name(&self) -> &'static str {
+        "zellij"
     }
-    Cow::Borrowed(command)
-}
 
-/// Rewrites an agent command to inject a prompt file's contents.
-fn rewrite_agent_command(
-    command: &str,
-    prompt_file: &Path,
-    working_dir: &Path,
-    effective_agent: Option<&str>,
-) -> Option<String> {
-    let agent_command = effective_agent?;
-    let trimmed_command = command.trim();
-    if trimmed_command.is_empty() {
-        return None;
+    fn window_term(&self) -> &'static str {
+        "tab"
     }
 
-    let (pane_token, pane_rest) = crate::config::split_first_token(trimmed_command)?;
-    let (config_token, _) = crate::config::split_first_token(agent_command)?;
+    fn is_running(&self) -> Result<bool> {
+        is_running(self.session.as_deref())
+    }
 
-    let resolved_pane_path = crate::config::resolve_executable_path(pane_token)
-        .unwrap_or_else(|| pane_token.to_string());
-    let resolved_config_path = crate::config::resolve_executable_path(config_token)
-        .unwrap_or_else(|| config_token.to_string());
+    fn list_names(&self) -> Result<HashSet<String>> {
+        get_all_tab_names(self.session.as_deref())
+    }
 
-    let pane_stem = Path::new(&resolved_pane_path).file_stem();
-    let config_stem = Path::new(&resolved_config_path).file_stem();
+    fn exists(&self, prefix: &str, name: &str) -> Result<bool> {
+        tab_exists(self.session.as_deref(), prefix, name)
+    }
 
-    if pane_stem != config_stem {
-        return None;
+    fn current_name(&self) -> Result<Option<String>> {
+        current_tab_name(self.session.as_deref())
     }
 
-    let relative = prompt_file.strip_prefix(working_dir).unwrap_or(prompt_file);
-    let prompt_path = relative.to_string_lossy();
-    let rest = pane_rest.trim_start();
+    fn create(&self, prefix: &str, name: &str, working_dir: &Path, detached: bool) -> Result<()> {
+        create_tab(self.session.as_deref(), prefix, name, working_dir, detached)
+    }
 
-    let mut cmd = pane_token.to_string();
+    fn select(&self, prefix: &str, name: &str) -> Result<()> {
+        select_tab(self.session.as_deref(), prefix, name)
+    }
 
-    if !rest.is_empty() {
-        cmd.push(' ');
-        cmd.push_str(rest);
+    fn close(&self, prefix: &str, name: &str) -> Result<()> {
+        close_tab(self.session.as_deref(), prefix, name)
     }
 
-    let is_gemini = pane_stem.and_then(|s| s.to_str()) == Some("gemini");
-    if is_gemini {
-        cmd.push_str(&format!(" -i \"$(cat {})\"", prompt_path));
-    } else {
-        cmd.push_str(&format!(" -- \"$(cat {})\"", prompt_path));
+    fn schedule_close(&self, prefix: &str, name: &str, delay: Duration) -> Result<()> {
+        schedule_tab_close(self.session.as_deref(), prefix, name, delay)
     }
 
-    Some(cmd)
+    fn schedule_select_and_close(
+        &self,
+        prefix: &str,
+        select_name: &str,
+        close_name: &str,
+        delay: Duration,
+    ) -> Result<()> {
+        schedule_select_and_close(
+            self.session.as_deref(),
+            prefix,
+            select_name,
+            close_name,
+            delay,
+        )
+    }
+
+    fn setup_panes(
+        &self,
+        prefix: &str,
+        name: &str,
+        panes: &[PaneConfig],
+        working_dir: &Path,
+        options: PaneSetupOptions<'_>,
+    ) -> Result<()> {
+        setup_tab(
+            self.session.as_deref(),
+            &prefixed(prefix, name),
+            panes,
+            working_dir,
+            options,
+        )
+    }
+
+    fn run_in_new_pane(
+        &self,
+        _prefix: &str,
+        _name: &str,
+        working_dir: &Path,
+        command: &str,
+    ) -> Result<()> {
+        run_command_in_tab(self.session.as_deref(), working_dir, command)
+    }
+
+    fn select_hint(&self, prefix: &str, name: &str) -> String {
+        format!(
+            "{} go-to-tab-name {}",
+            action_prefix(self.session.as_deref()),
+            shell_quote(&prefixed(prefix, name))
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
-    #[test]
-    fn test_prefixed() {
-        assert_eq!(prefixed("wm-", "feature"), "wm-feature");
-        assert_eq!(prefixed("", "feature"), "feature");
+    /// Tab names that would break or inject into an unquoted or double-quoted script
+    const HOSTILE_NAMES: &[&str] = &[
+        r#"wm-fix "quotes""#,
+        "wm-$(touch pwned)",
+        "wm-with spaces",
+        "wm-it's",
+        "wm-`id`; echo $HOME",
+    ];
+
+    /// Run `script` in `sh`, with `zellij` stubbed to print each argument on its own line
+    fn zellij_args(script: &str) -> Vec<String> {
+        let stubbed = format!(
+            "sleep() {{ :; }}; zellij() {{ printf '%s\\n' \"$@\"; }}; {}",
+            script
+        );
+        let output = Command::new("sh").args(["-c", &stubbed]).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
     }
 
     #[test]
-    fn test_rewrite_claude_command() {
-        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
-        let working_dir = PathBuf::from("/tmp/worktree");
-
-        let result = rewrite_agent_command("claude", &prompt_file, &working_dir, Some("claude"));
-        assert_eq!(result, Some("claude -- \"$(cat PROMPT.md)\"".to_string()));
+    fn tab_close_script_passes_tab_name_verbatim() {
+        for name in HOSTILE_NAMES {
+            let script = tab_close_script(None, name, Duration::from_millis(10));
+            assert_eq!(
+                zellij_args(&script),
+                ["action", "go-to-tab-name", name, "action", "close-tab"],
+                "script: {}",
+                script
+            );
+        }
     }
 
     #[test]
-    fn test_rewrite_gemini_command() {
-        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
-        let working_dir = PathBuf::from("/tmp/worktree");
-
-        let result = rewrite_agent_command("gemini", &prompt_file, &working_dir, Some("gemini"));
-        assert_eq!(result, Some("gemini -i \"$(cat PROMPT.md)\"".to_string()));
+    fn select_and_close_script_passes_both_names_verbatim() {
+        for name in HOSTILE_NAMES {
+            let script = select_and_close_script(None, "wm-main \"x\"", name, Duration::ZERO);
+            assert_eq!(
+                zellij_args(&script),
+                [
+                    "action",
+                    "go-to-tab-name",
+                    "wm-main \"x\"",
+                    "action",
+                    "go-to-tab-name",
+                    name,
+                    "action",
+                    "close-tab"
+                ],
+                "script: {}",
+                script
+            );
+        }
     }
 
     #[test]
-    fn test_rewrite_mismatched_agent() {
-        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
-        let working_dir = PathBuf::from("/tmp/worktree");
+    fn close_tab_command_targets_its_own_tab() {
+        for name in HOSTILE_NAMES {
+            assert_eq!(
+                zellij_args(&close_tab_command(None, name)),
+                ["action", "go-to-tab-name", name, "action", "close-tab"]
+            );
+        }
+    }
 
-        let result = rewrite_agent_command("claude", &prompt_file, &working_dir, Some("gemini"));
-        assert_eq!(result, None);
+    #[test]
+    fn shell_quote_survives_nested_sh_c() {
+        // spawn_detached hands the script to a second `sh -c`, so the quoting
+        // must round-trip through one more level of parsing
+        let script = tab_close_script(None, "wm-$(echo bad) \"it's\"", Duration::ZERO);
+        let output = Command::new("sh")
+            .args(["-c", &format!("printf '%s' {}", shell_quote(&script))])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), script);
     }
 
     #[test]
-    fn test_rewrite_empty_command() {
-        let prompt_file = PathBuf::from("/tmp/worktree/PROMPT.md");
-        let working_dir = PathBuf::from("/tmp/worktree");
+    fn session_is_passed_to_every_action() {
+        assert_eq!(
+            action_args(Some("dev"), &["go-to-tab-name", "wm-a"]),
+            ["--session", "dev", "action", "go-to-tab-name", "wm-a"]
+        );
+        assert_eq!(action_args(None, &["close-tab"]), ["action", "close-tab"]);
+
+        let session = "it's dev";
+        let scripts = [
+            tab_close_script(Some(session), "wm-a", Duration::ZERO),
+            select_and_close_script(Some(session), "wm-main", "wm-a", Duration::ZERO),
+            close_tab_command(Some(session), "wm-a"),
+        ];
+        for script in &scripts {
+            let args = zellij_args(script);
+            let actions: Vec<_> = args
+                .split(|arg| arg == "--session")
+                .skip(1)
+                .map(|rest| &rest[..2])
+                .collect();
+            assert!(!actions.is_empty(), "script: {}", script);
+            assert_eq!(
+                args.iter().filter(|arg| *arg == "action").count(),
+                actions.len(),
+                "script: {}",
+                script
+            );
+            for pair in actions {
+                assert_eq!(pair, [session, "action"], "script: {}", script);
+            }
+        }
 
-        let result = rewrite_agent_command("", &prompt_file, &working_dir, Some("claude"));
-        assert_eq!(result, None);
+        let hint = Zellij::new(Some(session.to_string())).select_hint("wm-", "feature");
+        assert_eq!(
+            zellij_args(&hint),
+            [
+                "--session",
+                session,
+                "action",
+                "go-to-tab-name",
+                "wm-feature"
+            ]
+        );
     }
 }
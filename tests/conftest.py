@@ -353,11 +353,24 @@ def workmux_exe_path() -> Path:
 def write_workmux_config(
     repo_path: Path,
     panes: Optional[List[Dict[str, Any]]] = None,
-    post_create: Optional[List[str]] = None,
+    post_create: Optional[List[Any]] = None,
     files: Optional[Dict[str, List[str]]] = None,
     env: Optional[ZellijEnvironment] = None,
     window_prefix: Optional[str] = None,
     agent: Optional[str] = None,
+    hooks_in_pane: Optional[bool] = None,
+    agent_ready_check: Optional[str] = None,
+    agent_ready_timeout: Optional[int] = None,
+    post_merge: Optional[List[str]] = None,
+    post_merge_switch_to: Optional[str] = None,
+    issue_branch_template: Optional[str] = None,
+    ignore_dirty_paths: Optional[List[str]] = None,
+    create_main_window: Optional[bool] = None,
+    commit_generated_files: Optional[bool] = None,
+    after_add: Optional[List[str]] = None,
+    list_exclude: Optional[List[str]] = None,
+    hook_timeout_secs: Optional[int] = None,
+    confirm: Optional[bool] = None,
 ):
     """Creates a .workmux.yaml file from structured data and optionally commits it."""
     config: Dict[str, Any] = {}
@@ -371,6 +384,32 @@ def write_workmux_config(
         config["window_prefix"] = window_prefix
     if agent:
         config["agent"] = agent
+    if hooks_in_pane is not None:
+        config["hooks_in_pane"] = hooks_in_pane
+    if agent_ready_check:
+        config["agent_ready_check"] = agent_ready_check
+    if agent_ready_timeout is not None:
+        config["agent_ready_timeout"] = agent_ready_timeout
+    if post_merge:
+        config["post_merge"] = post_merge
+    if post_merge_switch_to:
+        config["post_merge_switch_to"] = post_merge_switch_to
+    if issue_branch_template:
+        config["issue_branch_template"] = issue_branch_template
+    if ignore_dirty_paths:
+        config["ignore_dirty_paths"] = ignore_dirty_paths
+    if after_add:
+        config["after_add"] = after_add
+    if list_exclude:
+        config["list_exclude"] = list_exclude
+    if hook_timeout_secs is not None:
+        config["hook_timeout_secs"] = hook_timeout_secs
+    if create_main_window is not None:
+        config["create_main_window"] = create_main_window
+    if commit_generated_files is not None:
+        config["commit_generated_files"] = commit_generated_files
+    if confirm is not None:
+        config["confirm"] = confirm
     (repo_path / ".workmux.yaml").write_text(yaml.dump(config))
 
     # If env is provided, commit the config file to avoid uncommitted changes in merge tests
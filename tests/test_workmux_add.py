@@ -1,9 +1,12 @@
+import json
 import os
 import shlex
+import time
 from pathlib import Path
 from typing import Optional
 
 import pytest
+import yaml
 
 from .conftest import (
     ZellijEnvironment,
@@ -82,14 +85,19 @@ def wait_for_pane_output(
 
 def prompt_file_for_branch(branch_name: str) -> Path:
     """Return the path to the prompt file for the given branch."""
-    return Path(f"/tmp/workmux-prompt-{branch_name}.md")
+    return Path(f"/tmp/workmux-prompt-{branch_name.replace('/', '-')}.md")
 
 
 def assert_prompt_file_contents(branch_name: str, expected_text: str) -> None:
-    """Assert that a prompt file exists for the branch and matches the expected text."""
+    """Assert that a prompt file exists for the branch and matches the expected text.
+
+    Prompt files always end with a newline, which is added if the text lacks one.
+    """
     prompt_file = prompt_file_for_branch(branch_name)
     assert prompt_file.exists(), f"Prompt file not found at {prompt_file}"
     actual_text = prompt_file.read_text()
+    if not expected_text.endswith("\n"):
+        expected_text += "\n"
     assert actual_text == expected_text, (
         f"Content mismatch for prompt file: {prompt_file}"
     )
@@ -218,6 +226,77 @@ def test_add_creates_tmux_window(
     assert_tab_exists(env, window_name)
 
 
+def test_add_no_prefix_uses_branch_name_as_tab_name(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--no-prefix` names the tab after the branch and finds it again on remove."""
+    env = isolated_tmux_server
+    branch_name = "feature-verbatim"
+
+    write_workmux_config(repo_path)
+
+    add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name, extra_args="--no-prefix"
+    )
+
+    tabs = env.get_tabs()
+    assert branch_name in tabs
+    assert get_tab_name(branch_name) not in tabs
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"remove --no-prefix -f {branch_name}"
+    )
+
+    assert branch_name not in env.get_tabs()
+
+
+def test_add_commit_generated_files_commits_hook_output(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `commit_generated_files` commits files created by post_create hooks."""
+    env = isolated_tmux_server
+    branch_name = "feature-generated-files"
+
+    write_workmux_config(
+        repo_path,
+        post_create=["echo generated > PROMPT.md"],
+        commit_generated_files=True,
+        env=env,
+    )
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name
+    )
+
+    assert (worktree_path / "PROMPT.md").read_text() == "generated\n"
+    status = env.run_command(["git", "status", "--porcelain"], cwd=worktree_path)
+    assert status.stdout.strip() == ""
+    last_commit = env.run_command(
+        ["git", "log", "-1", "--format=%s", "--name-only"], cwd=worktree_path
+    )
+    assert "Add files generated by workmux setup" in last_commit.stdout
+    assert "PROMPT.md" in last_commit.stdout
+
+
+def test_add_window_prefix_expands_repo_name(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `{repo}` in window_prefix expands to the repo directory name everywhere."""
+    env = isolated_tmux_server
+    branch_name = "feature-repo-prefix"
+    expected_tab = f"{repo_path.name}-{branch_name}"
+
+    write_workmux_config(repo_path, window_prefix="{repo}-")
+
+    add_branch_and_get_worktree(env, workmux_exe_path, repo_path, branch_name)
+
+    assert expected_tab in env.get_tabs()
+
+    run_workmux_command(env, workmux_exe_path, repo_path, f"remove -f {branch_name}")
+
+    assert expected_tab not in env.get_tabs()
+
+
 def test_add_with_count_creates_numbered_worktrees(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -336,6 +415,172 @@ printf '%s' "$2" > "{output_filename}"
     assert agent_output.read_text() == prompt_text
 
 
+def test_add_repeated_inline_prompt_flags_become_lines(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Repeated --prompt flags are joined line by line into the prompt file."""
+    env = isolated_tmux_server
+    branch_name = "feature-multiline-prompt"
+    write_workmux_config(repo_path)
+
+    add_branch_and_get_worktree(
+        env,
+        workmux_exe_path,
+        repo_path,
+        branch_name,
+        # run_workmux_command splits on whitespace, so keep each line to one word
+        extra_args="-p Fix-the-login-bug --prompt Add-a-regression-test",
+    )
+
+    assert_prompt_file_contents(
+        branch_name, "Fix-the-login-bug\nAdd-a-regression-test"
+    )
+
+
+def install_fake_gh_issue(env: ZellijEnvironment, number: int, title: str, body: str):
+    """Creates a fake 'gh' that answers 'issue view <number> --json ...'."""
+    bin_dir = env.home_path / "bin"
+    bin_dir.mkdir(exist_ok=True)
+    issue_json = json.dumps({"number": number, "title": title, "body": body})
+    gh_script = bin_dir / "gh"
+    gh_script.write_text(
+        f"""#!/bin/sh
+if [ "$1" = "issue" ] && [ "$2" = "view" ] && [ "$3" = "{number}" ]; then
+    echo {shlex.quote(issue_json)}
+    exit 0
+fi
+echo "gh: command not implemented in fake" >&2
+exit 1
+"""
+    )
+    gh_script.chmod(0o755)
+
+
+def test_add_prompt_from_issue_names_branch_from_title(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """--prompt-from-issue without a branch name derives the branch and prompt from the issue."""
+    env = isolated_tmux_server
+    install_fake_gh_issue(
+        env, 123, "Fix: login fails on Safari!", "Steps to reproduce..."
+    )
+    write_workmux_config(repo_path)
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, "add --prompt-from-issue 123"
+    )
+
+    branch_name = "issue-123-fix-login-fails-on-safari"
+    assert get_worktree_path(repo_path, branch_name).is_dir()
+    assert_prompt_file_contents(
+        branch_name, "# Fix: login fails on Safari! (#123)\n\nSteps to reproduce..."
+    )
+
+
+def test_add_prompt_from_issue_uses_configured_template_and_explicit_name(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """issue_branch_template shapes the branch name; an explicit branch name wins."""
+    env = isolated_tmux_server
+    install_fake_gh_issue(env, 7, "Crash on start", "")
+    write_workmux_config(repo_path, issue_branch_template="fix/{number}-{slug}")
+
+    run_workmux_command(env, workmux_exe_path, repo_path, "add --prompt-from-issue 7")
+    assert get_worktree_path(repo_path, "fix/7-crash-on-start").is_dir()
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, "add my-branch --prompt-from-issue 7"
+    )
+    assert get_worktree_path(repo_path, "my-branch").is_dir()
+    assert_prompt_file_contents("my-branch", "# Crash on start (#7)")
+
+
+def test_add_after_add_hook_receives_workmux_env(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """after_add commands run in the worktree with WORKMUX_* variables exported."""
+    env = isolated_tmux_server
+    branch_name = "feature-after-add"
+    env_file = env.home_path / "after_add_env.txt"
+    write_workmux_config(
+        repo_path,
+        after_add=[
+            f'printf "%s\\n%s\\n%s\\n%s\\n" "$WORKMUX_BRANCH" "$WORKMUX_WORKTREE" '
+            f'"$WORKMUX_PROMPT_FILE" "$PWD" > {env_file}',
+            "exit 1",
+        ],
+    )
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name} -p hello"
+    )
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    assert env_file.read_text().splitlines() == [
+        branch_name,
+        str(worktree_path),
+        f"/tmp/workmux-prompt-{branch_name}.md",
+        str(worktree_path),
+    ]
+    assert "Warning: after-add command failed" in result.stderr
+
+
+def install_capturing_editor(env: ZellijEnvironment, capture: Path) -> None:
+    """Install an editor that saves what it was opened with, then appends a line."""
+    editor = env.home_path / "capturing_editor.sh"
+    editor.write_text(
+        f'#!/bin/sh\ncp "$1" {shlex.quote(str(capture))}\nprintf "\\nEdited\\n" >> "$1"\n'
+    )
+    editor.chmod(0o755)
+    env.env["VISUAL"] = str(editor)
+    env.env["EDITOR"] = str(editor)
+
+
+def test_add_prompt_editor_is_prefilled_from_prompt_file(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """--prompt-editor with --prompt-file opens the editor on the file's content."""
+    env = isolated_tmux_server
+    capture = env.home_path / "editor_saw.md"
+    install_capturing_editor(env, capture)
+    prompt_source = repo_path / "prompt_source.md"
+    prompt_source.write_text("Fix the login bug\n")
+    write_workmux_config(repo_path)
+
+    run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"add feature-edit-file --prompt-editor --prompt-file {prompt_source}",
+    )
+
+    assert capture.read_text() == prompt_source.read_text()
+    assert_prompt_file_contents("feature-edit-file", "Fix the login bug\n\nEdited")
+
+
+def test_add_prompt_editor_is_prefilled_from_issue(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """--prompt-editor with --prompt-from-issue opens the editor on the issue prompt."""
+    env = isolated_tmux_server
+    capture = env.home_path / "editor_saw.md"
+    install_capturing_editor(env, capture)
+    install_fake_gh_issue(env, 5, "Slow search", "Takes 10s")
+    write_workmux_config(repo_path)
+
+    run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        "add feature-edit-issue --prompt-editor --prompt-from-issue 5",
+    )
+
+    assert capture.read_text() == "# Slow search (#5)\n\nTakes 10s"
+    assert_prompt_file_contents(
+        "feature-edit-issue", "# Slow search (#5)\n\nTakes 10s\nEdited"
+    )
+
+
 @pytest.mark.skip(reason="Zellij test environment does not support pane command execution")
 def test_add_prompt_file_injects_into_gemini(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
@@ -391,6 +636,35 @@ printf '%s' "$2" > "{output_filename}"
     assert agent_output.read_text() == prompt_source.read_text()
 
 
+def test_add_prompt_file_inlines_includes(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `{{include "path"}}` in a prompt file is replaced by the snippet."""
+    env = isolated_tmux_server
+    branch_name = "feature-prompt-include"
+
+    prompts_dir = env.tmp_path / "prompts"
+    (prompts_dir / "snippets").mkdir(parents=True)
+    (prompts_dir / "snippets" / "testing.md").write_text(
+        "Run the tests for {{ base_name }}."
+    )
+    prompt_source = prompts_dir / "task.md"
+    prompt_source.write_text('Implement it.\n{{include "snippets/testing.md"}}\n')
+
+    write_workmux_config(repo_path, panes=[])
+    add_branch_and_get_worktree(
+        env,
+        workmux_exe_path,
+        repo_path,
+        branch_name,
+        extra_args=f"--prompt-file {shlex.quote(str(prompt_source))}",
+    )
+
+    assert_prompt_file_contents(
+        branch_name, f"Implement it.\nRun the tests for {branch_name}.\n"
+    )
+
+
 @pytest.mark.skip(reason="Zellij test environment does not support pane command execution")
 def test_add_uses_agent_from_config(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
@@ -690,6 +964,36 @@ def test_add_executes_post_create_hooks(
     assert (worktree_path / hook_file).exists()
 
 
+def test_add_skips_post_create_hooks_whose_condition_fails(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies object-form post_create hooks only run when their `onlyif` condition holds."""
+    env = isolated_tmux_server
+    branch_name = "feature-conditional-hooks"
+    (repo_path / "package.json").write_text("{}")
+
+    write_workmux_config(
+        repo_path,
+        post_create=[
+            "touch plain_hook.txt",
+            {"run": "touch node_hook.txt", "onlyif": {"exists": "package.json"}},
+            {"run": "touch rust_hook.txt", "onlyif": {"exists": "Cargo.toml"}},
+            {"run": "touch predicate_hook.txt", "onlyif": "test -f plain_hook.txt"},
+        ],
+    )
+    env.run_command(["git", "add", "package.json"], cwd=repo_path)
+    env.run_command(["git", "commit", "-m", "Add package.json"], cwd=repo_path)
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name
+    )
+
+    assert (worktree_path / "plain_hook.txt").exists()
+    assert (worktree_path / "node_hook.txt").exists()
+    assert (worktree_path / "predicate_hook.txt").exists()
+    assert not (worktree_path / "rust_hook.txt").exists()
+
+
 def test_add_without_prompt_skips_prompt_file(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -708,25 +1012,168 @@ def test_add_without_prompt_skips_prompt_file(
     assert not prompt_file_for_branch(branch_name).exists()
 
 
-def test_add_can_skip_post_create_hooks(
+def test_add_no_prompt_rewrite_still_writes_prompt_file(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
-    """`workmux add --no-hooks` should not run configured post_create hooks."""
+    """`--no-prompt-rewrite` leaves the agent command alone but still writes the prompt."""
     env = isolated_tmux_server
-    branch_name = "feature-skip-hooks"
-    hook_file = "hook_should_not_exist.txt"
+    branch_name = "feature-no-rewrite"
 
-    write_workmux_config(repo_path, post_create=[f"touch {hook_file}"])
+    write_workmux_config(repo_path, panes=[])
 
-    worktree_path = add_branch_and_get_worktree(
+    result = run_workmux_command(
         env,
         workmux_exe_path,
         repo_path,
-        branch_name,
-        extra_args="--no-hooks",
+        f"add {branch_name} --prompt handle-it-yourself --no-prompt-rewrite",
+    )
+
+    prompt_file = prompt_file_for_branch(branch_name)
+    assert prompt_file.read_text() == "handle-it-yourself\n"
+    assert f"Prompt: {prompt_file}" in result.stdout
+
+
+def test_add_can_skip_post_create_hooks(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """`workmux add --no-hooks` should not run configured post_create hooks, but
+    still creates the worktree and applies file operations."""
+    env = isolated_tmux_server
+    branch_name = "feature-skip-hooks"
+    hook_file = "hook_should_not_exist.txt"
+    (repo_path / ".env").write_text("SECRET=1\n")
+
+    write_workmux_config(
+        repo_path, post_create=[f"touch {hook_file}"], files={"copy": [".env"]}
+    )
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name} --no-hooks"
+    )
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    assert worktree_path.is_dir()
+    assert env.tab_exists(get_tab_name(branch_name))
+    assert not (worktree_path / hook_file).exists()
+    assert "Running setup commands" not in result.stdout
+    assert (worktree_path / ".env").read_text() == "SECRET=1\n"
+
+
+def test_add_hooks_in_pane_does_not_block(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """With `hooks_in_pane`, post_create hooks are handed to a pane instead of run inline."""
+    env = isolated_tmux_server
+    branch_name = "feature-hooks-pane"
+    hook_file = "hook_ran_inline.txt"
+
+    write_workmux_config(
+        repo_path, post_create=[f"touch {hook_file}"], hooks_in_pane=True
     )
 
+    result = run_workmux_command(env, workmux_exe_path, repo_path, f"add {branch_name}")
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    assert worktree_path.is_dir()
+    # The fake zellij doesn't execute pane commands, so the hook must not have run inline
     assert not (worktree_path / hook_file).exists()
+    assert "Running setup commands in a separate pane" in result.stdout
+    assert "Setup complete" not in result.stdout
+
+
+def test_add_uses_worktree_path_template(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """`worktree_path` places the worktree at the rendered template, flattening slashes."""
+    env = isolated_tmux_server
+    branch_name = "user/feature-login"
+
+    (repo_path / ".workmux.yaml").write_text(
+        'worktree_path: "../custom_wt/{{ repo }}/{{ branch }}"\n'
+    )
+
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    expected = repo_path.parent / "custom_wt" / repo_path.name / "user-feature-login"
+    assert expected.is_dir()
+    assert not get_worktree_path(repo_path, branch_name).exists()
+
+
+def test_add_waits_for_agent_ready_check(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """`workmux add` succeeds once `agent_ready_check` passes in the new worktree."""
+    env = isolated_tmux_server
+    branch_name = "feature-agent-ready"
+
+    write_workmux_config(
+        repo_path,
+        post_create=["touch agent_ready.txt"],
+        agent_ready_check="test -f agent_ready.txt",
+        agent_ready_timeout=5,
+    )
+
+    run_workmux_command(env, workmux_exe_path, repo_path, f"add {branch_name}")
+
+    assert get_worktree_path(repo_path, branch_name).is_dir()
+
+
+def test_add_fails_when_agent_never_ready(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """`workmux add` fails once `agent_ready_timeout` passes without the check succeeding."""
+    env = isolated_tmux_server
+    branch_name = "feature-agent-never-ready"
+
+    write_workmux_config(repo_path, agent_ready_check="false", agent_ready_timeout=1)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name}", expect_fail=True
+    )
+
+    assert "Agent did not become ready within 1s" in result.stderr
+
+
+def test_add_stops_hung_post_create_hook_after_timeout(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """A post_create hook running past `hook_timeout_secs` fails `add` and keeps the worktree."""
+    env = isolated_tmux_server
+    branch_name = "feature-hung-hook"
+
+    write_workmux_config(
+        repo_path,
+        env=env,
+        post_create=["sleep 30", "touch after-hang"],
+        hook_timeout_secs=1,
+    )
+
+    start = time.monotonic()
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name}", expect_fail=True
+    )
+
+    assert time.monotonic() - start < 20
+    assert "'sleep 30' hung and was stopped" in result.stderr
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    assert worktree_path.is_dir()
+    assert not (worktree_path / "after-hang").exists()
+
+
+def test_add_no_pane_cmds_skips_agent_ready_check(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """With `--no-pane-cmds` no agent is launched, so there is nothing to wait for."""
+    env = isolated_tmux_server
+    branch_name = "feature-agent-ready-skipped"
+
+    write_workmux_config(repo_path, agent_ready_check="false", agent_ready_timeout=1)
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name} --no-pane-cmds"
+    )
+
+    assert get_worktree_path(repo_path, branch_name).is_dir()
 
 
 def test_add_executes_pane_commands(
@@ -970,6 +1417,35 @@ def test_global_placeholder_merges_post_create_commands(
     assert (worktree_dir / after_hook).exists()
 
 
+def test_local_config_overrides_project_config(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """An uncommitted .workmux.local.yaml overlays .workmux.yaml, also when run from another worktree."""
+    env = isolated_tmux_server
+
+    write_workmux_config(repo_path, post_create=["touch project_hook.txt"])
+    (repo_path / ".workmux.local.yaml").write_text(
+        'post_create:\n  - "<global>"\n  - touch local_hook.txt\n'
+    )
+
+    first = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, "feature-local-config"
+    )
+    assert (first / "project_hook.txt").exists()
+    assert (first / "local_hook.txt").exists()
+    # The local file is not committed, so it was not checked out into the worktree
+    assert not (first / ".workmux.local.yaml").exists()
+
+    second = add_branch_and_get_worktree(
+        env,
+        workmux_exe_path,
+        repo_path,
+        "feature-local-config-nested",
+        working_dir=first,
+    )
+    assert (second / "local_hook.txt").exists()
+
+
 def test_global_placeholder_merges_file_operations(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -1264,6 +1740,206 @@ def test_add_reuses_existing_branch(
     assert branch_tip == branch_head
 
 
+def test_add_prefers_branch_over_tag_with_same_name(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux add` checks out the branch when a tag has the same name."""
+    env = isolated_tmux_server
+    name = "release/1.0"
+
+    write_workmux_config(repo_path)
+
+    # Tag the current commit, then move the branch of the same name ahead of it
+    env.run_command(["git", "tag", name], cwd=repo_path)
+    tag_commit = env.run_command(
+        ["git", "rev-parse", f"refs/tags/{name}"], cwd=repo_path
+    ).stdout.strip()
+    env.run_command(["git", "checkout", "-b", name], cwd=repo_path)
+    create_commit(env, repo_path, "Release branch work")
+    branch_head = env.run_command(
+        ["git", "rev-parse", f"refs/heads/{name}"], cwd=repo_path
+    ).stdout.strip()
+    env.run_command(["git", "checkout", "main"], cwd=repo_path)
+    assert branch_head != tag_commit
+
+    worktree_path = add_branch_and_get_worktree(env, workmux_exe_path, repo_path, name)
+
+    current_branch = env.run_command(
+        ["git", "branch", "--show-current"], cwd=worktree_path
+    ).stdout.strip()
+    assert current_branch == name
+    head = env.run_command(
+        ["git", "rev-parse", "HEAD"], cwd=worktree_path
+    ).stdout.strip()
+    assert head == branch_head
+
+
+def test_add_creates_branch_when_only_tag_exists(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies a tag doesn't count as an existing branch, so a new branch is created."""
+    env = isolated_tmux_server
+    name = "release/2.0"
+
+    write_workmux_config(repo_path)
+    env.run_command(["git", "tag", name], cwd=repo_path)
+
+    worktree_path = add_branch_and_get_worktree(env, workmux_exe_path, repo_path, name)
+
+    # The worktree is on a new branch rather than a detached HEAD at the tag
+    current_branch = env.run_command(
+        ["git", "branch", "--show-current"], cwd=worktree_path
+    ).stdout.strip()
+    assert current_branch == name
+    branch_result = env.run_command(
+        ["git", "show-ref", "--verify", f"refs/heads/{name}"], cwd=repo_path
+    )
+    assert branch_result.returncode == 0
+
+
+def test_add_base_accepts_tag(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--base <tag>` starts the new branch at the tagged commit."""
+    env = isolated_tmux_server
+    branch_name = "feature-from-tag"
+
+    write_workmux_config(repo_path)
+    create_commit(env, repo_path, "feat: tagged")
+    tagged = env.run_command(["git", "rev-parse", "HEAD"], cwd=repo_path).stdout.strip()
+    env.run_command(["git", "tag", "v1.0"], cwd=repo_path)
+    create_commit(env, repo_path, "feat: after tag")
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name, extra_args="--base v1.0"
+    )
+
+    head = env.run_command(["git", "rev-parse", "HEAD"], cwd=worktree_path).stdout.strip()
+    assert head == tagged
+    current_branch = env.run_command(
+        ["git", "branch", "--show-current"], cwd=worktree_path
+    ).stdout.strip()
+    assert current_branch == branch_name
+
+
+def test_add_base_accepts_short_sha(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--base <short-sha>` starts the new branch at that commit."""
+    env = isolated_tmux_server
+    branch_name = "feature-from-sha"
+
+    write_workmux_config(repo_path)
+    create_commit(env, repo_path, "feat: pinned")
+    pinned = env.run_command(["git", "rev-parse", "HEAD"], cwd=repo_path).stdout.strip()
+    create_commit(env, repo_path, "feat: later")
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name, extra_args=f"--base {pinned[:7]}"
+    )
+
+    head = env.run_command(["git", "rev-parse", "HEAD"], cwd=worktree_path).stdout.strip()
+    assert head == pinned
+
+
+def test_add_base_rejects_unknown_ref(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies an unresolvable `--base` fails clearly without creating a worktree."""
+    env = isolated_tmux_server
+    branch_name = "feature-bad-base"
+
+    write_workmux_config(repo_path)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"add {branch_name} --base no-such-ref",
+        expect_fail=True,
+    )
+
+    assert "Base 'no-such-ref' does not resolve to a commit" in result.stderr
+    assert not get_worktree_path(repo_path, branch_name).exists()
+
+
+def test_add_copy_config_seeds_local_config_in_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--copy-config` writes the project config to .workmux.local.yaml."""
+    env = isolated_tmux_server
+    branch_name = "feature-copied-config"
+
+    write_workmux_config(
+        repo_path, env=env, window_prefix="cfg-", post_create=["echo seeded"]
+    )
+    (repo_path / ".gitignore").write_text(".workmux.local.yaml\n")
+    create_commit(env, repo_path, "chore: ignore local config")
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name, extra_args="--copy-config"
+    )
+
+    local_config = yaml.safe_load(
+        (worktree_path / ".workmux.local.yaml").read_text()
+    )
+    assert local_config["window_prefix"] == "cfg-"
+    assert local_config["post_create"] == ["echo seeded"]
+    # Only project settings are copied; defaults and global settings still apply
+    assert "network_timeout" not in local_config
+    status = env.run_command(
+        ["git", "status", "--porcelain"], cwd=worktree_path
+    ).stdout.strip()
+    assert status == ""
+
+
+def test_add_orphan_creates_empty_branch_without_history(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--orphan` creates an empty worktree on a branch with no commits."""
+    env = isolated_tmux_server
+    branch_name = "gh-pages"
+
+    write_workmux_config(repo_path, env=env)
+    create_commit(env, repo_path, "feat: main history")
+
+    worktree_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, branch_name, extra_args="--orphan"
+    )
+
+    current_branch = env.run_command(
+        ["git", "branch", "--show-current"], cwd=worktree_path
+    ).stdout.strip()
+    assert current_branch == branch_name
+    assert [p.name for p in worktree_path.iterdir()] == [".git"]
+    status = env.run_command(
+        ["git", "status", "--porcelain"], cwd=worktree_path
+    ).stdout.strip()
+    assert status == ""
+    has_commits = env.run_command(
+        ["git", "rev-parse", "--verify", "--quiet", "HEAD"],
+        cwd=worktree_path,
+        check=False,
+    )
+    assert has_commits.returncode != 0
+
+
+def test_add_orphan_rejects_existing_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--orphan` refuses a branch that already exists."""
+    env = isolated_tmux_server
+    write_workmux_config(repo_path)
+    env.run_command(["git", "branch", "existing"], cwd=repo_path)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, "add existing --orphan", expect_fail=True
+    )
+
+    assert "--orphan only creates new branches" in result.stderr
+    assert not get_worktree_path(repo_path, "existing").exists()
+
+
 def test_add_from_remote_branch(
     isolated_tmux_server: ZellijEnvironment,
     workmux_exe_path: Path,
@@ -1299,6 +1975,10 @@ def test_add_from_remote_branch(
         ["git", "update-ref", "-d", f"refs/remotes/{remote_ref}"],
         cwd=repo_path,
     )
+    # Tracking must not depend on git setting it up implicitly
+    env.run_command(
+        ["git", "config", "branch.autoSetupMerge", "false"], cwd=repo_path
+    )
 
     worktree_path = add_branch_and_get_worktree(
         env,
@@ -1328,6 +2008,72 @@ def test_add_from_remote_branch(
     assert origin_tip == remote_tip
 
 
+def test_add_plain_name_of_remote_only_branch_tracks_remote(
+    isolated_tmux_server: ZellijEnvironment,
+    workmux_exe_path: Path,
+    repo_path: Path,
+    remote_repo_path: Path,
+):
+    """`add feature` for a branch that only exists as origin/feature checks it out
+    from the remote instead of creating an unrelated branch off main."""
+    env = isolated_tmux_server
+    branch_name = "remote-only"
+    commit_message = "Remote-only work"
+
+    write_workmux_config(repo_path)
+    env.run_command(
+        ["git", "remote", "add", "origin", str(remote_repo_path)], cwd=repo_path
+    )
+    env.run_command(["git", "push", "-u", "origin", "main"], cwd=repo_path)
+    env.run_command(["git", "checkout", "-b", branch_name], cwd=repo_path)
+    create_commit(env, repo_path, commit_message)
+    remote_tip = env.run_command(
+        ["git", "rev-parse", branch_name], cwd=repo_path
+    ).stdout.strip()
+    env.run_command(["git", "push", "origin", branch_name], cwd=repo_path)
+    env.run_command(["git", "checkout", "main"], cwd=repo_path)
+    env.run_command(["git", "branch", "-D", branch_name], cwd=repo_path)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"add {branch_name}"
+    )
+
+    assert f"only exists as 'origin/{branch_name}'" in result.stdout
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    assert file_for_commit(worktree_path, commit_message).exists()
+    upstream = env.run_command(
+        ["git", "rev-parse", "--abbrev-ref", f"{branch_name}@{{upstream}}"],
+        cwd=repo_path,
+    ).stdout.strip()
+    assert upstream == f"origin/{branch_name}"
+    branch_tip = env.run_command(
+        ["git", "rev-parse", branch_name], cwd=repo_path
+    ).stdout.strip()
+    assert branch_tip == remote_tip
+
+
+def test_add_from_inside_worktree_creates_sibling_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux add` run inside another worktree still places the new one
+    next to it under the main worktree's worktrees directory."""
+    env = isolated_tmux_server
+    write_workmux_config(repo_path)
+
+    outer_path = add_branch_and_get_worktree(
+        env, workmux_exe_path, repo_path, "feature-outer"
+    )
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, "add feature-inner", working_dir=outer_path
+    )
+
+    inner_path = get_worktree_path(repo_path, "feature-inner")
+    assert inner_path == outer_path.parent / "feature-inner"
+    assert inner_path.is_dir()
+    assert "nested" not in result.stderr
+
+
 def test_add_fails_when_worktree_exists(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -1792,6 +2538,29 @@ def test_add_background_creates_tab_without_switching(
     assert current_after == initial_tab
 
 
+def test_add_detach_creates_tabs_without_switching(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies that `--detach` keeps focus on the current tab across several adds."""
+    env = isolated_tmux_server
+    initial_tab = "test"  # Default tab from ZellijEnvironment
+
+    write_workmux_config(repo_path)
+    assert env.get_current_tab() == initial_tab
+
+    for branch_name in ["feature-detach-one", "feature-detach-two"]:
+        worktree_path = add_branch_and_get_worktree(
+            env,
+            workmux_exe_path,
+            repo_path,
+            branch_name,
+            extra_args="--detach",
+        )
+        assert worktree_path.is_dir()
+        assert_tab_exists(env, get_tab_name(branch_name))
+        assert env.get_current_tab() == initial_tab
+
+
 def test_rescue_moves_uncommitted_changes_to_new_worktree(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
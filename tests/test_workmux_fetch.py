@@ -0,0 +1,129 @@
+from pathlib import Path
+
+from .conftest import (
+    ZellijEnvironment,
+    create_commit,
+    get_worktree_path,
+    run_workmux_add,
+    run_workmux_command,
+    write_workmux_config,
+)
+from .test_workmux_list import parse_list_output
+
+
+def setup_tracked_worktree(
+    env: ZellijEnvironment,
+    workmux_exe_path: Path,
+    repo_path: Path,
+    remote_repo_path: Path,
+    branch_name: str,
+) -> Path:
+    """Creates a worktree whose branch tracks a branch on the bare remote."""
+    write_workmux_config(repo_path)
+    env.run_command(
+        ["git", "remote", "add", "origin", str(remote_repo_path)], cwd=repo_path
+    )
+    env.run_command(["git", "push", "-u", "origin", "main"], cwd=repo_path)
+
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    env.run_command(["git", "push", "-u", "origin", branch_name], cwd=worktree_path)
+    return worktree_path
+
+
+def push_commit_from_clone(
+    env: ZellijEnvironment, remote_repo_path: Path, branch_name: str, message: str
+) -> str:
+    """Pushes a new commit to the remote branch from a separate clone, returning its SHA."""
+    clone_path = env.tmp_path.parent / f"clone_{branch_name}"
+    env.run_command(
+        ["git", "clone", "-b", branch_name, str(remote_repo_path), str(clone_path)]
+    )
+    env.run_command(["git", "config", "user.name", "Other User"], cwd=clone_path)
+    env.run_command(
+        ["git", "config", "user.email", "other@example.com"], cwd=clone_path
+    )
+    create_commit(env, clone_path, message)
+    env.run_command(["git", "push", "origin", branch_name], cwd=clone_path)
+    return env.run_command(["git", "rev-parse", "HEAD"], cwd=clone_path).stdout.strip()
+
+
+def test_fetch_fast_forwards_behind_worktree(
+    isolated_tmux_server: ZellijEnvironment,
+    workmux_exe_path: Path,
+    repo_path: Path,
+    remote_repo_path: Path,
+):
+    """Verifies `workmux fetch` fast-forwards a worktree that is behind its upstream."""
+    env = isolated_tmux_server
+    branch_name = "feature-behind"
+    worktree_path = setup_tracked_worktree(
+        env, workmux_exe_path, repo_path, remote_repo_path, branch_name
+    )
+    remote_tip = push_commit_from_clone(
+        env, remote_repo_path, branch_name, "Remote work"
+    )
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "fetch")
+
+    rows = parse_list_output(result.stdout)
+    entry = next((r for r in rows if r["BRANCH"] == branch_name), None)
+    assert entry is not None
+    assert entry["STATUS"].startswith("advanced")
+
+    head = env.run_command(["git", "rev-parse", "HEAD"], cwd=worktree_path)
+    assert head.stdout.strip() == remote_tip
+
+
+def test_fetch_reports_diverged_branch(
+    isolated_tmux_server: ZellijEnvironment,
+    workmux_exe_path: Path,
+    repo_path: Path,
+    remote_repo_path: Path,
+):
+    """Verifies `workmux fetch` leaves a diverged branch untouched and reports it."""
+    env = isolated_tmux_server
+    branch_name = "feature-diverged"
+    worktree_path = setup_tracked_worktree(
+        env, workmux_exe_path, repo_path, remote_repo_path, branch_name
+    )
+    push_commit_from_clone(env, remote_repo_path, branch_name, "Remote work")
+    create_commit(env, worktree_path, "Local work")
+    local_tip = env.run_command(
+        ["git", "rev-parse", "HEAD"], cwd=worktree_path
+    ).stdout.strip()
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"fetch {branch_name}"
+    )
+
+    rows = parse_list_output(result.stdout)
+    assert [r["BRANCH"] for r in rows] == [branch_name]
+    assert rows[0]["STATUS"] == "diverged"
+
+    head = env.run_command(["git", "rev-parse", "HEAD"], cwd=worktree_path)
+    assert head.stdout.strip() == local_tip
+
+
+def test_fetch_reports_branch_without_upstream(
+    isolated_tmux_server: ZellijEnvironment,
+    workmux_exe_path: Path,
+    repo_path: Path,
+    remote_repo_path: Path,
+):
+    """Verifies `workmux fetch` reports worktrees whose branch has no upstream."""
+    env = isolated_tmux_server
+    branch_name = "feature-local"
+    write_workmux_config(repo_path)
+    env.run_command(
+        ["git", "remote", "add", "origin", str(remote_repo_path)], cwd=repo_path
+    )
+    env.run_command(["git", "push", "-u", "origin", "main"], cwd=repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "fetch")
+
+    rows = parse_list_output(result.stdout)
+    entry = next((r for r in rows if r["BRANCH"] == branch_name), None)
+    assert entry is not None
+    assert entry["STATUS"] == "no upstream"
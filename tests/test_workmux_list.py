@@ -188,6 +188,48 @@ def test_list_with_detached_tab(
     assert worktree_entry["UNMERGED"] == "-"
 
 
+def test_list_open_only_and_no_tab_filter_by_tab(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `--open-only` and `--no-tab` split worktrees by whether their tab is open."""
+    env = isolated_tmux_server
+    open_branch = "feature-open"
+    closed_branch = "feature-closed"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, open_branch)
+    run_workmux_add(env, workmux_exe_path, repo_path, closed_branch)
+    env.close_tab(get_tab_name(closed_branch))
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "list --open-only")
+    branches = {row["BRANCH"] for row in parse_list_output(result.stdout)}
+    assert branches == {open_branch}
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "list --no-tab")
+    branches = {row["BRANCH"] for row in parse_list_output(result.stdout)}
+    # The main worktree has no workmux tab either
+    assert branches == {"main", closed_branch}
+
+
+def test_list_exclude_hides_matching_worktrees_unless_all(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `list_exclude` hides matching branches and `--all` shows them again."""
+    env = isolated_tmux_server
+    write_workmux_config(repo_path)
+    for branch in ["docs", "scratch/notes", "feature-visible"]:
+        run_workmux_add(env, workmux_exe_path, repo_path, branch)
+    write_workmux_config(repo_path, list_exclude=["docs", "scratch/*", "ma*"])
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "list")
+    branches = {row["BRANCH"] for row in parse_list_output(result.stdout)}
+    # The main branch is listed even though "ma*" matches it
+    assert branches == {"main", "feature-visible"}
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "list --all")
+    branches = {row["BRANCH"] for row in parse_list_output(result.stdout)}
+    assert branches == {"main", "docs", "scratch/notes", "feature-visible"}
+
+
 def test_list_alias_ls_works(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -202,3 +244,70 @@ def test_list_alias_ls_works(
     assert parsed_output[0]["BRANCH"] == "main"
     # When run from repo_path, main branch shows as "(here)"
     assert parsed_output[0]["PATH"] == "(here)"
+
+
+def test_list_marks_current_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `list` marks the worktree containing the CWD, even from a subdirectory."""
+    env = isolated_tmux_server
+    branch_name = "feature-current"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    subdir = worktree_path / "nested" / "dir"
+    subdir.mkdir(parents=True)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, "list", working_dir=subdir
+    )
+    parsed_output = parse_list_output(result.stdout)
+
+    current = [r["BRANCH"] for r in parsed_output if r["CURRENT"] == "*"]
+    assert current == [branch_name]
+
+
+def test_list_flags_worktrees_outside_worktree_dir(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `list` marks worktrees created before `worktree_dir` was configured."""
+    env = isolated_tmux_server
+    branch_name = "feature-old-location"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    old_path = get_worktree_path(repo_path, branch_name)
+
+    (repo_path / ".workmux.yaml").write_text("worktree_dir: .worktrees\n")
+
+    output = run_workmux_list(env, workmux_exe_path, repo_path)
+    table, hint = output.split("\n\n", 1)
+    parsed_output = parse_list_output(table)
+
+    entry = next(r for r in parsed_output if r["BRANCH"] == branch_name)
+    assert entry["PATH"].endswith("⚠")
+    main_entry = next(r for r in parsed_output if r["BRANCH"] != branch_name)
+    assert "⚠" not in main_entry["PATH"]
+
+    assert "1 worktree is outside the configured worktree_dir" in hint
+    expected_target = repo_path / ".worktrees" / branch_name
+    assert f"git worktree move {old_path} {expected_target}" in hint
+
+
+def test_list_labels_detached_worktree_with_its_commit(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `list` shows a detached worktree by the commit it has checked out."""
+    env = isolated_tmux_server
+    detached_path = repo_path.parent / f"{repo_path.name}__detached"
+    env.run_command(
+        ["git", "worktree", "add", "--detach", str(detached_path)], cwd=repo_path
+    )
+    head = env.run_command(
+        ["git", "rev-parse", "--short=7", "HEAD"], cwd=detached_path
+    ).stdout.strip()
+
+    output = run_workmux_list(env, workmux_exe_path, repo_path)
+    branches = [row["BRANCH"] for row in parse_list_output(output)]
+
+    assert f"(detached at {head})" in branches
@@ -0,0 +1,65 @@
+from pathlib import Path
+
+from .conftest import (
+    ZellijEnvironment,
+    create_commit,
+    get_worktree_path,
+    run_workmux_add,
+    run_workmux_command,
+    write_workmux_config,
+)
+
+
+def test_log_lists_branch_commits_up_to_limit(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux log --limit` shows only the newest commits since the base."""
+    env = isolated_tmux_server
+    branch_name = "feature-log"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    for message in ["feat one", "feat two", "feat three"]:
+        create_commit(env, worktree_path, message)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, f"log {branch_name}")
+    assert [line.split(" ", 1)[1] for line in result.stdout.splitlines()] == [
+        "feat three",
+        "feat two",
+        "feat one",
+    ]
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"log {branch_name} --limit 2"
+    )
+    assert [line.split(" ", 1)[1] for line in result.stdout.splitlines()] == [
+        "feat three",
+        "feat two",
+    ]
+
+
+def test_diff_name_only_and_stat(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux diff --name-only` and `--stat` summarize the branch's changes."""
+    env = isolated_tmux_server
+    branch_name = "feature-diff"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat diffed")
+    create_commit(env, repo_path, "main only")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"diff {branch_name} --name-only"
+    )
+    assert result.stdout.split() == ["file_for_feat_diffed.txt"]
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"diff {branch_name} --stat"
+    )
+    assert "file_for_feat_diffed.txt" in result.stdout
+    assert "1 file changed" in result.stdout
+    assert "main_only" not in result.stdout
@@ -1,3 +1,4 @@
+import json
 from pathlib import Path
 
 from .conftest import (
@@ -7,6 +8,7 @@ from .conftest import (
     get_tab_name,
     get_worktree_path,
     run_workmux_add,
+    run_workmux_command,
     run_workmux_merge,
     write_workmux_config,
 )
@@ -127,6 +129,52 @@ def test_merge_rebase_strategy_succeeds(
     )
 
 
+def test_merge_rebase_preview_lists_commits_and_proceeds(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --preview prints the commits to rebase and proceeds when not on a TTY."""
+    env = isolated_tmux_server
+    branch_name = "feature-preview"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: first previewed change")
+    create_commit(env, worktree_path, "feat: second previewed change")
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --rebase --preview",
+    )
+
+    assert f"Commits on '{branch_name}' to rebase onto 'main' (2):" in result.stderr
+    assert "feat: first previewed change" in result.stderr
+    assert "feat: second previewed change" in result.stderr
+    assert not worktree_path.exists()
+
+
+def test_merge_preview_requires_rebase(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --preview is rejected without --rebase."""
+    env = isolated_tmux_server
+    branch_name = "feature-preview-no-rebase"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --preview",
+        expect_fail=True,
+    )
+
+    assert "--rebase" in result.stderr
+    assert get_worktree_path(repo_path, branch_name).exists()
+
 def test_merge_squash_strategy_succeeds(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -221,6 +269,42 @@ def test_merge_commits_staged_changes_before_merge(
     assert "staged content" in show_result.stdout, "Staged file should be in main"
 
 
+def test_merge_into_itself_fails_before_touching_the_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies merging a branch into itself errors before committing or stashing anything."""
+    env = isolated_tmux_server
+    branch_name = "feature-self-merge"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    (worktree_path / "staged_file.txt").write_text("staged content")
+    env.run_command(["git", "add", "staged_file.txt"], cwd=worktree_path)
+    (worktree_path / "unstaged_file.txt").write_text("untracked")
+    head_before = env.run_command(
+        ["git", "rev-parse", "HEAD"], cwd=worktree_path
+    ).stdout
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --target {branch_name} --stash",
+        expect_fail=True,
+    )
+
+    assert "into itself" in result.stderr
+    assert worktree_path.exists()
+    assert (
+        env.run_command(["git", "rev-parse", "HEAD"], cwd=worktree_path).stdout
+        == head_before
+    )
+    status = env.run_command(["git", "status", "--porcelain"], cwd=worktree_path)
+    assert "A  staged_file.txt" in status.stdout
+    assert "stash@" not in env.run_command(["git", "stash", "list"]).stdout
+
+
 def test_merge_fails_if_main_worktree_has_uncommitted_changes(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -240,6 +324,32 @@ def test_merge_fails_if_main_worktree_has_uncommitted_changes(
     assert worktree_path.exists(), "Worktree should remain when merge fails"
 
 
+def test_merge_force_dirty_target_merges_into_dirty_main_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --force-dirty-target warns about a dirty main worktree and merges anyway."""
+    env = isolated_tmux_server
+    branch_name = "feature-dirty-target"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: work done")
+
+    create_dirty_file(repo_path, "dirty_in_main.txt")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --force-dirty-target"
+    )
+
+    assert "has uncommitted changes; merging anyway" in result.stderr
+    main_log = env.run_command(["git", "log", "--oneline", "-1", "main"])
+    assert "feat: work done" in main_log.stdout
+    # The unrelated change in the main worktree is left alone
+    assert (repo_path / "dirty_in_main.txt").read_text() == "uncommitted changes"
+    assert not worktree_path.exists()
+
+
 def test_merge_with_keep_flag_skips_cleanup(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -269,3 +379,459 @@ def test_merge_with_keep_flag_skips_cleanup(
     assert env.tab_exists(tab_name), "Zellij tab should still exist"
     branch_list_result = env.run_command(["git", "branch", "--list", branch_name])
     assert branch_name in branch_list_result.stdout, "Local branch should still exist"
+
+
+def test_merge_runs_post_merge_hooks_in_main_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies post_merge hooks run in the main worktree after a successful merge."""
+    env = isolated_tmux_server
+    branch_name = "feature-post-merge"
+    marker = "workmux_post_merge.txt"
+    write_workmux_config(
+        repo_path,
+        env=env,
+        post_merge=["false", f"git log -1 --format=%s > {marker}"],
+    )
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: merged work")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name}"
+    )
+
+    # The failing first hook is reported but doesn't stop the rest or the cleanup
+    assert "post-merge command failed" in result.stderr
+    assert (repo_path / marker).read_text().strip() == "feat: merged work"
+    assert not worktree_path.exists()
+
+
+def test_merge_conflict_skips_post_merge_hooks(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies post_merge hooks don't run when the merge fails with conflicts."""
+    env = isolated_tmux_server
+    branch_name = "feature-conflict"
+    marker = "workmux_post_merge.txt"
+    write_workmux_config(repo_path, env=env, post_merge=[f"touch {marker}"])
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    # Edit the same file differently on both branches
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    (worktree_path / "shared.txt").write_text("feature version")
+    env.run_command(["git", "add", "shared.txt"], cwd=worktree_path)
+    env.run_command(["git", "commit", "-m", "feature edit"], cwd=worktree_path)
+    (repo_path / "shared.txt").write_text("main version")
+    env.run_command(["git", "add", "shared.txt"], cwd=repo_path)
+    env.run_command(["git", "commit", "-m", "main edit"], cwd=repo_path)
+
+    run_workmux_merge(env, workmux_exe_path, repo_path, branch_name, expect_fail=True)
+
+    assert not (repo_path / marker).exists()
+    assert worktree_path.exists()
+
+
+def test_merge_target_path_merges_into_worktree_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --target-path merges into the branch checked out in that worktree."""
+    env = isolated_tmux_server
+    branch_name = "feature-to-integrate"
+    target_name = "feature-target"
+    integration_branch = "integration"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    run_workmux_add(env, workmux_exe_path, repo_path, target_name)
+
+    # Switch branches inside the target worktree so its name no longer matches
+    target_path = get_worktree_path(repo_path, target_name)
+    env.run_command(["git", "switch", "-c", integration_branch], cwd=target_path)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: work to integrate")
+    commit_hash = env.run_command(
+        ["git", "rev-parse", "--short", "HEAD"], cwd=worktree_path
+    ).stdout.strip()
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --keep --target-path {target_path}",
+    )
+
+    assert f"into '{integration_branch}'" in result.stdout
+    integration_log = env.run_command(["git", "log", "--oneline", integration_branch])
+    assert commit_hash in integration_log.stdout
+    main_log = env.run_command(["git", "log", "--oneline", "main"])
+    assert commit_hash not in main_log.stdout
+    # The merge happened in the target worktree, which is still on its branch
+    current = env.run_command(
+        ["git", "branch", "--show-current"], cwd=target_path
+    ).stdout.strip()
+    assert current == integration_branch
+
+
+def test_merge_target_path_rejects_non_directory(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --target-path fails clearly when the path doesn't exist."""
+    env = isolated_tmux_server
+    write_workmux_config(repo_path, env=env)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge some-branch --target-path {repo_path / 'missing'}",
+        expect_fail=True,
+    )
+
+    assert "is not a directory" in result.stderr
+
+
+def test_merge_target_branch_without_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --target merges into a branch that isn't checked out anywhere."""
+    env = isolated_tmux_server
+    branch_name = "feature-for-release"
+    target_branch = "release"
+    write_workmux_config(repo_path, env=env)
+    env.run_command(["git", "branch", target_branch], cwd=repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: release work")
+    commit_hash = env.run_command(
+        ["git", "rev-parse", "--short", "HEAD"], cwd=worktree_path
+    ).stdout.strip()
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --target {target_branch}"
+    )
+
+    release_log = env.run_command(["git", "log", "--oneline", target_branch])
+    assert commit_hash in release_log.stdout
+    main_log = env.run_command(["git", "log", "--oneline", "main"])
+    assert commit_hash not in main_log.stdout
+    assert not worktree_path.exists()
+
+
+def test_merge_post_merge_switch_to_restores_main_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies post_merge_switch_to checks out the configured branch after merging."""
+    env = isolated_tmux_server
+    branch_name = "feature-for-release-switch"
+    target_branch = "release"
+    write_workmux_config(repo_path, env=env, post_merge_switch_to="main")
+    env.run_command(["git", "branch", target_branch], cwd=repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: release work")
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --target {target_branch}"
+    )
+
+    # The merge landed on release, but the main worktree is back on main
+    release_log = env.run_command(["git", "log", "--oneline", "-1", target_branch])
+    assert "feat: release work" in release_log.stdout
+    current = env.run_command(["git", "branch", "--show-current"], cwd=repo_path)
+    assert current.stdout.strip() == "main"
+    assert not worktree_path.exists()
+
+
+def test_merge_target_branch_is_created_when_missing(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --create-target creates a --target branch that doesn't exist yet from main."""
+    env = isolated_tmux_server
+    branch_name = "feature-for-new-release"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: new release work")
+    commit_hash = env.run_command(
+        ["git", "rev-parse", "--short", "HEAD"], cwd=worktree_path
+    ).stdout.strip()
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --target release-2 --create-target",
+    )
+
+    assert "Target branch 'release-2' does not exist" in result.stderr
+    release_log = env.run_command(["git", "log", "--oneline", "release-2"])
+    assert commit_hash in release_log.stdout
+    main_log = env.run_command(["git", "log", "--oneline", "main"])
+    assert commit_hash not in main_log.stdout
+
+
+def test_merge_refuses_missing_target_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies --target fails on a branch that doesn't exist instead of creating it."""
+    env = isolated_tmux_server
+    branch_name = "feature-for-typo"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: work for a typo")
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --target relaese",
+        expect_fail=True,
+    )
+
+    assert "--create-target" in result.stderr
+    branches = env.run_command(["git", "branch", "--list", "relaese"])
+    assert branches.stdout.strip() == ""
+    assert worktree_path.exists()
+
+
+def test_merge_creates_missing_main_tab_when_configured(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `create_main_window` opens a main tab to land on after merging."""
+    env = isolated_tmux_server
+    branch_name = "feature-land-on-main"
+    write_workmux_config(repo_path, env=env, create_main_window=True)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: land")
+
+    assert not env.tab_exists(get_tab_name("main"))
+
+    run_workmux_merge(env, workmux_exe_path, repo_path, branch_name)
+
+    assert env.tab_exists(get_tab_name("main"))
+    assert not env.tab_exists(get_tab_name(branch_name))
+
+
+def test_merge_leaves_main_tab_missing_by_default(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies no main tab is created unless `create_main_window` is set."""
+    env = isolated_tmux_server
+    branch_name = "feature-no-main-tab"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: stay")
+
+    run_workmux_merge(env, workmux_exe_path, repo_path, branch_name)
+
+    assert not env.tab_exists(get_tab_name("main"))
+
+
+def test_merge_keep_with_open_switches_to_target_tab(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --keep --open` lands in the target branch's tab and closes nothing."""
+    env = isolated_tmux_server
+    branch_name = "feature-keep-open"
+    target_branch = "release-keep-open"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, target_branch)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: keep and open")
+    env.set_current_tab(get_tab_name(branch_name))
+
+    run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --target {target_branch} --keep --open",
+    )
+
+    assert env.get_current_tab() == get_tab_name(target_branch)
+    assert env.tab_exists(get_tab_name(branch_name))
+    assert worktree_path.exists()
+
+
+def test_merge_json_reports_result_for_keep(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --keep --json` prints the merge result with no cleanup."""
+    env = isolated_tmux_server
+    branch_name = "feature-json-keep"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: json")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --keep --json"
+    )
+
+    assert json.loads(result.stdout) == {
+        "branch_merged": branch_name,
+        "target_branch": "main",
+        "had_staged_changes": False,
+        "already_merged": False,
+        "cleanup": None,
+        "stash": None,
+    }
+
+
+def test_merge_already_merged_branch_skips_merge_and_cleans_up(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge` of a branch main already contains only cleans it up."""
+    env = isolated_tmux_server
+    branch_name = "feature-already-merged"
+    hook_file = "post_merge_ran.txt"
+    write_workmux_config(repo_path, env=env, post_merge=[f"touch {hook_file}"])
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: merged by hand")
+    env.run_command(["git", "merge", "--ff-only", branch_name], cwd=repo_path)
+    main_head = env.run_command(["git", "rev-parse", "main"], cwd=repo_path).stdout
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name}"
+    )
+
+    assert f"'{branch_name}' is already merged into 'main'; nothing to do" in (
+        result.stdout
+    )
+    assert "Merging" not in result.stdout
+    assert env.run_command(["git", "rev-parse", "main"], cwd=repo_path).stdout == (
+        main_head
+    )
+    assert not (repo_path / hook_file).exists()
+    assert not worktree_path.exists()
+    branches = env.run_command(["git", "branch", "--list", branch_name], cwd=repo_path)
+    assert branch_name not in branches.stdout
+
+
+def test_merge_stash_restores_unstaged_changes_when_kept(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --stash --keep` merges a dirty worktree and restores its changes."""
+    env = isolated_tmux_server
+    branch_name = "feature-stash-keep"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: stash")
+    tracked_file = worktree_path / "file_for_feat_stash.txt"
+    tracked_file.write_text("work in progress")
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --stash --keep"
+    )
+
+    log = env.run_command(["git", "log", "--oneline", "main"], cwd=repo_path)
+    assert "feat: stash" in log.stdout
+    assert tracked_file.read_text() == "work in progress"
+    stash_list = env.run_command(["git", "stash", "list"], cwd=repo_path)
+    assert stash_list.stdout.strip() == ""
+
+
+def test_merge_stash_keeps_stash_when_worktree_removed(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --stash` leaves the stash in place once the worktree is gone."""
+    env = isolated_tmux_server
+    branch_name = "feature-stash-cleanup"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: stash cleanup")
+    (worktree_path / "file_for_feat_stash_cleanup.txt").write_text("work in progress")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --stash"
+    )
+
+    assert not worktree_path.exists()
+    stash = env.run_command(["git", "rev-parse", "refs/stash"], cwd=repo_path)
+    assert f"git stash apply {stash.stdout.strip()}" in result.stdout
+
+
+def test_merge_json_reports_cleanup(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --json` includes what cleanup did, including remote errors."""
+    env = isolated_tmux_server
+    branch_name = "feature-json-cleanup"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: cleanup")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"merge {branch_name} --json"
+    )
+
+    cleanup = json.loads(result.stdout)["cleanup"]
+    assert set(cleanup) == {
+        "window_closed",
+        "worktree_removed",
+        "local_branch_deleted",
+        "remote_branch_deleted",
+        "remote_delete_error",
+        "ran_inside_target_window",
+        "window_skip_reason",
+        "worktree_skip_reason",
+        "local_branch_skip_reason",
+        "remote_branch_skip_reason",
+    }
+    assert cleanup["worktree_removed"] is True
+    assert cleanup["local_branch_deleted"] is True
+    assert cleanup["remote_delete_error"] is None
+
+
+def test_merge_json_keeps_progress_off_stdout(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `merge --json` writes only the JSON document to stdout, with progress on stderr."""
+    env = isolated_tmux_server
+    branch_name = "feature-json-quiet"
+    write_workmux_config(repo_path, env=env, post_merge=["true"])
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: quiet json")
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --rebase --preview --json",
+    )
+
+    assert json.loads(result.stdout)["branch_merged"] == branch_name
+    assert f"Rebasing '{branch_name}' onto 'main'..." in result.stderr
+    assert "feat: quiet json" in result.stderr
+    assert "Running post-merge commands..." in result.stderr
+
+
+def test_merge_json_with_delete_remote_prints_only_json(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies the `--delete-remote` notice goes to stderr so `merge --json` stays parseable."""
+    env = isolated_tmux_server
+    branch_name = "feature-json-remote"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_commit(env, get_worktree_path(repo_path, branch_name), "feat: remote json")
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"merge {branch_name} --delete-remote --force --json",
+    )
+
+    assert json.loads(result.stdout)["branch_merged"] == branch_name
+    assert f"The remote branch 'origin/{branch_name}' will be deleted." in result.stderr
@@ -5,6 +5,7 @@ from .conftest import (
     get_tab_name,
     get_worktree_path,
     run_workmux_add,
+    run_workmux_command,
     run_workmux_open,
     write_workmux_config,
 )
@@ -35,6 +36,53 @@ def test_open_recreates_zellij_tab_for_existing_worktree(
     assert env.tab_exists(tab_name)
 
 
+def test_open_all_reopens_tabs_missing_for_worktrees(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux open --all` opens tabs only for worktrees without one."""
+    env = isolated_tmux_server
+    closed = ["feature-closed-a", "feature-closed-b"]
+    still_open = "feature-still-open"
+
+    write_workmux_config(repo_path)
+    for branch in closed + [still_open]:
+        run_workmux_add(env, workmux_exe_path, repo_path, branch)
+    for branch in closed:
+        _close_tab(env, branch)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "open --all")
+
+    for branch in closed + [still_open]:
+        assert env.tab_exists(get_tab_name(branch))
+    assert "Opened 2, skipped 1 (already open), failed 0" in result.stdout
+
+
+def test_open_all_with_jobs_runs_hooks_and_groups_output_by_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux open --all --jobs` runs hooks for every worktree and prints each one's output together."""
+    env = isolated_tmux_server
+    branches = ["feature-jobs-a", "feature-jobs-b", "feature-jobs-c"]
+
+    write_workmux_config(
+        repo_path, post_create=["touch jobs_hook.txt", "echo hook-ran-in-worktree"]
+    )
+    for branch in branches:
+        run_workmux_add(env, workmux_exe_path, repo_path, branch)
+        (get_worktree_path(repo_path, branch) / "jobs_hook.txt").unlink()
+        _close_tab(env, branch)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, "open --all --run-hooks --jobs 2"
+    )
+
+    for branch in branches:
+        assert env.tab_exists(get_tab_name(branch))
+        assert (get_worktree_path(repo_path, branch) / "jobs_hook.txt").exists()
+        assert f"── {branch} ──\nhook-ran-in-worktree" in result.stdout
+    assert "Opened 3, skipped 0 (already open), failed 0" in result.stdout
+
+
 def test_open_fails_when_tab_already_exists(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -76,6 +124,34 @@ def test_open_fails_when_worktree_missing(
     assert "No worktree found for branch" in result.stderr
 
 
+def test_open_select_pane_rejects_index_outside_configured_panes(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux open --select-pane` fails before opening for an unknown pane."""
+    env = isolated_tmux_server
+    branch_name = "feature-open-select-pane"
+    tab_name = get_tab_name(branch_name)
+
+    write_workmux_config(
+        repo_path, panes=[{"command": "clear"}, {"command": "clear", "split": "vertical"}]
+    )
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    env.close_tab(tab_name)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"open {branch_name} --select-pane 2",
+        expect_fail=True,
+    )
+
+    assert "--select-pane 2 is out of range: 2 pane(s) are configured (0-1)" in (
+        result.stderr
+    )
+    assert not env.tab_exists(tab_name)
+
+
 def test_open_with_run_hooks_reexecutes_post_create_commands(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -65,6 +65,7 @@ def test_add_pr_from_same_repo(
     pr_data = {
         "headRefName": "feature-branch",
         "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "main",
         "state": "OPEN",
         "isDraft": False,
         "title": "Add new feature",
@@ -85,6 +86,112 @@ def test_add_pr_from_same_repo(
     assert env.tab_exists(tab_name)
 
 
+def test_merge_pr_worktree_defaults_to_pr_base_branch(
+    isolated_tmux_server, workmux_exe_path, remote_repo_path
+):
+    """Test `merge` targets the PR's base branch rather than main"""
+    env = isolated_tmux_server
+    repo_path = env.tmp_path
+    setup_git_repo(repo_path, env.env)
+
+    setup_pr_remote_and_branch(env, repo_path, remote_repo_path, "feature-branch")
+    env.run_command(["git", "branch", "develop"], cwd=repo_path)
+
+    pr_data = {
+        "headRefName": "feature-branch",
+        "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "develop",
+        "state": "OPEN",
+        "isDraft": False,
+        "title": "Add new feature",
+        "author": {"login": "contributor"},
+    }
+    install_fake_gh_cli(env, pr_number=123, json_response=pr_data)
+
+    run_workmux_command(env, workmux_exe_path, repo_path, "add --pr 123")
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, "merge feature-branch --keep"
+    )
+
+    assert "into 'develop'" in result.stdout
+    develop_log = env.run_command(["git", "log", "--oneline", "develop"], cwd=repo_path)
+    assert "PR changes" in develop_log.stdout
+    main_log = env.run_command(["git", "log", "--oneline", "main"], cwd=repo_path)
+    assert "PR changes" not in main_log.stdout
+
+
+def test_add_pr_from_fork_fetches_pull_ref_and_tracks_fork_remote(
+    isolated_tmux_server, workmux_exe_path, remote_repo_path
+):
+    """Test a fork PR is fetched from origin's pull/<n>/head into pr-<n>
+    and tracks a remote named after the fork owner"""
+    env = isolated_tmux_server
+    repo_path = env.tmp_path
+    setup_git_repo(repo_path, env.env)
+
+    # GitHub exposes the fork's head on origin as refs/pull/<n>/head
+    setup_pr_remote_and_branch(env, repo_path, remote_repo_path, "fork-feature")
+    env.run_command(
+        ["git", "push", "origin", "origin/fork-feature:refs/pull/456/head"],
+        cwd=repo_path,
+    )
+    env.run_command(
+        ["git", "push", "origin", "--delete", "fork-feature"], cwd=repo_path
+    )
+
+    pr_data = {
+        # Fork PRs are often opened from the fork's main branch
+        "headRefName": "main",
+        "headRepositoryOwner": {"login": "forker"},
+        "baseRefName": "main",
+        "state": "OPEN",
+        "isDraft": False,
+        "title": "Fix from a fork",
+        "author": {"login": "forker"},
+    }
+    install_fake_gh_cli(env, pr_number=456, json_response=pr_data)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "add --pr 456")
+
+    assert "into 'pr-456'" in result.stdout
+    worktree_path = get_worktree_path(repo_path, "pr-456")
+    assert worktree_path.exists()
+    log = env.run_command(["git", "log", "--oneline", "-1"], cwd=worktree_path)
+    assert "PR changes" in log.stdout
+    assert env.tab_exists(get_tab_name("pr-456"))
+
+    remotes = env.run_command(["git", "remote"], cwd=repo_path)
+    assert sorted(remotes.stdout.split()) == ["forker", "origin"]
+    fork_url = env.run_command(
+        ["git", "config", "remote.forker.url"], cwd=repo_path
+    ).stdout.strip()
+    assert fork_url == "https://github.com/forker/testrepo.git"
+    branch_remote = env.run_command(
+        ["git", "config", "branch.pr-456.remote"], cwd=repo_path
+    ).stdout.strip()
+    assert branch_remote == "forker"
+    branch_merge = env.run_command(
+        ["git", "config", "branch.pr-456.merge"], cwd=repo_path
+    ).stdout.strip()
+    assert branch_merge == "refs/heads/main"
+
+    # A second PR from the same fork reuses the remote
+    env.run_command(
+        ["git", "push", "origin", "origin/main:refs/pull/457/head"], cwd=repo_path
+    )
+    install_fake_gh_cli(
+        env, pr_number=457, json_response={**pr_data, "headRefName": "other-fix"}
+    )
+    run_workmux_command(env, workmux_exe_path, repo_path, "add --pr 457")
+
+    remotes = env.run_command(["git", "remote"], cwd=repo_path)
+    assert sorted(remotes.stdout.split()) == ["forker", "origin"]
+    branch_remote = env.run_command(
+        ["git", "config", "branch.pr-457.remote"], cwd=repo_path
+    ).stdout.strip()
+    assert branch_remote == "forker"
+
+
 def test_add_pr_with_custom_branch_name(
     isolated_tmux_server, workmux_exe_path, remote_repo_path
 ):
@@ -98,6 +205,7 @@ def test_add_pr_with_custom_branch_name(
     pr_data = {
         "headRefName": "feature-branch",
         "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "main",
         "state": "OPEN",
         "isDraft": False,
         "title": "Add new feature",
@@ -131,6 +239,7 @@ def test_add_pr_merged_state_warning(
     pr_data = {
         "headRefName": "merged-branch",
         "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "main",
         "state": "MERGED",
         "isDraft": False,
         "title": "Already merged PR",
@@ -158,6 +267,7 @@ def test_add_pr_draft_warning(isolated_tmux_server, workmux_exe_path, remote_rep
     pr_data = {
         "headRefName": "draft-branch",
         "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "main",
         "state": "OPEN",
         "isDraft": True,
         "title": "WIP: Work in progress",
@@ -262,6 +372,7 @@ def test_add_pr_fails_when_worktree_exists(
     pr_data = {
         "headRefName": "feature-branch",
         "headRepositoryOwner": {"login": "testowner"},
+        "baseRefName": "main",
         "state": "OPEN",
         "isDraft": False,
         "title": "Add new feature",
@@ -0,0 +1,93 @@
+import shutil
+import subprocess
+from pathlib import Path
+
+from .conftest import (
+    ZellijEnvironment,
+    get_tab_name,
+    get_worktree_path,
+    run_workmux_add,
+    run_workmux_command,
+    write_workmux_config,
+)
+
+
+def add_and_delete_worktree(
+    env: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path, branch_name: str
+) -> Path:
+    """Creates a worktree with workmux, then removes its directory behind git's back."""
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    shutil.rmtree(worktree_path)
+    return worktree_path
+
+
+def branch_exists(env: ZellijEnvironment, repo_path: Path, branch_name: str) -> bool:
+    result = env.run_command(["git", "branch", "--list", branch_name], cwd=repo_path)
+    return branch_name in result.stdout
+
+
+def test_prune_cleans_metadata_and_tab_but_keeps_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux prune --yes` removes stale worktree metadata and closes the tab, keeping the branch."""
+    env = isolated_tmux_server
+    branch_name = "deleted-by-hand"
+    add_and_delete_worktree(env, workmux_exe_path, repo_path, branch_name)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "prune --yes")
+
+    assert "Pruned stale worktrees" in result.stdout
+    assert "workmux prune --force" in result.stdout
+    assert not env.tab_exists(get_tab_name(branch_name))
+    assert branch_exists(env, repo_path, branch_name)
+
+    worktrees = env.run_command(["git", "worktree", "list"], cwd=repo_path)
+    assert branch_name not in worktrees.stdout
+
+
+def test_prune_force_deletes_branch_from_earlier_prune(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux prune --force` deletes a branch kept by a previous prune."""
+    env = isolated_tmux_server
+    branch_name = "deleted-by-hand"
+    add_and_delete_worktree(env, workmux_exe_path, repo_path, branch_name)
+
+    run_workmux_command(env, workmux_exe_path, repo_path, "prune")
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "prune --force")
+
+    assert "Deleted orphaned branches" in result.stdout
+    assert not branch_exists(env, repo_path, branch_name)
+
+    result = run_workmux_command(env, workmux_exe_path, repo_path, "prune")
+    assert "Nothing to prune" in result.stdout
+
+
+def test_prune_without_confirmation_keeps_tabs(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux prune` only closes tabs once confirmed, and never unrelated ones."""
+    env = isolated_tmux_server
+    branch_name = "deleted-by-hand"
+    add_and_delete_worktree(env, workmux_exe_path, repo_path, branch_name)
+    env.run_command(["zellij", "action", "new-tab", "--name", "wm-other-repo-branch"])
+    env.set_current_tab("test")
+
+    result = subprocess.run(
+        [str(workmux_exe_path), "prune"],
+        cwd=repo_path,
+        env=env.env,
+        capture_output=True,
+        text=True,
+        input="n\n",
+    )
+
+    assert result.returncode == 0, result.stderr
+    assert "Pruned stale worktrees" in result.stdout
+    assert env.tab_exists(get_tab_name(branch_name))
+    assert env.tab_exists("wm-other-repo-branch")
+
+    run_workmux_command(env, workmux_exe_path, repo_path, "prune --yes")
+    assert env.tab_exists("wm-other-repo-branch")
@@ -1,3 +1,5 @@
+import json
+import shutil
 import uuid
 from pathlib import Path
 
@@ -9,6 +11,7 @@ from .conftest import (
     get_tab_name,
     get_worktree_path,
     run_workmux_add,
+    run_workmux_command,
     run_workmux_remove,
     write_workmux_config,
 )
@@ -36,6 +39,31 @@ def test_remove_clean_branch_succeeds_without_prompt(
     assert branch_name not in branch_list_result.stdout
 
 
+def test_remove_delete_remote_refuses_without_terminal_or_force(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `remove --delete-remote` names the remote ref and refuses non-interactively without --force."""
+    env = isolated_tmux_server
+    branch_name = "remote-gated"
+    write_workmux_config(repo_path)
+
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"remove --delete-remote {branch_name}",
+        expect_fail=True,
+    )
+
+    assert f"The remote branch 'origin/{branch_name}' will be deleted." in result.stderr
+    assert "Use --force" in result.stderr
+    assert worktree_path.is_dir()
+    assert env.tab_exists(get_tab_name(branch_name))
+
+
 def test_remove_unmerged_branch_with_confirmation(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -110,6 +138,138 @@ def test_remove_fails_on_uncommitted_changes(
     assert worktree_path.exists(), "Worktree should not be removed when command fails"
 
 
+def test_remove_multiple_branches_continues_past_failures(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux remove a b c` removes the others when one fails and exits non-zero."""
+    env = isolated_tmux_server
+    write_workmux_config(repo_path)
+    branches = ["multi-first", "multi-dirty", "multi-last"]
+    for branch_name in branches:
+        run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    dirty_path = get_worktree_path(repo_path, "multi-dirty")
+    create_dirty_file(dirty_path)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        "remove " + " ".join(branches),
+        expect_fail=True,
+    )
+
+    assert "Failed to remove 1 of 3 branches" in result.stderr
+    assert "✓ multi-first: removed" in result.stdout
+    assert "✗ multi-dirty: failed" in result.stdout
+    assert "✓ multi-last: removed" in result.stdout
+    assert dirty_path.exists()
+    assert env.tab_exists(get_tab_name("multi-dirty"))
+    for branch_name in ["multi-first", "multi-last"]:
+        assert not get_worktree_path(repo_path, branch_name).exists()
+        assert not env.tab_exists(get_tab_name(branch_name))
+
+
+def test_remove_ignores_changes_in_ignore_dirty_paths(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies files listed in `ignore_dirty_paths` don't block `workmux remove`."""
+    env = isolated_tmux_server
+    branch_name = "ignored-dirt"
+    write_workmux_config(repo_path, ignore_dirty_paths=["NOTES.md"])
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_dirty_file(worktree_path, "NOTES.md")
+
+    run_workmux_remove(env, workmux_exe_path, repo_path, branch_name, force=False)
+
+    assert not worktree_path.exists()
+
+    # Other files still count as uncommitted changes
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    create_dirty_file(worktree_path, "NOTES.md")
+    create_dirty_file(worktree_path)
+    run_workmux_remove(
+        env,
+        workmux_exe_path,
+        repo_path,
+        branch_name,
+        force=False,
+        expect_fail=True,
+    )
+    assert worktree_path.exists()
+
+
+def test_remove_json_reports_kept_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `remove --keep-branch --json` reports the worktree removed and branch kept."""
+    env = isolated_tmux_server
+    branch_name = "json-keep-branch"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"remove {branch_name} --keep-branch --json"
+    )
+
+    output = json.loads(result.stdout)
+    assert output["branch_removed"] == branch_name
+    assert output["cleanup"]["worktree_removed"] is True
+    assert output["cleanup"]["local_branch_deleted"] is False
+    assert output["cleanup"]["local_branch_skip_reason"] == "kept"
+    assert output["cleanup"]["remote_delete_error"] is None
+
+
+def test_remove_prune_metadata_only_skips_tab_and_files(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `remove --prune-metadata-only` only prunes git metadata and deletes the branch."""
+    env = isolated_tmux_server
+    branch_name = "prune-metadata-only"
+    tab_name = get_tab_name(branch_name)
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    shutil.rmtree(worktree_path)
+
+    run_workmux_command(
+        env, workmux_exe_path, repo_path, f"remove {branch_name} --prune-metadata-only"
+    )
+
+    worktree_list = env.run_command(["git", "worktree", "list", "--porcelain"])
+    assert str(worktree_path) not in worktree_list.stdout
+    branch_list_result = env.run_command(["git", "branch", "--list", branch_name])
+    assert branch_name not in branch_list_result.stdout
+    # Tab operations are skipped entirely
+    assert env.tab_exists(tab_name)
+
+
+def test_remove_prune_metadata_only_refuses_existing_worktree(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `remove --prune-metadata-only` leaves a worktree that is still on disk alone."""
+    env = isolated_tmux_server
+    branch_name = "prune-metadata-existing"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+    worktree_path = get_worktree_path(repo_path, branch_name)
+
+    result = run_workmux_command(
+        env,
+        workmux_exe_path,
+        repo_path,
+        f"remove {branch_name} --prune-metadata-only",
+        expect_fail=True,
+    )
+
+    assert "still exists" in result.stderr
+    assert worktree_path.is_dir()
+    branch_list_result = env.run_command(["git", "branch", "--list", branch_name])
+    assert branch_name in branch_list_result.stdout
+
+
 def test_remove_with_force_on_unmerged_branch(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -128,6 +288,45 @@ def test_remove_with_force_on_unmerged_branch(
     assert not worktree_path.exists(), "Worktree should be removed"
 
 
+def test_remove_with_yes_on_unmerged_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux remove --yes` removes an unmerged branch without a prompt."""
+    env = isolated_tmux_server
+    branch_name = "yes-remove-unmerged"
+    write_workmux_config(repo_path)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: something unmerged")
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"remove --yes {branch_name}"
+    )
+
+    assert "[y/N]" not in result.stderr
+    assert not worktree_path.exists(), "Worktree should be removed"
+    branch_list_result = env.run_command(["git", "branch", "--list", branch_name])
+    assert branch_name not in branch_list_result.stdout
+
+
+def test_remove_with_confirm_disabled_in_config(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `confirm: false` answers the unmerged-commits prompt like --yes."""
+    env = isolated_tmux_server
+    branch_name = "no-confirm-unmerged"
+    write_workmux_config(repo_path, confirm=False)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: something unmerged")
+
+    run_workmux_remove(env, workmux_exe_path, repo_path, branch_name)
+
+    assert not worktree_path.exists(), "Worktree should be removed"
+
+
 def test_remove_with_force_on_uncommitted_changes(
     isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
 ):
@@ -0,0 +1,62 @@
+import json
+from pathlib import Path
+
+from .conftest import (
+    ZellijEnvironment,
+    create_commit,
+    create_dirty_file,
+    get_worktree_path,
+    run_workmux_add,
+    run_workmux_command,
+    write_workmux_config,
+)
+
+
+def test_status_json_reports_ahead_behind_and_changes(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux status --json` counts commits against the base and flags local changes."""
+    env = isolated_tmux_server
+    branch_name = "feature-status"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: first")
+    create_commit(env, worktree_path, "feat: second")
+    create_commit(env, repo_path, "main moved on")
+
+    (worktree_path / "staged.txt").write_text("staged")
+    env.run_command(["git", "add", "staged.txt"], cwd=worktree_path)
+    create_dirty_file(worktree_path)
+
+    result = run_workmux_command(
+        env, workmux_exe_path, repo_path, f"status {branch_name} --json"
+    )
+    status = json.loads(result.stdout)
+
+    assert status["branch"] == branch_name
+    assert status["base"] == "main"
+    assert status["ahead"] == 2
+    assert status["behind"] == 1
+    assert status["dirty"] is True
+    assert status["staged"] is True
+
+
+def test_status_defaults_to_current_branch(
+    isolated_tmux_server: ZellijEnvironment, workmux_exe_path: Path, repo_path: Path
+):
+    """Verifies `workmux status` inside a worktree reports that worktree's branch as a table."""
+    env = isolated_tmux_server
+    branch_name = "feature-current"
+    write_workmux_config(repo_path, env=env)
+    run_workmux_add(env, workmux_exe_path, repo_path, branch_name)
+
+    worktree_path = get_worktree_path(repo_path, branch_name)
+    create_commit(env, worktree_path, "feat: work")
+
+    result = run_workmux_command(env, workmux_exe_path, worktree_path, "status")
+
+    lines = result.stdout.strip().splitlines()
+    assert lines[0].split() == ["BRANCH", "BASE", "AHEAD", "BEHIND", "DIRTY", "STAGED"]
+    assert lines[1].split() == [branch_name, "main", "1", "0", "-", "-"]